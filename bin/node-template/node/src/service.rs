@@ -130,6 +130,7 @@ pub fn new_partial(
 			check_for_equivocation: Default::default(),
 			telemetry: telemetry.as_ref().map(|x| x.handle()),
 			compatibility_mode: Default::default(),
+			verification_parallelism: config.import_verification_parallelism,
 		})?;
 
 	Ok(sc_service::PartialComponents {
@@ -249,6 +250,7 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
 		);
 
 		let slot_duration = sc_consensus_aura::slot_duration(&*client)?;
+		let inherent_data_client = client.clone();
 
 		let aura = sc_consensus_aura::start_aura::<AuraPair, _, _, _, _, _, _, _, _, _, _>(
 			StartAuraParams {
@@ -257,16 +259,19 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
 				select_chain,
 				block_import,
 				proposer_factory,
-				create_inherent_data_providers: move |_, ()| async move {
-					let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
-
-					let slot =
-						sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_slot_duration(
-							*timestamp,
-							slot_duration,
+				create_inherent_data_providers: move |parent, ()| {
+					let client = inherent_data_client.clone();
+					async move {
+						// Follow slot duration changes made by runtime upgrades.
+						let slot_schedule = sc_consensus_aura::slot_schedule_at(&*client, parent)?;
+						let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
+
+						let slot = sp_consensus_aura::inherents::InherentDataProvider::new(
+							slot_schedule.slot_at(*timestamp),
 						);
 
-					Ok((slot, timestamp))
+						Ok((slot, timestamp))
+					}
 				},
 				force_authoring,
 				backoff_authoring_blocks,
@@ -298,6 +303,7 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
 			justification_generation_period: GRANDPA_JUSTIFICATION_PERIOD,
 			name: Some(name),
 			observer_enabled: false,
+			observer_report_equivocations: false,
 			keystore,
 			local_role: role,
 			telemetry: telemetry.as_ref().map(|x| x.handle()),
@@ -94,6 +94,7 @@ fn new_node(tokio_handle: Handle) -> node_cli::service::NewFullBase {
 		max_runtime_instances: 8,
 		runtime_cache_size: 2,
 		announce_block: true,
+		import_verification_parallelism: None,
 		data_path: base_path.path().into(),
 		base_path,
 		informant_output_format: Default::default(),
@@ -60,6 +60,7 @@ fn new_node(tokio_handle: Handle) -> node_cli::service::NewFullBase {
 			future: PoolLimit { count: 100_000, total_bytes: 100 * 1024 * 1024 },
 			reject_future_transactions: false,
 			ban_time: Duration::from_secs(30 * 60),
+			..Default::default()
 		},
 		network: network_config,
 		keystore: KeystoreConfig::InMemory,
@@ -90,6 +91,7 @@ fn new_node(tokio_handle: Handle) -> node_cli::service::NewFullBase {
 		max_runtime_instances: 8,
 		runtime_cache_size: 2,
 		announce_block: true,
+		import_verification_parallelism: None,
 		data_path: base_path.path().into(),
 		base_path,
 		informant_output_format: Default::default(),
@@ -232,6 +232,7 @@ pub fn new_partial(
 			spawner: &task_manager.spawn_essential_handle(),
 			registry: config.prometheus_registry(),
 			telemetry: telemetry.as_ref().map(|x| x.handle()),
+			verification_parallelism: config.import_verification_parallelism,
 			offchain_tx_pool_factory: OffchainTransactionPoolFactory::new(transaction_pool.clone()),
 		})?;
 
@@ -533,6 +534,7 @@ pub fn new_full_base(
 		justification_generation_period: GRANDPA_JUSTIFICATION_PERIOD,
 		name: Some(name),
 		observer_enabled: false,
+		observer_report_equivocations: false,
 		keystore,
 		local_role: role.clone(),
 		telemetry: telemetry.as_ref().map(|x| x.handle()),
@@ -143,7 +143,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	// and set impl_version to 0. If only runtime
 	// implementation changes and behavior does not, then leave spec_version as
 	// is and increment impl_version.
-	spec_version: 268,
+	spec_version: 269,
 	impl_version: 0,
 	apis: RUNTIME_API_VERSIONS,
 	transaction_version: 2,
@@ -2348,6 +2348,7 @@ impl_runtime_apis! {
 		}
 	}
 
+	#[api_version(5)]
 	impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<
 		Block,
 		Balance,
@@ -2364,6 +2365,16 @@ impl_runtime_apis! {
 		fn query_length_to_fee(length: u32) -> Balance {
 			TransactionPayment::length_to_fee(length)
 		}
+		fn query_tip_for_priority(
+			uxt: <Block as BlockT>::Extrinsic,
+			len: u32,
+			priority: TransactionPriority,
+		) -> Balance {
+			TransactionPayment::query_tip_for_priority(uxt, len, priority)
+		}
+		fn query_block_limits() -> (Weight, u32) {
+			TransactionPayment::query_block_limits()
+		}
 	}
 
 	impl pallet_asset_conversion::AssetConversionApi<
@@ -29,7 +29,7 @@ use sp_runtime::{
 	Justification, Justifications, StateVersion, Storage,
 };
 use sp_state_machine::{
-	backend::AsTrieBackend, ChildStorageCollection, IndexOperation, IterArgs,
+	backend::AsTrieBackend, ChildStorageCollection, IndexOperation, IterArgs, MerkleValue,
 	OffchainChangesCollection, StorageCollection, StorageIterator,
 };
 use sp_storage::{ChildInfo, StorageData, StorageKey};
@@ -476,6 +476,31 @@ pub trait StorageProvider<Block: BlockT, B: Backend<Block>> {
 		child_info: &ChildInfo,
 		key: &StorageKey,
 	) -> sp_blockchain::Result<Option<Block::Hash>>;
+
+	/// Given a block's `Hash` and a key, return the merkle value of the closest descendant node
+	/// of the key in that block.
+	///
+	/// Returns an error by default, for providers that can't compute merkle values.
+	fn closest_merkle_value(
+		&self,
+		_hash: Block::Hash,
+		_key: &StorageKey,
+	) -> sp_blockchain::Result<Option<MerkleValue<Block::Hash>>> {
+		Err(sp_blockchain::Error::Backend("Merkle values are not supported".into()))
+	}
+
+	/// Given a block's `Hash`, a key and a child storage key, return the merkle value of the
+	/// closest descendant node of the key in that block.
+	///
+	/// Returns an error by default, for providers that can't compute merkle values.
+	fn child_closest_merkle_value(
+		&self,
+		_hash: Block::Hash,
+		_child_info: &ChildInfo,
+		_key: &StorageKey,
+	) -> sp_blockchain::Result<Option<MerkleValue<Block::Hash>>> {
+		Err(sp_blockchain::Error::Backend("Merkle values are not supported".into()))
+	}
 }
 
 /// Client backend.
@@ -621,6 +646,13 @@ pub trait Backend<Block: BlockT>: AuxStore + Send + Sync {
 
 	/// Tells whether the backend requires full-sync mode.
 	fn requires_full_sync(&self) -> bool;
+
+	/// Get a state trie node by its hash.
+	///
+	/// By default no trie nodes are found.
+	fn trie_node(&self, _hash: Block::Hash) -> sp_blockchain::Result<Option<Vec<u8>>> {
+		Ok(None)
+	}
 }
 
 /// Mark for all Backend implementations, that are making use of state data, stored locally.
@@ -149,6 +149,14 @@ pub trait BlockBackend<Block: BlockT> {
 		Ok(self.indexed_transaction(hash)?.is_some())
 	}
 
+	/// Get a state trie node by its hash.
+	///
+	/// By default no trie nodes are found. See
+	/// [`Backend::trie_node`](crate::backend::Backend::trie_node).
+	fn trie_node(&self, _hash: Block::Hash) -> sp_blockchain::Result<Option<Vec<u8>>> {
+		Ok(None)
+	}
+
 	/// Tells whether the current client configuration requires full-sync mode.
 	fn requires_full_sync(&self) -> bool;
 }
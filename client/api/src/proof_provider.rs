@@ -40,6 +40,25 @@ pub trait ProofProvider<Block: BlockT> {
 		keys: &mut dyn Iterator<Item = &[u8]>,
 	) -> sp_blockchain::Result<StorageProof>;
 
+	/// Reads the storage values at a given block of all keys starting with `prefix`,
+	/// returning read proof and the number of keys read.
+	///
+	/// Keys are read from the child trie `child_info`, or from the main trie if `None`,
+	/// starting at `start_key` inclusively. The proof is built until size limit is reached
+	/// and always includes at least one key if there is any.
+	///
+	/// By default reading descendants proofs is not supported.
+	fn read_descendants_proof(
+		&self,
+		_hash: Block::Hash,
+		_child_info: Option<&ChildInfo>,
+		_prefix: &[u8],
+		_start_key: Option<&[u8]>,
+		_size_limit: usize,
+	) -> sp_blockchain::Result<(StorageProof, u32)> {
+		Err(sp_blockchain::Error::Backend("Reading descendants proofs is not supported".into()))
+	}
+
 	/// Execute a call to a contract on top of state in a block of given hash
 	/// AND returning execution proof.
 	///
@@ -36,7 +36,7 @@ use sp_core::traits::CallContext;
 use sp_runtime::{
 	legacy,
 	traits::{Block as BlockT, Hash, HashFor, Header as HeaderT, NumberFor, One},
-	Digest,
+	Digest, DispatchOutcome,
 };
 
 use sc_client_api::backend;
@@ -200,6 +200,17 @@ where
 	///
 	/// This will ensure the extrinsic can be validly executed (by executing it).
 	pub fn push(&mut self, xt: <Block as BlockT>::Extrinsic) -> Result<(), Error> {
+		self.push_with_outcome(xt).map(drop)
+	}
+
+	/// Push onto the block's list of extrinsics, returning the outcome of its dispatch.
+	///
+	/// Like [`Self::push`], the extrinsic is only included if it could be applied, an extrinsic
+	/// whose dispatch failed is still included.
+	pub fn push_with_outcome(
+		&mut self,
+		xt: <Block as BlockT>::Extrinsic,
+	) -> Result<DispatchOutcome, Error> {
 		let parent_hash = self.parent_hash;
 		let extrinsics = &mut self.extrinsics;
 		let version = self.version;
@@ -214,9 +225,9 @@ where
 			};
 
 			match res {
-				Ok(Ok(_)) => {
+				Ok(Ok(outcome)) => {
 					extrinsics.push(xt);
-					TransactionOutcome::Commit(Ok(()))
+					TransactionOutcome::Commit(Ok(outcome))
 				},
 				Ok(Err(tx_validity)) => TransactionOutcome::Rollback(Err(
 					ApplyExtrinsicFailed::Validity(tx_validity).into(),
@@ -19,7 +19,7 @@
 //! Substrate chain configurations.
 #![warn(missing_docs)]
 
-use crate::{extension::GetExtension, ChainType, Properties, RuntimeGenesis};
+use crate::{extension::GetExtension, ChainType, Properties, RuntimeGenesis, SyncCheckpoint};
 use sc_network::config::MultiaddrWithPeerId;
 use sc_telemetry::TelemetryEndpoints;
 use serde::{Deserialize, Serialize};
@@ -187,6 +187,9 @@ struct ClientSpec<E> {
 	/// given block number until the `spec_version` on chain changes.
 	#[serde(default)]
 	code_substitutes: BTreeMap<String, Bytes>,
+	/// Trusted finalized block nodes can start syncing from, instead of genesis.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	checkpoint: Option<SyncCheckpoint>,
 }
 
 /// A type denoting empty extensions.
@@ -249,6 +252,16 @@ impl<G, E> ChainSpec<G, E> {
 		self.client_spec.boot_nodes.push(addr)
 	}
 
+	/// Trusted finalized block to start a checkpoint sync from.
+	pub fn checkpoint(&self) -> Option<&SyncCheckpoint> {
+		self.client_spec.checkpoint.as_ref()
+	}
+
+	/// Set the trusted finalized block to start a checkpoint sync from.
+	pub fn set_checkpoint(&mut self, checkpoint: Option<SyncCheckpoint>) {
+		self.client_spec.checkpoint = checkpoint;
+	}
+
 	/// Returns a reference to the defined chain spec extensions.
 	pub fn extensions(&self) -> &E {
 		&self.client_spec.extensions
@@ -285,6 +298,7 @@ impl<G, E> ChainSpec<G, E> {
 			consensus_engine: (),
 			genesis: Default::default(),
 			code_substitutes: BTreeMap::new(),
+			checkpoint: None,
 		};
 
 		ChainSpec { client_spec, genesis: GenesisSource::Factory(Arc::new(constructor)) }
@@ -440,6 +454,10 @@ where
 			.map(|(h, c)| (h.clone(), c.0.clone()))
 			.collect()
 	}
+
+	fn checkpoint(&self) -> Option<&SyncCheckpoint> {
+		ChainSpec::checkpoint(self)
+	}
 }
 
 #[cfg(test)]
@@ -472,6 +490,28 @@ mod tests {
 		assert_eq!(spec2.chain_type(), ChainType::Live)
 	}
 
+	#[test]
+	fn checkpoint_round_trips_through_json() {
+		let mut spec = TestSpec::from_json_bytes(Cow::Owned(
+			include_bytes!("../res/chain_spec.json").to_vec(),
+		))
+		.unwrap();
+		assert_eq!(spec.checkpoint(), None);
+
+		let checkpoint = SyncCheckpoint {
+			hash: Bytes(vec![1; 32]),
+			header: Bytes(vec![2; 100]),
+			state_root: Bytes(vec![3; 32]),
+		};
+		spec.set_checkpoint(Some(checkpoint.clone()));
+
+		let json = spec.as_json(false).unwrap();
+		assert!(json.contains("\"stateRoot\""));
+
+		let spec = TestSpec::from_json_bytes(json.into_bytes()).unwrap();
+		assert_eq!(spec.checkpoint(), Some(&checkpoint));
+	}
+
 	#[derive(Debug, Serialize, Deserialize, Clone)]
 	#[serde(rename_all = "camelCase")]
 	struct Extension1 {
@@ -180,6 +180,11 @@
 //! /// there is no other way around it and only patch the problematic bug, the rest should be done
 //! /// with a on-chain runtime upgrade.
 //! "codeSubstitutes": [],
+//!
+//! /// Optional trusted finalized block that nodes started with `--sync checkpoint` download the
+//! /// state of, instead of syncing from genesis. All fields are hex encoded, `header` being the
+//! /// SCALE-encoded block header.
+//! "checkpoint": { "hash": "0x..", "header": "0x..", "stateRoot": "0x.." },
 //! ```
 //!
 //! See [`ChainSpec`] for a trait representation of the above.
@@ -204,7 +209,7 @@ pub use sc_chain_spec_derive::{ChainSpecExtension, ChainSpecGroup};
 use sc_network::config::MultiaddrWithPeerId;
 use sc_telemetry::TelemetryEndpoints;
 use serde::{de::DeserializeOwned, Serialize};
-use sp_core::storage::Storage;
+use sp_core::{storage::Storage, Bytes};
 use sp_runtime::BuildStorage;
 
 /// The type of a chain.
@@ -232,6 +237,19 @@ impl Default for ChainType {
 /// Arbitrary properties defined in chain spec as a JSON object
 pub type Properties = serde_json::map::Map<String, serde_json::Value>;
 
+/// A trusted finalized block nodes can start syncing from.
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+pub struct SyncCheckpoint {
+	/// Hash of the block.
+	pub hash: Bytes,
+	/// SCALE-encoded header of the block.
+	pub header: Bytes,
+	/// State root of the block.
+	pub state_root: Bytes,
+}
+
 /// A set of traits for the runtime genesis config.
 pub trait RuntimeGenesis: Serialize + DeserializeOwned + BuildStorage {}
 impl<T: Serialize + DeserializeOwned + BuildStorage> RuntimeGenesis for T {}
@@ -274,6 +292,10 @@ pub trait ChainSpec: BuildStorage + Send + Sync {
 	fn set_storage(&mut self, storage: Storage);
 	/// Returns code substitutes that should be used for the on chain wasm.
 	fn code_substitutes(&self) -> std::collections::BTreeMap<String, Vec<u8>>;
+	/// Trusted finalized block to start a checkpoint sync from.
+	fn checkpoint(&self) -> Option<&SyncCheckpoint> {
+		None
+	}
 }
 
 impl std::fmt::Debug for dyn ChainSpec {
@@ -241,6 +241,10 @@ pub enum SyncMode {
 	FastUnsafe,
 	/// Prove finality and download the latest state.
 	Warp,
+	/// Trust a finalized block, download its state and continue with full sync from there.
+	///
+	/// The block is taken from `--checkpoint-*` or the `checkpoint` of the chain spec.
+	Checkpoint,
 }
 
 impl Into<sc_network::config::SyncMode> for SyncMode {
@@ -256,6 +260,7 @@ impl Into<sc_network::config::SyncMode> for SyncMode {
 				storage_chain_mode: false,
 			},
 			SyncMode::Warp => sc_network::config::SyncMode::Warp,
+			SyncMode::Checkpoint => sc_network::config::SyncMode::Checkpoint,
 		}
 	}
 }
@@ -33,7 +33,7 @@ use sc_service::{
 	BlocksPruning, ChainSpec, TracingReceiver,
 };
 use sc_tracing::logging::LoggerBuilder;
-use std::{net::SocketAddr, path::PathBuf};
+use std::{net::SocketAddr, num::NonZeroUsize, path::PathBuf};
 
 /// The maximum number of characters for a node name.
 pub(crate) const NODE_NAME_MAX_LENGTH: usize = 64;
@@ -290,6 +290,14 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 		self.import_params().map(|x| x.wasm_runtime_overrides()).unwrap_or_default()
 	}
 
+	/// Get the number of blocks the import queue verifies in parallel.
+	///
+	/// By default this is retrieved from `ImportParams` if it is available. Otherwise its `None`.
+	/// If `None` is returned blocks are verified sequentially.
+	fn import_verification_parallelism(&self) -> Result<Option<NonZeroUsize>> {
+		Ok(self.import_params().and_then(|x| x.import_verification_parallelism))
+	}
+
 	/// Get the RPC address.
 	fn rpc_addr(&self, _default_listen_port: u16) -> Result<Option<SocketAddr>> {
 		Ok(None)
@@ -468,12 +476,21 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 		let keystore = self.keystore_config(&config_dir)?;
 		let telemetry_endpoints = self.telemetry_endpoints(&chain_spec)?;
 		let runtime_cache_size = self.runtime_cache_size()?;
+		let database = self.database_config(&config_dir, database_cache_size, database)?;
+		let mut transaction_pool = self.transaction_pool(is_dev)?;
+		if let Some(journal_path) = transaction_pool.journal_path.as_mut() {
+			if journal_path.is_relative() {
+				let database_dir =
+					database.path().and_then(|path| path.parent()).unwrap_or(&config_dir);
+				*journal_path = database_dir.join(&journal_path);
+			}
+		}
 
 		Ok(Configuration {
 			impl_name: C::impl_name(),
 			impl_version: C::impl_version(),
 			tokio_handle,
-			transaction_pool: self.transaction_pool(is_dev)?,
+			transaction_pool,
 			network: self.network_config(
 				&chain_spec,
 				is_dev,
@@ -485,7 +502,7 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 				DCV::p2p_listen_port(),
 			)?,
 			keystore,
-			database: self.database_config(&config_dir, database_cache_size, database)?,
+			database,
 			data_path: config_dir,
 			trie_cache_maximum_size: self.trie_cache_maximum_size()?,
 			state_pruning: self.state_pruning()?,
@@ -514,6 +531,7 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 			chain_spec,
 			max_runtime_instances,
 			announce_block: self.announce_block()?,
+			import_verification_parallelism: self.import_verification_parallelism()?,
 			role,
 			base_path,
 			informant_output_format: Default::default(),
@@ -24,7 +24,7 @@ use crate::{
 	params::{DatabaseParams, PruningParams},
 };
 use clap::Args;
-use std::path::PathBuf;
+use std::{num::NonZeroUsize, path::PathBuf};
 
 /// Parameters for block import.
 #[derive(Debug, Clone, Args)]
@@ -78,6 +78,11 @@ pub struct ImportParams {
 	#[arg(long, value_name = "Bytes", default_value_t = 67108864)]
 	pub trie_cache_size: usize,
 
+	/// Number of blocks to verify in parallel ahead of their import.
+	/// By default blocks are verified sequentially.
+	#[arg(long, value_name = "COUNT")]
+	pub import_verification_parallelism: Option<NonZeroUsize>,
+
 	/// DEPRECATED
 	/// Switch to `--trie-cache-size`.
 	#[arg(long)]
@@ -20,15 +20,21 @@ use crate::{arg_enums::SyncMode, params::node_key_params::NodeKeyParams};
 use clap::Args;
 use sc_network::{
 	config::{
-		NetworkConfiguration, NodeKeyConfig, NonReservedPeerMode, SetConfig, TransportConfig,
+		CheckpointSyncConfig, GapSyncConfig, NetworkConfiguration, NodeKeyConfig,
+		NonReservedPeerMode, SetConfig, TransportConfig,
 	},
 	multiaddr::Protocol,
 };
 use sc_service::{
 	config::{Multiaddr, MultiaddrWithPeerId},
-	ChainSpec, ChainType,
+	ChainSpec, ChainType, SyncCheckpoint,
+};
+use sp_core::Bytes;
+use std::{
+	borrow::Cow,
+	num::{NonZeroU32, NonZeroUsize},
+	path::PathBuf,
 };
-use std::{borrow::Cow, num::NonZeroUsize, path::PathBuf};
 
 /// Parameters used to create the network configuration.
 #[derive(Debug, Clone, Args)]
@@ -132,6 +138,16 @@ pub struct NetworkParams {
 	#[arg(long)]
 	pub ipfs_server: bool,
 
+	/// Also serve block headers and bodies over bitswap protocol.
+	#[arg(long, requires = "ipfs_server")]
+	pub ipfs_serve_blocks: bool,
+
+	/// Also serve state trie nodes over bitswap protocol.
+	///
+	/// Only supported with the ParityDb database.
+	#[arg(long, requires = "ipfs_server")]
+	pub ipfs_serve_trie_nodes: bool,
+
 	/// Blockchain syncing mode.
 	#[arg(
 		long,
@@ -143,6 +159,41 @@ pub struct NetworkParams {
 	)]
 	pub sync: SyncMode,
 
+	/// Hex encoded hash of the trusted finalized block to start `--sync checkpoint` from.
+	///
+	/// Must be given together with `--checkpoint-header` and `--checkpoint-state-root`.
+	/// Overrides the checkpoint of the chain spec.
+	#[arg(long, value_name = "HASH", requires_all = ["checkpoint_header", "checkpoint_state_root"])]
+	pub checkpoint_hash: Option<Bytes>,
+
+	/// Hex encoded SCALE-encoded header of the trusted finalized block to start
+	/// `--sync checkpoint` from.
+	#[arg(long, value_name = "HEADER", requires_all = ["checkpoint_hash", "checkpoint_state_root"])]
+	pub checkpoint_header: Option<Bytes>,
+
+	/// Hex encoded state root of the trusted finalized block to start `--sync checkpoint` from.
+	#[arg(long, value_name = "HASH", requires_all = ["checkpoint_hash", "checkpoint_header"])]
+	pub checkpoint_state_root: Option<Bytes>,
+
+	/// Do not download the block history missing after warp or checkpoint sync.
+	///
+	/// The node will not be able to serve the blocks below the block it synced from.
+	#[arg(long)]
+	pub no_gap_sync: bool,
+
+	/// Last block of the history to download after warp or checkpoint sync.
+	///
+	/// The history is downloaded upwards from the first missing block. By default the whole
+	/// history is downloaded.
+	#[arg(long, value_name = "NUMBER")]
+	pub gap_sync_target: Option<u64>,
+
+	/// Maximum number of history blocks requested per second after warp or checkpoint sync.
+	///
+	/// By default the history download is not rate limited.
+	#[arg(long, value_name = "COUNT")]
+	pub gap_sync_max_blocks_per_second: Option<NonZeroU32>,
+
 	/// Maximum number of blocks per request.
 	///
 	/// Try reducing this number from the default value if you have a slow network connection
@@ -243,15 +294,49 @@ impl NetworkParams {
 			kademlia_replication_factor: self.kademlia_replication_factor,
 			yamux_window_size: None,
 			ipfs_server: self.ipfs_server,
+			ipfs_serve_blocks: self.ipfs_serve_blocks,
+			ipfs_serve_trie_nodes: self.ipfs_serve_trie_nodes,
 			sync_mode: self.sync.into(),
+			sync_checkpoint: self.sync_checkpoint(chain_spec),
+			gap_sync: GapSyncConfig {
+				enabled: !self.no_gap_sync,
+				target: self.gap_sync_target,
+				max_blocks_per_second: self.gap_sync_max_blocks_per_second,
+			},
 		}
 	}
+
+	/// The checkpoint to start `--sync checkpoint` from.
+	///
+	/// The checkpoint given on the command line takes precedence over the one of the chain spec.
+	fn sync_checkpoint(&self, chain_spec: &Box<dyn ChainSpec>) -> Option<CheckpointSyncConfig> {
+		if self.sync != SyncMode::Checkpoint {
+			return None
+		}
+
+		let checkpoint =
+			match (&self.checkpoint_hash, &self.checkpoint_header, &self.checkpoint_state_root) {
+				(Some(hash), Some(header), Some(state_root)) => SyncCheckpoint {
+					hash: hash.clone(),
+					header: header.clone(),
+					state_root: state_root.clone(),
+				},
+				_ => chain_spec.checkpoint()?.clone(),
+			};
+
+		Some(CheckpointSyncConfig {
+			hash: checkpoint.hash.0,
+			header: checkpoint.header.0,
+			state_root: checkpoint.state_root.0,
+		})
+	}
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
 	use clap::Parser;
+	use sc_service::{GenericChainSpec, NoExtension};
 
 	#[derive(Parser)]
 	struct Cli {
@@ -298,4 +383,67 @@ mod tests {
 
 		assert_eq!(SyncMode::Warp, params.network_params.sync);
 	}
+
+	#[test]
+	fn checkpoint_flags_must_be_given_together() {
+		assert!(Cli::try_parse_from([
+			"",
+			"--sync",
+			"checkpoint",
+			"--checkpoint-hash",
+			"0x0101",
+			"--checkpoint-state-root",
+			"0x0202",
+		])
+		.is_err());
+
+		let params = Cli::try_parse_from([
+			"",
+			"--sync",
+			"checkpoint",
+			"--checkpoint-hash",
+			"0x0101",
+			"--checkpoint-header",
+			"0x03",
+			"--checkpoint-state-root",
+			"0x0202",
+		])
+		.expect("Parses network params");
+
+		let mut chain_spec = GenericChainSpec::<()>::from_genesis(
+			"test",
+			"test_id",
+			ChainType::Development,
+			|| unimplemented!("Not required in tests"),
+			Vec::new(),
+			None,
+			None,
+			None,
+			None,
+			NoExtension::None,
+		);
+		chain_spec.set_checkpoint(Some(SyncCheckpoint {
+			hash: Bytes(vec![4]),
+			header: Bytes(vec![5]),
+			state_root: Bytes(vec![6]),
+		}));
+		let chain_spec: Box<dyn ChainSpec> = Box::new(chain_spec);
+
+		// The command line takes precedence over the chain spec.
+		assert_eq!(
+			params.network_params.sync_checkpoint(&chain_spec),
+			Some(CheckpointSyncConfig {
+				hash: vec![1, 1],
+				header: vec![3],
+				state_root: vec![2, 2]
+			}),
+		);
+
+		let params =
+			Cli::try_parse_from(["", "--sync", "checkpoint"]).expect("Parses network params");
+		assert_eq!(
+			params.network_params.sync_checkpoint(&chain_spec),
+			Some(CheckpointSyncConfig { hash: vec![4], header: vec![5], state_root: vec![6] }),
+		);
+	}
 }
@@ -19,6 +19,7 @@
 use crate::error;
 use clap::Args;
 use sc_service::{BlocksPruning, PruningMode};
+use std::time::Duration;
 
 /// Parameters to define the pruning mode
 #[derive(Debug, Clone, Args)]
@@ -33,6 +34,10 @@ pub struct PruningParams {
 	///  - archive: Keep the state of all blocks.
 	///  - 'archive-canonical' Keep only the state of finalized blocks.
 	///  - number Keep the state of the last number of finalized blocks.
+	///  - duration Keep the state of finalized blocks canonicalized within the given duration,
+	///  e.g. '30m', '12h' or '7d'.
+	///  - size Keep at most the given amount of non-canonical state and state of finalized
+	///  blocks besides the latest finalized state, e.g. '512MB' or '10GB' (1GB = 1024MB).
 	/// [default: 256]
 	#[arg(alias = "pruning", long, value_name = "PRUNING_MODE")]
 	pub state_pruning: Option<DatabasePruningMode>,
@@ -49,7 +54,8 @@ pub struct PruningParams {
 		alias = "keep-blocks",
 		long,
 		value_name = "PRUNING_MODE",
-		default_value = "archive-canonical"
+		default_value = "archive-canonical",
+		value_parser = parse_blocks_pruning
 	)]
 	pub blocks_pruning: DatabasePruningMode,
 }
@@ -79,19 +85,45 @@ pub enum DatabasePruningMode {
 	ArchiveCanonical,
 	/// Keep the data of the last number of finalized blocks.
 	Custom(u32),
+	/// Keep the data of finalized blocks canonicalized within the given duration.
+	MaxAge(Duration),
+	/// Keep at most the given number of bytes of finalized data.
+	MaxSize(u64),
 }
 
 impl std::str::FromStr for DatabasePruningMode {
 	type Err = String;
 
 	fn from_str(input: &str) -> Result<Self, Self::Err> {
+		const INVALID: &str = "Invalid pruning mode specified";
+
 		match input {
 			"archive" => Ok(Self::Archive),
 			"archive-canonical" => Ok(Self::ArchiveCanonical),
-			bc => bc
-				.parse()
-				.map_err(|_| "Invalid pruning mode specified".to_string())
-				.map(Self::Custom),
+			bc => {
+				if let Ok(n) = bc.parse() {
+					return Ok(Self::Custom(n))
+				}
+				let split = bc.find(|c: char| !c.is_ascii_digit()).ok_or(INVALID)?;
+				let (value, unit) = bc.split_at(split);
+				let value: u64 = value.parse().map_err(|_| INVALID)?;
+				let age =
+					|scale: u64| Ok(Self::MaxAge(Duration::from_secs(value.saturating_mul(scale))));
+				let size = |scale: u64| {
+					value.checked_mul(scale).map(Self::MaxSize).ok_or_else(|| INVALID.to_string())
+				};
+				match unit {
+					"s" => age(1),
+					"m" => age(60),
+					"h" => age(60 * 60),
+					"d" => age(24 * 60 * 60),
+					"KB" => size(1 << 10),
+					"MB" => size(1 << 20),
+					"GB" => size(1 << 30),
+					"TB" => size(1 << 40),
+					_ => Err(INVALID.into()),
+				}
+			},
 		}
 	}
 }
@@ -102,16 +134,66 @@ impl Into<PruningMode> for DatabasePruningMode {
 			DatabasePruningMode::Archive => PruningMode::ArchiveAll,
 			DatabasePruningMode::ArchiveCanonical => PruningMode::ArchiveCanonical,
 			DatabasePruningMode::Custom(n) => PruningMode::blocks_pruning(n),
+			DatabasePruningMode::MaxAge(age) => PruningMode::age_pruning(age),
+			DatabasePruningMode::MaxSize(size) => PruningMode::size_pruning(size),
 		}
 	}
 }
 
+/// Blocks are only pruned by number, age and size constraints fall back to keeping the finalized
+/// blocks. `--blocks-pruning` rejects them when parsing.
 impl Into<BlocksPruning> for DatabasePruningMode {
 	fn into(self) -> BlocksPruning {
 		match self {
 			DatabasePruningMode::Archive => BlocksPruning::KeepAll,
-			DatabasePruningMode::ArchiveCanonical => BlocksPruning::KeepFinalized,
+			DatabasePruningMode::ArchiveCanonical |
+			DatabasePruningMode::MaxAge(_) |
+			DatabasePruningMode::MaxSize(_) => BlocksPruning::KeepFinalized,
 			DatabasePruningMode::Custom(n) => BlocksPruning::Some(n),
 		}
 	}
 }
+
+/// Parse a blocks pruning mode, which can't be constrained by age or size.
+fn parse_blocks_pruning(input: &str) -> Result<DatabasePruningMode, String> {
+	match input.parse()? {
+		DatabasePruningMode::MaxAge(_) | DatabasePruningMode::MaxSize(_) =>
+			Err("Blocks pruning only supports a number of blocks".into()),
+		mode => Ok(mode),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::str::FromStr;
+
+	#[test]
+	fn parses_pruning_modes() {
+		let parse = |s| DatabasePruningMode::from_str(s);
+
+		assert_eq!(parse("archive"), Ok(DatabasePruningMode::Archive));
+		assert_eq!(parse("archive-canonical"), Ok(DatabasePruningMode::ArchiveCanonical));
+		assert_eq!(parse("256"), Ok(DatabasePruningMode::Custom(256)));
+		assert_eq!(parse("90s"), Ok(DatabasePruningMode::MaxAge(Duration::from_secs(90))));
+		assert_eq!(parse("30m"), Ok(DatabasePruningMode::MaxAge(Duration::from_secs(1800))));
+		assert_eq!(parse("12h"), Ok(DatabasePruningMode::MaxAge(Duration::from_secs(43200))));
+		assert_eq!(parse("7d"), Ok(DatabasePruningMode::MaxAge(Duration::from_secs(604800))));
+		assert_eq!(parse("512MB"), Ok(DatabasePruningMode::MaxSize(512 * 1024 * 1024)));
+		assert_eq!(parse("10GB"), Ok(DatabasePruningMode::MaxSize(10 * 1024 * 1024 * 1024)));
+
+		for invalid in ["", "d", "7w", "10gb", "-1", "1.5GB", "18446744073709551615TB"] {
+			assert!(parse(invalid).is_err(), "{invalid} should not parse");
+		}
+	}
+
+	#[test]
+	fn blocks_pruning_rejects_age_and_size() {
+		assert_eq!(parse_blocks_pruning("10"), Ok(DatabasePruningMode::Custom(10)));
+		assert!(parse_blocks_pruning("1s").is_err());
+		assert!(parse_blocks_pruning("1MB").is_err());
+
+		let blocks_pruning: BlocksPruning = DatabasePruningMode::Custom(10).into();
+		assert_eq!(blocks_pruning, BlocksPruning::Some(10));
+	}
+}
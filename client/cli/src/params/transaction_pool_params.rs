@@ -33,8 +33,51 @@ pub struct TransactionPoolParams {
 	/// How long a transaction is banned for, if it is considered invalid. Defaults to 1800s.
 	#[arg(long, value_name = "SECONDS")]
 	pub tx_ban_seconds: Option<u64>,
+
+	/// Maximum number of ready transactions of a single sender in the transaction pool.
+	/// Unlimited by default.
+	#[arg(long, value_name = "COUNT")]
+	pub pool_sender_limit: Option<usize>,
+
+	/// Maximum number of future transactions of a single sender in the transaction pool.
+	/// Unlimited by default.
+	#[arg(long, value_name = "COUNT")]
+	pub pool_sender_future_limit: Option<usize>,
+
+	/// Length of the encoded account id identifying the sender of a transaction.
+	///
+	/// The sender is taken from the `(AccountId, Nonce)` tag provided by FRAME based transactions.
+	#[arg(long, value_name = "BYTES", default_value_t = 32)]
+	pub pool_sender_account_id_len: usize,
+
+	/// Length of the encoded nonce in the tag identifying the sender of a transaction.
+	#[arg(long, value_name = "BYTES", default_value_t = 4)]
+	pub pool_sender_nonce_len: usize,
+
+	/// Number of ready transactions reserved for locally submitted and operational transactions.
+	/// Transactions within the reserved lane are not evicted in favour of other transactions.
+	#[arg(long, value_name = "COUNT", default_value_t = 0)]
+	pub pool_reserved: usize,
+
+	/// Minimal priority of a transaction to be considered operational and use the reserved lane.
+	/// By default only locally submitted transactions use the reserved lane.
+	#[arg(long, value_name = "PRIORITY")]
+	pub pool_operational_priority: Option<u64>,
+
+	/// Percentage by which the priority of a transaction has to exceed the priority of the
+	/// transaction it replaces. By default any higher priority is enough.
+	#[arg(long, value_name = "PERCENT", default_value_t = 0)]
+	pub pool_replacement_bump: u32,
+
+	/// Persist the transaction pool to a journal in the database directory.
+	/// Journaled transactions are revalidated and imported back into the pool on restart.
+	#[arg(long)]
+	pub pool_journal: bool,
 }
 
+/// Name of the transaction pool journal file within the database directory.
+const POOL_JOURNAL_FILE: &str = "txpool_journal";
+
 impl TransactionPoolParams {
 	/// Fill the given `PoolConfiguration` by looking at the cli parameters.
 	pub fn transaction_pool(&self, is_dev: bool) -> TransactionPoolOptions {
@@ -49,6 +92,22 @@ impl TransactionPoolParams {
 		opts.future.count = self.pool_limit / factor;
 		opts.future.total_bytes = self.pool_kbytes * 1024 / factor;
 
+		// per-sender limits
+		opts.per_sender.ready = self.pool_sender_limit;
+		opts.per_sender.future = self.pool_sender_future_limit;
+		opts.per_sender.account_id_len = self.pool_sender_account_id_len;
+		opts.per_sender.nonce_len = self.pool_sender_nonce_len;
+
+		// reserved lane
+		opts.reserved.limit.count = self.pool_reserved;
+		opts.reserved.limit.total_bytes = opts.ready.total_bytes;
+		opts.reserved.operational_priority = self.pool_operational_priority;
+
+		opts.replacement_bump = self.pool_replacement_bump;
+
+		// journal, resolved against the database directory later on
+		opts.journal_path = self.pool_journal.then(|| POOL_JOURNAL_FILE.into());
+
 		opts.ban_time = if let Some(ban_seconds) = self.tx_ban_seconds {
 			std::time::Duration::from_secs(ban_seconds)
 		} else if is_dev {
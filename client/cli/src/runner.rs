@@ -285,6 +285,7 @@ mod tests {
 				tracing_receiver: Default::default(),
 				max_runtime_instances: 8,
 				announce_block: true,
+				import_verification_parallelism: None,
 				base_path: sc_service::BasePath::new(root.clone()),
 				data_path: root,
 				informant_output_format: Default::default(),
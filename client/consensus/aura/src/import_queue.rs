@@ -19,11 +19,15 @@
 //! Module implementing the logic for verifying and importing AuRa blocks.
 
 use crate::{
-	authorities, standalone::SealVerificationError, AuthorityId, CompatibilityMode, Error,
-	LOG_TARGET,
+	authorities,
+	standalone::{
+		check_header_slot_and_seal_with, slot_schedule_at, verify_seal, SealVerificationError,
+	},
+	AuthorityId, CompatibilityMode, Error, LOG_TARGET,
 };
 use codec::{Codec, Decode, Encode};
 use log::{debug, info, trace};
+use parking_lot::Mutex;
 use prometheus_endpoint::Registry;
 use sc_client_api::{backend::AuxStore, BlockOf, UsageProvider};
 use sc_consensus::{
@@ -44,12 +48,19 @@ use sp_runtime::{
 	traits::{Block as BlockT, Header, NumberFor},
 	DigestItem,
 };
-use std::{fmt::Debug, hash::Hash, marker::PhantomData, sync::Arc};
+use sp_timestamp::TimestampInherentData;
+use std::{fmt::Debug, hash::Hash, marker::PhantomData, num::NonZeroUsize, sync::Arc};
+
+/// Intermediate key of the author whose signature of the seal was verified ahead of the import
+/// of the parent.
+pub(crate) const VERIFIED_SEAL_KEY: &[u8] = b"aura_seal";
 
 /// check a header has been signed by the right key. If the slot is too far in the future, an error
 /// will be returned. If it's successful, returns the pre-header and the digest item
 /// containing the seal.
 ///
+/// The signature isn't checked again if it was already verified for `verified_author`.
+///
 /// This digest item will always return `Some` when used with `as_aura_seal`.
 fn check_header<C, B: BlockT, P: Pair>(
 	client: &C,
@@ -57,6 +68,7 @@ fn check_header<C, B: BlockT, P: Pair>(
 	header: B::Header,
 	hash: B::Hash,
 	authorities: &[AuthorityId<P>],
+	verified_author: Option<&AuthorityId<P>>,
 	check_for_equivocation: CheckForEquivocation,
 ) -> Result<CheckedHeader<B::Header, (Slot, DigestItem)>, Error<B>>
 where
@@ -65,7 +77,7 @@ where
 	P::Public: Encode + Decode + PartialEq + Clone,
 {
 	let check_result =
-		crate::standalone::check_header_slot_and_seal::<B, P>(slot_now, header, authorities);
+		check_header_slot_and_seal_with::<B, P>(slot_now, header, authorities, verified_author);
 
 	match check_result {
 		Ok((header, slot, seal)) => {
@@ -99,16 +111,21 @@ where
 }
 
 /// A verifier for Aura blocks.
-pub struct AuraVerifier<C, P, CIDP, N> {
+///
+/// Clones share the inherent data providers factory, so that blocks can be verified in parallel.
+/// They also share the authorities of the last verified block, which the seals of blocks are
+/// verified against ahead of the import of their parent.
+pub struct AuraVerifier<C, P: Pair, CIDP, N> {
 	client: Arc<C>,
 	phantom: PhantomData<P>,
-	create_inherent_data_providers: CIDP,
+	create_inherent_data_providers: Arc<CIDP>,
 	check_for_equivocation: CheckForEquivocation,
 	telemetry: Option<TelemetryHandle>,
 	compatibility_mode: CompatibilityMode<N>,
+	known_authorities: Arc<Mutex<Arc<Vec<AuthorityId<P>>>>>,
 }
 
-impl<C, P, CIDP, N> AuraVerifier<C, P, CIDP, N> {
+impl<C, P: Pair, CIDP, N> AuraVerifier<C, P, CIDP, N> {
 	pub(crate) fn new(
 		client: Arc<C>,
 		create_inherent_data_providers: CIDP,
@@ -118,10 +135,25 @@ impl<C, P, CIDP, N> AuraVerifier<C, P, CIDP, N> {
 	) -> Self {
 		Self {
 			client,
-			create_inherent_data_providers,
+			create_inherent_data_providers: Arc::new(create_inherent_data_providers),
 			check_for_equivocation,
 			telemetry,
 			compatibility_mode,
+			known_authorities: Default::default(),
+			phantom: PhantomData,
+		}
+	}
+}
+
+impl<C, P: Pair, CIDP, N: Clone> Clone for AuraVerifier<C, P, CIDP, N> {
+	fn clone(&self) -> Self {
+		Self {
+			client: self.client.clone(),
+			create_inherent_data_providers: self.create_inherent_data_providers.clone(),
+			check_for_equivocation: self.check_for_equivocation,
+			telemetry: self.telemetry.clone(),
+			compatibility_mode: self.compatibility_mode.clone(),
+			known_authorities: self.known_authorities.clone(),
 			phantom: PhantomData,
 		}
 	}
@@ -129,7 +161,7 @@ impl<C, P, CIDP, N> AuraVerifier<C, P, CIDP, N> {
 
 impl<C, P, CIDP, N> AuraVerifier<C, P, CIDP, N>
 where
-	P: Send + Sync + 'static,
+	P: Pair + Send + Sync + 'static,
 	CIDP: Send,
 {
 	async fn check_inherents<B: BlockT>(
@@ -178,6 +210,8 @@ where
 		&mut self,
 		mut block: BlockImportParams<B, ()>,
 	) -> Result<BlockImportParams<B, ()>, String> {
+		let verified_author = block.remove_intermediate::<AuthorityId<P>>(VERIFIED_SEAL_KEY).ok();
+
 		// Skip checks that include execution, if being told so or when importing only state.
 		//
 		// This is done for example when gap syncing and it is expected that the block after the gap
@@ -192,13 +226,16 @@ where
 
 		let hash = block.header.hash();
 		let parent_hash = *block.header.parent_hash();
-		let authorities = authorities(
-			self.client.as_ref(),
-			parent_hash,
-			*block.header.number(),
-			&self.compatibility_mode,
-		)
-		.map_err(|e| format!("Could not fetch authorities at {:?}: {}", parent_hash, e))?;
+		let authorities = Arc::new(
+			authorities(
+				self.client.as_ref(),
+				parent_hash,
+				*block.header.number(),
+				&self.compatibility_mode,
+			)
+			.map_err(|e| format!("Could not fetch authorities at {:?}: {}", parent_hash, e))?,
+		);
+		*self.known_authorities.lock() = authorities.clone();
 
 		let create_inherent_data_providers = self
 			.create_inherent_data_providers
@@ -211,7 +248,15 @@ where
 			.await
 			.map_err(Error::<B>::Inherent)?;
 
-		let slot_now = create_inherent_data_providers.slot();
+		// The slot duration may have been changed by a runtime upgrade, so derive the current slot
+		// from the timestamp using the slot schedule in effect for the children of the parent.
+		let slot_now = match (
+			inherent_data.timestamp_inherent_data(),
+			slot_schedule_at::<AuthorityId<P>, B, _>(&*self.client, parent_hash),
+		) {
+			(Ok(Some(timestamp)), Ok(slot_schedule)) => slot_schedule.slot_at(timestamp),
+			_ => create_inherent_data_providers.slot(),
+		};
 
 		// we add one to allow for some small drift.
 		// FIXME #1019 in the future, alter this queue to allow deferring of
@@ -222,6 +267,7 @@ where
 			block.header,
 			hash,
 			&authorities[..],
+			verified_author.as_ref(),
 			self.check_for_equivocation,
 		)
 		.map_err(|e| e.to_string())?;
@@ -286,6 +332,28 @@ where
 			},
 		}
 	}
+
+	async fn verify_ahead_of_parent(
+		&mut self,
+		mut block: BlockImportParams<B, ()>,
+	) -> Result<BlockImportParams<B, ()>, String> {
+		if block.with_state() || block.state_action.skip_execution_checks() {
+			return Ok(block)
+		}
+
+		// The authorities rarely change, so the seal is checked against the ones of the last
+		// verified block. `verify` checks the seal again if the expected author differs.
+		let authorities = self.known_authorities.lock().clone();
+		if let Some(author) = verify_seal::<B, P>(&block.header, &authorities) {
+			block.insert_intermediate(VERIFIED_SEAL_KEY, author);
+		}
+
+		Ok(block)
+	}
+
+	fn verifies_ahead_of_parent(&self) -> bool {
+		true
+	}
 }
 
 /// Should we check for equivocation of a block author?
@@ -334,6 +402,10 @@ pub struct ImportQueueParams<'a, Block: BlockT, I, C, S, CIDP> {
 	///
 	/// If in doubt, use `Default::default()`.
 	pub compatibility_mode: CompatibilityMode<NumberFor<Block>>,
+	/// Number of blocks to verify in parallel ahead of their import.
+	///
+	/// If `None`, blocks are verified sequentially.
+	pub verification_parallelism: Option<NonZeroUsize>,
 }
 
 /// Start an import queue for the Aura consensus algorithm.
@@ -348,6 +420,7 @@ pub fn import_queue<P, Block, I, C, S, CIDP>(
 		check_for_equivocation,
 		telemetry,
 		compatibility_mode,
+		verification_parallelism,
 	}: ImportQueueParams<Block, I, C, S, CIDP>,
 ) -> Result<DefaultImportQueue<Block, C>, sp_consensus::Error>
 where
@@ -380,7 +453,18 @@ where
 		compatibility_mode,
 	});
 
-	Ok(BasicQueue::new(verifier, Box::new(block_import), justification_import, spawner, registry))
+	let block_import = Box::new(block_import);
+	Ok(match verification_parallelism {
+		Some(parallelism) => BasicQueue::with_parallel_verification(
+			verifier,
+			block_import,
+			justification_import,
+			spawner,
+			registry,
+			parallelism,
+		),
+		None => BasicQueue::new(verifier, block_import, justification_import, spawner, registry),
+	})
 }
 
 /// Parameters of [`build_verifier`].
@@ -400,7 +484,7 @@ pub struct BuildVerifierParams<C, CIDP, N> {
 }
 
 /// Build the [`AuraVerifier`]
-pub fn build_verifier<P, C, CIDP, N>(
+pub fn build_verifier<P: Pair, C, CIDP, N>(
 	BuildVerifierParams {
 		client,
 		create_inherent_data_providers,
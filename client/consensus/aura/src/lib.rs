@@ -40,14 +40,14 @@ use sc_client_api::{backend::AuxStore, BlockOf};
 use sc_consensus::{BlockImport, BlockImportParams, ForkChoiceStrategy, StateAction};
 use sc_consensus_slots::{
 	BackoffAuthoringBlocksStrategy, InherentDataProviderExt, SimpleSlotWorkerToSlotWorker,
-	SlotInfo, StorageChanges,
+	SlotInfo, SlotScheduleProvider, StorageChanges,
 };
 use sc_telemetry::TelemetryHandle;
 use sp_api::{Core, ProvideRuntimeApi};
 use sp_application_crypto::AppPublic;
 use sp_blockchain::HeaderBackend;
 use sp_consensus::{BlockOrigin, Environment, Error as ConsensusError, Proposer, SelectChain};
-use sp_consensus_slots::Slot;
+use sp_consensus_slots::{Slot, SlotSchedule};
 use sp_core::crypto::{Pair, Public};
 use sp_inherents::CreateInherentDataProviders;
 use sp_keystore::KeystorePtr;
@@ -56,7 +56,7 @@ use sp_runtime::traits::{Block as BlockT, Header, Member, NumberFor};
 mod import_queue;
 pub mod standalone;
 
-pub use crate::standalone::{find_pre_digest, slot_duration};
+pub use crate::standalone::{find_pre_digest, slot_duration, slot_duration_at, slot_schedule_at};
 pub use import_queue::{
 	build_verifier, import_queue, AuraVerifier, BuildVerifierParams, CheckForEquivocation,
 	ImportQueueParams,
@@ -112,7 +112,11 @@ impl<N> Default for CompatibilityMode<N> {
 
 /// Parameters of [`start_aura`].
 pub struct StartAuraParams<C, SC, I, PF, SO, L, CIDP, BS, N> {
-	/// The duration of a slot.
+	/// The duration of a slot, used when it can't be read from the runtime.
+	///
+	/// The slot schedule is otherwise read from the runtime at the best block for every slot, so
+	/// that slot duration changes made by runtime upgrades are followed. The inherent data
+	/// providers should compute the slot in the same way, see [`slot_schedule_at`].
 	pub slot_duration: SlotDuration,
 	/// The client to interact with the chain.
 	pub client: Arc<C>,
@@ -189,6 +193,12 @@ where
 	BS: BackoffAuthoringBlocksStrategy<NumberFor<B>> + Send + Sync + 'static,
 	Error: std::error::Error + Send + From<ConsensusError> + 'static,
 {
+	let slot_schedule = RuntimeSlotSchedule::<_, P> {
+		client: client.clone(),
+		fallback: slot_duration,
+		_phantom: PhantomData,
+	};
+
 	let worker = build_aura_worker::<P, _, _, _, _, _, _, _, _>(BuildAuraWorkerParams {
 		client,
 		block_import,
@@ -205,7 +215,7 @@ where
 	});
 
 	Ok(sc_consensus_slots::start_slot_worker(
-		slot_duration,
+		slot_schedule,
 		select_chain,
 		SimpleSlotWorkerToSlotWorker(worker),
 		sync_oracle,
@@ -213,6 +223,37 @@ where
 	))
 }
 
+/// Reads the slot schedule from the runtime, falling back to a fixed slot duration.
+struct RuntimeSlotSchedule<C, P> {
+	client: Arc<C>,
+	fallback: SlotDuration,
+	_phantom: PhantomData<P>,
+}
+
+impl<B, C, P> SlotScheduleProvider<B> for RuntimeSlotSchedule<C, P>
+where
+	B: BlockT,
+	C: ProvideRuntimeApi<B> + AuxStore,
+	C::Api: AuraApi<B, AuthorityId<P>>,
+	P: Pair,
+	P::Public: Codec,
+{
+	fn slot_schedule(&self, parent: &B::Header) -> Option<SlotSchedule> {
+		let slot_schedule = slot_schedule_at::<AuthorityId<P>, B, _>(&*self.client, parent.hash())
+			.unwrap_or_else(|err| {
+				log::warn!(
+					target: LOG_TARGET,
+					"Failed to read the slot schedule at {:?}, using {:?}: {}",
+					parent.hash(),
+					self.fallback,
+					err,
+				);
+				SlotSchedule::from_slot_duration(self.fallback)
+			});
+		Some(slot_schedule)
+	}
+}
+
 /// Parameters of [`build_aura_worker`].
 pub struct BuildAuraWorkerParams<C, I, PF, SO, L, BS, N> {
 	/// The client to interact with the chain.
@@ -554,7 +595,13 @@ mod tests {
 	use parking_lot::Mutex;
 	use sc_block_builder::BlockBuilderProvider;
 	use sc_client_api::BlockchainEvents;
-	use sc_consensus::BoxJustificationImport;
+	use sc_consensus::{
+		import_queue::{
+			BasicQueue, BlockImportError, BlockImportStatus, ImportQueue, IncomingBlock, Link,
+			Verifier,
+		},
+		BoxJustificationImport,
+	};
 	use sc_consensus_slots::{BackoffAuthoringOnFinalizedHeadLagging, SimpleSlotWorker};
 	use sc_keystore::LocalKeystore;
 	use sc_network_test::{Block as TestBlock, *};
@@ -570,6 +617,8 @@ mod tests {
 	};
 	use sp_timestamp::Timestamp;
 	use std::{
+		num::NonZeroUsize,
+		sync::atomic::{AtomicUsize, Ordering},
 		task::Poll,
 		time::{Duration, Instant},
 	};
@@ -879,4 +928,298 @@ mod tests {
 		// The returned block should be imported and we should be able to get its header by now.
 		assert!(client.header(res.block.hash()).unwrap().is_some());
 	}
+
+	#[tokio::test]
+	async fn slot_worker_reads_slot_duration_from_runtime() {
+		let mut net = AuraTestNet::new(1);
+		let client = net.peer(0).client().as_client();
+		let head = client.expect_header(client.info().genesis_hash).unwrap();
+
+		// The configured slot duration is stale, e.g. it was changed by a runtime upgrade.
+		let provider = RuntimeSlotSchedule::<_, AuthorityPair> {
+			client,
+			fallback: SlotDuration::from_millis(SLOT_DURATION_MS * 2),
+			_phantom: PhantomData,
+		};
+
+		// The test runtime doesn't provide a slot schedule, so slots are counted from the epoch.
+		assert_eq!(
+			SlotScheduleProvider::<TestBlock>::slot_schedule(&provider, &head),
+			Some(SlotSchedule::from_slot_duration(SlotDuration::from_millis(SLOT_DURATION_MS))),
+		);
+	}
+
+	#[tokio::test]
+	async fn verifier_follows_slot_duration_change() {
+		let mut net = AuraTestNet::new(1);
+
+		let keystore_path = tempfile::tempdir().expect("Creates keystore path");
+		let keystore = LocalKeystore::open(keystore_path.path(), None).expect("Creates keystore.");
+		keystore
+			.sr25519_generate_new(AuthorityPair::ID, Some(&Keyring::Alice.to_seed()))
+			.expect("Key should be created");
+
+		let client = net.peer(0).client().as_client();
+		let genesis_hash = client.info().genesis_hash;
+		let authorities = client.runtime_api().authorities(genesis_hash).unwrap();
+		let alice = authorities
+			.iter()
+			.position(|a| *a == Keyring::Alice.public().into())
+			.expect("Alice is an authority") as u64;
+
+		// Author a block in the latest slot of Alice, according to the runtime slot duration.
+		let timestamp = Timestamp::current();
+		let slot_now =
+			*Slot::from_timestamp(timestamp, SlotDuration::from_millis(SLOT_DURATION_MS));
+		let n = authorities.len() as u64;
+		let slot = slot_now - (slot_now + n - alice) % n;
+
+		let mut worker = AuraWorker {
+			client: client.clone(),
+			block_import: client.clone(),
+			env: DummyFactory(client.clone()),
+			keystore: keystore.into(),
+			sync_oracle: DummyOracle,
+			justification_sync_link: (),
+			force_authoring: false,
+			backoff_authoring_blocks: Option::<()>::None,
+			telemetry: None,
+			_key_type: PhantomData::<AuthorityPair>,
+			block_proposal_slot_portion: SlotProportion::new(0.5),
+			max_block_proposal_slot_portion: None,
+			compatibility_mode: Default::default(),
+		};
+		let block = worker
+			.on_slot(SlotInfo {
+				slot: slot.into(),
+				ends_at: Instant::now() + Duration::from_secs(100),
+				create_inherent_data: Box::new(()),
+				duration: Duration::from_millis(SLOT_DURATION_MS),
+				chain_head: client.expect_header(genesis_hash).unwrap(),
+				block_size_limit: None,
+			})
+			.await
+			.unwrap()
+			.block;
+
+		// The inherent data providers still use the slot duration from before the change.
+		let stale_slot_duration = SlotDuration::from_millis(SLOT_DURATION_MS * 2);
+
+		// Without a timestamp the stale slot is used and the block looks to be from the future.
+		let mut verifier = import_queue::AuraVerifier::<_, AuthorityPair, _, u64>::new(
+			client.clone(),
+			move |_, _| async move {
+				let slot = InherentDataProvider::from_timestamp_and_slot_duration(
+					Timestamp::current(),
+					stale_slot_duration,
+				);
+				Ok((slot,))
+			},
+			CheckForEquivocation::No,
+			None,
+			CompatibilityMode::None,
+		);
+		let params = BlockImportParams::new(BlockOrigin::NetworkBroadcast, block.header.clone());
+		assert!(verifier.verify(params).await.is_err());
+
+		// With a timestamp the slot is computed using the slot duration of the runtime.
+		let mut verifier = import_queue::AuraVerifier::<_, AuthorityPair, _, u64>::new(
+			client.clone(),
+			move |_, _| async move {
+				let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
+				let slot = InherentDataProvider::from_timestamp_and_slot_duration(
+					*timestamp,
+					stale_slot_duration,
+				);
+				Ok((slot, timestamp))
+			},
+			CheckForEquivocation::No,
+			None,
+			CompatibilityMode::None,
+		);
+		let params = BlockImportParams::new(BlockOrigin::NetworkBroadcast, block.header.clone());
+		assert!(verifier.verify(params).await.is_ok());
+	}
+
+	/// Verifier counting the blocks whose seal was verified ahead of the import of their parent.
+	#[derive(Clone)]
+	struct CountingVerifier<V> {
+		inner: V,
+		verified_ahead: Arc<AtomicUsize>,
+	}
+
+	#[async_trait::async_trait]
+	impl<V: Verifier<TestBlock> + Clone> Verifier<TestBlock> for CountingVerifier<V> {
+		async fn verify(
+			&mut self,
+			block: BlockImportParams<TestBlock, ()>,
+		) -> Result<BlockImportParams<TestBlock, ()>, String> {
+			if block.intermediates.contains_key(import_queue::VERIFIED_SEAL_KEY) {
+				self.verified_ahead.fetch_add(1, Ordering::SeqCst);
+			}
+			self.inner.verify(block).await
+		}
+
+		async fn verify_ahead_of_parent(
+			&mut self,
+			block: BlockImportParams<TestBlock, ()>,
+		) -> Result<BlockImportParams<TestBlock, ()>, String> {
+			self.inner.verify_ahead_of_parent(block).await
+		}
+
+		fn verifies_ahead_of_parent(&self) -> bool {
+			self.inner.verifies_ahead_of_parent()
+		}
+	}
+
+	#[derive(Default)]
+	struct ImportedBlocks {
+		imported: usize,
+		processed: usize,
+	}
+
+	impl Link<TestBlock> for ImportedBlocks {
+		fn blocks_processed(
+			&mut self,
+			imported: usize,
+			count: usize,
+			_results: Vec<(Result<BlockImportStatus<u64>, BlockImportError>, H256)>,
+		) {
+			self.imported += imported;
+			self.processed += count;
+		}
+	}
+
+	/// Import `blocks` with `queue`, returning the number of imported blocks.
+	async fn import_blocks<Transaction: Send + 'static>(
+		queue: &mut BasicQueue<TestBlock, Transaction>,
+		blocks: Vec<IncomingBlock<TestBlock>>,
+	) -> usize {
+		let count = blocks.len();
+		queue.service_ref().import_blocks(BlockOrigin::NetworkInitialSync, blocks);
+
+		let mut link = ImportedBlocks::default();
+		future::poll_fn(|cx| {
+			queue.poll_actions(cx, &mut link);
+			match link.processed < count {
+				true => Poll::Pending,
+				false => Poll::Ready(link.imported),
+			}
+		})
+		.await
+	}
+
+	#[tokio::test]
+	async fn verifies_seals_of_linear_batch_ahead_of_parent() {
+		let mut net = AuraTestNet::new(2);
+
+		let keystore_path = tempfile::tempdir().expect("Creates keystore path");
+		let keystore = LocalKeystore::open(keystore_path.path(), None).expect("Creates keystore.");
+		keystore
+			.sr25519_generate_new(AuthorityPair::ID, Some(&Keyring::Alice.to_seed()))
+			.expect("Key should be created");
+
+		let client = net.peer(0).client().as_client();
+		let genesis_hash = client.info().genesis_hash;
+		let authorities = client.runtime_api().authorities(genesis_hash).unwrap();
+		let alice = authorities
+			.iter()
+			.position(|a| *a == Keyring::Alice.public().into())
+			.expect("Alice is an authority") as u64;
+
+		// Author a chain of blocks in the past slots of Alice.
+		let blocks_count = 6;
+		let n = authorities.len() as u64;
+		let slot_now = *Slot::from_timestamp(
+			Timestamp::current(),
+			SlotDuration::from_millis(SLOT_DURATION_MS),
+		);
+		let first_slot = slot_now - (slot_now + n - alice) % n - (blocks_count - 1) * n;
+
+		let mut worker = AuraWorker {
+			client: client.clone(),
+			block_import: client.clone(),
+			env: DummyFactory(client.clone()),
+			keystore: keystore.into(),
+			sync_oracle: DummyOracle,
+			justification_sync_link: (),
+			force_authoring: false,
+			backoff_authoring_blocks: Option::<()>::None,
+			telemetry: None,
+			_key_type: PhantomData::<AuthorityPair>,
+			block_proposal_slot_portion: SlotProportion::new(0.5),
+			max_block_proposal_slot_portion: None,
+			compatibility_mode: Default::default(),
+		};
+		let mut chain_head = client.expect_header(genesis_hash).unwrap();
+		let mut blocks = Vec::new();
+		for i in 0..blocks_count {
+			let block = worker
+				.on_slot(SlotInfo {
+					slot: (first_slot + i * n).into(),
+					ends_at: Instant::now() + Duration::from_secs(100),
+					create_inherent_data: Box::new(()),
+					duration: Duration::from_millis(SLOT_DURATION_MS),
+					chain_head: chain_head.clone(),
+					block_size_limit: None,
+				})
+				.await
+				.unwrap()
+				.block;
+			chain_head = client.expect_header(block.hash()).unwrap();
+
+			blocks.push(IncomingBlock {
+				hash: block.hash(),
+				header: Some(block.header),
+				body: Some(block.extrinsics),
+				indexed_body: None,
+				justifications: None,
+				origin: None,
+				allow_missing_state: false,
+				import_existing: false,
+				state: None,
+				skip_execution: false,
+			});
+		}
+
+		// Import the chain on the other peer, verifying up to 4 blocks in parallel.
+		let client = net.peer(1).client().as_client();
+		let verified_ahead = Arc::new(AtomicUsize::new(0));
+		let verifier = CountingVerifier {
+			inner: import_queue::AuraVerifier::<_, AuthorityPair, _, u64>::new(
+				client.clone(),
+				|_, _| async {
+					let slot = InherentDataProvider::from_timestamp_and_slot_duration(
+						Timestamp::current(),
+						SlotDuration::from_millis(SLOT_DURATION_MS),
+					);
+					Ok((slot,))
+				},
+				CheckForEquivocation::No,
+				None,
+				CompatibilityMode::None,
+			),
+			verified_ahead: verified_ahead.clone(),
+		};
+		let mut queue = BasicQueue::with_parallel_verification(
+			verifier,
+			Box::new(client.clone()),
+			None,
+			&sp_core::testing::TaskExecutor::new(),
+			None,
+			NonZeroUsize::new(4).unwrap(),
+		);
+
+		// The first block lets the verifier learn the authorities.
+		let rest = blocks.split_off(1);
+		assert_eq!(import_blocks(&mut queue, blocks).await, 1);
+		assert_eq!(verified_ahead.load(Ordering::SeqCst), 0);
+
+		// The second block of the batch is fully verified on the pool as its parent is already
+		// imported, the seals of the following ones are verified while their parent is still in
+		// flight.
+		assert_eq!(import_blocks(&mut queue, rest).await, blocks_count as usize - 1);
+		assert_eq!(verified_ahead.load(Ordering::SeqCst), blocks_count as usize - 2);
+		assert_eq!(client.info().best_number, blocks_count);
+	}
 }
@@ -25,11 +25,11 @@ use log::trace;
 use codec::Codec;
 
 use sc_client_api::{backend::AuxStore, UsageProvider};
-use sp_api::{Core, ProvideRuntimeApi};
+use sp_api::{ApiExt, Core, ProvideRuntimeApi};
 use sp_application_crypto::{AppCrypto, AppPublic};
 use sp_blockchain::Result as CResult;
 use sp_consensus::Error as ConsensusError;
-use sp_consensus_slots::Slot;
+use sp_consensus_slots::{Slot, SlotSchedule};
 use sp_core::crypto::{ByteArray, Pair};
 use sp_keystore::KeystorePtr;
 use sp_runtime::{
@@ -65,6 +65,24 @@ where
 	client.runtime_api().slot_duration(block_hash).map_err(|err| err.into())
 }
 
+/// Get the slot schedule for the children of a given block by reading from a runtime API.
+///
+/// Slots are counted from the unix epoch using [`slot_duration_at`] if the runtime doesn't
+/// provide a slot schedule.
+pub fn slot_schedule_at<A, B, C>(client: &C, block_hash: B::Hash) -> CResult<SlotSchedule>
+where
+	A: Codec,
+	B: BlockT,
+	C: AuxStore + ProvideRuntimeApi<B>,
+	C::Api: AuraApi<B, A>,
+{
+	let runtime_api = client.runtime_api();
+	match runtime_api.api_version::<dyn AuraApi<B, A>>(block_hash)? {
+		Some(version) if version >= 2 => Ok(runtime_api.slot_schedule(block_hash)?),
+		_ => slot_duration_at(client, block_hash).map(SlotSchedule::from_slot_duration),
+	}
+}
+
 /// Get the slot author for given block along with authorities.
 pub fn slot_author<P: Pair>(slot: Slot, authorities: &[AuthorityId<P>]) -> Option<&AuthorityId<P>> {
 	if authorities.is_empty() {
@@ -286,9 +304,24 @@ pub enum SealVerificationError<Header> {
 ///
 /// This digest item will always return `Some` when used with `as_aura_seal`.
 pub fn check_header_slot_and_seal<B: BlockT, P: Pair>(
+	slot_now: Slot,
+	header: B::Header,
+	authorities: &[AuthorityId<P>],
+) -> Result<(B::Header, Slot, DigestItem), SealVerificationError<B::Header>>
+where
+	P::Signature: Codec,
+	P::Public: Codec + PartialEq + Clone,
+{
+	check_header_slot_and_seal_with::<B, P>(slot_now, header, authorities, None)
+}
+
+/// Like [`check_header_slot_and_seal`], but the signature isn't checked again if the expected
+/// author is `verified_author`, as returned by [`verify_seal`].
+pub(crate) fn check_header_slot_and_seal_with<B: BlockT, P: Pair>(
 	slot_now: Slot,
 	mut header: B::Header,
 	authorities: &[AuthorityId<P>],
+	verified_author: Option<&AuthorityId<P>>,
 ) -> Result<(B::Header, Slot, DigestItem), SealVerificationError<B::Header>>
 where
 	P::Signature: Codec,
@@ -312,7 +345,9 @@ where
 
 		let pre_hash = header.hash();
 
-		if P::verify(&sig, pre_hash.as_ref(), expected_author) {
+		if verified_author == Some(expected_author) ||
+			P::verify(&sig, pre_hash.as_ref(), expected_author)
+		{
 			Ok((header, slot, seal))
 		} else {
 			Err(SealVerificationError::BadSignature)
@@ -320,6 +355,27 @@ where
 	}
 }
 
+/// Verify the signature of the seal of `header` by the author of its slot among `authorities`.
+///
+/// Returns the author if the signature is valid. Unlike [`check_header_slot_and_seal`], the slot
+/// isn't checked against the current slot.
+pub(crate) fn verify_seal<B: BlockT, P: Pair>(
+	header: &B::Header,
+	authorities: &[AuthorityId<P>],
+) -> Option<AuthorityId<P>>
+where
+	P::Signature: Codec,
+	P::Public: Codec + PartialEq + Clone,
+{
+	let mut header = header.clone();
+	let seal = header.digest_mut().pop()?;
+	let sig = seal.as_aura_seal()?;
+	let slot = find_pre_digest::<B, P::Signature>(&header).ok()?;
+	let author = slot_author::<P>(slot, authorities)?;
+
+	P::verify(&sig, header.hash().as_ref(), author).then(|| author.clone())
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
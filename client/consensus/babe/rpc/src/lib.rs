@@ -28,14 +28,14 @@ use jsonrpsee::{
 };
 use serde::{Deserialize, Serialize};
 
-use sc_consensus_babe::{authorship, BabeWorkerHandle};
+use sc_consensus_babe::{authorship, BabeWorkerHandle, Epoch};
 use sc_consensus_epochs::Epoch as EpochT;
 use sc_rpc_api::DenyUnsafe;
 use sp_api::ProvideRuntimeApi;
 use sp_application_crypto::AppCrypto;
 use sp_blockchain::{Error as BlockChainError, HeaderBackend, HeaderMetadata};
 use sp_consensus::{Error as ConsensusError, SelectChain};
-use sp_consensus_babe::{digests::PreDigest, AuthorityId, BabeApi as BabeRuntimeApi};
+use sp_consensus_babe::{digests::PreDigest, AuthorityId, BabeApi as BabeRuntimeApi, Slot};
 use sp_core::crypto::ByteArray;
 use sp_keystore::KeystorePtr;
 use sp_runtime::traits::{Block as BlockT, Header as _};
@@ -49,6 +49,14 @@ pub trait BabeApi {
 	/// with the keys in the keystore.
 	#[method(name = "babe_epochAuthorship")]
 	async fn epoch_authorship(&self) -> RpcResult<HashMap<AuthorityId, EpochAuthorship>>;
+
+	/// Returns the predicted slot schedule of the given epoch, defaulting to the current one.
+	///
+	/// Secondary slot authors are predicted for all authorities, while primary slot leaders
+	/// are only predicted for the keys in the keystore. Only the current and the next epoch
+	/// are supported.
+	#[method(name = "babe_epochSlotSchedule")]
+	async fn epoch_slot_schedule(&self, epoch_index: Option<u64>) -> RpcResult<EpochSlotSchedule>;
 }
 
 /// Provides RPC methods for interacting with Babe.
@@ -78,6 +86,53 @@ impl<B: BlockT, C, SC> Babe<B, C, SC> {
 	}
 }
 
+impl<B, C, SC> Babe<B, C, SC>
+where
+	B: BlockT,
+	C: ProvideRuntimeApi<B>
+		+ HeaderBackend<B>
+		+ HeaderMetadata<B, Error = BlockChainError>
+		+ 'static,
+	C::Api: BabeRuntimeApi<B>,
+	SC: SelectChain<B> + Clone + 'static,
+{
+	/// Fetch the data of the epoch a child of the best block would belong to at `slot`, or at
+	/// the start of the current epoch if `slot` is `None`.
+	async fn epoch_data(&self, slot: Option<Slot>) -> Result<Epoch, Error> {
+		let best_header = self.select_chain.best_chain().map_err(Error::SelectChain).await?;
+
+		let slot = match slot {
+			Some(slot) => slot,
+			None => self
+				.client
+				.runtime_api()
+				.current_epoch_start(best_header.hash())
+				.map_err(|_| Error::FetchEpoch)?,
+		};
+
+		self.babe_worker_handle
+			.epoch_data_for_child_of(best_header.hash(), *best_header.number(), slot)
+			.await
+			.map_err(|_| Error::FetchEpoch)
+	}
+
+	/// Keys of the epoch's authorities available in the keystore, with their authority index.
+	fn local_keys(&self, epoch: &Epoch) -> Vec<(AuthorityId, usize)> {
+		epoch
+			.authorities
+			.iter()
+			.enumerate()
+			.filter_map(|(i, a)| {
+				if self.keystore.has_keys(&[(a.0.to_raw_vec(), AuthorityId::ID)]) {
+					Some((a.0.clone(), i))
+				} else {
+					None
+				}
+			})
+			.collect()
+	}
+}
+
 #[async_trait]
 impl<B: BlockT, C, SC> BabeApiServer for Babe<B, C, SC>
 where
@@ -92,37 +147,12 @@ where
 	async fn epoch_authorship(&self) -> RpcResult<HashMap<AuthorityId, EpochAuthorship>> {
 		self.deny_unsafe.check_if_safe()?;
 
-		let best_header = self.select_chain.best_chain().map_err(Error::SelectChain).await?;
-
-		let epoch_start = self
-			.client
-			.runtime_api()
-			.current_epoch_start(best_header.hash())
-			.map_err(|_| Error::FetchEpoch)?;
-
-		let epoch = self
-			.babe_worker_handle
-			.epoch_data_for_child_of(best_header.hash(), *best_header.number(), epoch_start)
-			.await
-			.map_err(|_| Error::FetchEpoch)?;
+		let epoch = self.epoch_data(None).await?;
 
 		let (epoch_start, epoch_end) = (epoch.start_slot(), epoch.end_slot());
 		let mut claims: HashMap<AuthorityId, EpochAuthorship> = HashMap::new();
 
-		let keys = {
-			epoch
-				.authorities
-				.iter()
-				.enumerate()
-				.filter_map(|(i, a)| {
-					if self.keystore.has_keys(&[(a.0.to_raw_vec(), AuthorityId::ID)]) {
-						Some((a.0.clone(), i))
-					} else {
-						None
-					}
-				})
-				.collect::<Vec<_>>()
-		};
+		let keys = self.local_keys(&epoch);
 
 		for slot in *epoch_start..*epoch_end {
 			if let Some((claim, key)) =
@@ -144,6 +174,40 @@ where
 
 		Ok(claims)
 	}
+
+	async fn epoch_slot_schedule(&self, epoch_index: Option<u64>) -> RpcResult<EpochSlotSchedule> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let current = self.epoch_data(None).await?;
+		let epoch = match epoch_index {
+			None => current,
+			Some(index) if index == current.epoch_index => current,
+			Some(index) if index == current.epoch_index + 1 => {
+				let next = self.epoch_data(Some(current.end_slot())).await?;
+				// The next epoch is only known once it has been announced.
+				if next.epoch_index != index {
+					return Err(Error::EpochNotAvailable(index).into())
+				}
+				next
+			},
+			Some(index) => return Err(Error::EpochNotAvailable(index).into()),
+		};
+
+		let keys = self.local_keys(&epoch);
+		let slots = (*epoch.start_slot()..*epoch.end_slot())
+			.map(|slot| {
+				let authors =
+					authorship::predict_slot_authors(slot.into(), &epoch, &self.keystore, &keys);
+				SlotSchedule { slot, primary: authors.primary, secondary: authors.secondary }
+			})
+			.collect();
+
+		Ok(EpochSlotSchedule {
+			epoch_index: epoch.epoch_index,
+			secondary_vrf: epoch.config.allowed_slots.is_secondary_vrf_slots_allowed(),
+			slots,
+		})
+	}
 }
 
 /// Holds information about the `slot`'s that can be claimed by a given key.
@@ -157,6 +221,28 @@ pub struct EpochAuthorship {
 	secondary_vrf: Vec<u64>,
 }
 
+/// Predicted slot schedule of an epoch.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct EpochSlotSchedule {
+	/// The index of the epoch.
+	epoch_index: u64,
+	/// Whether secondary slots are claimed with a VRF.
+	secondary_vrf: bool,
+	/// The predicted authors of each slot of the epoch.
+	slots: Vec<SlotSchedule>,
+}
+
+/// Predicted authors of a slot.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SlotSchedule {
+	/// The slot number.
+	slot: u64,
+	/// Local authorities able to claim the slot as primary slot leaders.
+	primary: Vec<AuthorityId>,
+	/// The secondary author of the slot, if secondary slots are allowed.
+	secondary: Option<AuthorityId>,
+}
+
 /// Top-level error type for the RPC handler.
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -166,6 +252,9 @@ pub enum Error {
 	/// Failed to fetch epoch data.
 	#[error("Failed to fetch epoch data")]
 	FetchEpoch,
+	/// The requested epoch is neither the current nor the next one.
+	#[error("Epoch data not available for epoch {0}")]
+	EpochNotAvailable(u64),
 }
 
 impl From<Error> for JsonRpseeError {
@@ -173,6 +262,7 @@ impl From<Error> for JsonRpseeError {
 		let error_code = match error {
 			Error::SelectChain(_) => 1,
 			Error::FetchEpoch => 2,
+			Error::EpochNotAvailable(_) => 3,
 		};
 
 		JsonRpseeError::Call(CallError::Custom(ErrorObject::owned(
@@ -236,6 +326,7 @@ mod tests {
 			spawner: &task_executor,
 			registry: None,
 			telemetry: None,
+			verification_parallelism: None,
 			offchain_tx_pool_factory: OffchainTransactionPoolFactory::new(
 				RejectAllTxPool::default(),
 			),
@@ -257,6 +348,38 @@ mod tests {
 		assert_eq!(&response.result, expected);
 	}
 
+	#[tokio::test]
+	async fn epoch_slot_schedule_works() {
+		let babe_rpc = test_babe_rpc_module(DenyUnsafe::No);
+		let api = babe_rpc.into_rpc();
+
+		let schedule: EpochSlotSchedule =
+			api.call("babe_epochSlotSchedule", [None::<u64>]).await.unwrap();
+		assert_eq!(schedule.epoch_index, 0);
+
+		// Matches the slots claimable by the local key, as returned by `babe_epochAuthorship`.
+		let alice: AuthorityId = Sr25519Keyring::Alice.public().into();
+		let primary = schedule
+			.slots
+			.iter()
+			.filter(|s| s.primary.contains(&alice))
+			.map(|s| s.slot)
+			.collect::<Vec<_>>();
+		let secondary = schedule
+			.slots
+			.iter()
+			.filter(|s| !s.primary.contains(&alice) && s.secondary.as_ref() == Some(&alice))
+			.map(|s| s.slot)
+			.collect::<Vec<_>>();
+		assert_eq!(primary, vec![0]);
+		assert_eq!(secondary, vec![1, 2, 4]);
+		assert!(schedule.slots.iter().all(|s| s.secondary.is_some()));
+
+		let request = r#"{"jsonrpc":"2.0","method":"babe_epochSlotSchedule","params":[5],"id":1}"#;
+		let (response, _) = api.raw_json_request(request).await.unwrap();
+		assert!(response.result.contains("Epoch data not available for epoch 5"));
+	}
+
 	#[tokio::test]
 	async fn epoch_authorship_is_unsafe() {
 		let babe_rpc = test_babe_rpc_module(DenyUnsafe::Yes);
@@ -221,6 +221,45 @@ pub fn claim_slot_using_keys(
 	})
 }
 
+/// Authors expected to claim a slot, as predicted by [`predict_slot_authors`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SlotAuthors {
+	/// Authorities able to claim the slot as primary slot leaders.
+	pub primary: Vec<AuthorityId>,
+	/// Authority assigned to the slot as secondary author, if secondary slots are allowed.
+	pub secondary: Option<AuthorityId>,
+}
+
+/// Predict which authorities can author a block at the given slot.
+///
+/// Secondary slot assignments are deterministic and thus predicted for all authorities of the
+/// epoch. Primary slot leadership requires evaluating the authority's VRF, so it is only
+/// predicted for the given `keys`, which are expected to be in the `keystore`.
+pub fn predict_slot_authors(
+	slot: Slot,
+	epoch: &Epoch,
+	keystore: &KeystorePtr,
+	keys: &[(AuthorityId, usize)],
+) -> SlotAuthors {
+	let primary = keys
+		.iter()
+		.filter_map(|key| {
+			claim_primary_slot(slot, epoch, epoch.config.c, keystore, std::slice::from_ref(key))
+		})
+		.map(|(_, authority_id)| authority_id)
+		.collect();
+
+	let secondary = if epoch.config.allowed_slots.is_secondary_plain_slots_allowed() ||
+		epoch.config.allowed_slots.is_secondary_vrf_slots_allowed()
+	{
+		secondary_slot_author(slot, &epoch.authorities, epoch.randomness).cloned()
+	} else {
+		None
+	};
+
+	SlotAuthors { primary, secondary }
+}
+
 /// Claim a primary slot if it is our turn.  Returns `None` if it is not our turn.
 /// This hashes the slot number, epoch, genesis hash, and chain randomness into
 /// the VRF.  If the VRF produces a value less than `threshold`, it is our turn,
@@ -307,4 +346,45 @@ mod tests {
 		epoch.authorities.push((valid_public_key.into(), 10));
 		assert_eq!(claim_slot(10.into(), &epoch, &keystore).unwrap().1, valid_public_key.into());
 	}
+
+	#[test]
+	fn predict_slot_authors_works() {
+		let keystore: KeystorePtr = MemoryKeystore::new().into();
+		let local_key: AuthorityId = keystore
+			.sr25519_generate_new(AuthorityId::ID, Some(sp_core::crypto::DEV_PHRASE))
+			.unwrap()
+			.into();
+
+		let mut epoch = Epoch {
+			epoch_index: 10,
+			start_slot: 0.into(),
+			duration: 20,
+			authorities: vec![
+				(AuthorityId::from(Pair::generate().0.public()), 1),
+				(local_key.clone(), 1),
+			],
+			randomness: Default::default(),
+			config: BabeEpochConfiguration {
+				c: (3, 10),
+				allowed_slots: AllowedSlots::PrimaryAndSecondaryPlainSlots,
+			},
+		};
+		let keys = vec![(local_key.clone(), 1)];
+
+		for slot in 0..20u64 {
+			let slot = slot.into();
+			let authors = predict_slot_authors(slot, &epoch, &keystore, &keys);
+			let primary = claim_primary_slot(slot, &epoch, epoch.config.c, &keystore, &keys)
+				.map(|(_, authority_id)| authority_id);
+			assert_eq!(authors.primary, primary.into_iter().collect::<Vec<_>>());
+			assert_eq!(
+				authors.secondary.as_ref(),
+				secondary_slot_author(slot, &epoch.authorities, epoch.randomness),
+			);
+		}
+
+		epoch.config.allowed_slots = AllowedSlots::PrimarySlots;
+		let authors = predict_slot_authors(0.into(), &epoch, &keystore, &[]);
+		assert_eq!(authors, SlotAuthors::default());
+	}
 }
@@ -69,6 +69,7 @@
 use std::{
 	collections::HashSet,
 	future::Future,
+	num::NonZeroUsize,
 	pin::Pin,
 	sync::Arc,
 	task::{Context, Poll},
@@ -139,6 +140,7 @@ pub use sp_consensus_babe::{
 
 pub use aux_schema::load_block_weight as block_weight;
 
+mod metrics;
 mod migration;
 mod verification;
 
@@ -985,15 +987,49 @@ impl<Block: BlockT> BabeLink<Block> {
 	}
 }
 
+/// Intermediate key of the header checked ahead of the import of its parent.
+const VERIFIED_AHEAD_KEY: &[u8] = b"babe_ahead";
+
+/// Header checked against `epoch` ahead of the import of its parent.
+struct VerifiedAheadOfParent<B: BlockT> {
+	epoch: Epoch,
+	pre_header: B::Header,
+	info: verification::VerifiedHeaderInfo,
+}
+
 /// A verifier for Babe blocks.
+///
+/// Clones share the inherent data providers factory, so that blocks can be verified in parallel.
+/// They also share the epoch of the last verified block, which the headers of blocks are checked
+/// against ahead of the import of their parent.
 pub struct BabeVerifier<Block: BlockT, Client, SelectChain, CIDP> {
 	client: Arc<Client>,
 	select_chain: SelectChain,
-	create_inherent_data_providers: CIDP,
+	create_inherent_data_providers: Arc<CIDP>,
 	config: BabeConfiguration,
 	epoch_changes: SharedEpochChanges<Block, Epoch>,
 	telemetry: Option<TelemetryHandle>,
 	offchain_tx_pool_factory: OffchainTransactionPoolFactory<Block>,
+	metrics: Option<metrics::Metrics>,
+	known_epoch: Arc<Mutex<Option<Epoch>>>,
+}
+
+impl<Block: BlockT, Client, SelectChain: Clone, CIDP> Clone
+	for BabeVerifier<Block, Client, SelectChain, CIDP>
+{
+	fn clone(&self) -> Self {
+		Self {
+			client: self.client.clone(),
+			select_chain: self.select_chain.clone(),
+			create_inherent_data_providers: self.create_inherent_data_providers.clone(),
+			config: self.config.clone(),
+			epoch_changes: self.epoch_changes.clone(),
+			telemetry: self.telemetry.clone(),
+			offchain_tx_pool_factory: self.offchain_tx_pool_factory.clone(),
+			metrics: self.metrics.clone(),
+			known_epoch: self.known_epoch.clone(),
+		}
+	}
 }
 
 impl<Block, Client, SelectChain, CIDP> BabeVerifier<Block, Client, SelectChain, CIDP>
@@ -1148,6 +1184,10 @@ where
 			block.body,
 		);
 
+		let verified_ahead = block
+			.remove_intermediate::<VerifiedAheadOfParent<Block>>(VERIFIED_AHEAD_KEY)
+			.ok();
+
 		let hash = block.header.hash();
 		let parent_hash = *block.header.parent_hash();
 
@@ -1185,6 +1225,13 @@ where
 			.map_err(Error::<Block>::FetchParentHeader)?;
 
 		let pre_digest = find_pre_digest::<Block>(&block.header)?;
+		// The parent slot is only needed for reporting missed slots, don't bother otherwise.
+		let parent_slot = self
+			.metrics
+			.as_ref()
+			.and_then(|_| self.client.header(parent_hash).ok().flatten())
+			.and_then(|parent_header| find_pre_digest::<Block>(&parent_header).ok())
+			.map(|parent_pre_digest| parent_pre_digest.slot());
 		let (check_header, epoch_descriptor) = {
 			let epoch_changes = self.epoch_changes.shared_data();
 			let epoch_descriptor = epoch_changes
@@ -1200,16 +1247,39 @@ where
 				.viable_epoch(&epoch_descriptor, |slot| Epoch::genesis(&self.config, slot))
 				.ok_or(Error::<Block>::FetchEpoch(parent_hash))?;
 
-			// We add one to the current slot to allow for some small drift.
-			// FIXME #1019 in the future, alter this queue to allow deferring of headers
-			let v_params = verification::VerificationParams {
-				header: block.header.clone(),
-				pre_digest: Some(pre_digest),
-				slot_now: slot_now + 1,
-				epoch: viable_epoch.as_ref(),
+			let epoch = viable_epoch.as_ref();
+			let check_header = match verified_ahead {
+				// The header was already checked against the same epoch, only the slot is left
+				// to check.
+				Some(verified) if verified.epoch == *epoch && pre_digest.slot() <= slot_now + 1 =>
+					CheckedHeader::Checked(verified.pre_header, verified.info),
+				_ => {
+					// We add one to the current slot to allow for some small drift.
+					// FIXME #1019 in the future, alter this queue to allow deferring of headers
+					let v_params = verification::VerificationParams {
+						header: block.header.clone(),
+						pre_digest: Some(pre_digest.clone()),
+						slot_now: slot_now + 1,
+						epoch,
+					};
+					verification::check_header::<Block>(v_params)?
+				},
 			};
 
-			(verification::check_header::<Block>(v_params)?, epoch_descriptor)
+			let mut known_epoch = self.known_epoch.lock();
+			if known_epoch.as_ref() != Some(epoch) {
+				*known_epoch = Some(epoch.clone());
+			}
+			drop(known_epoch);
+
+			// Slots observed during initial sync are most likely stale, don't report them.
+			if let (Some(metrics), CheckedHeader::Checked(..)) = (&self.metrics, &check_header) {
+				if block.origin != BlockOrigin::NetworkInitialSync {
+					metrics.report_slots(viable_epoch.as_ref(), parent_slot, &pre_digest);
+				}
+			}
+
+			(check_header, epoch_descriptor)
 		};
 
 		match check_header {
@@ -1294,6 +1364,46 @@ where
 			},
 		}
 	}
+
+	async fn verify_ahead_of_parent(
+		&mut self,
+		mut block: BlockImportParams<Block, ()>,
+	) -> Result<BlockImportParams<Block, ()>, String> {
+		if block.with_state() {
+			return Ok(block)
+		}
+
+		let Some(epoch) = self.known_epoch.lock().clone() else { return Ok(block) };
+		let Ok(pre_digest) = find_pre_digest::<Block>(&block.header) else { return Ok(block) };
+
+		// Only blocks of the epoch of the last verified block can be checked before their parent
+		// is imported, `verify` checks the header again if it turns out to be in another epoch.
+		if pre_digest.slot() < epoch.start_slot() || pre_digest.slot() >= epoch.end_slot() {
+			return Ok(block)
+		}
+
+		// The slot is checked against the current one by `verify`.
+		let v_params = verification::VerificationParams {
+			header: block.header.clone(),
+			pre_digest: Some(pre_digest),
+			slot_now: Slot::from(u64::MAX),
+			epoch: &epoch,
+		};
+		if let Ok(CheckedHeader::Checked(pre_header, info)) =
+			verification::check_header::<Block>(v_params)
+		{
+			block.insert_intermediate(
+				VERIFIED_AHEAD_KEY,
+				VerifiedAheadOfParent::<Block> { epoch, pre_header, info },
+			);
+		}
+
+		Ok(block)
+	}
+
+	fn verifies_ahead_of_parent(&self) -> bool {
+		true
+	}
 }
 
 /// A block-import handler for BABE.
@@ -1803,6 +1913,10 @@ pub struct ImportQueueParams<'a, Block: BlockT, BI, Client, CIDP, SelectChain, S
 	///
 	/// Will be used when sending equivocation reports.
 	pub offchain_tx_pool_factory: OffchainTransactionPoolFactory<Block>,
+	/// Number of blocks to verify in parallel ahead of their import.
+	///
+	/// If `None`, blocks are verified sequentially.
+	pub verification_parallelism: Option<NonZeroUsize>,
 }
 
 /// Start an import queue for the BABE consensus algorithm.
@@ -1826,6 +1940,7 @@ pub fn import_queue<Block: BlockT, Client, SelectChain, BI, CIDP, Spawn>(
 		registry,
 		telemetry,
 		offchain_tx_pool_factory,
+		verification_parallelism,
 	}: ImportQueueParams<'_, Block, BI, Client, CIDP, SelectChain, Spawn>,
 ) -> ClientResult<(DefaultImportQueue<Block, Client>, BabeWorkerHandle<Block>)>
 where
@@ -1853,12 +1968,20 @@ where
 
 	let verifier = BabeVerifier {
 		select_chain,
-		create_inherent_data_providers,
+		create_inherent_data_providers: Arc::new(create_inherent_data_providers),
 		config: babe_link.config.clone(),
 		epoch_changes: babe_link.epoch_changes.clone(),
 		telemetry,
 		client: client.clone(),
 		offchain_tx_pool_factory,
+		metrics: registry.and_then(|registry| {
+			metrics::Metrics::register(registry)
+				.map_err(
+					|err| warn!(target: LOG_TARGET, "Failed to register BABE metrics: {}", err),
+				)
+				.ok()
+		}),
+		known_epoch: Default::default(),
 	};
 
 	let (worker_tx, worker_rx) = channel(HANDLE_BUFFER_SIZE);
@@ -1868,10 +1991,20 @@ where
 
 	spawner.spawn_essential("babe-worker", Some("babe"), answer_requests.boxed());
 
-	Ok((
-		BasicQueue::new(verifier, Box::new(block_import), justification_import, spawner, registry),
-		BabeWorkerHandle(worker_tx),
-	))
+	let block_import = Box::new(block_import);
+	let import_queue = match verification_parallelism {
+		Some(parallelism) => BasicQueue::with_parallel_verification(
+			verifier,
+			block_import,
+			justification_import,
+			spawner,
+			registry,
+			parallelism,
+		),
+		None => BasicQueue::new(verifier, block_import, justification_import, spawner, registry),
+	};
+
+	Ok((import_queue, BabeWorkerHandle(worker_tx)))
 }
 
 /// Reverts protocol aux data to at most the last finalized block.
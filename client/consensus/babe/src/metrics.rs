@@ -0,0 +1,118 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Prometheus metrics about BABE slot leadership, observed in imported headers.
+
+use std::sync::{
+	atomic::{AtomicU64, Ordering},
+	Arc,
+};
+
+use prometheus_endpoint::{register, CounterVec, Opts, PrometheusError, Registry, U64};
+use sc_consensus_epochs::Epoch as EpochT;
+use sp_consensus_babe::digests::PreDigest;
+use sp_consensus_slots::Slot;
+
+use crate::{authorship::secondary_slot_author, Epoch};
+
+/// BABE slot leadership metrics.
+///
+/// Only the authorities of the latest epoch seen are reported, to bound the number of series.
+#[derive(Clone)]
+pub(crate) struct Metrics {
+	claimed_slots: CounterVec<U64>,
+	missed_secondary_slots: CounterVec<U64>,
+	epoch_index: Arc<AtomicU64>,
+}
+
+impl Metrics {
+	pub(crate) fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			claimed_slots: register(
+				CounterVec::new(
+					Opts::new(
+						"substrate_babe_claimed_slots_total",
+						"Number of slots claimed in imported headers of the current epoch, per \
+						 authority and slot kind",
+					),
+					&["authority", "kind"],
+				)?,
+				registry,
+			)?,
+			missed_secondary_slots: register(
+				CounterVec::new(
+					Opts::new(
+						"substrate_babe_missed_secondary_slots_total",
+						"Number of empty slots skipped in imported headers of the current epoch, \
+						 per assigned secondary author",
+					),
+					&["authority"],
+				)?,
+				registry,
+			)?,
+			epoch_index: Arc::new(AtomicU64::new(0)),
+		})
+	}
+
+	/// Report the slot claimed by a header of `epoch` with the given `pre_digest`, along with
+	/// the empty slots since its parent, authored at `parent_slot`.
+	///
+	/// Empty slots are accounted to their secondary author, if secondary slots are allowed. Empty
+	/// slots of previous epochs are ignored.
+	///
+	/// The series of the previous epoch are removed once a header of a newer epoch is reported,
+	/// and headers of older epochs are ignored.
+	pub(crate) fn report_slots(
+		&self,
+		epoch: &Epoch,
+		parent_slot: Option<Slot>,
+		pre_digest: &PreDigest,
+	) {
+		let latest_epoch = self.epoch_index.fetch_max(epoch.epoch_index, Ordering::Relaxed);
+		if epoch.epoch_index < latest_epoch {
+			return
+		}
+		if epoch.epoch_index > latest_epoch {
+			self.claimed_slots.reset();
+			self.missed_secondary_slots.reset();
+		}
+
+		let kind = match pre_digest {
+			PreDigest::Primary(_) => "primary",
+			PreDigest::SecondaryPlain(_) => "secondary_plain",
+			PreDigest::SecondaryVRF(_) => "secondary_vrf",
+		};
+		if let Some((author, _)) = epoch.authorities.get(pre_digest.authority_index() as usize) {
+			self.claimed_slots.with_label_values(&[&author.to_string(), kind]).inc();
+		}
+
+		let secondary_allowed = epoch.config.allowed_slots.is_secondary_plain_slots_allowed() ||
+			epoch.config.allowed_slots.is_secondary_vrf_slots_allowed();
+		let Some(parent_slot) = parent_slot.filter(|_| secondary_allowed) else { return };
+
+		let first = (*parent_slot + 1).max(*epoch.start_slot());
+		let last = (*pre_digest.slot()).min(*epoch.end_slot());
+		for slot in first..last {
+			if let Some(author) =
+				secondary_slot_author(slot.into(), &epoch.authorities, epoch.randomness)
+			{
+				self.missed_secondary_slots.with_label_values(&[&author.to_string()]).inc();
+			}
+		}
+	}
+}
@@ -274,19 +274,21 @@ impl TestNetFactory for BabeTestNet {
 			inner: BabeVerifier {
 				client: client.clone(),
 				select_chain: longest_chain,
-				create_inherent_data_providers: Box::new(|_, _| async {
+				create_inherent_data_providers: Arc::new(Box::new(|_, _| async {
 					let slot = InherentDataProvider::from_timestamp_and_slot_duration(
 						Timestamp::current(),
 						SlotDuration::from_millis(SLOT_DURATION_MS),
 					);
 					Ok((slot,))
-				}),
+				})),
 				config: data.link.config.clone(),
 				epoch_changes: data.link.epoch_changes.clone(),
 				telemetry: None,
 				offchain_tx_pool_factory: OffchainTransactionPoolFactory::new(
 					RejectAllTxPool::default(),
 				),
+				metrics: None,
+				known_epoch: Default::default(),
 			},
 			mutator: MUTATOR.with(|m| m.borrow().clone()),
 		}
@@ -564,6 +566,50 @@ fn claim_epoch_slots() {
 	assert_eq!(claims, [1, 0, 0, 1, 0, 0, 0, 0, 0, 1]);
 }
 
+#[test]
+fn slot_metrics_only_keep_series_of_latest_epoch() {
+	use sp_consensus_babe::digests::SecondaryPlainPreDigest;
+
+	let registry = prometheus_endpoint::Registry::new();
+	let metrics = metrics::Metrics::register(&registry).unwrap();
+
+	let epoch = |epoch_index, authority: Sr25519Keyring| Epoch {
+		start_slot: (epoch_index * 10).into(),
+		authorities: vec![(authority.public().into(), 1)],
+		randomness: [0; 32],
+		epoch_index,
+		duration: 10,
+		config: BabeEpochConfiguration { c: (3, 10), allowed_slots: AllowedSlots::PrimarySlots },
+	};
+	let claim = |slot: u64| {
+		PreDigest::SecondaryPlain(SecondaryPlainPreDigest { authority_index: 0, slot: slot.into() })
+	};
+	let reported_authorities = || {
+		registry
+			.gather()
+			.into_iter()
+			.filter(|family| family.get_name() == "substrate_babe_claimed_slots_total")
+			.flat_map(|family| family.get_metric().to_vec())
+			.flat_map(|metric| metric.get_label().to_vec())
+			.filter(|label| label.get_name() == "authority")
+			.map(|label| label.get_value().to_string())
+			.collect::<Vec<_>>()
+	};
+	let alice = AuthorityId::from(Sr25519Keyring::Alice.public()).to_string();
+	let bob = AuthorityId::from(Sr25519Keyring::Bob.public()).to_string();
+
+	metrics.report_slots(&epoch(1, Sr25519Keyring::Alice), None, &claim(10));
+	assert_eq!(reported_authorities(), vec![alice]);
+
+	// the authorities of the previous epoch are dropped.
+	metrics.report_slots(&epoch(2, Sr25519Keyring::Bob), None, &claim(20));
+	assert_eq!(reported_authorities(), vec![bob.clone()]);
+
+	// late headers of the previous epoch are ignored.
+	metrics.report_slots(&epoch(1, Sr25519Keyring::Alice), None, &claim(11));
+	assert_eq!(reported_authorities(), vec![bob]);
+}
+
 #[test]
 fn claim_vrf_check() {
 	let authority = Sr25519Keyring::Alice;
@@ -21,12 +21,17 @@
 #![warn(missing_docs)]
 
 use parking_lot::RwLock;
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use sc_rpc::SubscriptionTaskExecutor;
 use sp_runtime::traits::Block as BlockT;
 
-use futures::{task::SpawnError, FutureExt, StreamExt};
+use futures::{
+	future::{self, Either},
+	task::SpawnError,
+	FutureExt, StreamExt,
+};
+use futures_timer::Delay;
 use jsonrpsee::{
 	core::{async_trait, Error as JsonRpseeError, RpcResult},
 	proc_macros::rpc,
@@ -36,11 +41,15 @@ use jsonrpsee::{
 use log::warn;
 
 use sc_consensus_beefy::communication::notification::{
-	BeefyBestBlockStream, BeefyVersionedFinalityProofStream,
+	BeefyBestBlockStream, BeefyJustificationRequestSender, BeefyRequestedJustificationStream,
+	BeefyVersionedFinalityProofStream, JustificationRequestError,
 };
 
 mod notification;
 
+/// How long to wait for the voter to fetch a requested justification from peers.
+const JUSTIFICATION_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
 #[derive(Debug, thiserror::Error)]
 /// Top-level error type for the RPC handler
 pub enum Error {
@@ -50,6 +59,9 @@ pub enum Error {
 	/// The BEEFY RPC background task failed to spawn.
 	#[error("BEEFY RPC background task failed to spawn")]
 	RpcTaskFailure(#[from] SpawnError),
+	/// The requested BEEFY justification can't be provided.
+	#[error("BEEFY justification not available: {0}")]
+	JustificationUnavailable(#[from] JustificationRequestError),
 }
 
 /// The error codes returned by jsonrpc.
@@ -58,6 +70,8 @@ pub enum ErrorCode {
 	NotReady = 1,
 	/// Returned on BEEFY RPC background task failure.
 	TaskFailure = 2,
+	/// Returned when a requested BEEFY justification can't be provided.
+	JustificationUnavailable = 3,
 }
 
 impl From<Error> for ErrorCode {
@@ -65,6 +79,7 @@ impl From<Error> for ErrorCode {
 		match error {
 			Error::EndpointNotReady => ErrorCode::NotReady,
 			Error::RpcTaskFailure(_) => ErrorCode::TaskFailure,
+			Error::JustificationUnavailable(_) => ErrorCode::JustificationUnavailable,
 		}
 	}
 }
@@ -99,12 +114,23 @@ pub trait BeefyApi<Notification, Hash> {
 	/// In such case an error would be returned.
 	#[method(name = "beefy_getFinalizedHead")]
 	async fn latest_finalized(&self) -> RpcResult<Hash>;
+
+	/// Returns the BEEFY justification of the given block.
+	///
+	/// Justifications not stored locally are fetched from peers, which only works for blocks
+	/// already finalized by BEEFY. An error is returned for unknown or not yet finalized blocks,
+	/// and if no peer provided the justification. `None` is returned if the justification
+	/// couldn't be retrieved in time.
+	#[method(name = "beefy_getJustification")]
+	async fn justification(&self, block: Hash) -> RpcResult<Option<Notification>>;
 }
 
 /// Implements the BeefyApi RPC trait for interacting with BEEFY.
 pub struct Beefy<Block: BlockT> {
 	finality_proof_stream: BeefyVersionedFinalityProofStream<Block>,
 	beefy_best_block: Arc<RwLock<Option<Block::Hash>>>,
+	justification_request_sender: BeefyJustificationRequestSender<Block>,
+	requested_justification_stream: BeefyRequestedJustificationStream<Block>,
 	executor: SubscriptionTaskExecutor,
 }
 
@@ -116,6 +142,8 @@ where
 	pub fn new(
 		finality_proof_stream: BeefyVersionedFinalityProofStream<Block>,
 		best_block_stream: BeefyBestBlockStream<Block>,
+		justification_request_sender: BeefyJustificationRequestSender<Block>,
+		requested_justification_stream: BeefyRequestedJustificationStream<Block>,
 		executor: SubscriptionTaskExecutor,
 	) -> Result<Self, Error> {
 		let beefy_best_block = Arc::new(RwLock::new(None));
@@ -128,7 +156,13 @@ where
 		});
 
 		executor.spawn("substrate-rpc-subscription", Some("rpc"), future.map(drop).boxed());
-		Ok(Self { finality_proof_stream, beefy_best_block, executor })
+		Ok(Self {
+			finality_proof_stream,
+			beefy_best_block,
+			justification_request_sender,
+			requested_justification_stream,
+			executor,
+		})
 	}
 }
 
@@ -160,6 +194,28 @@ where
 			.ok_or(Error::EndpointNotReady)
 			.map_err(Into::into)
 	}
+
+	async fn justification(
+		&self,
+		block: Block::Hash,
+	) -> RpcResult<Option<notification::EncodedVersionedFinalityProof>> {
+		// Subscribe before sending the request so the response can't be missed.
+		let mut responses = Box::pin(
+			self.requested_justification_stream
+				.subscribe(100_000)
+				.filter_map(move |(hash, proof)| future::ready((hash == block).then_some(proof))),
+		);
+		self.justification_request_sender
+			.notify(|| Ok::<_, ()>(block))
+			.expect("forwards closure result; the closure always returns Ok; qed.");
+
+		match future::select(responses.next(), Delay::new(JUSTIFICATION_REQUEST_TIMEOUT)).await {
+			Either::Left((Some(Ok(proof)), _)) =>
+				Ok(Some(notification::EncodedVersionedFinalityProof::new::<Block>(proof))),
+			Either::Left((Some(Err(e)), _)) => Err(Error::from(e).into()),
+			Either::Left((None, _)) | Either::Right(_) => Ok(None),
+		}
+	}
 }
 
 #[cfg(test)]
@@ -169,7 +225,9 @@ mod tests {
 	use codec::{Decode, Encode};
 	use jsonrpsee::{types::EmptyServerParams as EmptyParams, RpcModule};
 	use sc_consensus_beefy::{
-		communication::notification::BeefyVersionedFinalityProofSender,
+		communication::notification::{
+			BeefyJustificationRequestStream, BeefyVersionedFinalityProofSender,
+		},
 		justification::BeefyVersionedFinalityProof,
 	};
 	use sp_consensus_beefy::{known_payloads, Payload, SignedCommitment};
@@ -186,10 +244,18 @@ mod tests {
 	) -> (RpcModule<Beefy<Block>>, BeefyVersionedFinalityProofSender<Block>) {
 		let (finality_proof_sender, finality_proof_stream) =
 			BeefyVersionedFinalityProofStream::<Block>::channel();
-
-		let handler =
-			Beefy::new(finality_proof_stream, best_block_stream, sc_rpc::testing::test_executor())
-				.expect("Setting up the BEEFY RPC handler works");
+		let (justification_request_sender, _) = BeefyJustificationRequestStream::<Block>::channel();
+		let (_, requested_justification_stream) =
+			BeefyRequestedJustificationStream::<Block>::channel();
+
+		let handler = Beefy::new(
+			finality_proof_stream,
+			best_block_stream,
+			justification_request_sender,
+			requested_justification_stream,
+			sc_rpc::testing::test_executor(),
+		)
+		.expect("Setting up the BEEFY RPC handler works");
 
 		(handler.into_rpc(), finality_proof_sender)
 	}
@@ -278,6 +344,87 @@ mod tests {
 		})
 	}
 
+	#[tokio::test]
+	async fn justification_rpc_answers_requested_block() {
+		let (_, best_block_stream) = BeefyBestBlockStream::<Block>::channel();
+		let (_, finality_proof_stream) = BeefyVersionedFinalityProofStream::<Block>::channel();
+		let (justification_request_sender, justification_request_stream) =
+			BeefyJustificationRequestStream::<Block>::channel();
+		let (requested_justification_sender, requested_justification_stream) =
+			BeefyRequestedJustificationStream::<Block>::channel();
+		let rpc = Beefy::new(
+			finality_proof_stream,
+			best_block_stream,
+			justification_request_sender,
+			requested_justification_stream,
+			sc_rpc::testing::test_executor(),
+		)
+		.expect("Setting up the BEEFY RPC handler works")
+		.into_rpc();
+
+		// Answer requests like the voter would, also sending a justification for another block.
+		let finality_proof = create_finality_proof();
+		let proof = finality_proof.clone();
+		let mut requests = justification_request_stream.subscribe(100_000);
+		tokio::spawn(async move {
+			while let Some(hash) = requests.next().await {
+				let other = BlakeTwo256::hash(b"other");
+				let r: Result<(), ()> =
+					requested_justification_sender.notify(|| Ok((other, Ok(proof.clone()))));
+				r.unwrap();
+				let r: Result<(), ()> =
+					requested_justification_sender.notify(|| Ok((hash, Ok(proof.clone()))));
+				r.unwrap();
+			}
+		});
+
+		let hash = BlakeTwo256::hash(b"42");
+		let response: Option<sp_core::Bytes> =
+			rpc.call("beefy_getJustification", [hash]).await.unwrap();
+		let recv_finality_proof: BeefyVersionedFinalityProof<Block> =
+			Decode::decode(&mut &response.unwrap()[..]).unwrap();
+		assert_eq!(recv_finality_proof, finality_proof);
+	}
+
+	#[tokio::test]
+	async fn justification_rpc_fails_fast_for_rejected_requests() {
+		let (_, best_block_stream) = BeefyBestBlockStream::<Block>::channel();
+		let (_, finality_proof_stream) = BeefyVersionedFinalityProofStream::<Block>::channel();
+		let (justification_request_sender, justification_request_stream) =
+			BeefyJustificationRequestStream::<Block>::channel();
+		let (requested_justification_sender, requested_justification_stream) =
+			BeefyRequestedJustificationStream::<Block>::channel();
+		let rpc = Beefy::new(
+			finality_proof_stream,
+			best_block_stream,
+			justification_request_sender,
+			requested_justification_stream,
+			sc_rpc::testing::test_executor(),
+		)
+		.expect("Setting up the BEEFY RPC handler works")
+		.into_rpc();
+
+		// Reject requests like the voter would for not yet finalized blocks.
+		let mut requests = justification_request_stream.subscribe(100_000);
+		tokio::spawn(async move {
+			while let Some(hash) = requests.next().await {
+				let r: Result<(), ()> = requested_justification_sender
+					.notify(|| Ok((hash, Err(JustificationRequestError::NotFinalized))));
+				r.unwrap();
+			}
+		});
+
+		// The rejection is returned right away instead of waiting for the request to time out.
+		let request = format!(
+			r#"{{"jsonrpc":"2.0","method":"beefy_getJustification","params":["{:?}"],"id":1}}"#,
+			BlakeTwo256::hash(b"42"),
+		);
+		let expected_response = r#"{"jsonrpc":"2.0","error":{"code":3,"message":"BEEFY justification not available: block not finalized by BEEFY yet"},"id":1}"#.to_string();
+		let (response, _) = rpc.raw_json_request(&request).await.unwrap();
+
+		assert_eq!(expected_response, response.result);
+	}
+
 	#[tokio::test]
 	async fn subscribe_and_listen_to_one_justification() {
 		let (rpc, finality_proof_sender) = setup_io_handler();
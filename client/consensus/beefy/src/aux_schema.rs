@@ -18,7 +18,7 @@
 
 //! Schema for BEEFY state persisted in the aux-db.
 
-use crate::{worker::PersistedState, LOG_TARGET};
+use crate::{fetcher::BackfillState, worker::PersistedState, LOG_TARGET};
 use codec::{Decode, Encode};
 use log::{info, trace};
 use sc_client_api::{backend::AuxStore, Backend};
@@ -27,6 +27,7 @@ use sp_runtime::traits::Block as BlockT;
 
 const VERSION_KEY: &[u8] = b"beefy_auxschema_version";
 const WORKER_STATE_KEY: &[u8] = b"beefy_voter_state";
+const BACKFILL_STATE_KEY: &[u8] = b"beefy_justification_backfill";
 
 const CURRENT_VERSION: u32 = 4;
 
@@ -44,6 +45,22 @@ pub(crate) fn write_voter_state<B: BlockT, BE: AuxStore>(
 	AuxStore::insert_aux(backend, &[(WORKER_STATE_KEY, state.encode().as_slice())], &[])
 }
 
+/// Write justification backfill progress.
+pub(crate) fn write_backfill_state<B: BlockT, BE: AuxStore>(
+	backend: &BE,
+	state: &BackfillState<B>,
+) -> ClientResult<()> {
+	trace!(target: LOG_TARGET, "🥩 persisting {:?}", state);
+	AuxStore::insert_aux(backend, &[(BACKFILL_STATE_KEY, state.encode().as_slice())], &[])
+}
+
+/// Load justification backfill progress, if any.
+pub(crate) fn load_backfill_state<B: BlockT, BE: AuxStore>(
+	backend: &BE,
+) -> ClientResult<Option<BackfillState<B>>> {
+	load_decode(backend, BACKFILL_STATE_KEY)
+}
+
 fn load_decode<BE: AuxStore, T: Decode>(backend: &BE, key: &[u8]) -> ClientResult<Option<T>> {
 	match backend.get_aux(key)? {
 		None => Ok(None),
@@ -40,6 +40,48 @@ pub type BeefyVersionedFinalityProofSender<Block> =
 pub type BeefyVersionedFinalityProofStream<Block> =
 	NotificationStream<BeefyVersionedFinalityProof<Block>, BeefyVersionedFinalityProofTracingKey>;
 
+/// The sending half of the notifications channel(s) used to request the BEEFY
+/// justification of a finalized block, e.g. on behalf of RPC clients.
+pub type BeefyJustificationRequestSender<Block> = NotificationSender<<Block as BlockT>::Hash>;
+
+/// The receiving half of a notifications channel used to receive requests for
+/// BEEFY justifications of finalized blocks.
+pub type BeefyJustificationRequestStream<Block> =
+	NotificationStream<<Block as BlockT>::Hash, BeefyJustificationRequestTracingKey>;
+
+/// Reasons why a requested BEEFY justification can't be provided.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum JustificationRequestError {
+	/// The block is unknown.
+	#[error("unknown block")]
+	UnknownBlock,
+	/// The block isn't finalized by BEEFY yet.
+	#[error("block not finalized by BEEFY yet")]
+	NotFinalized,
+	/// Too many justifications are being fetched already.
+	#[error("too many pending justification requests")]
+	TooManyRequests,
+	/// No peer provided the justification.
+	#[error("justification not provided by any peer")]
+	Unavailable,
+}
+
+/// The outcome of a BEEFY justification request, along with the hash of the requested block.
+pub type BeefyRequestedJustification<Block> = (
+	<Block as BlockT>::Hash,
+	Result<BeefyVersionedFinalityProof<Block>, JustificationRequestError>,
+);
+
+/// The sending half of the notifications channel(s) used to answer the requests
+/// for BEEFY justifications.
+pub type BeefyRequestedJustificationSender<Block> =
+	NotificationSender<BeefyRequestedJustification<Block>>;
+
+/// The receiving half of a notifications channel used to receive the answers to
+/// requests for BEEFY justifications.
+pub type BeefyRequestedJustificationStream<Block> =
+	NotificationStream<BeefyRequestedJustification<Block>, BeefyRequestedJustificationTracingKey>;
+
 /// Provides tracing key for BEEFY best block stream.
 #[derive(Clone)]
 pub struct BeefyBestBlockTracingKey;
@@ -53,3 +95,17 @@ pub struct BeefyVersionedFinalityProofTracingKey;
 impl TracingKeyStr for BeefyVersionedFinalityProofTracingKey {
 	const TRACING_KEY: &'static str = "mpsc_beefy_versioned_finality_proof_notification_stream";
 }
+
+/// Provides tracing key for BEEFY justification request stream.
+#[derive(Clone)]
+pub struct BeefyJustificationRequestTracingKey;
+impl TracingKeyStr for BeefyJustificationRequestTracingKey {
+	const TRACING_KEY: &'static str = "mpsc_beefy_justification_request_notification_stream";
+}
+
+/// Provides tracing key for BEEFY requested justification stream.
+#[derive(Clone)]
+pub struct BeefyRequestedJustificationTracingKey;
+impl TracingKeyStr for BeefyRequestedJustificationTracingKey {
+	const TRACING_KEY: &'static str = "mpsc_beefy_requested_justification_notification_stream";
+}
@@ -132,13 +132,20 @@ impl<B: Block> OnDemandJustificationsEngine<B> {
 		self.state = State::AwaitingResponse(peer, req_info, rx);
 	}
 
+	/// Whether there's no request in progress.
+	pub fn is_idle(&self) -> bool {
+		matches!(self.state, State::Idle)
+	}
+
 	/// Start new justification request for `block`, if no other request is in progress.
 	///
 	/// `active_set` will be used to verify validity of potential responses.
-	pub fn request(&mut self, block: NumberFor<B>, active_set: ValidatorSet<AuthorityId>) {
+	///
+	/// Returns whether a new request was started.
+	pub fn request(&mut self, block: NumberFor<B>, active_set: ValidatorSet<AuthorityId>) -> bool {
 		// ignore new requests while there's already one pending
 		if matches!(self.state, State::AwaitingResponse(_, _, _)) {
-			return
+			return false
 		}
 		self.reset_peers_cache_for_block(block);
 
@@ -146,12 +153,14 @@ impl<B: Block> OnDemandJustificationsEngine<B> {
 		// trigger a new request to the next peer in the `peers_cache` until there are none left.
 		if let Some(peer) = self.try_next_peer() {
 			self.request_from_peer(peer, RequestInfo { block, active_set });
+			true
 		} else {
 			metric_inc!(self, beefy_on_demand_justification_no_peer_to_request_from);
 			debug!(
 				target: BEEFY_SYNC_LOG_TARGET,
 				"🥩 no good peers to request justif #{:?} from", block
 			);
+			false
 		}
 	}
 
@@ -0,0 +1,320 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Fetching BEEFY justifications of already finalized blocks from peers.
+//!
+//! Justifications are fetched on request of RPC clients, and to backfill the justifications of
+//! mandatory blocks missing locally, e.g. because the blocks were imported through warp sync.
+
+use std::collections::BTreeMap;
+
+use codec::{Decode, Encode};
+use log::{debug, warn};
+use sc_client_api::{backend::AuxStore, Backend};
+use sp_blockchain::{Backend as BlockchainBackend, HeaderBackend, Result as ClientResult};
+use sp_consensus_beefy::{crypto::AuthorityId, ValidatorSet, BEEFY_ENGINE_ID};
+use sp_runtime::traits::{Block, Header, NumberFor, Zero};
+
+use crate::{aux_schema, worker::find_authorities_change, LOG_TARGET};
+
+/// Maximum number of headers scanned for mandatory blocks per call to
+/// [`JustificationFetcher::backfill`].
+const BACKFILL_SCAN_BATCH: usize = 512;
+
+/// Maximum number of blocks requested through RPC queued for fetching.
+pub(crate) const MAX_REQUESTED_JUSTIFICATIONS: usize = 128;
+
+/// Number of times all peers are asked for a justification before giving up on it.
+pub(crate) const MAX_FETCH_ATTEMPTS: u32 = 3;
+
+/// A block whose justification should be fetched from peers.
+#[derive(Debug, Decode, Encode, PartialEq)]
+struct Fetch {
+	/// The validator set that signed the block.
+	active_set: ValidatorSet<AuthorityId>,
+	/// Number of times all peers were asked for the justification.
+	attempts: u32,
+}
+
+impl Fetch {
+	fn new(active_set: ValidatorSet<AuthorityId>) -> Self {
+		Fetch { active_set, attempts: 0 }
+	}
+}
+
+/// Progress of the justification backfill, persisted in the aux-db.
+#[derive(Debug, Decode, Encode, PartialEq)]
+pub(crate) struct BackfillState<B: Block> {
+	/// Mandatory blocks missing their justification.
+	pending: BTreeMap<NumberFor<B>, Fetch>,
+	/// Next header to scan for mandatory blocks, walking back towards BEEFY genesis.
+	/// `None` once the scan is complete.
+	scan_from: Option<B::Hash>,
+}
+
+/// Queue of blocks whose justification should be fetched from peers.
+pub(crate) struct JustificationFetcher<B: Block> {
+	/// Blocks requested through RPC.
+	requested: BTreeMap<NumberFor<B>, Fetch>,
+	/// Backfill of the mandatory blocks missing their justification.
+	backfill: BackfillState<B>,
+	/// Whether `backfill` changed since it was last persisted.
+	backfill_changed: bool,
+}
+
+impl<B: Block> JustificationFetcher<B> {
+	/// Create a new fetcher, backfilling mandatory blocks from `scan_from` back to BEEFY
+	/// genesis.
+	pub fn new(scan_from: Option<B::Hash>) -> Self {
+		JustificationFetcher {
+			requested: BTreeMap::new(),
+			backfill: BackfillState { pending: BTreeMap::new(), scan_from },
+			backfill_changed: true,
+		}
+	}
+
+	/// Resume the backfill persisted in `backend`, or start a new one from `scan_from`.
+	///
+	/// Mandatory blocks finalized after the backfill started are justified by the voter itself,
+	/// so a resumed backfill doesn't need to scan them.
+	pub fn load<BE: AuxStore>(backend: &BE, scan_from: Option<B::Hash>) -> ClientResult<Self> {
+		Ok(match aux_schema::load_backfill_state::<B, _>(backend)? {
+			Some(backfill) => JustificationFetcher {
+				requested: BTreeMap::new(),
+				backfill,
+				backfill_changed: false,
+			},
+			None => Self::new(scan_from),
+		})
+	}
+
+	/// Persist the backfill progress, if it changed since the last call.
+	pub fn persist<BE: AuxStore>(&mut self, backend: &BE) -> ClientResult<()> {
+		if self.backfill_changed {
+			aux_schema::write_backfill_state(backend, &self.backfill)?;
+			self.backfill_changed = false;
+		}
+		Ok(())
+	}
+
+	/// Queue fetching the justification of `block`, which is verified against `active_set`.
+	///
+	/// Returns `false` if too many blocks are queued already.
+	pub fn request(&mut self, block: NumberFor<B>, active_set: ValidatorSet<AuthorityId>) -> bool {
+		if !self.requested.contains_key(&block) &&
+			self.requested.len() >= MAX_REQUESTED_JUSTIFICATIONS
+		{
+			return false
+		}
+		self.requested.entry(block).or_insert_with(|| Fetch::new(active_set));
+		true
+	}
+
+	/// Return the next block to fetch, along with its validator set. Blocks requested through
+	/// RPC take priority over the backfill.
+	pub fn next(&self) -> Option<(NumberFor<B>, ValidatorSet<AuthorityId>)> {
+		self.requested
+			.first_key_value()
+			.or_else(|| self.backfill.pending.first_key_value())
+			.map(|(block, fetch)| (*block, fetch.active_set.clone()))
+	}
+
+	/// Note that all peers were asked for the justification of `block`.
+	pub fn note_attempt(&mut self, block: NumberFor<B>) {
+		if let Some(fetch) = self.requested.get_mut(&block) {
+			fetch.attempts += 1;
+		} else if let Some(fetch) = self.backfill.pending.get_mut(&block) {
+			fetch.attempts += 1;
+			self.backfill_changed = true;
+		}
+	}
+
+	/// Give up on the blocks whose justification couldn't be fetched in
+	/// [`MAX_FETCH_ATTEMPTS`], returning the ones requested through RPC.
+	pub fn drop_exhausted(&mut self) -> Vec<NumberFor<B>> {
+		let exhausted = |fetch: &Fetch| fetch.attempts >= MAX_FETCH_ATTEMPTS;
+
+		let before = self.backfill.pending.len();
+		self.backfill.pending.retain(|block, fetch| {
+			if exhausted(fetch) {
+				warn!(
+					target: LOG_TARGET,
+					"🥩 Give up backfilling justification for mandatory block #{:?}.", block
+				);
+			}
+			!exhausted(fetch)
+		});
+		self.backfill_changed |= self.backfill.pending.len() != before;
+
+		let dropped = self
+			.requested
+			.iter()
+			.filter(|(_, fetch)| exhausted(fetch))
+			.map(|(block, _)| *block)
+			.collect::<Vec<_>>();
+		for block in &dropped {
+			debug!(target: LOG_TARGET, "🥩 Give up fetching justification for block #{:?}.", block);
+			self.requested.remove(block);
+		}
+		dropped
+	}
+
+	/// Note that the justification of `block` was fetched, returning whether it was queued.
+	pub fn note_fetched(&mut self, block: NumberFor<B>) -> bool {
+		let requested = self.requested.remove(&block).is_some();
+		let backfilled = self.backfill.pending.remove(&block).is_some();
+		self.backfill_changed |= backfilled;
+		requested || backfilled
+	}
+
+	/// Scan the next batch of headers for mandatory blocks missing their justification.
+	///
+	/// The scan pauses at headers which aren't available yet, as is the case while the block
+	/// history is downloaded after warp sync, and resumes from there on the next call.
+	pub fn backfill<BE: Backend<B>>(&mut self, backend: &BE, beefy_genesis: NumberFor<B>) {
+		let blockchain = backend.blockchain();
+
+		for _ in 0..BACKFILL_SCAN_BATCH {
+			let Some(hash) = self.backfill.scan_from else { return };
+			let header = match blockchain.header(hash) {
+				Ok(Some(header)) => header,
+				_ => return,
+			};
+			let number = *header.number();
+			self.backfill_changed = true;
+
+			if number < beefy_genesis {
+				self.backfill.scan_from = None;
+				return
+			}
+
+			if let Some(active_set) = find_authorities_change::<B>(&header) {
+				let has_justification = blockchain
+					.justifications(hash)
+					.ok()
+					.flatten()
+					.map_or(false, |justifs| justifs.get(BEEFY_ENGINE_ID).is_some());
+
+				if !has_justification {
+					debug!(
+						target: LOG_TARGET,
+						"🥩 Queue justification backfill for mandatory block #{:?}.", number
+					);
+					self.backfill.pending.insert(number, Fetch::new(active_set));
+				}
+			}
+
+			self.backfill.scan_from = if number.is_zero() || number == beefy_genesis {
+				None
+			} else {
+				Some(*header.parent_hash())
+			};
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::tests::{make_beefy_ids, BeefyTestNet};
+	use sc_network_test::TestNetFactory;
+	use sp_consensus_beefy::Keyring;
+	use substrate_test_runtime_client::runtime::Block;
+
+	fn set(id: u64) -> ValidatorSet<AuthorityId> {
+		ValidatorSet::new(make_beefy_ids(&[Keyring::Alice]), id).unwrap()
+	}
+
+	#[test]
+	fn requested_blocks_take_priority_over_backfill() {
+		let mut fetcher = JustificationFetcher::<Block>::new(None);
+		assert!(fetcher.next().is_none());
+
+		fetcher.backfill.pending.insert(10, Fetch::new(set(1)));
+		assert!(fetcher.request(20, set(2)));
+		assert_eq!(fetcher.next(), Some((20, set(2))));
+
+		assert!(fetcher.note_fetched(20));
+		assert!(!fetcher.note_fetched(20));
+		assert_eq!(fetcher.next(), Some((10, set(1))));
+
+		assert!(fetcher.note_fetched(10));
+		assert!(fetcher.next().is_none());
+	}
+
+	#[test]
+	fn requested_blocks_are_bounded() {
+		let mut fetcher = JustificationFetcher::<Block>::new(None);
+		for block in 0..MAX_REQUESTED_JUSTIFICATIONS as u64 {
+			assert!(fetcher.request(block, set(1)));
+		}
+
+		// already queued blocks can still be requested, others are rejected.
+		assert!(fetcher.request(0, set(1)));
+		assert!(!fetcher.request(MAX_REQUESTED_JUSTIFICATIONS as u64, set(1)));
+
+		assert!(fetcher.note_fetched(0));
+		assert!(fetcher.request(MAX_REQUESTED_JUSTIFICATIONS as u64, set(1)));
+	}
+
+	#[test]
+	fn gives_up_after_max_attempts() {
+		let mut fetcher = JustificationFetcher::<Block>::new(None);
+		fetcher.backfill.pending.insert(10, Fetch::new(set(1)));
+		assert!(fetcher.request(20, set(2)));
+
+		for _ in 0..MAX_FETCH_ATTEMPTS {
+			assert!(fetcher.drop_exhausted().is_empty());
+			let (block, _) = fetcher.next().unwrap();
+			assert_eq!(block, 20);
+			fetcher.note_attempt(block);
+		}
+		assert_eq!(fetcher.drop_exhausted(), vec![20]);
+
+		for _ in 0..MAX_FETCH_ATTEMPTS {
+			let (block, _) = fetcher.next().unwrap();
+			assert_eq!(block, 10);
+			fetcher.note_attempt(block);
+		}
+		assert!(fetcher.drop_exhausted().is_empty());
+		assert!(fetcher.next().is_none());
+	}
+
+	#[tokio::test]
+	async fn backfill_progress_is_persisted() {
+		let mut net = BeefyTestNet::new(1);
+		let backend = net.peer(0).client().as_backend();
+		let genesis = net.peer(0).client().info().genesis_hash;
+
+		// nothing persisted yet, start scanning from the given block.
+		let mut fetcher = JustificationFetcher::<Block>::load(&*backend, Some(genesis)).unwrap();
+		assert_eq!(fetcher.backfill.scan_from, Some(genesis));
+
+		fetcher.backfill.pending.insert(10, Fetch::new(set(1)));
+		fetcher.note_attempt(10);
+		fetcher.persist(&*backend).unwrap();
+
+		// a restarted fetcher resumes the persisted backfill.
+		let fetcher = JustificationFetcher::<Block>::load(&*backend, None).unwrap();
+		assert_eq!(fetcher.backfill.scan_from, Some(genesis));
+		assert_eq!(
+			fetcher.backfill.pending.get(&10),
+			Some(&Fetch { active_set: set(1), attempts: 1 })
+		);
+	}
+}
@@ -19,7 +19,9 @@
 use crate::{
 	communication::{
 		notification::{
-			BeefyBestBlockSender, BeefyBestBlockStream, BeefyVersionedFinalityProofSender,
+			BeefyBestBlockSender, BeefyBestBlockStream, BeefyJustificationRequestSender,
+			BeefyJustificationRequestStream, BeefyRequestedJustificationSender,
+			BeefyRequestedJustificationStream, BeefyVersionedFinalityProofSender,
 			BeefyVersionedFinalityProofStream,
 		},
 		peers::KnownPeers,
@@ -27,6 +29,7 @@ use crate::{
 			outgoing_requests_engine::OnDemandJustificationsEngine, BeefyJustifsRequestHandler,
 		},
 	},
+	fetcher::JustificationFetcher,
 	import::BeefyBlockImport,
 	metrics::register_metrics,
 	round::Rounds,
@@ -59,6 +62,7 @@ use std::{
 
 mod aux_schema;
 mod error;
+mod fetcher;
 mod keystore;
 mod metrics;
 mod round;
@@ -117,6 +121,12 @@ pub struct BeefyVoterLinks<B: Block> {
 	pub to_rpc_justif_sender: BeefyVersionedFinalityProofSender<B>,
 	/// Sends BEEFY best block hashes from voter to RPC.
 	pub to_rpc_best_block_sender: BeefyBestBlockSender<B>,
+	/// Sends requested BEEFY justifications from voter to RPC.
+	pub to_rpc_requested_justif_sender: BeefyRequestedJustificationSender<B>,
+
+	// RPC -> Voter links
+	/// Stream of BEEFY justification requests from RPC to voter.
+	pub from_rpc_justif_request_stream: BeefyJustificationRequestStream<B>,
 }
 
 /// Links used by the BEEFY RPC layer, from the BEEFY background voter.
@@ -126,6 +136,10 @@ pub struct BeefyRPCLinks<B: Block> {
 	pub from_voter_justif_stream: BeefyVersionedFinalityProofStream<B>,
 	/// Stream of BEEFY best block hashes coming from the voter.
 	pub from_voter_best_beefy_stream: BeefyBestBlockStream<B>,
+	/// Stream of requested BEEFY justifications coming from the voter.
+	pub from_voter_requested_justif_stream: BeefyRequestedJustificationStream<B>,
+	/// Sends BEEFY justification requests from RPC to voter.
+	pub to_voter_justif_request_sender: BeefyJustificationRequestSender<B>,
 }
 
 /// Make block importer and link half necessary to tie the background voter to it.
@@ -149,6 +163,12 @@ where
 		BeefyVersionedFinalityProofStream::<B>::channel();
 	let (to_rpc_best_block_sender, from_voter_best_beefy_stream) =
 		BeefyBestBlockStream::<B>::channel();
+	let (to_rpc_requested_justif_sender, from_voter_requested_justif_stream) =
+		BeefyRequestedJustificationStream::<B>::channel();
+
+	// RPC -> Voter links
+	let (to_voter_justif_request_sender, from_rpc_justif_request_stream) =
+		BeefyJustificationRequestStream::<B>::channel();
 
 	// BlockImport -> Voter links
 	let (to_voter_justif_sender, from_block_import_justif_stream) =
@@ -167,8 +187,15 @@ where
 		from_block_import_justif_stream,
 		to_rpc_justif_sender,
 		to_rpc_best_block_sender,
+		to_rpc_requested_justif_sender,
+		from_rpc_justif_request_stream,
+	};
+	let rpc_links = BeefyRPCLinks {
+		from_voter_best_beefy_stream,
+		from_voter_justif_stream,
+		from_voter_requested_justif_stream,
+		to_voter_justif_request_sender,
 	};
-	let rpc_links = BeefyRPCLinks { from_voter_best_beefy_stream, from_voter_justif_stream };
 
 	(import, voter_links, rpc_links)
 }
@@ -305,6 +332,19 @@ pub async fn start_beefy_gadget<B, BE, C, N, P, R, S>(
 		return
 	}
 
+	// Backfill justifications of mandatory blocks from the latest finalized block down to
+	// BEEFY genesis, or resume the backfill of a previous run.
+	let justification_fetcher = match JustificationFetcher::load(
+		&*backend,
+		Some(backend.blockchain().info().finalized_hash),
+	) {
+		Ok(fetcher) => fetcher,
+		Err(e) => {
+			error!(target: LOG_TARGET, "Error: {:?}. Terminating.", e);
+			return
+		},
+	};
+
 	let worker = worker::BeefyWorker {
 		backend,
 		payload_provider,
@@ -318,6 +358,7 @@ pub async fn start_beefy_gadget<B, BE, C, N, P, R, S>(
 		links,
 		metrics,
 		pending_justifications: BTreeMap::new(),
+		justification_fetcher,
 		persisted_state,
 	};
 
@@ -61,6 +61,8 @@ pub struct VoterMetrics {
 	pub beefy_imported_justifications: Counter<U64>,
 	/// Number of justifications dropped due to full buffers
 	pub beefy_buffered_justifications_dropped: Counter<U64>,
+	/// Number of justifications of already finalized blocks fetched from peers
+	pub beefy_fetched_justifications: Counter<U64>,
 }
 
 impl PrometheusRegister for VoterMetrics {
@@ -157,6 +159,13 @@ impl PrometheusRegister for VoterMetrics {
 				)?,
 				registry,
 			)?,
+			beefy_fetched_justifications: register(
+				Counter::new(
+					"substrate_beefy_fetched_justifications",
+					"Number of justifications of already finalized blocks fetched from peers",
+				)?,
+				registry,
+			)?,
 		})
 	}
 }
@@ -457,7 +457,7 @@ pub(crate) fn get_beefy_streams(
 	let mut versioned_finality_proof_streams = Vec::new();
 	peers.for_each(|(index, _)| {
 		let beefy_rpc_links = net.peer(index).data.beefy_rpc_links.lock().clone().unwrap();
-		let BeefyRPCLinks { from_voter_justif_stream, from_voter_best_beefy_stream } =
+		let BeefyRPCLinks { from_voter_justif_stream, from_voter_best_beefy_stream, .. } =
 			beefy_rpc_links;
 		best_block_streams.push(from_voter_best_beefy_stream.subscribe(100_000));
 		versioned_finality_proof_streams.push(from_voter_justif_stream.subscribe(100_000));
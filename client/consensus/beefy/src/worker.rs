@@ -19,10 +19,12 @@
 use crate::{
 	communication::{
 		gossip::{proofs_topic, votes_topic, GossipFilterCfg, GossipMessage, GossipValidator},
+		notification::JustificationRequestError,
 		peers::PeerReport,
 		request_response::outgoing_requests_engine::{OnDemandJustificationsEngine, ResponseInfo},
 	},
 	error::Error,
+	fetcher::JustificationFetcher,
 	justification::BeefyVersionedFinalityProof,
 	keystore::{BeefyKeystore, BeefySignatureHasher},
 	metric_inc, metric_set,
@@ -38,6 +40,7 @@ use sc_network_gossip::GossipEngine;
 use sc_utils::{mpsc::TracingUnboundedReceiver, notification::NotificationReceiver};
 use sp_api::{BlockId, ProvideRuntimeApi};
 use sp_arithmetic::traits::{AtLeast32Bit, Saturating};
+use sp_blockchain::Backend as BlockchainBackend;
 use sp_consensus::SyncOracle;
 use sp_consensus_beefy::{
 	check_equivocation_proof,
@@ -337,6 +340,8 @@ pub(crate) struct BeefyWorker<B: Block, BE, P, RuntimeApi, S> {
 	pub metrics: Option<VoterMetrics>,
 	/// Buffer holding justifications for future processing.
 	pub pending_justifications: BTreeMap<NumberFor<B>, BeefyVersionedFinalityProof<B>>,
+	/// Justifications of already BEEFY-finalized blocks to be fetched from peers.
+	pub justification_fetcher: JustificationFetcher<B>,
 	/// Persisted voter state.
 	pub persisted_state: PersistedState<B>,
 }
@@ -542,6 +547,126 @@ where
 		Ok(())
 	}
 
+	/// Handle a justification fetched from a peer.
+	///
+	/// Justifications queued by the [`JustificationFetcher`] are for already BEEFY-finalized
+	/// blocks, so they are only stored and forwarded to RPC. Anything else goes through
+	/// [`Self::triage_incoming_justif`].
+	///
+	/// Expects `justification` to be valid.
+	fn handle_fetched_justif(
+		&mut self,
+		justification: BeefyVersionedFinalityProof<B>,
+	) -> Result<(), Error> {
+		let block_num = match justification {
+			VersionedFinalityProof::V1(ref sc) => sc.commitment.block_number,
+		};
+		if block_num > self.voting_oracle().best_beefy_block ||
+			!self.justification_fetcher.note_fetched(block_num)
+		{
+			return self.triage_incoming_justif(justification)
+		}
+
+		debug!(target: LOG_TARGET, "🥩 Fetched justification for block #{:?}.", block_num);
+		metric_inc!(self, beefy_fetched_justifications);
+		let hash = self
+			.backend
+			.blockchain()
+			.expect_block_hash_from_id(&BlockId::Number(block_num))
+			.map_err(|e| Error::Backend(e.to_string()))?;
+		self.backend
+			.append_justification(hash, (BEEFY_ENGINE_ID, justification.encode()))
+			.map_err(|e| Error::Backend(e.to_string()))?;
+		self.answer_justif_request(hash, Ok(justification));
+		Ok(())
+	}
+
+	/// Answer the RPC requests for the justification of block `hash`.
+	fn answer_justif_request(
+		&self,
+		hash: B::Hash,
+		justification: Result<BeefyVersionedFinalityProof<B>, JustificationRequestError>,
+	) {
+		self.links
+			.to_rpc_requested_justif_sender
+			.notify(|| Ok::<_, ()>((hash, justification)))
+			.expect("forwards closure result; the closure always returns Ok; qed.");
+	}
+
+	/// Handle an RPC request for the justification of block `hash`.
+	///
+	/// Locally stored justifications are answered right away, others are fetched from peers
+	/// if the block is already BEEFY-finalized. Requests which can't be served are rejected
+	/// right away.
+	fn handle_justif_request(&mut self, hash: B::Hash) -> Result<(), Error> {
+		let blockchain = self.backend.blockchain();
+		let stored = blockchain
+			.justifications(hash)
+			.map_err(|e| Error::Backend(e.to_string()))?
+			.and_then(|justifs| justifs.into_justification(BEEFY_ENGINE_ID));
+		if let Some(encoded) = stored {
+			let justification = BeefyVersionedFinalityProof::<B>::decode(&mut &*encoded)
+				.map_err(|e| Error::Backend(e.to_string()))?;
+			self.answer_justif_request(hash, Ok(justification));
+			return Ok(())
+		}
+
+		let Some(header) = blockchain.header(hash).map_err(|e| Error::Backend(e.to_string()))?
+		else {
+			self.answer_justif_request(hash, Err(JustificationRequestError::UnknownBlock));
+			return Ok(())
+		};
+		let number = *header.number();
+		if number > self.voting_oracle().best_beefy_block {
+			debug!(
+				target: LOG_TARGET,
+				"🥩 Reject justification request for not yet BEEFY-finalized block #{:?}.", number
+			);
+			self.answer_justif_request(hash, Err(JustificationRequestError::NotFinalized));
+			return Ok(())
+		}
+
+		let Some(active_set) = find_authorities_change::<B>(&header)
+			.or_else(|| self.runtime.runtime_api().validator_set(hash).ok().flatten())
+		else {
+			self.answer_justif_request(hash, Err(JustificationRequestError::Unavailable));
+			return Err(Error::Backend(format!("No validator set for block #{:?}", number)))
+		};
+		if !self.justification_fetcher.request(number, active_set) {
+			self.answer_justif_request(hash, Err(JustificationRequestError::TooManyRequests));
+			return Ok(())
+		}
+		debug!(target: LOG_TARGET, "🥩 Fetch justification for block #{:?}.", number);
+		Ok(())
+	}
+
+	/// Use the idle on-demand justification requests engine to fetch the justifications queued
+	/// in the [`JustificationFetcher`], and progress the backfill.
+	fn fetch_justifications(&mut self) {
+		self.justification_fetcher
+			.backfill(&*self.backend, self.persisted_state.pallet_genesis());
+
+		if self.on_demand_justifications.is_idle() {
+			for block in self.justification_fetcher.drop_exhausted() {
+				match self.backend.blockchain().hash(block) {
+					Ok(Some(hash)) => self
+						.answer_justif_request(hash, Err(JustificationRequestError::Unavailable)),
+					_ => debug!(target: LOG_TARGET, "🥩 Unknown hash of block #{:?}.", block),
+				}
+			}
+
+			if let Some((block, active)) = self.justification_fetcher.next() {
+				if self.on_demand_justifications.request(block, active) {
+					self.justification_fetcher.note_attempt(block);
+				}
+			}
+		}
+
+		if let Err(e) = self.justification_fetcher.persist(&*self.backend) {
+			debug!(target: LOG_TARGET, "🥩 Failed to persist justification backfill: {}", e);
+		}
+	}
+
 	fn handle_vote(
 		&mut self,
 		vote: VoteMessage<NumberFor<B>, AuthorityId, Signature>,
@@ -788,6 +913,9 @@ where
 			if let Some((block, active)) = self.voting_oracle().mandatory_pending() {
 				// This only starts new request if there isn't already an active one.
 				self.on_demand_justifications.request(block, active);
+			} else {
+				// Otherwise use the spare request slot for justifications of older blocks.
+				self.fetch_justifications();
 			}
 		}
 	}
@@ -830,6 +958,8 @@ where
 				})
 				.fuse(),
 		);
+		let mut justif_requests =
+			self.links.from_rpc_justif_request_stream.subscribe(100_000).fuse();
 
 		loop {
 			// Act on changed 'state'.
@@ -852,19 +982,19 @@ where
 						self.handle_finality_notification(&notif).ok()
 					}).is_none() {
 						error!(target: LOG_TARGET, "🥩 Finality stream terminated, closing worker.");
-						return;
+						return
 					}
 				},
 				// Make sure to pump gossip engine.
 				_ = gossip_engine => {
 					error!(target: LOG_TARGET, "🥩 Gossip engine has terminated, closing worker.");
-					return;
+					return
 				},
 				// Process incoming justifications as these can make some in-flight votes obsolete.
 				response_info = self.on_demand_justifications.next().fuse() => {
 					match response_info {
 						ResponseInfo::ValidProof(justif, peer_report) => {
-							if let Err(err) = self.triage_incoming_justif(justif) {
+							if let Err(err) = self.handle_fetched_justif(justif) {
 								debug!(target: LOG_TARGET, "🥩 {}", err);
 							}
 							gossip_report = Some(peer_report);
@@ -882,7 +1012,7 @@ where
 						}
 					} else {
 						error!(target: LOG_TARGET, "🥩 Block import stream terminated, closing worker.");
-						return;
+						return
 					}
 				},
 				justif = gossip_proofs.next() => {
@@ -896,7 +1026,20 @@ where
 							target: LOG_TARGET,
 							"🥩 Finality proofs gossiping stream terminated, closing worker."
 						);
-						return;
+						return
+					}
+				},
+				request = justif_requests.next() => {
+					if let Some(hash) = request {
+						if let Err(err) = self.handle_justif_request(hash) {
+							debug!(target: LOG_TARGET, "🥩 {}", err);
+						}
+					} else {
+						error!(
+							target: LOG_TARGET,
+							"🥩 RPC justification requests stream terminated, closing worker."
+						);
+						return
 					}
 				},
 				// Finally process incoming votes.
@@ -911,7 +1054,7 @@ where
 							target: LOG_TARGET,
 							"🥩 Votes gossiping stream terminated, closing worker."
 						);
-						return;
+						return
 					}
 				},
 				// Process peer reports.
@@ -1041,7 +1184,10 @@ where
 pub(crate) mod tests {
 	use super::*;
 	use crate::{
-		communication::notification::{BeefyBestBlockStream, BeefyVersionedFinalityProofStream},
+		communication::notification::{
+			BeefyBestBlockStream, BeefyJustificationRequestStream,
+			BeefyRequestedJustificationStream, BeefyVersionedFinalityProofStream,
+		},
 		tests::{
 			create_beefy_keystore, get_beefy_streams, make_beefy_ids, BeefyPeer, BeefyTestNet,
 			TestApi,
@@ -1054,7 +1200,6 @@ pub(crate) mod tests {
 	use sc_network_sync::SyncingService;
 	use sc_network_test::TestNetFactory;
 	use sp_api::HeaderT;
-	use sp_blockchain::Backend as BlockchainBackendT;
 	use sp_consensus_beefy::{
 		generate_equivocation_proof, known_payloads, known_payloads::MMR_ROOT_ID,
 		mmr::MmrRootProvider, Keyring, Payload, SignedCommitment,
@@ -1107,17 +1252,27 @@ pub(crate) mod tests {
 			BeefyVersionedFinalityProofStream::<Block>::channel();
 		let (to_rpc_best_block_sender, from_voter_best_beefy_stream) =
 			BeefyBestBlockStream::<Block>::channel();
+		let (to_rpc_requested_justif_sender, from_voter_requested_justif_stream) =
+			BeefyRequestedJustificationStream::<Block>::channel();
+		let (to_voter_justif_request_sender, from_rpc_justif_request_stream) =
+			BeefyJustificationRequestStream::<Block>::channel();
 		let (_, from_block_import_justif_stream) =
 			BeefyVersionedFinalityProofStream::<Block>::channel();
 
-		let beefy_rpc_links =
-			BeefyRPCLinks { from_voter_justif_stream, from_voter_best_beefy_stream };
+		let beefy_rpc_links = BeefyRPCLinks {
+			from_voter_justif_stream,
+			from_voter_best_beefy_stream,
+			from_voter_requested_justif_stream,
+			to_voter_justif_request_sender,
+		};
 		*peer.data.beefy_rpc_links.lock() = Some(beefy_rpc_links);
 
 		let links = BeefyVoterLinks {
 			from_block_import_justif_stream,
 			to_rpc_justif_sender,
 			to_rpc_best_block_sender,
+			to_rpc_requested_justif_sender,
+			from_rpc_justif_request_stream,
 		};
 
 		let backend = peer.client().as_backend();
@@ -1171,6 +1326,7 @@ pub(crate) mod tests {
 			sync: Arc::new(sync),
 			on_demand_justifications,
 			pending_justifications: BTreeMap::new(),
+			justification_fetcher: JustificationFetcher::new(None),
 			persisted_state,
 		}
 	}
@@ -102,6 +102,25 @@ pub trait Verifier<B: BlockT>: Send + Sync {
 		&mut self,
 		block: BlockImportParams<B, ()>,
 	) -> Result<BlockImportParams<B, ()>, String>;
+
+	/// Verify the parts of `block` that don't depend on the import of its parent.
+	///
+	/// When verifying blocks in parallel, see [`BasicQueue::with_parallel_verification`], this is
+	/// called while the parent of the block is waiting for its import, if
+	/// [`Self::verifies_ahead_of_parent`] returns `true`. [`Self::verify`] is still called on the
+	/// returned block once the parent is imported and can skip the checks done here, e.g. passed
+	/// as intermediates which it then has to remove.
+	async fn verify_ahead_of_parent(
+		&mut self,
+		block: BlockImportParams<B, ()>,
+	) -> Result<BlockImportParams<B, ()>, String> {
+		Ok(block)
+	}
+
+	/// Whether [`Self::verify_ahead_of_parent`] verifies anything.
+	fn verifies_ahead_of_parent(&self) -> bool {
+		false
+	}
 }
 
 /// Blocks import queue API.
@@ -242,6 +261,41 @@ pub(crate) async fn import_single_block_metered<
 	verifier: &mut V,
 	metrics: Option<Metrics>,
 ) -> BlockImportResult<B> {
+	let PreparedBlock { peer, check_params, import_params } = prepare_block(block_origin, block)?;
+
+	if let Some(status) = check_block(import_handle, check_params.clone(), peer).await? {
+		return Ok(status)
+	}
+
+	let started = std::time::Instant::now();
+	let import_params = verify_block(verifier, import_params, peer, metrics.as_ref()).await?;
+
+	import_verified_block(
+		import_handle,
+		check_params,
+		import_params,
+		peer,
+		started,
+		metrics.as_ref(),
+	)
+	.await
+}
+
+/// Block split into the parameters needed by the different stages of the import.
+pub(crate) struct PreparedBlock<B: BlockT> {
+	/// The peer we received the block from.
+	pub peer: Option<RuntimeOrigin>,
+	/// Parameters used to check the block against the chain before verifying it.
+	pub check_params: BlockCheckParams<B>,
+	/// Parameters handed to the verifier.
+	pub import_params: BlockImportParams<B, ()>,
+}
+
+/// Turn an incoming block into the parameters needed to check, verify and import it.
+pub(crate) fn prepare_block<B: BlockT>(
+	block_origin: BlockOrigin,
+	block: IncomingBlock<B>,
+) -> Result<PreparedBlock<B>, BlockImportError> {
 	let peer = block.origin;
 
 	let (header, justifications) = match (block.header, block.justifications) {
@@ -258,63 +312,19 @@ pub(crate) async fn import_single_block_metered<
 
 	trace!(target: LOG_TARGET, "Header {} has {:?} logs", block.hash, header.digest().logs().len());
 
-	let number = *header.number();
-	let hash = block.hash;
-	let parent_hash = *header.parent_hash();
-
-	let import_handler = |import| match import {
-		Ok(ImportResult::AlreadyInChain) => {
-			trace!(target: LOG_TARGET, "Block already in chain {}: {:?}", number, hash);
-			Ok(BlockImportStatus::ImportedKnown(number, peer))
-		},
-		Ok(ImportResult::Imported(aux)) =>
-			Ok(BlockImportStatus::ImportedUnknown(number, aux, peer)),
-		Ok(ImportResult::MissingState) => {
-			debug!(
-				target: LOG_TARGET,
-				"Parent state is missing for {}: {:?}, parent: {:?}", number, hash, parent_hash
-			);
-			Err(BlockImportError::MissingState)
-		},
-		Ok(ImportResult::UnknownParent) => {
-			debug!(
-				target: LOG_TARGET,
-				"Block with unknown parent {}: {:?}, parent: {:?}", number, hash, parent_hash
-			);
-			Err(BlockImportError::UnknownParent)
-		},
-		Ok(ImportResult::KnownBad) => {
-			debug!(target: LOG_TARGET, "Peer gave us a bad block {}: {:?}", number, hash);
-			Err(BlockImportError::BadBlock(peer))
-		},
-		Err(e) => {
-			debug!(target: LOG_TARGET, "Error importing block {}: {:?}: {}", number, hash, e);
-			Err(BlockImportError::Other(e))
-		},
+	let check_params = BlockCheckParams {
+		hash: block.hash,
+		number: *header.number(),
+		parent_hash: *header.parent_hash(),
+		allow_missing_state: block.allow_missing_state,
+		import_existing: block.import_existing,
+		allow_missing_parent: block.state.is_some(),
 	};
 
-	match import_handler(
-		import_handle
-			.check_block(BlockCheckParams {
-				hash,
-				number,
-				parent_hash,
-				allow_missing_state: block.allow_missing_state,
-				import_existing: block.import_existing,
-				allow_missing_parent: block.state.is_some(),
-			})
-			.await,
-	)? {
-		BlockImportStatus::ImportedUnknown { .. } => (),
-		r => return Ok(r), // Any other successful result means that the block is already imported.
-	}
-
-	let started = std::time::Instant::now();
-
 	let mut import_block = BlockImportParams::new(block_origin, header);
 	import_block.body = block.body;
 	import_block.justifications = justifications;
-	import_block.post_hash = Some(hash);
+	import_block.post_hash = Some(block.hash);
 	import_block.import_existing = block.import_existing;
 	import_block.indexed_body = block.indexed_body;
 
@@ -327,7 +337,44 @@ pub(crate) async fn import_single_block_metered<
 		import_block.state_action = StateAction::ExecuteIfPossible;
 	}
 
-	let import_block = verifier.verify(import_block).await.map_err(|msg| {
+	Ok(PreparedBlock { peer, check_params, import_params: import_block })
+}
+
+/// Check the block against the chain.
+///
+/// Returns `Ok(None)` if the block still needs to be verified and imported.
+pub(crate) async fn check_block<B: BlockT, Transaction: Send + 'static>(
+	import_handle: &mut impl BlockImport<B, Transaction = Transaction, Error = ConsensusError>,
+	check_params: BlockCheckParams<B>,
+	peer: Option<RuntimeOrigin>,
+) -> Result<Option<BlockImportStatus<NumberFor<B>>>, BlockImportError> {
+	let (hash, number, parent_hash) =
+		(check_params.hash, check_params.number, check_params.parent_hash);
+
+	match handle_import_result::<B>(
+		import_handle.check_block(check_params).await,
+		hash,
+		number,
+		parent_hash,
+		peer,
+	)? {
+		BlockImportStatus::ImportedUnknown { .. } => Ok(None),
+		r => Ok(Some(r)), // Any other successful result means that the block is already imported.
+	}
+}
+
+/// Run the verifier on the given block.
+pub(crate) async fn verify_block<B: BlockT, V: Verifier<B>>(
+	verifier: &mut V,
+	import_params: BlockImportParams<B, ()>,
+	peer: Option<RuntimeOrigin>,
+	metrics: Option<&Metrics>,
+) -> Result<BlockImportParams<B, ()>, BlockImportError> {
+	let started = std::time::Instant::now();
+	let number = *import_params.header.number();
+	let hash = import_params.post_hash();
+
+	let verified = verifier.verify(import_params).await.map_err(|msg| {
 		if let Some(ref peer) = peer {
 			trace!(
 				target: LOG_TARGET,
@@ -340,20 +387,77 @@ pub(crate) async fn import_single_block_metered<
 		} else {
 			trace!(target: LOG_TARGET, "Verifying {}({}) failed: {}", number, hash, msg);
 		}
-		if let Some(metrics) = metrics.as_ref() {
-			metrics.report_verification(false, started.elapsed());
-		}
 		BlockImportError::VerificationFailed(peer, msg)
-	})?;
+	});
 
-	if let Some(metrics) = metrics.as_ref() {
-		metrics.report_verification(true, started.elapsed());
+	if let Some(metrics) = metrics {
+		metrics.report_verification(verified.is_ok(), started.elapsed());
 	}
 
-	let import_block = import_block.clear_storage_changes_and_mutate();
+	verified
+}
+
+/// Import a block that passed verification.
+///
+/// `started` is the instant the verification of the block started.
+pub(crate) async fn import_verified_block<B: BlockT, Transaction: Send + 'static>(
+	import_handle: &mut impl BlockImport<B, Transaction = Transaction, Error = ConsensusError>,
+	check_params: BlockCheckParams<B>,
+	import_params: BlockImportParams<B, ()>,
+	peer: Option<RuntimeOrigin>,
+	started: std::time::Instant,
+	metrics: Option<&Metrics>,
+) -> BlockImportResult<B> {
+	let import_block = import_params.clear_storage_changes_and_mutate();
 	let imported = import_handle.import_block(import_block).await;
-	if let Some(metrics) = metrics.as_ref() {
+	if let Some(metrics) = metrics {
 		metrics.report_verification_and_import(started.elapsed());
 	}
-	import_handler(imported)
+
+	handle_import_result::<B>(
+		imported,
+		check_params.hash,
+		check_params.number,
+		check_params.parent_hash,
+		peer,
+	)
+}
+
+fn handle_import_result<B: BlockT>(
+	import: Result<ImportResult, ConsensusError>,
+	hash: B::Hash,
+	number: NumberFor<B>,
+	parent_hash: B::Hash,
+	peer: Option<RuntimeOrigin>,
+) -> BlockImportResult<B> {
+	match import {
+		Ok(ImportResult::AlreadyInChain) => {
+			trace!(target: LOG_TARGET, "Block already in chain {}: {:?}", number, hash);
+			Ok(BlockImportStatus::ImportedKnown(number, peer))
+		},
+		Ok(ImportResult::Imported(aux)) =>
+			Ok(BlockImportStatus::ImportedUnknown(number, aux, peer)),
+		Ok(ImportResult::MissingState) => {
+			debug!(
+				target: LOG_TARGET,
+				"Parent state is missing for {}: {:?}, parent: {:?}", number, hash, parent_hash
+			);
+			Err(BlockImportError::MissingState)
+		},
+		Ok(ImportResult::UnknownParent) => {
+			debug!(
+				target: LOG_TARGET,
+				"Block with unknown parent {}: {:?}, parent: {:?}", number, hash, parent_hash
+			);
+			Err(BlockImportError::UnknownParent)
+		},
+		Ok(ImportResult::KnownBad) => {
+			debug!(target: LOG_TARGET, "Peer gave us a bad block {}: {:?}", number, hash);
+			Err(BlockImportError::BadBlock(peer))
+		},
+		Err(e) => {
+			debug!(target: LOG_TARGET, "Error importing block {}: {:?}: {}", number, hash, e);
+			Err(BlockImportError::Other(e))
+		},
+	}
 }
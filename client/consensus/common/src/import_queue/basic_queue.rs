@@ -16,6 +16,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 use futures::{
+	channel::oneshot,
 	prelude::*,
 	task::{Context, Poll},
 };
@@ -28,14 +29,23 @@ use sp_runtime::{
 	traits::{Block as BlockT, Header as HeaderT, NumberFor},
 	Justification, Justifications,
 };
-use std::{marker::PhantomData, pin::Pin, time::Duration};
+use std::{
+	collections::VecDeque,
+	marker::PhantomData,
+	num::NonZeroUsize,
+	pin::Pin,
+	sync::Arc,
+	time::{Duration, Instant},
+};
 
 use crate::{
+	block_import::{BlockCheckParams, BlockImportParams},
 	import_queue::{
 		buffered_link::{self, BufferedLinkReceiver, BufferedLinkSender},
-		import_single_block_metered, BlockImportError, BlockImportStatus, BoxBlockImport,
+		check_block, import_single_block_metered, import_verified_block, prepare_block,
+		verify_block, BlockImportError, BlockImportResult, BlockImportStatus, BoxBlockImport,
 		BoxJustificationImport, ImportQueue, ImportQueueService, IncomingBlock, Link,
-		RuntimeOrigin, Verifier, LOG_TARGET,
+		PreparedBlock, RuntimeOrigin, Verifier, LOG_TARGET,
 	},
 	metrics::Metrics,
 };
@@ -69,19 +79,57 @@ impl<B: BlockT, Transaction: Send + 'static> BasicQueue<B, Transaction> {
 		spawner: &impl sp_core::traits::SpawnEssentialNamed,
 		prometheus_registry: Option<&Registry>,
 	) -> Self {
-		let (result_sender, result_port) = buffered_link::buffered_link(100_000);
+		let metrics = register_metrics(prometheus_registry);
 
-		let metrics = prometheus_registry.and_then(|r| {
-			Metrics::register(r)
-				.map_err(|err| {
-					log::warn!("Failed to register Prometheus metrics: {}", err);
-				})
-				.ok()
-		});
+		Self::spawn(verifier, block_import, justification_import, spawner, metrics, None)
+	}
+
+	/// Instantiate a new basic queue that verifies blocks in parallel ahead of their import.
+	///
+	/// Up to `verification_parallelism` blocks of an import batch are verified concurrently on
+	/// blocking tasks spawned with `spawner`, each with its own clone of `verifier`, while the
+	/// blocks are still imported one after the other in their original order. Blocks waiting for
+	/// the import of their parent are only verified as far as
+	/// [`Verifier::verify_ahead_of_parent`] allows, and the rest of their verification is done once
+	/// the parent is imported. Verifiers not verifying anything ahead of the parent only verify
+	/// blocks of different forks in parallel.
+	pub fn with_parallel_verification<V: 'static + Verifier<B> + Clone>(
+		verifier: V,
+		block_import: BoxBlockImport<B, Transaction>,
+		justification_import: Option<BoxJustificationImport<B>>,
+		spawner: &impl sp_core::traits::SpawnEssentialNamed,
+		prometheus_registry: Option<&Registry>,
+		verification_parallelism: NonZeroUsize,
+	) -> Self {
+		let metrics = register_metrics(prometheus_registry);
+
+		let verification_pool =
+			VerificationPool::new(&verifier, verification_parallelism, spawner, metrics.clone());
+
+		Self::spawn(
+			verifier,
+			block_import,
+			justification_import,
+			spawner,
+			metrics,
+			Some(verification_pool),
+		)
+	}
+
+	fn spawn<V: 'static + Verifier<B>>(
+		verifier: V,
+		block_import: BoxBlockImport<B, Transaction>,
+		justification_import: Option<BoxJustificationImport<B>>,
+		spawner: &impl sp_core::traits::SpawnEssentialNamed,
+		metrics: Option<Metrics>,
+		verification_pool: Option<VerificationPool<B>>,
+	) -> Self {
+		let (result_sender, result_port) = buffered_link::buffered_link(100_000);
 
 		let (future, justification_sender, block_import_sender) = BlockImportWorker::new(
 			result_sender,
 			verifier,
+			verification_pool,
 			block_import,
 			justification_import,
 			metrics,
@@ -101,6 +149,16 @@ impl<B: BlockT, Transaction: Send + 'static> BasicQueue<B, Transaction> {
 	}
 }
 
+fn register_metrics(prometheus_registry: Option<&Registry>) -> Option<Metrics> {
+	prometheus_registry.and_then(|r| {
+		Metrics::register(r)
+			.map_err(|err| {
+				log::warn!("Failed to register Prometheus metrics: {}", err);
+			})
+			.ok()
+	})
+}
+
 #[derive(Clone)]
 struct BasicQueueHandle<B: BlockT> {
 	/// Channel to send justification import messages to the background task.
@@ -223,6 +281,7 @@ mod worker_messages {
 async fn block_import_process<B: BlockT, Transaction: Send + 'static>(
 	mut block_import: BoxBlockImport<B, Transaction>,
 	mut verifier: impl Verifier<B>,
+	verification_pool: Option<VerificationPool<B>>,
 	mut result_sender: BufferedLinkSender<B>,
 	mut block_import_receiver: TracingUnboundedReceiver<worker_messages::ImportBlocks<B>>,
 	metrics: Option<Metrics>,
@@ -246,6 +305,7 @@ async fn block_import_process<B: BlockT, Transaction: Send + 'static>(
 			origin,
 			blocks,
 			&mut verifier,
+			verification_pool.as_ref(),
 			delay_between_blocks,
 			metrics.clone(),
 		)
@@ -265,6 +325,7 @@ impl<B: BlockT> BlockImportWorker<B> {
 	fn new<V: 'static + Verifier<B>, Transaction: Send + 'static>(
 		result_sender: BufferedLinkSender<B>,
 		verifier: V,
+		verification_pool: Option<VerificationPool<B>>,
 		block_import: BoxBlockImport<B, Transaction>,
 		justification_import: Option<BoxJustificationImport<B>>,
 		metrics: Option<Metrics>,
@@ -296,6 +357,7 @@ impl<B: BlockT> BlockImportWorker<B> {
 			let block_import_process = block_import_process(
 				block_import,
 				verifier,
+				verification_pool,
 				worker.result_sender.clone(),
 				block_import_port,
 				worker.metrics.clone(),
@@ -389,6 +451,9 @@ struct ImportManyBlocksResult<B: BlockT> {
 
 /// Import several blocks at once, returning import result for each block.
 ///
+/// With a `verification_pool`, the blocks following the one being imported are verified on the
+/// pool in the meantime, as far as the verifier allows. The blocks are still imported in order.
+///
 /// This will yield after each imported block once, to ensure that other futures can
 /// be called as well.
 async fn import_many_blocks<B: BlockT, V: Verifier<B>, Transaction: Send + 'static>(
@@ -396,6 +461,7 @@ async fn import_many_blocks<B: BlockT, V: Verifier<B>, Transaction: Send + 'stat
 	blocks_origin: BlockOrigin,
 	blocks: Vec<IncomingBlock<B>>,
 	verifier: &mut V,
+	verification_pool: Option<&VerificationPool<B>>,
 	delay_between_blocks: Duration,
 	metrics: Option<Metrics>,
 ) -> ImportManyBlocksResult<B> {
@@ -415,34 +481,62 @@ async fn import_many_blocks<B: BlockT, V: Verifier<B>, Transaction: Send + 'stat
 	let mut imported = 0;
 	let mut results = vec![];
 	let mut has_error = false;
-	let mut blocks = blocks.into_iter();
+	let mut blocks = blocks.into_iter().peekable();
+	let mut speculative_blocks = VecDeque::new();
 
 	// Blocks in the response/drain should be in ascending order.
 	loop {
-		// Is there any block left to import?
-		let block = match blocks.next() {
-			Some(b) => b,
-			None => {
-				// No block left to import, success!
-				return ImportManyBlocksResult { block_count: count, imported, results }
-			},
-		};
+		if let Some(pool) = verification_pool {
+			// Keep the pool busy with the blocks following the one about to be imported.
+			while !has_error && speculative_blocks.len() < pool.parallelism {
+				let Some(block) =
+					blocks.next_if(|block| pool.can_verify_ahead(block, &speculative_blocks))
+				else {
+					break
+				};
+				let ahead_of_parent = waits_for_parent(&block, &speculative_blocks);
+				speculative_blocks.push_back(pool.verify(blocks_origin, block, ahead_of_parent));
+			}
+		}
 
-		let block_number = block.header.as_ref().map(|h| *h.number());
-		let block_hash = block.hash;
-		let import_result = if has_error {
-			Err(BlockImportError::Cancelled)
-		} else {
-			// The actual import.
-			import_single_block_metered(
-				import_handle,
-				blocks_origin,
-				block,
-				verifier,
-				metrics.clone(),
-			)
-			.await
-		};
+		let (block_number, block_hash, import_result) =
+			if let Some(block) = speculative_blocks.pop_front() {
+				let (block_number, block_hash) = (block.number, block.hash);
+				let import_result = if has_error {
+					Err(BlockImportError::Cancelled)
+				} else {
+					import_speculative_block(import_handle, block, verifier, metrics.as_ref()).await
+				};
+
+				(block_number, block_hash, import_result)
+			} else {
+				// Is there any block left to import?
+				let block = match blocks.next() {
+					Some(b) => b,
+					None => {
+						// No block left to import, success!
+						return ImportManyBlocksResult { block_count: count, imported, results }
+					},
+				};
+
+				let block_number = block.header.as_ref().map(|h| *h.number());
+				let block_hash = block.hash;
+				let import_result = if has_error {
+					Err(BlockImportError::Cancelled)
+				} else {
+					// The actual import.
+					import_single_block_metered(
+						import_handle,
+						blocks_origin,
+						block,
+						verifier,
+						metrics.clone(),
+					)
+					.await
+				};
+
+				(block_number, block_hash, import_result)
+			};
 
 		if let Some(metrics) = metrics.as_ref() {
 			metrics.report_import::<B>(&import_result);
@@ -470,6 +564,178 @@ async fn import_many_blocks<B: BlockT, V: Verifier<B>, Transaction: Send + 'stat
 	}
 }
 
+type VerificationResult<B> = Result<BlockImportParams<B, ()>, BlockImportError>;
+
+/// Block to verify on the [`VerificationPool`].
+struct VerificationJob<B: BlockT> {
+	import_params: BlockImportParams<B, ()>,
+	peer: Option<RuntimeOrigin>,
+	/// Only verify what doesn't depend on the import of the parent.
+	ahead_of_parent: bool,
+	result: oneshot::Sender<VerificationResult<B>>,
+}
+
+/// Verifies blocks on tasks of their own ahead of their import.
+struct VerificationPool<B: BlockT> {
+	/// Maximum number of blocks being verified at the same time.
+	parallelism: usize,
+	/// Whether blocks are partly verified before their parent is imported.
+	ahead_of_parent: bool,
+	/// Sends blocks to the verification tasks.
+	jobs: TracingUnboundedSender<VerificationJob<B>>,
+}
+
+impl<B: BlockT> VerificationPool<B> {
+	/// Spawn `parallelism` verification tasks, each with its own clone of `verifier`.
+	fn new<V: 'static + Verifier<B> + Clone>(
+		verifier: &V,
+		parallelism: NonZeroUsize,
+		spawner: &impl sp_core::traits::SpawnEssentialNamed,
+		metrics: Option<Metrics>,
+	) -> Self {
+		let (jobs, job_port) = tracing_unbounded("mpsc_block_verification", 100_000);
+		let job_port = Arc::new(futures::lock::Mutex::new(job_port));
+
+		for _ in 0..parallelism.get() {
+			spawner.spawn_essential_blocking(
+				"basic-block-verification-worker",
+				Some("block-import"),
+				verification_worker(verifier.clone(), job_port.clone(), metrics.clone()).boxed(),
+			);
+		}
+
+		Self {
+			parallelism: parallelism.get(),
+			ahead_of_parent: verifier.verifies_ahead_of_parent(),
+			jobs,
+		}
+	}
+
+	/// Whether `block` can be sent to the pool before the blocks in `queued` are imported.
+	///
+	/// Blocks waiting for their parent are only sent if the verifier can verify anything ahead of
+	/// the parent, otherwise they are verified when being imported.
+	fn can_verify_ahead(
+		&self,
+		block: &IncomingBlock<B>,
+		queued: &VecDeque<SpeculativeBlock<B>>,
+	) -> bool {
+		self.ahead_of_parent || !waits_for_parent(block, queued)
+	}
+
+	/// Start verifying `block` on the pool.
+	///
+	/// If `ahead_of_parent`, only what doesn't depend on the import of the parent is verified.
+	fn verify(
+		&self,
+		block_origin: BlockOrigin,
+		block: IncomingBlock<B>,
+		ahead_of_parent: bool,
+	) -> SpeculativeBlock<B> {
+		let hash = block.hash;
+		let number = block.header.as_ref().map(|h| *h.number());
+
+		let verification = prepare_block(block_origin, block).map(
+			|PreparedBlock { peer, check_params, import_params }| {
+				let (result, verified) = oneshot::channel();
+				let _ = self.jobs.unbounded_send(VerificationJob {
+					import_params,
+					peer,
+					ahead_of_parent,
+					result,
+				});
+				(check_params, peer, verified)
+			},
+		);
+
+		SpeculativeBlock { hash, number, ahead_of_parent, verification }
+	}
+}
+
+/// Whether the parent of `block` is one of the blocks in `queued`, waiting for its import.
+fn waits_for_parent<B: BlockT>(
+	block: &IncomingBlock<B>,
+	queued: &VecDeque<SpeculativeBlock<B>>,
+) -> bool {
+	block
+		.header
+		.as_ref()
+		.map_or(false, |header| queued.iter().any(|queued| queued.hash == *header.parent_hash()))
+}
+
+/// Task verifying the blocks sent to the [`VerificationPool`] until it is dropped.
+async fn verification_worker<B: BlockT, V: Verifier<B>>(
+	mut verifier: V,
+	job_port: Arc<futures::lock::Mutex<TracingUnboundedReceiver<VerificationJob<B>>>>,
+	metrics: Option<Metrics>,
+) {
+	loop {
+		let Some(VerificationJob { import_params, peer, ahead_of_parent, result }) =
+			job_port.lock().await.next().await
+		else {
+			return
+		};
+
+		let verified = if ahead_of_parent {
+			verifier.verify_ahead_of_parent(import_params).await.map_err(|msg| {
+				trace!(target: LOG_TARGET, "Verifying ahead of the parent failed: {}", msg);
+				BlockImportError::VerificationFailed(peer, msg)
+			})
+		} else {
+			verify_block(&mut verifier, import_params, peer, metrics.as_ref()).await
+		};
+		let _ = result.send(verified);
+	}
+}
+
+/// Block whose verification was started on the [`VerificationPool`] ahead of its import.
+struct SpeculativeBlock<B: BlockT> {
+	hash: B::Hash,
+	number: Option<NumberFor<B>>,
+	/// Whether the block still needs to be verified once its parent is imported.
+	ahead_of_parent: bool,
+	/// The running verification, or the reason why the block could not be verified.
+	verification: Result<
+		(BlockCheckParams<B>, Option<RuntimeOrigin>, oneshot::Receiver<VerificationResult<B>>),
+		BlockImportError,
+	>,
+}
+
+/// Import a block whose verification was started on the [`VerificationPool`].
+///
+/// Blocks only verified ahead of their parent are verified with `verifier` before their import.
+async fn import_speculative_block<B: BlockT, V: Verifier<B>, Transaction: Send + 'static>(
+	import_handle: &mut BoxBlockImport<B, Transaction>,
+	block: SpeculativeBlock<B>,
+	verifier: &mut V,
+	metrics: Option<&Metrics>,
+) -> BlockImportResult<B> {
+	let (check_params, peer, verification) = block.verification?;
+
+	if let Some(status) = check_block(import_handle, check_params.clone(), peer).await? {
+		return Ok(status)
+	}
+
+	let started = Instant::now();
+	// The verification task only goes away when the node is shutting down.
+	let verified = verification.await.unwrap_or(Err(BlockImportError::Cancelled));
+	if let Some(metrics) = metrics {
+		metrics.report_verification_wait(started.elapsed());
+		metrics.report_parallel_verification(if verified.is_ok() {
+			"success"
+		} else {
+			"verification_failed"
+		});
+	}
+
+	let verified = match block.ahead_of_parent {
+		true => verify_block(verifier, verified?, peer, metrics).await?,
+		false => verified?,
+	};
+
+	import_verified_block(import_handle, check_params, verified, peer, started, metrics).await
+}
+
 /// A future that will always `yield` on the first call of `poll` but schedules the
 /// current task for re-execution.
 ///
@@ -507,6 +773,7 @@ mod tests {
 		import_queue::Verifier,
 	};
 	use futures::{executor::block_on, Future};
+	use parking_lot::Mutex;
 	use sp_test_primitives::{Block, BlockNumber, Extrinsic, Hash, Header};
 
 	#[async_trait::async_trait]
@@ -596,7 +863,7 @@ mod tests {
 		let (result_sender, mut result_port) = buffered_link::buffered_link(100_000);
 
 		let (worker, finality_sender, block_import_sender) =
-			BlockImportWorker::new(result_sender, (), Box::new(()), Some(Box::new(())), None);
+			BlockImportWorker::new(result_sender, (), None, Box::new(()), Some(Box::new(())), None);
 		futures::pin_mut!(worker);
 
 		let import_block = |n| {
@@ -688,4 +955,198 @@ mod tests {
 			]
 		);
 	}
+
+	/// Block import recording the hashes of imported blocks in order.
+	struct ChainImport(Arc<Mutex<Vec<Hash>>>);
+
+	#[async_trait::async_trait]
+	impl BlockImport<Block> for ChainImport {
+		type Error = sp_consensus::Error;
+		type Transaction = Extrinsic;
+
+		async fn check_block(
+			&mut self,
+			_block: BlockCheckParams<Block>,
+		) -> Result<ImportResult, Self::Error> {
+			Ok(ImportResult::imported(false))
+		}
+
+		async fn import_block(
+			&mut self,
+			block: BlockImportParams<Block, Self::Transaction>,
+		) -> Result<ImportResult, Self::Error> {
+			self.0.lock().push(block.post_hash());
+			Ok(ImportResult::imported(true))
+		}
+	}
+
+	/// Verifier recording the blocks it verified.
+	///
+	/// Only accepts blocks whose parent was imported by [`ChainImport`], but verifies blocks ahead
+	/// of their parent if `ahead_of_parent`.
+	#[derive(Clone)]
+	struct TestVerifier {
+		imported: Arc<Mutex<Vec<Hash>>>,
+		verified: Arc<Mutex<Vec<Hash>>>,
+		verified_ahead: Arc<Mutex<Vec<Hash>>>,
+		ahead_of_parent: bool,
+	}
+
+	#[async_trait::async_trait]
+	impl Verifier<Block> for TestVerifier {
+		async fn verify(
+			&mut self,
+			block: BlockImportParams<Block, ()>,
+		) -> Result<BlockImportParams<Block, ()>, String> {
+			self.verified.lock().push(block.post_hash());
+
+			let parent_hash = *block.header.parent_hash();
+			if parent_hash != Hash::zero() && !self.imported.lock().contains(&parent_hash) {
+				return Err("parent not imported".into())
+			}
+
+			Ok(BlockImportParams::new(block.origin, block.header))
+		}
+
+		async fn verify_ahead_of_parent(
+			&mut self,
+			block: BlockImportParams<Block, ()>,
+		) -> Result<BlockImportParams<Block, ()>, String> {
+			assert!(self.ahead_of_parent);
+			self.verified_ahead.lock().push(block.post_hash());
+			Ok(block)
+		}
+
+		fn verifies_ahead_of_parent(&self) -> bool {
+			self.ahead_of_parent
+		}
+	}
+
+	fn chain(len: u64) -> Vec<IncomingBlock<Block>> {
+		let mut parent_hash = Hash::zero();
+		(1..=len)
+			.map(|number| {
+				let header = Header {
+					parent_hash,
+					number,
+					extrinsics_root: Hash::random(),
+					state_root: Default::default(),
+					digest: Default::default(),
+				};
+				parent_hash = header.hash();
+
+				IncomingBlock {
+					hash: parent_hash,
+					header: Some(header),
+					body: None,
+					indexed_body: None,
+					justifications: None,
+					origin: None,
+					allow_missing_state: false,
+					import_existing: false,
+					state: None,
+					skip_execution: false,
+				}
+			})
+			.collect()
+	}
+
+	/// Import `blocks` verifying them on a pool, returning the hashes of the imported blocks, of
+	/// the verified blocks and of the blocks verified ahead of their parent.
+	fn import_in_parallel(
+		blocks: Vec<IncomingBlock<Block>>,
+		parallelism: usize,
+		ahead_of_parent: bool,
+	) -> (ImportManyBlocksResult<Block>, Vec<Hash>, Vec<Hash>, Vec<Hash>) {
+		let imported = Arc::new(Mutex::new(Vec::new()));
+		let verified = Arc::new(Mutex::new(Vec::new()));
+		let verified_ahead = Arc::new(Mutex::new(Vec::new()));
+		let mut verifier = TestVerifier {
+			imported: imported.clone(),
+			verified: verified.clone(),
+			verified_ahead: verified_ahead.clone(),
+			ahead_of_parent,
+		};
+		let mut block_import: BoxBlockImport<Block, Extrinsic> =
+			Box::new(ChainImport(imported.clone()));
+		let pool = VerificationPool::new(
+			&verifier,
+			NonZeroUsize::new(parallelism).unwrap(),
+			&sp_core::testing::TaskExecutor::new(),
+			None,
+		);
+
+		let res = block_on(import_many_blocks(
+			&mut block_import,
+			BlockOrigin::NetworkInitialSync,
+			blocks,
+			&mut verifier,
+			Some(&pool),
+			Duration::default(),
+			None,
+		));
+
+		let imported = imported.lock().clone();
+		let verified = verified.lock().clone();
+		let verified_ahead = verified_ahead.lock().clone();
+		(res, imported, verified, verified_ahead)
+	}
+
+	#[test]
+	fn parallel_verification_imports_blocks_in_order() {
+		for ahead_of_parent in [false, true] {
+			let blocks = chain(10);
+			let hashes = blocks.iter().map(|b| b.hash).collect::<Vec<_>>();
+
+			let (res, imported, mut verified, mut verified_ahead) =
+				import_in_parallel(blocks, 4, ahead_of_parent);
+
+			assert_eq!(res.block_count, 10);
+			assert_eq!(res.imported, 10);
+			assert_eq!(res.results.into_iter().map(|(_, hash)| hash).collect::<Vec<_>>(), hashes);
+			assert_eq!(imported, hashes);
+
+			// Every block is verified once.
+			verified.sort();
+			let mut expected = hashes.clone();
+			expected.sort();
+			assert_eq!(verified, expected);
+
+			// All blocks but the first one wait for their parent while in flight, so they are
+			// verified ahead of it if the verifier can.
+			verified_ahead.sort();
+			let mut expected = if ahead_of_parent { hashes[1..].to_vec() } else { Vec::new() };
+			expected.sort();
+			assert_eq!(verified_ahead, expected);
+		}
+	}
+
+	#[test]
+	fn parallel_verification_verifies_forks_ahead_of_their_import() {
+		// Blocks of different forks don't wait for each other's import.
+		let forks = (0..4).map(|_| chain(1).pop().unwrap()).collect::<Vec<_>>();
+		let hashes = forks.iter().map(|b| b.hash).collect::<Vec<_>>();
+
+		let (res, imported, verified, _) = import_in_parallel(forks, 4, false);
+
+		assert_eq!(res.imported, 4);
+		assert_eq!(imported, hashes);
+		assert_eq!(verified.len(), 4);
+	}
+
+	#[test]
+	fn parallel_verification_cancels_blocks_after_failure() {
+		// The third block doesn't build on the second one, so it never passes verification.
+		let mut blocks = chain(5);
+		blocks[2] = chain(3).pop().unwrap();
+
+		let (res, imported, ..) = import_in_parallel(blocks, 2, false);
+
+		assert_eq!(res.imported, 2);
+		assert!(matches!(res.results[2].0, Err(BlockImportError::VerificationFailed(..))));
+		assert!(res.results[3..]
+			.iter()
+			.all(|(r, _)| matches!(r, Err(BlockImportError::Cancelled))));
+		assert_eq!(imported.len(), 2);
+	}
 }
@@ -34,6 +34,8 @@ pub(crate) struct Metrics {
 	pub block_verification_time: HistogramVec,
 	pub block_verification_and_import_time: Histogram,
 	pub justification_import_time: Histogram,
+	pub parallel_verification: CounterVec<U64>,
+	pub verification_wait_time: Histogram,
 }
 
 impl Metrics {
@@ -73,6 +75,23 @@ impl Metrics {
 				))?,
 				registry,
 			)?,
+			parallel_verification: register(
+				CounterVec::new(
+					Opts::new(
+						"substrate_import_queue_parallel_verifications_total",
+						"Blocks verified in parallel ahead of their import",
+					),
+					&["result"],
+				)?,
+				registry,
+			)?,
+			verification_wait_time: register(
+				Histogram::with_opts(HistogramOpts::new(
+					"substrate_import_queue_verification_wait_time",
+					"Time the import of a block waited for its parallel verification",
+				))?,
+				registry,
+			)?,
 		})
 	}
 
@@ -103,4 +122,12 @@ impl Metrics {
 	pub fn report_verification_and_import(&self, time: std::time::Duration) {
 		self.block_verification_and_import_time.observe(time.as_secs_f64());
 	}
+
+	pub fn report_parallel_verification(&self, result: &str) {
+		self.parallel_verification.with_label_values(&[result]).inc();
+	}
+
+	pub fn report_verification_wait(&self, time: std::time::Duration) {
+		self.verification_wait_time.observe(time.as_secs_f64());
+	}
 }
@@ -1698,6 +1698,7 @@ mod tests {
 			name: None,
 			local_role: Role::Authority,
 			observer_enabled: true,
+			observer_report_equivocations: false,
 			telemetry: None,
 			protocol_name: communication::grandpa_protocol_name::NAME.into(),
 		}
@@ -249,6 +249,7 @@ fn config() -> crate::Config {
 		name: None,
 		local_role: Role::Authority,
 		observer_enabled: true,
+		observer_report_equivocations: false,
 		telemetry: None,
 		protocol_name: grandpa_protocol_name::NAME.into(),
 	}
@@ -302,6 +303,20 @@ pub(crate) fn make_test_network() -> (impl Future<Output = Tester>, TestNetwork)
 	)
 }
 
+/// Encode a signed vote as gossiped over the network.
+pub(crate) fn encode_vote_message(
+	round: u64,
+	set_id: u64,
+	message: crate::SignedMessage<<Block as sp_runtime::traits::Block>::Header>,
+) -> Vec<u8> {
+	gossip::GossipMessage::<Block>::Vote(gossip::VoteMessage {
+		round: Round(round),
+		set_id: SetId(set_id),
+		message,
+	})
+	.encode()
+}
+
 fn make_ids(keys: &[Ed25519Keyring]) -> AuthorityList {
 	keys.iter().map(|&key| key.public().into()).map(|id| (id, 1)).collect()
 }
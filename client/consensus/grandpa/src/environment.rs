@@ -492,11 +492,9 @@ where
 	S: SyncingT<Block>,
 	SC: SelectChainT<Block>,
 {
-	/// Report the given equivocation to the GRANDPA runtime module. This method
-	/// generates a session membership proof of the offender and then submits an
-	/// extrinsic to report the equivocation. In particular, the session membership
-	/// proof must be generated at the block at which the given set was active which
-	/// isn't necessarily the best block if there are pending authority set changes.
+	/// Report the given equivocation to the GRANDPA runtime module, refusing to
+	/// do so for equivocations of the local voter. See
+	/// [`submit_equivocation_report`] for details.
 	pub(crate) fn report_equivocation(
 		&self,
 		equivocation: Equivocation<Block::Hash, NumberFor<Block>>,
@@ -509,86 +507,109 @@ where
 			}
 		}
 
-		let is_descendent_of = is_descendent_of(&*self.client, None);
-
-		let (best_block_hash, best_block_number) = {
-			// TODO [#9158]: Use SelectChain::best_chain() to get a potentially
-			// more accurate best block
-			let info = self.client.info();
-			(info.best_hash, info.best_number)
-		};
+		submit_equivocation_report(
+			&*self.client,
+			&self.authority_set,
+			&self.offchain_tx_pool_factory,
+			equivocation,
+		)
+	}
+}
 
-		let authority_set = self.authority_set.inner();
+/// Report the given equivocation to the GRANDPA runtime module. This method
+/// generates a session membership proof of the offender and then submits an
+/// extrinsic to report the equivocation. In particular, the session membership
+/// proof must be generated at the block at which the given set was active which
+/// isn't necessarily the best block if there are pending authority set changes.
+pub(crate) fn submit_equivocation_report<Block, BE, C>(
+	client: &C,
+	authority_set: &SharedAuthoritySet<Block::Hash, NumberFor<Block>>,
+	offchain_tx_pool_factory: &OffchainTransactionPoolFactory<Block>,
+	equivocation: Equivocation<Block::Hash, NumberFor<Block>>,
+) -> Result<(), Error>
+where
+	Block: BlockT,
+	BE: BackendT<Block>,
+	C: ClientForGrandpa<Block, BE>,
+	C::Api: GrandpaApi<Block>,
+{
+	let is_descendent_of = is_descendent_of(client, None);
 
-		// block hash and number of the next pending authority set change in the
-		// given best chain.
-		let next_change = authority_set
-			.next_change(&best_block_hash, &is_descendent_of)
-			.map_err(|e| Error::Safety(e.to_string()))?;
+	let (best_block_hash, best_block_number) = {
+		// TODO [#9158]: Use SelectChain::best_chain() to get a potentially
+		// more accurate best block
+		let info = client.info();
+		(info.best_hash, info.best_number)
+	};
 
-		// find the hash of the latest block in the current set
-		let current_set_latest_hash = match next_change {
-			Some((_, n)) if n.is_zero() =>
-				return Err(Error::Safety("Authority set change signalled at genesis.".to_string())),
-			// the next set starts at `n` so the current one lasts until `n - 1`. if
-			// `n` is later than the best block, then the current set is still live
-			// at best block.
-			Some((_, n)) if n > best_block_number => best_block_hash,
-			Some((h, _)) => {
-				// this is the header at which the new set will start
-				let header = self.client.header(h)?.expect(
-					"got block hash from registered pending change; \
-					 pending changes are only registered on block import; qed.",
-				);
+	let authority_set = authority_set.inner();
+
+	// block hash and number of the next pending authority set change in the
+	// given best chain.
+	let next_change = authority_set
+		.next_change(&best_block_hash, &is_descendent_of)
+		.map_err(|e| Error::Safety(e.to_string()))?;
+
+	// find the hash of the latest block in the current set
+	let current_set_latest_hash = match next_change {
+		Some((_, n)) if n.is_zero() =>
+			return Err(Error::Safety("Authority set change signalled at genesis.".to_string())),
+		// the next set starts at `n` so the current one lasts until `n - 1`. if
+		// `n` is later than the best block, then the current set is still live
+		// at best block.
+		Some((_, n)) if n > best_block_number => best_block_hash,
+		Some((h, _)) => {
+			// this is the header at which the new set will start
+			let header = client.header(h)?.expect(
+				"got block hash from registered pending change; \
+				 pending changes are only registered on block import; qed.",
+			);
 
-				// its parent block is the last block in the current set
-				*header.parent_hash()
-			},
-			// there is no pending change, the latest block for the current set is
-			// the best block.
-			None => best_block_hash,
-		};
+			// its parent block is the last block in the current set
+			*header.parent_hash()
+		},
+		// there is no pending change, the latest block for the current set is
+		// the best block.
+		None => best_block_hash,
+	};
 
-		// generate key ownership proof at that block
-		let key_owner_proof = match self
-			.client
-			.runtime_api()
-			.generate_key_ownership_proof(
-				current_set_latest_hash,
-				authority_set.set_id,
-				equivocation.offender().clone(),
-			)
-			.map_err(Error::RuntimeApi)?
-		{
-			Some(proof) => proof,
-			None => {
-				debug!(
-					target: LOG_TARGET,
-					"Equivocation offender is not part of the authority set."
-				);
-				return Ok(())
-			},
-		};
+	// generate key ownership proof at that block
+	let key_owner_proof = match client
+		.runtime_api()
+		.generate_key_ownership_proof(
+			current_set_latest_hash,
+			authority_set.set_id,
+			equivocation.offender().clone(),
+		)
+		.map_err(Error::RuntimeApi)?
+	{
+		Some(proof) => proof,
+		None => {
+			debug!(
+				target: LOG_TARGET,
+				"Equivocation offender is not part of the authority set."
+			);
+			return Ok(())
+		},
+	};
 
-		// submit equivocation report at **best** block
-		let equivocation_proof = EquivocationProof::new(authority_set.set_id, equivocation);
+	// submit equivocation report at **best** block
+	let equivocation_proof = EquivocationProof::new(authority_set.set_id, equivocation);
 
-		let mut runtime_api = self.client.runtime_api();
+	let mut runtime_api = client.runtime_api();
 
-		runtime_api.register_extension(
-			self.offchain_tx_pool_factory.offchain_transaction_pool(best_block_hash),
-		);
+	runtime_api
+		.register_extension(offchain_tx_pool_factory.offchain_transaction_pool(best_block_hash));
 
-		runtime_api
-			.submit_report_equivocation_unsigned_extrinsic(
-				best_block_hash,
-				equivocation_proof,
-				key_owner_proof,
-			)
-			.map_err(Error::RuntimeApi)?;
+	runtime_api
+		.submit_report_equivocation_unsigned_extrinsic(
+			best_block_hash,
+			equivocation_proof,
+			key_owner_proof,
+		)
+		.map_err(Error::RuntimeApi)?;
 
-		Ok(())
-	}
+	Ok(())
 }
 
 impl<BE, Block, C, N, S, SC, VR> finality_grandpa::Chain<Block::Hash, NumberFor<Block>>
@@ -135,10 +135,10 @@ pub use finality_proof::{FinalityProof, FinalityProofError, FinalityProofProvide
 pub use import::{find_forced_change, find_scheduled_change, GrandpaBlockImport};
 pub use justification::GrandpaJustification;
 pub use notification::{GrandpaJustificationSender, GrandpaJustificationStream};
-pub use observer::run_grandpa_observer;
+pub use observer::{run_grandpa_observer, run_grandpa_observer_with_params, GrandpaObserverParams};
 pub use voting_rule::{
-	BeforeBestBlockBy, ThreeQuartersOfTheUnfinalizedChain, VotingRule, VotingRuleResult,
-	VotingRulesBuilder,
+	BeforeBestBlockBy, OnChainFinalityLag, ThreeQuartersOfTheUnfinalizedChain, VotingRule,
+	VotingRuleResult, VotingRulesBuilder,
 };
 
 use aux_schema::PersistentData;
@@ -224,6 +224,10 @@ pub struct Config {
 	/// protocol (we will only issue catch-up requests to authorities when the
 	/// observer protocol is enabled).
 	pub observer_enabled: bool,
+	/// Whether a GRANDPA observer reports the prevote and precommit
+	/// equivocations it sees to the runtime. Ignored by the voter, which always
+	/// reports them.
+	pub observer_report_equivocations: bool,
 	/// The role of the local node (i.e. authority, full-node or light).
 	pub local_role: sc_network::config::Role,
 	/// Some local identifier of the voter.
@@ -17,33 +17,40 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use std::{
+	collections::{hash_map::Entry, HashMap},
 	marker::{PhantomData, Unpin},
 	pin::Pin,
-	sync::Arc,
+	sync::{
+		atomic::{AtomicU64, Ordering},
+		Arc,
+	},
 	task::{Context, Poll},
 };
 
 use finality_grandpa::{voter, voter_set::VoterSet, BlockNumberOps, Error as GrandpaError};
 use futures::prelude::*;
 use log::{debug, info, warn};
+use prometheus_endpoint::{register, Counter, PrometheusError, Registry, U64};
 
 use sc_client_api::backend::Backend;
 use sc_telemetry::TelemetryHandle;
+use sc_transaction_pool_api::OffchainTransactionPoolFactory;
 use sc_utils::mpsc::TracingUnboundedReceiver;
 use sp_blockchain::HeaderMetadata;
 use sp_consensus::SelectChain;
-use sp_consensus_grandpa::AuthorityId;
+use sp_consensus_grandpa::{AuthorityId, AuthoritySignature, Equivocation, GrandpaApi};
 use sp_keystore::KeystorePtr;
-use sp_runtime::traits::{Block as BlockT, NumberFor};
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT, NumberFor};
 
 use crate::{
 	authorities::SharedAuthoritySet,
 	aux_schema::PersistentData,
-	communication::{Network as NetworkT, NetworkBridge, Syncing as SyncingT},
-	environment, global_communication,
+	communication::{Network as NetworkT, NetworkBridge, Round, SetId, Syncing as SyncingT},
+	environment::{self, HasVoted},
+	global_communication,
 	notification::GrandpaJustificationSender,
-	ClientForGrandpa, CommandOrError, CommunicationIn, Config, Error, LinkHalf, VoterCommand,
-	VoterSetState, LOG_TARGET,
+	ClientForGrandpa, CommandOrError, CommunicationIn, Config, Error, LinkHalf, Precommit, Prevote,
+	SignedMessage, VoterCommand, VoterSetState, LOG_TARGET,
 };
 
 struct ObserverChain<'a, Block: BlockT, Client> {
@@ -67,6 +74,175 @@ where
 	}
 }
 
+/// Prometheus metrics of the observer's equivocation reporting.
+struct Metrics {
+	equivocations: Counter<U64>,
+	equivocation_reports: Counter<U64>,
+	equivocation_report_failures: Counter<U64>,
+}
+
+impl Metrics {
+	fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			equivocations: register(
+				Counter::new(
+					"substrate_finality_grandpa_observer_equivocations_total",
+					"Total number of GRANDPA equivocations detected by the observer.",
+				)?,
+				registry,
+			)?,
+			equivocation_reports: register(
+				Counter::new(
+					"substrate_finality_grandpa_observer_equivocation_reports_total",
+					"Total number of GRANDPA equivocation reports submitted by the observer.",
+				)?,
+				registry,
+			)?,
+			equivocation_report_failures: register(
+				Counter::new(
+					"substrate_finality_grandpa_observer_equivocation_report_failures_total",
+					"Total number of GRANDPA equivocation reports the observer failed to submit.",
+				)?,
+				registry,
+			)?,
+		})
+	}
+}
+
+/// Votes of a single stage seen in a round, keyed by voter. Along with the
+/// first vote we keep whether an equivocation was already reported for it.
+type SeenVotes<V> = HashMap<AuthorityId, (V, AuthoritySignature, bool)>;
+
+/// Detects prevote and precommit equivocations among the signed messages of a
+/// single round.
+struct EquivocationDetector<Header: HeaderT> {
+	round: u64,
+	prevotes: SeenVotes<Prevote<Header>>,
+	precommits: SeenVotes<Precommit<Header>>,
+}
+
+impl<Header: HeaderT> EquivocationDetector<Header> {
+	fn new(round: u64) -> Self {
+		EquivocationDetector { round, prevotes: HashMap::new(), precommits: HashMap::new() }
+	}
+
+	/// Note a signed message of the round, returning an equivocation if the
+	/// vote conflicts with an earlier one of the same voter. Each voter is
+	/// reported at most once per stage.
+	fn note_message(
+		&mut self,
+		message: SignedMessage<Header>,
+	) -> Option<Equivocation<Header::Hash, Header::Number>> {
+		let SignedMessage { message, signature, id } = message;
+
+		match message {
+			finality_grandpa::Message::Prevote(prevote) =>
+				note_vote(&mut self.prevotes, self.round, id, prevote, signature)
+					.map(Equivocation::Prevote),
+			finality_grandpa::Message::Precommit(precommit) =>
+				note_vote(&mut self.precommits, self.round, id, precommit, signature)
+					.map(Equivocation::Precommit),
+			finality_grandpa::Message::PrimaryPropose(_) => None,
+		}
+	}
+}
+
+fn note_vote<V: Clone + PartialEq>(
+	votes: &mut SeenVotes<V>,
+	round_number: u64,
+	identity: AuthorityId,
+	vote: V,
+	signature: AuthoritySignature,
+) -> Option<finality_grandpa::Equivocation<AuthorityId, V, AuthoritySignature>> {
+	match votes.entry(identity.clone()) {
+		Entry::Vacant(entry) => {
+			entry.insert((vote, signature, false));
+			None
+		},
+		Entry::Occupied(mut entry) => {
+			let (first_vote, first_signature, reported) = entry.get_mut();
+			if *reported || *first_vote == vote {
+				return None
+			}
+
+			*reported = true;
+
+			Some(finality_grandpa::Equivocation {
+				round_number,
+				identity,
+				first: (first_vote.clone(), first_signature.clone()),
+				second: (vote, signature),
+			})
+		},
+	}
+}
+
+/// Submits an equivocation report to the runtime.
+type SubmitReport<Block> = Box<
+	dyn Fn(Equivocation<<Block as BlockT>::Hash, NumberFor<Block>>) -> Result<(), Error> + Send,
+>;
+
+/// The votes of a round watched for equivocations.
+struct WatchedRound<Header: HeaderT> {
+	detector: EquivocationDetector<Header>,
+	incoming: Option<Pin<Box<dyn Stream<Item = SignedMessage<Header>> + Send>>>,
+}
+
+/// Watches the votes gossiped in the rounds the observer is following and
+/// reports any equivocations found to the runtime.
+struct EquivocationReporter<Block: BlockT> {
+	submit: SubmitReport<Block>,
+	metrics: Option<Metrics>,
+	rounds: Vec<WatchedRound<Block::Header>>,
+}
+
+impl<Block: BlockT> EquivocationReporter<Block> {
+	fn new(submit: SubmitReport<Block>, prometheus_registry: Option<&Registry>) -> Self {
+		let metrics = match prometheus_registry.map(Metrics::register) {
+			Some(Ok(metrics)) => Some(metrics),
+			Some(Err(e)) => {
+				debug!(target: LOG_TARGET, "Failed to register metrics: {:?}", e);
+				None
+			},
+			None => None,
+		};
+
+		EquivocationReporter { submit, metrics, rounds: Vec::new() }
+	}
+
+	/// Stop watching any round, e.g. because the authority set changed.
+	fn reset(&mut self) {
+		self.rounds.clear();
+	}
+
+	fn report(&self, equivocation: Equivocation<Block::Hash, NumberFor<Block>>) {
+		warn!(
+			target: LOG_TARGET,
+			"Detected equivocation by {} in round {}, reporting it.",
+			equivocation.offender(),
+			equivocation.round_number(),
+		);
+
+		if let Some(metrics) = &self.metrics {
+			metrics.equivocations.inc();
+		}
+
+		match (self.submit)(equivocation) {
+			Ok(()) =>
+				if let Some(metrics) = &self.metrics {
+					metrics.equivocation_reports.inc();
+				},
+			Err(e) => {
+				debug!(target: LOG_TARGET, "Error reporting equivocation: {}", e);
+
+				if let Some(metrics) = &self.metrics {
+					metrics.equivocation_report_failures.inc();
+				}
+			},
+		}
+	}
+}
+
 fn grandpa_observer<BE, Block: BlockT, Client, S, F>(
 	client: &Arc<Client>,
 	authority_set: &SharedAuthoritySet<Block::Hash, NumberFor<Block>>,
@@ -161,6 +337,8 @@ where
 /// listening for and validating GRANDPA commits instead of following the full
 /// protocol. Provide configuration and a link to a block import worker that has
 /// already been instantiated with `block_import`.
+///
+/// Equivocations are not reported, see [`run_grandpa_observer_with_params`] for that.
 /// NOTE: this is currently not part of the crate's public API since we don't consider
 /// it stable enough to use on a live network.
 pub fn run_grandpa_observer<BE, Block: BlockT, Client, N, S, SC>(
@@ -169,6 +347,90 @@ pub fn run_grandpa_observer<BE, Block: BlockT, Client, N, S, SC>(
 	network: N,
 	sync: S,
 ) -> sp_blockchain::Result<impl Future<Output = ()> + Send>
+where
+	BE: Backend<Block> + Unpin + 'static,
+	N: NetworkT<Block>,
+	S: SyncingT<Block>,
+	SC: SelectChain<Block>,
+	NumberFor<Block>: BlockNumberOps,
+	Client: ClientForGrandpa<Block, BE> + 'static,
+{
+	observer(config, link, network, sync, None)
+}
+
+/// Parameters used to run a GRANDPA observer.
+pub struct GrandpaObserverParams<Block: BlockT, C, N, S, SC> {
+	/// Configuration for the GRANDPA service.
+	pub config: Config,
+	/// A link to the block import worker.
+	pub link: LinkHalf<Block, C, SC>,
+	/// The Network instance.
+	pub network: N,
+	/// Event stream for syncing-related events.
+	pub sync: S,
+	/// The prometheus metrics registry.
+	pub prometheus_registry: Option<Registry>,
+	/// Offchain transaction pool factory.
+	///
+	/// This will be used to create an offchain transaction pool instance for sending an
+	/// equivocation report from the runtime, if [`Config::observer_report_equivocations`]
+	/// is set.
+	pub offchain_tx_pool_factory: OffchainTransactionPoolFactory<Block>,
+}
+
+/// Run a GRANDPA observer as a task, like [`run_grandpa_observer`].
+///
+/// With [`Config::observer_report_equivocations`] set, the observer also watches
+/// the votes of the latest concluded round and of the round being voted in, and
+/// reports any prevote or precommit equivocations through the runtime. The number
+/// of detected and reported equivocations is exposed through the
+/// `prometheus_registry`.
+pub fn run_grandpa_observer_with_params<BE, Block: BlockT, Client, N, S, SC>(
+	params: GrandpaObserverParams<Block, Client, N, S, SC>,
+) -> sp_blockchain::Result<impl Future<Output = ()> + Send>
+where
+	BE: Backend<Block> + Unpin + 'static,
+	N: NetworkT<Block>,
+	S: SyncingT<Block>,
+	SC: SelectChain<Block>,
+	NumberFor<Block>: BlockNumberOps,
+	Client: ClientForGrandpa<Block, BE> + 'static,
+	Client::Api: GrandpaApi<Block>,
+{
+	let GrandpaObserverParams {
+		config,
+		link,
+		network,
+		sync,
+		prometheus_registry,
+		offchain_tx_pool_factory,
+	} = params;
+
+	let equivocation_reporter = config.observer_report_equivocations.then(|| {
+		let client = link.client.clone();
+		let authority_set = link.persistent_data.authority_set.clone();
+		let submit: SubmitReport<Block> = Box::new(move |equivocation| {
+			environment::submit_equivocation_report::<Block, BE, Client>(
+				&*client,
+				&authority_set,
+				&offchain_tx_pool_factory,
+				equivocation,
+			)
+		});
+
+		EquivocationReporter::new(submit, prometheus_registry.as_ref())
+	});
+
+	observer(config, link, network, sync, equivocation_reporter)
+}
+
+fn observer<BE, Block: BlockT, Client, N, S, SC>(
+	config: Config,
+	link: LinkHalf<Block, Client, SC>,
+	network: N,
+	sync: S,
+	equivocation_reporter: Option<EquivocationReporter<Block>>,
+) -> sp_blockchain::Result<impl Future<Output = ()> + Send>
 where
 	BE: Backend<Block> + Unpin + 'static,
 	N: NetworkT<Block>,
@@ -203,6 +465,7 @@ where
 		voter_commands_rx,
 		Some(justification_sender),
 		telemetry,
+		equivocation_reporter,
 	);
 
 	let observer_work = observer_work.map_ok(|_| ()).map_err(|e| {
@@ -224,6 +487,10 @@ struct ObserverWork<B: BlockT, BE, Client, N: NetworkT<B>, S: SyncingT<B>> {
 	voter_commands_rx: TracingUnboundedReceiver<VoterCommand<B::Hash, NumberFor<B>>>,
 	justification_sender: Option<GrandpaJustificationSender<B>>,
 	telemetry: Option<TelemetryHandle>,
+	/// The latest round noted by the observer, `0` until the first commit of
+	/// the current set is observed.
+	observed_round: Arc<AtomicU64>,
+	equivocation_reporter: Option<EquivocationReporter<B>>,
 	_phantom: PhantomData<BE>,
 }
 
@@ -244,6 +511,7 @@ where
 		voter_commands_rx: TracingUnboundedReceiver<VoterCommand<B::Hash, NumberFor<B>>>,
 		justification_sender: Option<GrandpaJustificationSender<B>>,
 		telemetry: Option<TelemetryHandle>,
+		equivocation_reporter: Option<EquivocationReporter<B>>,
 	) -> Self {
 		let mut work = ObserverWork {
 			// `observer` is set to a temporary value and replaced below when
@@ -256,6 +524,8 @@ where
 			voter_commands_rx,
 			justification_sender,
 			telemetry,
+			observed_round: Arc::new(AtomicU64::new(0)),
+			equivocation_reporter,
 			_phantom: PhantomData,
 		};
 		work.rebuild_observer();
@@ -281,6 +551,13 @@ where
 
 		let last_finalized_number = self.client.info().finalized_number;
 
+		// votes of the previous set are no longer of interest to the
+		// equivocation reporter.
+		self.observed_round.store(0, Ordering::Relaxed);
+		if let Some(reporter) = self.equivocation_reporter.as_mut() {
+			reporter.reset();
+		}
+
 		// NOTE: since we are not using `round_communication` we have to
 		// manually note the round with the gossip validator, otherwise we won't
 		// relay round messages. we want all full nodes to contribute to vote
@@ -288,13 +565,11 @@ where
 		let note_round = {
 			let network = self.network.clone();
 			let voters = voters.clone();
+			let observed_round = self.observed_round.clone();
 
 			move |round| {
-				network.note_round(
-					crate::communication::Round(round),
-					crate::communication::SetId(set_id),
-					&voters,
-				)
+				observed_round.store(round, Ordering::Relaxed);
+				network.note_round(Round(round), SetId(set_id), &voters)
 			}
 		};
 
@@ -349,6 +624,63 @@ where
 		self.rebuild_observer();
 		Ok(())
 	}
+
+	/// Watches the votes of the latest observed round and of the round after
+	/// it, which is the one being voted in, and reports any equivocations among
+	/// them. Does nothing unless equivocation reporting is enabled.
+	fn poll_equivocations(&mut self, cx: &mut Context) {
+		let Some(reporter) = self.equivocation_reporter.as_mut() else {
+			return
+		};
+
+		let round = self.observed_round.load(Ordering::Relaxed);
+		if round == 0 {
+			return
+		}
+
+		// late votes of the concluded round are still gossiped, while the
+		// voters moved on to the next round.
+		reporter
+			.rounds
+			.retain(|watched| (round..=round + 1).contains(&watched.detector.round));
+		for round in [round, round + 1] {
+			if reporter.rounds.iter().any(|watched| watched.detector.round == round) {
+				continue
+			}
+
+			let set_id = self.persistent_data.authority_set.set_id();
+			let voters = Arc::new(self.persistent_data.authority_set.current_authorities());
+
+			let (incoming, _) = self.network.round_communication(
+				None,
+				Round(round),
+				SetId(set_id),
+				voters,
+				HasVoted::No,
+			);
+
+			reporter.rounds.push(WatchedRound {
+				detector: EquivocationDetector::new(round),
+				incoming: Some(Box::pin(incoming)),
+			});
+		}
+
+		let mut equivocations = Vec::new();
+		for watched in reporter.rounds.iter_mut() {
+			while let Some(incoming) = watched.incoming.as_mut() {
+				match incoming.poll_next_unpin(cx) {
+					Poll::Ready(Some(message)) =>
+						equivocations.extend(watched.detector.note_message(message)),
+					Poll::Ready(None) => watched.incoming = None,
+					Poll::Pending => break,
+				}
+			}
+		}
+
+		for equivocation in equivocations {
+			reporter.report(equivocation);
+		}
+	}
 }
 
 impl<B, BE, C, N, S> Future for ObserverWork<B, BE, C, N, S>
@@ -394,6 +726,8 @@ where
 			},
 		}
 
+		self.poll_equivocations(cx);
+
 		Future::poll(Pin::new(&mut self.network), cx)
 	}
 }
@@ -404,13 +738,22 @@ mod tests {
 
 	use crate::{
 		aux_schema,
-		communication::tests::{make_test_network, Event},
+		communication::{
+			grandpa_protocol_name,
+			tests::{encode_vote_message, make_test_network, Event},
+		},
 	};
 	use assert_matches::assert_matches;
-	use sc_network::PeerId;
+	use parking_lot::Mutex;
+	use sc_network::{event::Event as NetworkEvent, PeerId};
+	use sc_network_common::role::ObservedRole;
 	use sc_utils::mpsc::tracing_unbounded;
 	use sp_blockchain::HeaderBackend as _;
-	use substrate_test_runtime_client::{TestClientBuilder, TestClientBuilderExt};
+	use sp_core::H256;
+	use substrate_test_runtime_client::{
+		runtime::{Block, Header},
+		TestClientBuilder, TestClientBuilderExt,
+	};
 
 	use futures::executor;
 
@@ -452,6 +795,7 @@ mod tests {
 			voter_command_rx,
 			None,
 			None,
+			None,
 		);
 
 		// Trigger a reputation change through the gossip validator.
@@ -472,4 +816,149 @@ mod tests {
 			assert_matches!(tester.events.next().now_or_never(), Some(Some(Event::Report(_, _))));
 		});
 	}
+
+	#[test]
+	fn equivocation_detector_reports_conflicting_votes_once() {
+		let alice = sp_keyring::Ed25519Keyring::Alice;
+		let signed = |message| SignedMessage::<Header> {
+			message,
+			signature: alice.sign(&[]).into(),
+			id: alice.public().into(),
+		};
+		let prevote = |byte| {
+			finality_grandpa::Message::Prevote(finality_grandpa::Prevote {
+				target_hash: H256::repeat_byte(byte),
+				target_number: 1,
+			})
+		};
+		let precommit = |byte| {
+			finality_grandpa::Message::Precommit(finality_grandpa::Precommit {
+				target_hash: H256::repeat_byte(byte),
+				target_number: 1,
+			})
+		};
+
+		let mut detector = EquivocationDetector::<Header>::new(3);
+
+		// repeating the same vote or voting in another stage isn't an equivocation
+		assert!(detector.note_message(signed(prevote(1))).is_none());
+		assert!(detector.note_message(signed(prevote(1))).is_none());
+		assert!(detector.note_message(signed(precommit(2))).is_none());
+
+		assert_matches!(
+			detector.note_message(signed(prevote(2))),
+			Some(Equivocation::Prevote(equivocation)) if equivocation.round_number == 3 &&
+				equivocation.first.0.target_hash == H256::repeat_byte(1) &&
+				equivocation.second.0.target_hash == H256::repeat_byte(2)
+		);
+
+		// the offender is only reported once per stage
+		assert!(detector.note_message(signed(prevote(3))).is_none());
+		assert_matches!(
+			detector.note_message(signed(precommit(3))),
+			Some(Equivocation::Precommit(_))
+		);
+		assert!(detector.note_message(signed(precommit(4))).is_none());
+	}
+
+	#[test]
+	fn observer_reports_equivocations_of_the_current_round() {
+		let (tester_fut, _network) = make_test_network();
+		let mut tester = executor::block_on(tester_fut);
+
+		let (client, backend) = {
+			let builder = TestClientBuilder::with_default_backend();
+			let backend = builder.backend();
+			let (client, _) = builder.build_with_longest_chain();
+			(Arc::new(client), backend)
+		};
+
+		let alice = sp_keyring::Ed25519Keyring::Alice;
+		let alice_id: AuthorityId = alice.public().into();
+		let voters = vec![(alice_id.clone(), 1)];
+
+		let persistent_data =
+			aux_schema::load_persistent(&*backend, client.info().genesis_hash, 0, || Ok(voters))
+				.unwrap();
+
+		let (_tx, voter_command_rx) = tracing_unbounded("test_mpsc_voter_command", 100_000);
+
+		let reports = Arc::new(Mutex::new(Vec::new()));
+		let submit: SubmitReport<Block> = {
+			let reports = reports.clone();
+			Box::new(move |equivocation| {
+				reports.lock().push(equivocation);
+				Ok(())
+			})
+		};
+
+		let mut observer = ObserverWork::new(
+			client,
+			tester.net_handle.clone(),
+			persistent_data,
+			None,
+			voter_command_rx,
+			None,
+			None,
+			Some(EquivocationReporter::new(submit, None)),
+		);
+
+		// the observer concluded round 1, the voters are now voting in round 2.
+		observer.observed_round.store(1, Ordering::Relaxed);
+		assert!((&mut observer).now_or_never().is_none());
+
+		let sender = match tester.events.next().now_or_never() {
+			Some(Some(Event::EventStream(sender))) => sender,
+			_ => panic!("expected event stream request"),
+		};
+
+		// alice prevotes for two different blocks in round 2.
+		let prevote = |byte| {
+			let message = finality_grandpa::Message::Prevote(finality_grandpa::Prevote {
+				target_hash: H256::repeat_byte(byte),
+				target_number: 1,
+			});
+			let payload = sp_consensus_grandpa::localized_payload(2, 0, &message);
+			let signed = SignedMessage::<Header> {
+				message,
+				signature: alice.sign(&payload[..]).into(),
+				id: alice_id.clone(),
+			};
+
+			encode_vote_message(2, 0, signed)
+		};
+
+		let peer_id = PeerId::random();
+		let _ = sender.unbounded_send(NetworkEvent::NotificationStreamOpened {
+			remote: peer_id,
+			protocol: grandpa_protocol_name::NAME.into(),
+			negotiated_fallback: None,
+			role: ObservedRole::Full,
+			received_handshake: vec![],
+		});
+		let _ = sender.unbounded_send(NetworkEvent::NotificationsReceived {
+			remote: peer_id,
+			messages: vec![
+				(grandpa_protocol_name::NAME.into(), prevote(1).into()),
+				(grandpa_protocol_name::NAME.into(), prevote(2).into()),
+			],
+		});
+
+		executor::block_on(future::poll_fn(|cx| {
+			let _ = observer.poll_unpin(cx);
+			if reports.lock().is_empty() {
+				Poll::Pending
+			} else {
+				Poll::Ready(())
+			}
+		}));
+
+		let reports = reports.lock();
+		assert_eq!(reports.len(), 1);
+		assert_matches!(
+			&reports[0],
+			Equivocation::Prevote(equivocation) if equivocation.round_number == 2 &&
+				equivocation.identity == alice_id
+		);
+	}
 }
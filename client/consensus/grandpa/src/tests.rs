@@ -326,6 +326,7 @@ fn initialize_grandpa(
 				name: Some(format!("peer#{}", peer_id)),
 				local_role: Role::Authority,
 				observer_enabled: true,
+				observer_report_equivocations: false,
 				telemetry: None,
 				protocol_name: grandpa_protocol_name::NAME.into(),
 			},
@@ -481,6 +482,7 @@ async fn finalize_3_voters_1_full_observer() {
 				name: Some(format!("peer#{}", peer_id)),
 				local_role: Role::Authority,
 				observer_enabled: true,
+				observer_report_equivocations: false,
 				telemetry: None,
 				protocol_name: grandpa_protocol_name::NAME.into(),
 			},
@@ -576,6 +578,7 @@ async fn transition_3_voters_twice_1_full_observer() {
 				name: Some(format!("peer#{}", peer_id)),
 				local_role: Role::Authority,
 				observer_enabled: true,
+				observer_report_equivocations: false,
 				telemetry: None,
 				protocol_name: grandpa_protocol_name::NAME.into(),
 			},
@@ -1004,6 +1007,7 @@ async fn voter_persists_its_votes() {
 			name: Some(format!("peer#{}", 1)),
 			local_role: Role::Authority,
 			observer_enabled: true,
+			observer_report_equivocations: false,
 			telemetry: None,
 			protocol_name: grandpa_protocol_name::NAME.into(),
 		};
@@ -1046,6 +1050,7 @@ async fn voter_persists_its_votes() {
 				name: Some(format!("peer#{}", 0)),
 				local_role: Role::Authority,
 				observer_enabled: true,
+				observer_report_equivocations: false,
 				telemetry: None,
 				protocol_name: grandpa_protocol_name::NAME.into(),
 			},
@@ -1092,6 +1097,7 @@ async fn voter_persists_its_votes() {
 				name: Some(format!("peer#{}", 0)),
 				local_role: Role::Authority,
 				observer_enabled: true,
+				observer_report_equivocations: false,
 				telemetry: None,
 				protocol_name: grandpa_protocol_name::NAME.into(),
 			},
@@ -1257,6 +1263,7 @@ async fn finalize_3_voters_1_light_observer() {
 			name: Some("observer".to_string()),
 			local_role: Role::Full,
 			observer_enabled: true,
+			observer_report_equivocations: false,
 			telemetry: None,
 			protocol_name: grandpa_protocol_name::NAME.into(),
 		},
@@ -1305,6 +1312,7 @@ async fn voter_catches_up_to_latest_round_when_behind() {
 				name: Some(format!("peer#{}", peer_id)),
 				local_role: Role::Authority,
 				observer_enabled: true,
+				observer_report_equivocations: false,
 				telemetry: None,
 				protocol_name: grandpa_protocol_name::NAME.into(),
 			},
@@ -1420,6 +1428,7 @@ where
 		name: None,
 		local_role: Role::Authority,
 		observer_enabled: true,
+		observer_report_equivocations: false,
 		telemetry: None,
 		protocol_name: grandpa_protocol_name::NAME.into(),
 	};
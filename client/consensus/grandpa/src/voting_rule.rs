@@ -27,6 +27,8 @@ use std::{future::Future, pin::Pin, sync::Arc};
 use dyn_clone::DynClone;
 
 use sc_client_api::blockchain::HeaderBackend;
+use sp_api::ProvideRuntimeApi;
+use sp_consensus_grandpa::{FinalityLagApi, CLIENT_LOG_TARGET as LOG_TARGET};
 use sp_runtime::traits::{Block as BlockT, Header, NumberFor, One, Zero};
 
 /// A future returned by a `VotingRule` to restrict a given vote, if any restriction is necessary.
@@ -126,6 +128,74 @@ where
 	}
 }
 
+/// A custom voting rule that keeps the vote behind the best block like [`BeforeBestBlockBy`],
+/// by the number of blocks the runtime's [`FinalityLagApi`] returns at the best block.
+///
+/// This allows the lag to be changed on-chain, without upgrading the nodes. If the runtime
+/// doesn't implement the API, or calling it fails, the `fallback` lag is used.
+pub struct OnChainFinalityLag<Block: BlockT, C> {
+	client: Arc<C>,
+	fallback: NumberFor<Block>,
+}
+
+impl<Block: BlockT, C> Clone for OnChainFinalityLag<Block, C> {
+	fn clone(&self) -> Self {
+		OnChainFinalityLag { client: self.client.clone(), fallback: self.fallback }
+	}
+}
+
+impl<Block, C> OnChainFinalityLag<Block, C>
+where
+	Block: BlockT,
+	C: ProvideRuntimeApi<Block>,
+	C::Api: FinalityLagApi<Block>,
+{
+	/// Create a new rule reading the lag through the given client, using `fallback` when the
+	/// runtime doesn't provide it.
+	pub fn new(client: Arc<C>, fallback: NumberFor<Block>) -> Self {
+		OnChainFinalityLag { client, fallback }
+	}
+
+	fn lag(&self, at: Block::Hash) -> NumberFor<Block> {
+		let api = self.client.runtime_api();
+		let lag = match api.has_api::<dyn FinalityLagApi<Block>>(at) {
+			Ok(true) => api.finality_lag(at),
+			Ok(false) => return self.fallback,
+			Err(e) => Err(e),
+		};
+
+		lag.unwrap_or_else(|e| {
+			log::warn!(
+				target: LOG_TARGET,
+				"Failed to fetch the finality lag at {}, using {}: {}",
+				at,
+				self.fallback,
+				e,
+			);
+			self.fallback
+		})
+	}
+}
+
+impl<Block, B, C> VotingRule<Block, B> for OnChainFinalityLag<Block, C>
+where
+	Block: BlockT,
+	B: HeaderBackend<Block>,
+	C: ProvideRuntimeApi<Block> + Send + Sync,
+	C::Api: FinalityLagApi<Block>,
+{
+	fn restrict_vote(
+		&self,
+		backend: Arc<B>,
+		base: &Block::Header,
+		best_target: &Block::Header,
+		current_target: &Block::Header,
+	) -> VotingRuleResult<Block> {
+		let lag = self.lag(best_target.hash());
+		BeforeBestBlockBy(lag).restrict_vote(backend, base, best_target, current_target)
+	}
+}
+
 /// A custom voting rule that limits votes towards 3/4 of the unfinalized chain,
 /// using the given `base` and `best_target` to figure where the 3/4 target
 /// should fall.
@@ -330,14 +400,15 @@ where
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use parity_scale_codec::Encode;
 	use sc_block_builder::BlockBuilderProvider;
 	use sp_consensus::BlockOrigin;
 	use sp_runtime::traits::Header as _;
 
 	use substrate_test_runtime_client::{
-		runtime::{Block, Header},
-		Backend, Client, ClientBlockImportExt, DefaultTestClientBuilderExt, TestClientBuilder,
-		TestClientBuilderExt,
+		runtime::{Block, Header, FINALITY_LAG_KEY},
+		Backend, BlockBuilderExt, Client, ClientBlockImportExt, DefaultTestClientBuilderExt,
+		TestClientBuilder, TestClientBuilderExt,
 	};
 
 	/// A mock voting rule that subtracts a static number of block from the `current_target`.
@@ -437,4 +508,42 @@ mod tests {
 			assert_eq!(number, expected, "best = {}, lag = 2, base = {}", best_number, i);
 		}
 	}
+
+	#[test]
+	fn on_chain_finality_lag_follows_the_runtime() {
+		let rule =
+			OnChainFinalityLag::<Block, _>::new(Arc::new(TestClientBuilder::new().build()), 1);
+		let mut client = rule.client.clone();
+
+		let import_blocks = |client: &mut Arc<Client<Backend>>, n, lag: Option<u64>| {
+			for _ in 0..n {
+				let mut builder = client.new_block(Default::default()).unwrap();
+				if let Some(lag) = lag {
+					builder
+						.push_storage_change(FINALITY_LAG_KEY.to_vec(), Some(lag.encode()))
+						.unwrap();
+				}
+				let block = builder.build().unwrap().block;
+				futures::executor::block_on(client.import(BlockOrigin::Own, block)).unwrap();
+			}
+		};
+		let restrict = |client: &Arc<Client<Backend>>| {
+			let genesis = client.header(client.info().genesis_hash).unwrap().unwrap();
+			let best = client.header(client.info().best_hash).unwrap().unwrap();
+			futures::executor::block_on(rule.restrict_vote(client.clone(), &genesis, &best, &best))
+				.map(|(_, number)| number)
+		};
+
+		// no lag set on-chain, votes aren't restricted.
+		import_blocks(&mut client, 10, None);
+		assert_eq!(restrict(&client), None);
+
+		// the lag set on-chain at the best block is used.
+		import_blocks(&mut client, 1, Some(5));
+		import_blocks(&mut client, 9, None);
+		assert_eq!(restrict(&client), Some(15));
+
+		import_blocks(&mut client, 1, Some(2));
+		assert_eq!(restrict(&client), Some(19));
+	}
 }
@@ -34,9 +34,79 @@ use sp_runtime::traits::{Block as BlockT, Zero};
 use sp_timestamp::{InherentType, INHERENT_IDENTIFIER};
 use std::{
 	sync::{atomic, Arc},
-	time::SystemTime,
+	time::{Duration, SystemTime},
 };
 
+/// Shared clock offset that lets the timestamp used for new blocks be moved away from the
+/// system time, e.g. to test time dependent runtime logic without waiting.
+///
+/// All clones share the same offset, so one handle can be given to the RPC while another is
+/// used as (or attached to) the timestamp inherent data provider.
+#[derive(Clone, Default)]
+pub struct TimeWarp {
+	// offset in milliseconds, relative to the system time
+	offset: Arc<atomic::AtomicI64>,
+}
+
+impl TimeWarp {
+	/// Create a new `TimeWarp` that follows the system time.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Gets the warped time stamp.
+	pub fn timestamp(&self) -> sp_timestamp::Timestamp {
+		let now = system_millis() as i64;
+		let offset = self.offset.load(atomic::Ordering::SeqCst);
+		sp_timestamp::Timestamp::new(now.saturating_add(offset).max(0) as u64)
+	}
+
+	/// Moves the warped time forward by `duration`, returns the new time stamp.
+	pub fn advance(&self, duration: Duration) -> sp_timestamp::Timestamp {
+		let millis = i64::try_from(duration.as_millis()).unwrap_or(i64::MAX);
+		// `fetch_add` wraps, so go through `fetch_update` to saturate instead.
+		let _ = self
+			.offset
+			.fetch_update(atomic::Ordering::SeqCst, atomic::Ordering::SeqCst, |o| {
+				Some(o.saturating_add(millis))
+			});
+		self.timestamp()
+	}
+
+	/// Sets the warped time to `timestamp`, returns the new time stamp.
+	pub fn set(&self, timestamp: sp_timestamp::Timestamp) -> sp_timestamp::Timestamp {
+		let offset = (*timestamp as i64).saturating_sub(system_millis() as i64);
+		self.offset.store(offset, atomic::Ordering::SeqCst);
+		self.timestamp()
+	}
+}
+
+#[async_trait::async_trait]
+impl InherentDataProvider for TimeWarp {
+	async fn provide_inherent_data(
+		&self,
+		inherent_data: &mut InherentData,
+	) -> Result<(), sp_inherents::Error> {
+		let time: InherentType = self.timestamp();
+		inherent_data.put_data(INHERENT_IDENTIFIER, &time)
+	}
+
+	async fn try_handle_error(
+		&self,
+		_: &InherentIdentifier,
+		_: &[u8],
+	) -> Option<Result<(), sp_inherents::Error>> {
+		None
+	}
+}
+
+fn system_millis() -> u64 {
+	SystemTime::now()
+		.duration_since(SystemTime::UNIX_EPOCH)
+		.map(|d| d.as_millis() as u64)
+		.unwrap_or_default()
+}
+
 /// Provide duration since unix epoch in millisecond for timestamp inherent.
 /// Mocks the timestamp inherent to always produce a valid timestamp for the next slot.
 ///
@@ -50,6 +120,8 @@ pub struct SlotTimestampProvider {
 	unix_millis: atomic::AtomicU64,
 	// configured slot_duration in the runtime
 	slot_duration: SlotDuration,
+	// optional clock the mocked time is fast-forwarded to
+	time_warp: Option<TimeWarp>,
 }
 
 impl SlotTimestampProvider {
@@ -69,7 +141,7 @@ impl SlotTimestampProvider {
 			Ok(slot_number)
 		})?;
 
-		Ok(Self { unix_millis: atomic::AtomicU64::new(time), slot_duration })
+		Ok(Self { unix_millis: atomic::AtomicU64::new(time), slot_duration, time_warp: None })
 	}
 
 	/// Create a new mocked time stamp provider, for aura
@@ -87,7 +159,7 @@ impl SlotTimestampProvider {
 			Ok(slot_number)
 		})?;
 
-		Ok(Self { unix_millis: atomic::AtomicU64::new(time), slot_duration })
+		Ok(Self { unix_millis: atomic::AtomicU64::new(time), slot_duration, time_warp: None })
 	}
 
 	fn with_header<F, C, B>(
@@ -122,6 +194,15 @@ impl SlotTimestampProvider {
 		Ok(time)
 	}
 
+	/// Fast-forward the mocked time to the given [`TimeWarp`] whenever it is ahead.
+	///
+	/// The warped time is rounded down to a slot boundary and never moves the mocked time
+	/// backwards, as that would produce blocks for slots that are already taken.
+	pub fn with_time_warp(mut self, time_warp: TimeWarp) -> Self {
+		self.time_warp = Some(time_warp);
+		self
+	}
+
 	/// Get the current slot number
 	pub fn slot(&self) -> Slot {
 		Slot::from_timestamp(
@@ -142,6 +223,11 @@ impl InherentDataProvider for SlotTimestampProvider {
 		&self,
 		inherent_data: &mut InherentData,
 	) -> Result<(), sp_inherents::Error> {
+		if let Some(ref time_warp) = self.time_warp {
+			let slot_duration = self.slot_duration.as_millis() as u64;
+			let warped = *time_warp.timestamp() / slot_duration * slot_duration;
+			self.unix_millis.fetch_max(warped, atomic::Ordering::SeqCst);
+		}
 		// we update the time here.
 		let new_time: InherentType = self
 			.unix_millis
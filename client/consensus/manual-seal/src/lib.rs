@@ -22,8 +22,9 @@
 use futures::prelude::*;
 use futures_timer::Delay;
 use prometheus_endpoint::Registry;
+use sc_block_builder::{BlockBuilderApi, BlockBuilderProvider};
 use sc_client_api::{
-	backend::{Backend as ClientBackend, Finalizer},
+	backend::{Backend as ClientBackend, Finalizer, StateBackendFor},
 	client::BlockchainEvents,
 };
 use sc_consensus::{
@@ -39,6 +40,7 @@ use std::{marker::PhantomData, sync::Arc, time::Duration};
 
 mod error;
 mod finalize_block;
+mod revert_to;
 mod seal_block;
 
 pub mod consensus;
@@ -48,11 +50,15 @@ pub use self::{
 	consensus::ConsensusDataProvider,
 	error::Error,
 	finalize_block::{finalize_block, FinalizeBlockParams},
-	rpc::{CreatedBlock, EngineCommand},
-	seal_block::{seal_block, SealBlockParams, MAX_PROPOSAL_DURATION},
+	revert_to::{revert_to, RevertToParams},
+	rpc::{CreatedBlock, EngineCommand, ExtrinsicResult},
+	seal_block::{
+		seal_block, seal_extrinsics, BuildExtrinsicsBlock, ClientExtrinsicsBuilder,
+		ExtrinsicsBlock, SealBlockParams, SealExtrinsicsParams, MAX_PROPOSAL_DURATION,
+	},
 };
 use sc_transaction_pool_api::TransactionPool;
-use sp_api::{ProvideRuntimeApi, TransactionFor};
+use sp_api::{ApiExt, ProvideRuntimeApi, TransactionFor};
 
 const LOG_TARGET: &str = "manual-seal";
 
@@ -154,7 +160,98 @@ pub struct DelayedFinalizeParams<C, S> {
 }
 
 /// Creates the background authorship task for the manually seal engine.
+///
+/// [`EngineCommand::RevertTo`] is not supported, use [`run_manual_seal_with_revert`] for that.
+/// [`EngineCommand::SealExtrinsics`] is not supported, use [`run_manual_seal_with_extrinsics`]
+/// for that.
 pub async fn run_manual_seal<B, BI, CB, E, C, TP, SC, CS, CIDP, P>(
+	params: ManualSealParams<B, BI, E, C, TP, SC, CS, CIDP, P>,
+) where
+	B: BlockT + 'static,
+	BI: BlockImport<B, Error = sp_consensus::Error, Transaction = sp_api::TransactionFor<C, B>>
+		+ Send
+		+ Sync
+		+ 'static,
+	C: HeaderBackend<B> + Finalizer<B, CB> + ProvideRuntimeApi<B> + 'static,
+	CB: ClientBackend<B> + 'static,
+	E: Environment<B> + 'static,
+	E::Proposer: Proposer<B, Proof = P, Transaction = TransactionFor<C, B>>,
+	CS: Stream<Item = EngineCommand<<B as BlockT>::Hash>> + Unpin + 'static,
+	SC: SelectChain<B> + 'static,
+	TransactionFor<C, B>: 'static,
+	TP: TransactionPool<Block = B>,
+	CIDP: CreateInherentDataProviders<B, ()>,
+	P: Send + Sync + 'static,
+{
+	manual_seal(params, None, None).await
+}
+
+/// Creates the background authorship task for the manually seal engine, supporting
+/// [`EngineCommand::RevertTo`] by reverting blocks in the given `backend`.
+pub async fn run_manual_seal_with_revert<B, BI, CB, E, C, TP, SC, CS, CIDP, P>(
+	params: ManualSealParams<B, BI, E, C, TP, SC, CS, CIDP, P>,
+	backend: Arc<CB>,
+) where
+	B: BlockT + 'static,
+	BI: BlockImport<B, Error = sp_consensus::Error, Transaction = sp_api::TransactionFor<C, B>>
+		+ Send
+		+ Sync
+		+ 'static,
+	C: HeaderBackend<B> + Finalizer<B, CB> + ProvideRuntimeApi<B> + 'static,
+	CB: ClientBackend<B> + 'static,
+	E: Environment<B> + 'static,
+	E::Proposer: Proposer<B, Proof = P, Transaction = TransactionFor<C, B>>,
+	CS: Stream<Item = EngineCommand<<B as BlockT>::Hash>> + Unpin + 'static,
+	SC: SelectChain<B> + 'static,
+	TransactionFor<C, B>: 'static,
+	TP: TransactionPool<Block = B>,
+	CIDP: CreateInherentDataProviders<B, ()>,
+	P: Send + Sync + 'static,
+{
+	manual_seal(params, Some(backend), None).await
+}
+
+/// Creates the background authorship task for the manually seal engine, supporting
+/// [`EngineCommand::SealExtrinsics`] by building blocks with the block builder of the client.
+///
+/// If `backend` is given, [`EngineCommand::RevertTo`] is supported as well, see
+/// [`run_manual_seal_with_revert`].
+pub async fn run_manual_seal_with_extrinsics<B, BI, CB, E, C, TP, SC, CS, CIDP, P>(
+	params: ManualSealParams<B, BI, E, C, TP, SC, CS, CIDP, P>,
+	backend: Option<Arc<CB>>,
+) where
+	B: BlockT + 'static,
+	BI: BlockImport<B, Error = sp_consensus::Error, Transaction = sp_api::TransactionFor<C, B>>
+		+ Send
+		+ Sync
+		+ 'static,
+	C: HeaderBackend<B>
+		+ Finalizer<B, CB>
+		+ ProvideRuntimeApi<B>
+		+ BlockBuilderProvider<CB, B, C>
+		+ Send
+		+ Sync
+		+ 'static,
+	C::Api: ApiExt<B, StateBackend = StateBackendFor<CB, B>> + BlockBuilderApi<B>,
+	CB: ClientBackend<B> + 'static,
+	E: Environment<B> + 'static,
+	E::Proposer: Proposer<B, Proof = P, Transaction = TransactionFor<C, B>>,
+	CS: Stream<Item = EngineCommand<<B as BlockT>::Hash>> + Unpin + 'static,
+	SC: SelectChain<B> + 'static,
+	TransactionFor<C, B>: 'static,
+	TP: TransactionPool<Block = B> + 'static,
+	CIDP: CreateInherentDataProviders<B, ()>,
+	P: Send + Sync + 'static,
+{
+	let builder =
+		ClientExtrinsicsBuilder::<C, CB, TP, <E::Proposer as Proposer<B>>::ProofRecording>::new(
+			params.client.clone(),
+			params.pool.clone(),
+		);
+	manual_seal(params, backend, Some(Box::new(builder))).await
+}
+
+async fn manual_seal<B, BI, CB, E, C, TP, SC, CS, CIDP, P>(
 	ManualSealParams {
 		mut block_import,
 		mut env,
@@ -165,6 +262,8 @@ pub async fn run_manual_seal<B, BI, CB, E, C, TP, SC, CS, CIDP, P>(
 		consensus_data_provider,
 		create_inherent_data_providers,
 	}: ManualSealParams<B, BI, E, C, TP, SC, CS, CIDP, P>,
+	backend: Option<Arc<CB>>,
+	extrinsics_builder: Option<Box<dyn BuildExtrinsicsBlock<B, TransactionFor<C, B>, P>>>,
 ) where
 	B: BlockT + 'static,
 	BI: BlockImport<B, Error = sp_consensus::Error, Transaction = sp_api::TransactionFor<C, B>>
@@ -200,6 +299,35 @@ pub async fn run_manual_seal<B, BI, CB, E, C, TP, SC, CS, CIDP, P>(
 				})
 				.await;
 			},
+			EngineCommand::SealExtrinsics {
+				extrinsics,
+				include_pool,
+				finalize,
+				parent_hash,
+				mut sender,
+			} => {
+				let Some(builder) = extrinsics_builder.as_deref() else {
+					let error = Error::UnsupportedCommand(
+						"sealing extrinsics requires `run_manual_seal_with_extrinsics`".into(),
+					);
+					rpc::send_result(&mut sender, Err(error));
+					continue
+				};
+				seal_extrinsics(SealExtrinsicsParams {
+					sender,
+					extrinsics,
+					include_pool,
+					parent_hash,
+					finalize,
+					client: client.clone(),
+					builder,
+					select_chain: &select_chain,
+					block_import: &mut block_import,
+					consensus_data_provider: consensus_data_provider.as_deref(),
+					create_inherent_data_providers: &create_inherent_data_providers,
+				})
+				.await;
+			},
 			EngineCommand::FinalizeBlock { hash, sender, justification } => {
 				let justification = justification.map(|j| (MANUAL_SEAL_ENGINE_ID, j));
 				finalize_block(FinalizeBlockParams {
@@ -211,6 +339,14 @@ pub async fn run_manual_seal<B, BI, CB, E, C, TP, SC, CS, CIDP, P>(
 				})
 				.await
 			},
+			EngineCommand::RevertTo { hash, sender } =>
+				revert_to(RevertToParams {
+					hash,
+					sender,
+					client: client.clone(),
+					backend: backend.clone(),
+				})
+				.await,
 		}
 	}
 }
@@ -361,11 +497,17 @@ pub async fn run_delayed_finalize<B, CB, C, S>(
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::{
+		consensus::timestamp::TimeWarp,
+		rpc::{ManualSeal, ManualSealApiServer},
+	};
+	use codec::Encode;
 	use sc_basic_authorship::ProposerFactory;
+	use sc_client_api::BlockBackend;
 	use sc_consensus::ImportedAux;
 	use sc_transaction_pool::{BasicPool, FullChainApi, Options, RevalidationType};
 	use sc_transaction_pool_api::{MaintainedTransactionPool, TransactionPool, TransactionSource};
-	use sp_inherents::InherentData;
+	use sp_inherents::{InherentData, InherentDataProvider};
 	use sp_runtime::generic::{BlockId, Digest, DigestItem};
 	use substrate_test_runtime_client::{
 		AccountKeyring::*, DefaultTestClientBuilderExt, TestClientBuilder, TestClientBuilderExt,
@@ -475,7 +617,8 @@ mod tests {
 					needs_justification: false,
 					bad_justification: false,
 					is_new_best: true,
-				}
+				},
+				extrinsics: None,
 			}
 		);
 		// assert that there's a new block in the db.
@@ -565,7 +708,8 @@ mod tests {
 					needs_justification: false,
 					bad_justification: false,
 					is_new_best: true,
-				}
+				},
+				extrinsics: None,
 			}
 		);
 		// assert that there's a new block in the db.
@@ -641,7 +785,8 @@ mod tests {
 					needs_justification: false,
 					bad_justification: false,
 					is_new_best: true,
-				}
+				},
+				extrinsics: None,
 			}
 		);
 		// assert that there's a new block in the db.
@@ -727,7 +872,8 @@ mod tests {
 					needs_justification: false,
 					bad_justification: false,
 					is_new_best: true
-				}
+				},
+				extrinsics: None,
 			}
 		);
 
@@ -820,4 +966,202 @@ mod tests {
 		let header = client.header(created_block.hash).unwrap().unwrap();
 		assert_eq!(header.number, 1);
 	}
+
+	#[tokio::test]
+	async fn manual_seal_blocks_and_revert() {
+		let builder = TestClientBuilder::new();
+		let backend = builder.backend();
+		let (client, select_chain) = builder.build_with_longest_chain();
+		let client = Arc::new(client);
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let genesis_hash = client.info().genesis_hash;
+		let pool = Arc::new(BasicPool::with_revalidation_type(
+			Options::default(),
+			true.into(),
+			api(),
+			None,
+			RevalidationType::Full,
+			spawner.clone(),
+			0,
+			genesis_hash,
+			genesis_hash,
+		));
+		let env = ProposerFactory::new(spawner.clone(), client.clone(), pool.clone(), None, None);
+
+		let (sink, commands_stream) = futures::channel::mpsc::channel(1024);
+		let params = ManualSealParams {
+			block_import: client.clone(),
+			env,
+			client: client.clone(),
+			pool: pool.clone(),
+			commands_stream,
+			select_chain,
+			consensus_data_provider: None,
+			create_inherent_data_providers: |_, _| async { Ok(()) },
+		};
+		let future = run_manual_seal_with_revert(params, backend);
+		std::thread::spawn(|| {
+			let rt = tokio::runtime::Runtime::new().unwrap();
+			rt.block_on(future);
+		});
+		let rpc = ManualSeal::new(sink);
+
+		let blocks = rpc.seal_blocks(3, true, false).await.unwrap();
+		assert_eq!(blocks.len(), 3);
+		for (i, block) in blocks.iter().enumerate() {
+			assert_eq!(client.header(block.hash).unwrap().unwrap().number, i as u64 + 1);
+		}
+		assert_eq!(client.info().best_hash, blocks[2].hash);
+
+		// too many blocks can't be sealed at once.
+		assert!(rpc.seal_blocks(rpc::MAX_SEAL_BLOCKS + 1, true, false).await.is_err());
+		assert_eq!(client.info().best_number, 3);
+
+		// unknown blocks can't be reverted to.
+		assert!(rpc.revert_to(Default::default()).await.is_err());
+
+		assert!(rpc.revert_to(blocks[0].hash).await.unwrap());
+		assert_eq!(client.info().best_number, 1);
+		assert_eq!(client.info().best_hash, blocks[0].hash);
+
+		// new blocks are built on top of the reverted chain.
+		let block = rpc.create_block(true, false, None).await.unwrap();
+		assert_eq!(client.header(block.hash).unwrap().unwrap().number, 2);
+		assert_eq!(client.info().best_hash, block.hash);
+	}
+
+	#[tokio::test]
+	async fn manual_seal_revert_requires_backend() {
+		let builder = TestClientBuilder::new();
+		let (client, select_chain) = builder.build_with_longest_chain();
+		let client = Arc::new(client);
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let genesis_hash = client.info().genesis_hash;
+		let pool = Arc::new(BasicPool::with_revalidation_type(
+			Options::default(),
+			true.into(),
+			api(),
+			None,
+			RevalidationType::Full,
+			spawner.clone(),
+			0,
+			genesis_hash,
+			genesis_hash,
+		));
+		let env = ProposerFactory::new(spawner.clone(), client.clone(), pool.clone(), None, None);
+
+		let (sink, commands_stream) = futures::channel::mpsc::channel(1024);
+		let future = run_manual_seal(ManualSealParams {
+			block_import: client.clone(),
+			env,
+			client: client.clone(),
+			pool: pool.clone(),
+			commands_stream,
+			select_chain,
+			consensus_data_provider: None,
+			create_inherent_data_providers: |_, _| async { Ok(()) },
+		});
+		std::thread::spawn(|| {
+			let rt = tokio::runtime::Runtime::new().unwrap();
+			rt.block_on(future);
+		});
+		let rpc = ManualSeal::new(sink);
+
+		rpc.seal_blocks(2, true, false).await.unwrap();
+		assert!(rpc.revert_to(genesis_hash).await.is_err());
+		assert_eq!(client.info().best_number, 2);
+
+		// sealing an exact list of extrinsics isn't supported either.
+		let extrinsics = vec![uxt(Alice, 0).encode().into()];
+		assert!(rpc.create_block_with_extrinsics(extrinsics, false, false, None).await.is_err());
+		assert_eq!(client.info().best_number, 2);
+	}
+
+	#[tokio::test]
+	async fn manual_seal_with_extrinsics() {
+		let builder = TestClientBuilder::new();
+		let (client, select_chain) = builder.build_with_longest_chain();
+		let client = Arc::new(client);
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let genesis_hash = client.info().genesis_hash;
+		let pool = Arc::new(BasicPool::with_revalidation_type(
+			Options::default(),
+			true.into(),
+			api(),
+			None,
+			RevalidationType::Full,
+			spawner.clone(),
+			0,
+			genesis_hash,
+			genesis_hash,
+		));
+		let env = ProposerFactory::new(spawner.clone(), client.clone(), pool.clone(), None, None);
+
+		let (sink, commands_stream) = futures::channel::mpsc::channel(1024);
+		let future = run_manual_seal_with_extrinsics(
+			ManualSealParams {
+				block_import: client.clone(),
+				env,
+				client: client.clone(),
+				pool: pool.clone(),
+				commands_stream,
+				select_chain,
+				consensus_data_provider: None,
+				create_inherent_data_providers: |_, _| async { Ok(()) },
+			},
+			None,
+		);
+		std::thread::spawn(|| {
+			let rt = tokio::runtime::Runtime::new().unwrap();
+			rt.block_on(future);
+		});
+		let rpc = ManualSeal::new(sink);
+
+		let extrinsics = vec![
+			uxt(Alice, 0).encode().into(),
+			uxt(Bob, 0).encode().into(),
+			// stale nonce
+			uxt(Alice, 0).encode().into(),
+			// not an extrinsic at all
+			vec![1, 2, 3].into(),
+		];
+		let created_block =
+			rpc.create_block_with_extrinsics(extrinsics, false, false, None).await.unwrap();
+
+		let results = created_block.extrinsics.unwrap();
+		assert_eq!(results.len(), 4);
+		assert_eq!(results[0], ExtrinsicResult::Applied);
+		assert_eq!(results[1], ExtrinsicResult::Applied);
+		assert_matches::assert_matches!(results[2], ExtrinsicResult::Rejected(_));
+		assert_matches::assert_matches!(results[3], ExtrinsicResult::Rejected(_));
+
+		// only the applied extrinsics made it into the block, in the given order.
+		let body = client.body(created_block.hash).unwrap().unwrap();
+		assert_eq!(body, vec![uxt(Alice, 0), uxt(Bob, 0)]);
+		assert_eq!(client.info().best_hash, created_block.hash);
+	}
+
+	#[tokio::test]
+	async fn manual_seal_time_warp() {
+		let (sink, _commands_stream) =
+			futures::channel::mpsc::channel::<EngineCommand<sp_core::H256>>(1024);
+		// the timestamp methods are only served with a time warp.
+		assert!(ManualSeal::new(sink.clone()).set_timestamp(0).is_err());
+
+		let time_warp = TimeWarp::new();
+		let rpc = ManualSeal::new(sink).with_time_warp(time_warp.clone());
+
+		let future = 4_000_000_000_000u64;
+		assert!(rpc.set_timestamp(future).unwrap() >= future);
+		let hour = 60 * 60 * 1000;
+		let advanced = rpc.advance_timestamp(hour).unwrap();
+		assert!(advanced >= future + hour);
+		assert!(*time_warp.timestamp() >= advanced);
+
+		let mut inherent_data = InherentData::new();
+		time_warp.provide_inherent_data(&mut inherent_data).await.unwrap();
+		let timestamp: sp_timestamp::InherentType =
+			inherent_data.get_data(&sp_timestamp::INHERENT_IDENTIFIER).unwrap().unwrap();
+		assert!(*timestamp >= advanced && *timestamp < advanced + hour);
+	}
 }
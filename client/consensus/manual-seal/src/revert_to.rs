@@ -0,0 +1,92 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Chain reversion utilities
+
+use crate::{rpc, Error};
+use sc_client_api::backend::Backend as ClientBackend;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::{Block as BlockT, NumberFor, Zero};
+use std::sync::Arc;
+
+/// params for reverting the chain.
+pub struct RevertToParams<B: BlockT, C, CB> {
+	/// hash of the block the chain is reverted to.
+	pub hash: <B as BlockT>::Hash,
+	/// sender to report errors/success to the rpc.
+	pub sender: rpc::Sender<()>,
+	/// Client instance, used to look up the block.
+	pub client: Arc<C>,
+	/// Backend the blocks are reverted in, reverting is not supported without it.
+	pub backend: Option<Arc<CB>>,
+}
+
+/// reverts the unfinalized part of the best chain down to the block with the given hash, which
+/// becomes the new best block.
+pub async fn revert_to<B, C, CB>(params: RevertToParams<B, C, CB>)
+where
+	B: BlockT,
+	C: HeaderBackend<B>,
+	CB: ClientBackend<B>,
+{
+	let RevertToParams { hash, mut sender, client, backend } = params;
+
+	match revert(hash, &*client, backend.as_deref()) {
+		Err(e) => {
+			log::warn!("Failed to revert to block {}: {}", hash, e);
+			rpc::send_result(&mut sender, Err(e))
+		},
+		Ok(reverted) => {
+			log::info!("⏪ Reverted {} blocks, best block is now: {}", reverted, hash);
+			rpc::send_result(&mut sender, Ok(()))
+		},
+	}
+}
+
+fn revert<B, C, CB>(
+	hash: <B as BlockT>::Hash,
+	client: &C,
+	backend: Option<&CB>,
+) -> Result<NumberFor<B>, Error>
+where
+	B: BlockT,
+	C: HeaderBackend<B>,
+	CB: ClientBackend<B>,
+{
+	let backend = backend.ok_or_else(|| {
+		Error::UnsupportedCommand("reverting requires the backend to be provided".into())
+	})?;
+	let number = client
+		.number(hash)?
+		.ok_or_else(|| Error::StringError(format!("Block {} not found", hash)))?;
+	if client.hash(number)? != Some(hash) {
+		return Err(Error::StringError(format!("Block {} is not part of the best chain", hash)))
+	}
+
+	let info = client.info();
+	if number < info.finalized_number {
+		return Err(Error::StringError(format!("Block {} is below the finalized block", hash)))
+	}
+
+	let to_revert = info.best_number - number;
+	if to_revert.is_zero() {
+		return Ok(to_revert)
+	}
+	let (reverted, _) = backend.revert(to_revert, false)?;
+	Ok(reverted)
+}
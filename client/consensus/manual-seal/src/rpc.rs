@@ -18,7 +18,7 @@
 
 //! RPC interface for the `ManualSeal` Engine.
 
-use crate::error::Error;
+use crate::{consensus::timestamp::TimeWarp, error::Error};
 use futures::{
 	channel::{mpsc, oneshot},
 	SinkExt,
@@ -26,10 +26,16 @@ use futures::{
 use jsonrpsee::{
 	core::{async_trait, Error as JsonRpseeError, RpcResult},
 	proc_macros::rpc,
+	types::error::{CallError, ErrorCode, ErrorObject},
 };
 use sc_consensus::ImportedAux;
 use serde::{Deserialize, Serialize};
+use sp_core::Bytes;
 use sp_runtime::EncodedJustification;
+use std::time::Duration;
+
+/// Maximal number of blocks that can be sealed with a single `engine_sealBlocks` call.
+pub const MAX_SEAL_BLOCKS: u32 = 1000;
 
 /// Sender passed to the authorship task to report errors or successes.
 pub type Sender<T> = Option<oneshot::Sender<std::result::Result<T, Error>>>;
@@ -53,6 +59,22 @@ pub enum EngineCommand<Hash> {
 		/// sender to report errors/success to the rpc.
 		sender: Sender<CreatedBlock<Hash>>,
 	},
+	/// Tells the engine to seal a new block containing exactly the given extrinsics
+	///
+	/// The extrinsics are applied in order, the apply result of each is reported in
+	/// [`CreatedBlock::extrinsics`].
+	SealExtrinsics {
+		/// encoded extrinsics to include in the block
+		extrinsics: Vec<Bytes>,
+		/// if true, the ready transactions of the pool are included after the given extrinsics.
+		include_pool: bool,
+		/// instantly finalize this block?
+		finalize: bool,
+		/// specify the parent hash of the about-to-created block
+		parent_hash: Option<Hash>,
+		/// sender to report errors/success to the rpc.
+		sender: Sender<CreatedBlock<Hash>>,
+	},
 	/// Tells the engine to finalize the block with the supplied hash
 	FinalizeBlock {
 		/// hash of the block
@@ -62,6 +84,15 @@ pub enum EngineCommand<Hash> {
 		/// finalization justification
 		justification: Option<EncodedJustification>,
 	},
+	/// Tells the engine to revert the best chain to the block with the supplied hash
+	///
+	/// Only unfinalized blocks of the best chain can be reverted.
+	RevertTo {
+		/// hash of the block that becomes the new best block
+		hash: Hash,
+		/// sender to report errors/success to the rpc.
+		sender: Sender<()>,
+	},
 }
 
 /// RPC trait that provides methods for interacting with the manual-seal authorship task over rpc.
@@ -76,6 +107,17 @@ pub trait ManualSealApi<Hash> {
 		parent_hash: Option<Hash>,
 	) -> RpcResult<CreatedBlock<Hash>>;
 
+	/// Instructs the manual-seal authorship task to create a new block containing the given
+	/// extrinsics, in the given order
+	#[method(name = "engine_createBlockWithExtrinsics")]
+	async fn create_block_with_extrinsics(
+		&self,
+		extrinsics: Vec<Bytes>,
+		include_pool: bool,
+		finalize: bool,
+		parent_hash: Option<Hash>,
+	) -> RpcResult<CreatedBlock<Hash>>;
+
 	/// Instructs the manual-seal authorship task to finalize a block
 	#[method(name = "engine_finalizeBlock")]
 	async fn finalize_block(
@@ -83,11 +125,36 @@ pub trait ManualSealApi<Hash> {
 		hash: Hash,
 		justification: Option<EncodedJustification>,
 	) -> RpcResult<bool>;
+
+	/// Instructs the manual-seal authorship task to create `count` blocks on top of each other
+	///
+	/// At most [`MAX_SEAL_BLOCKS`] blocks can be created at once.
+	#[method(name = "engine_sealBlocks")]
+	async fn seal_blocks(
+		&self,
+		count: u32,
+		create_empty: bool,
+		finalize: bool,
+	) -> RpcResult<Vec<CreatedBlock<Hash>>>;
+
+	/// Instructs the manual-seal authorship task to revert the best chain to the given block
+	#[method(name = "engine_revertTo")]
+	async fn revert_to(&self, hash: Hash) -> RpcResult<bool>;
+
+	/// Sets the timestamp (in milliseconds) used for the next blocks, returns the new timestamp
+	#[method(name = "engine_setTimestamp")]
+	fn set_timestamp(&self, timestamp: u64) -> RpcResult<u64>;
+
+	/// Moves the timestamp used for the next blocks forward by `millis`, returns the new
+	/// timestamp
+	#[method(name = "engine_advanceTimestamp")]
+	fn advance_timestamp(&self, millis: u64) -> RpcResult<u64>;
 }
 
 /// A struct that implements the [`ManualSealApiServer`].
 pub struct ManualSeal<Hash> {
 	import_block_channel: mpsc::Sender<EngineCommand<Hash>>,
+	time_warp: Option<TimeWarp>,
 }
 
 /// return type of `engine_createBlock`
@@ -97,12 +164,42 @@ pub struct CreatedBlock<Hash> {
 	pub hash: Hash,
 	/// some extra details about the import operation
 	pub aux: ImportedAux,
+	/// apply results of the requested extrinsics, in the requested order. Only reported for
+	/// blocks sealed with explicit extrinsics.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub extrinsics: Option<Vec<ExtrinsicResult>>,
+}
+
+/// How an extrinsic passed to `engine_createBlockWithExtrinsics` was applied
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ExtrinsicResult {
+	/// the extrinsic is included in the block and was dispatched successfully.
+	Applied,
+	/// the extrinsic is included in the block, but its dispatch failed.
+	DispatchFailed(String),
+	/// the extrinsic couldn't be applied and is not part of the block.
+	Rejected(String),
 }
 
 impl<Hash> ManualSeal<Hash> {
 	/// Create new `ManualSeal` with the given reference to the client.
 	pub fn new(import_block_channel: mpsc::Sender<EngineCommand<Hash>>) -> Self {
-		Self { import_block_channel }
+		Self { import_block_channel, time_warp: None }
+	}
+
+	/// Serve the timestamp methods by moving the given [`TimeWarp`].
+	///
+	/// The same `TimeWarp` needs to provide the timestamp inherent for these to have an effect.
+	pub fn with_time_warp(mut self, time_warp: TimeWarp) -> Self {
+		self.time_warp = Some(time_warp);
+		self
+	}
+
+	fn time_warp(&self) -> Result<&TimeWarp, Error> {
+		self.time_warp.as_ref().ok_or_else(|| {
+			Error::UnsupportedCommand("no time warp configured for the timestamp inherent".into())
+		})
 	}
 }
 
@@ -133,6 +230,32 @@ impl<Hash: Send + 'static> ManualSealApiServer<Hash> for ManualSeal<Hash> {
 		}
 	}
 
+	async fn create_block_with_extrinsics(
+		&self,
+		extrinsics: Vec<Bytes>,
+		include_pool: bool,
+		finalize: bool,
+		parent_hash: Option<Hash>,
+	) -> RpcResult<CreatedBlock<Hash>> {
+		let mut sink = self.import_block_channel.clone();
+		let (sender, receiver) = oneshot::channel();
+		let command = EngineCommand::SealExtrinsics {
+			extrinsics,
+			include_pool,
+			finalize,
+			parent_hash,
+			sender: Some(sender),
+		};
+
+		sink.send(command).await?;
+
+		match receiver.await {
+			Ok(Ok(rx)) => Ok(rx),
+			Ok(Err(e)) => Err(e.into()),
+			Err(e) => Err(JsonRpseeError::to_call_error(e)),
+		}
+	}
+
 	async fn finalize_block(
 		&self,
 		hash: Hash,
@@ -144,6 +267,48 @@ impl<Hash: Send + 'static> ManualSealApiServer<Hash> for ManualSeal<Hash> {
 		sink.send(command).await?;
 		receiver.await.map(|_| true).map_err(|e| JsonRpseeError::to_call_error(e))
 	}
+
+	async fn seal_blocks(
+		&self,
+		count: u32,
+		create_empty: bool,
+		finalize: bool,
+	) -> RpcResult<Vec<CreatedBlock<Hash>>> {
+		if count > MAX_SEAL_BLOCKS {
+			return Err(JsonRpseeError::Call(CallError::Custom(ErrorObject::owned(
+				ErrorCode::InvalidParams.code(),
+				format!("Can't seal more than {} blocks at once", MAX_SEAL_BLOCKS),
+				None::<()>,
+			))))
+		}
+
+		let mut blocks = Vec::new();
+		for _ in 0..count {
+			blocks.push(self.create_block(create_empty, finalize, None).await?);
+		}
+		Ok(blocks)
+	}
+
+	async fn revert_to(&self, hash: Hash) -> RpcResult<bool> {
+		let mut sink = self.import_block_channel.clone();
+		let (sender, receiver) = oneshot::channel();
+		let command = EngineCommand::RevertTo { hash, sender: Some(sender) };
+		sink.send(command).await?;
+
+		match receiver.await {
+			Ok(Ok(())) => Ok(true),
+			Ok(Err(e)) => Err(e.into()),
+			Err(e) => Err(JsonRpseeError::to_call_error(e)),
+		}
+	}
+
+	fn set_timestamp(&self, timestamp: u64) -> RpcResult<u64> {
+		Ok(*self.time_warp()?.set(timestamp.into()))
+	}
+
+	fn advance_timestamp(&self, millis: u64) -> RpcResult<u64> {
+		Ok(*self.time_warp()?.advance(Duration::from_millis(millis)))
+	}
 }
 
 /// report any errors or successes encountered by the authorship task back
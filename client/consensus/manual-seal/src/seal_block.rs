@@ -18,20 +18,35 @@
 
 //! Block sealing utilities
 
-use crate::{rpc, ConsensusDataProvider, CreatedBlock, Error};
+use crate::{rpc, ConsensusDataProvider, CreatedBlock, Error, ExtrinsicResult};
+use codec::{Decode, Encode};
 use futures::prelude::*;
+use sc_block_builder::{BlockBuilderApi, BlockBuilderProvider, RecordProof};
+use sc_client_api::backend::{self, Backend as ClientBackend};
 use sc_consensus::{BlockImport, BlockImportParams, ForkChoiceStrategy, ImportResult, StateAction};
-use sc_transaction_pool_api::TransactionPool;
-use sp_api::{ProvideRuntimeApi, TransactionFor};
-use sp_blockchain::HeaderBackend;
-use sp_consensus::{self, BlockOrigin, Environment, Proposer, SelectChain};
-use sp_inherents::{CreateInherentDataProviders, InherentDataProvider};
-use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
-use std::{sync::Arc, time::Duration};
+use sc_transaction_pool_api::{InPoolTransaction, TransactionPool};
+use sp_api::{ApiExt, ProvideRuntimeApi, TransactionFor};
+use sp_blockchain::{ApplyExtrinsicFailed::Validity, Error::ApplyExtrinsicFailed, HeaderBackend};
+use sp_consensus::{self, BlockOrigin, Environment, ProofRecording, Proposer, SelectChain};
+use sp_core::Bytes;
+use sp_inherents::{CreateInherentDataProviders, InherentData, InherentDataProvider};
+use sp_runtime::{
+	traits::{Block as BlockT, Header as HeaderT},
+	Digest,
+};
+use std::{
+	marker::PhantomData,
+	sync::Arc,
+	time::{Duration, Instant},
+};
 
 /// max duration for creating a proposal in secs
 pub const MAX_PROPOSAL_DURATION: u64 = 10;
 
+/// max size of a block filled with pool transactions by [`seal_extrinsics`], the default of the
+/// proposer.
+const MAX_BLOCK_SIZE: usize = 4 * 1024 * 1024 + 512;
+
 /// params for sealing a new block
 pub struct SealBlockParams<'a, B: BlockT, BI, SC, C: ProvideRuntimeApi<B>, E, TP, CIDP, P> {
 	/// if true, empty blocks(without extrinsics) will be created.
@@ -154,8 +169,248 @@ pub async fn seal_block<B, BI, SC, C, E, TP, CIDP, P>(
 		post_header.digest_mut().logs.extend(params.post_digests.iter().cloned());
 
 		match block_import.import_block(params).await? {
-			ImportResult::Imported(aux) =>
-				Ok(CreatedBlock { hash: <B as BlockT>::Header::hash(&post_header), aux }),
+			ImportResult::Imported(aux) => Ok(CreatedBlock {
+				hash: <B as BlockT>::Header::hash(&post_header),
+				aux,
+				extrinsics: None,
+			}),
+			other => Err(other.into()),
+		}
+	};
+
+	rpc::send_result(&mut sender, future.await)
+}
+
+/// A block containing an exact list of extrinsics, built by a [`BuildExtrinsicsBlock`].
+pub struct ExtrinsicsBlock<B: BlockT, Transaction, Proof> {
+	/// the built block.
+	pub block: B,
+	/// storage changes of the block.
+	pub storage_changes: sc_consensus::StorageChanges<B, Transaction>,
+	/// storage proof, if proof recording is enabled.
+	pub proof: Proof,
+	/// how each of the given extrinsics was applied.
+	pub results: Vec<ExtrinsicResult>,
+}
+
+/// Builds blocks containing an exact list of extrinsics for [`seal_extrinsics`].
+///
+/// Building a block directly requires the block builder runtime api, this trait keeps these
+/// bounds off the entry points that don't seal extrinsics.
+pub trait BuildExtrinsicsBlock<B: BlockT, Transaction, Proof>: Send + Sync {
+	/// Builds a block on top of `parent` with the inherents, the given extrinsics and
+	/// optionally the ready transactions of the pool.
+	fn build(
+		&self,
+		parent: &B::Header,
+		digest: Digest,
+		inherent_data: InherentData,
+		extrinsics: Vec<Bytes>,
+		include_pool: bool,
+	) -> Result<ExtrinsicsBlock<B, Transaction, Proof>, Error>;
+}
+
+/// [`BuildExtrinsicsBlock`] building blocks with the block builder of the client.
+pub struct ClientExtrinsicsBuilder<C, CB, TP, PR> {
+	client: Arc<C>,
+	pool: Arc<TP>,
+	_phantom: PhantomData<fn() -> (CB, PR)>,
+}
+
+impl<C, CB, TP, PR> ClientExtrinsicsBuilder<C, CB, TP, PR> {
+	/// Creates a new [`ClientExtrinsicsBuilder`].
+	///
+	/// `PR` has to match the proof recording of the proposer used for [`seal_block`].
+	pub fn new(client: Arc<C>, pool: Arc<TP>) -> Self {
+		Self { client, pool, _phantom: PhantomData }
+	}
+}
+
+impl<B, C, CB, TP, PR> BuildExtrinsicsBlock<B, TransactionFor<C, B>, PR::Proof>
+	for ClientExtrinsicsBuilder<C, CB, TP, PR>
+where
+	B: BlockT,
+	C: HeaderBackend<B> + ProvideRuntimeApi<B> + BlockBuilderProvider<CB, B, C> + Send + Sync,
+	C::Api: ApiExt<B, StateBackend = backend::StateBackendFor<CB, B>> + BlockBuilderApi<B>,
+	CB: ClientBackend<B>,
+	TP: TransactionPool<Block = B>,
+	PR: ProofRecording,
+{
+	fn build(
+		&self,
+		parent: &B::Header,
+		digest: Digest,
+		inherent_data: InherentData,
+		extrinsics: Vec<Bytes>,
+		include_pool: bool,
+	) -> Result<ExtrinsicsBlock<B, TransactionFor<C, B>, PR::Proof>, Error> {
+		let deadline = Instant::now() + Duration::from_secs(MAX_PROPOSAL_DURATION);
+		let mut block_builder =
+			self.client
+				.new_block_at(parent.hash(), digest, RecordProof::from(PR::ENABLED))?;
+
+		for inherent in block_builder.create_inherents(inherent_data)? {
+			block_builder.push(inherent)?;
+		}
+
+		let results = extrinsics
+			.into_iter()
+			.map(|encoded| {
+				let extrinsic = match B::Extrinsic::decode(&mut &encoded[..]) {
+					Ok(extrinsic) => extrinsic,
+					Err(e) => return ExtrinsicResult::Rejected(format!("Failed to decode: {}", e)),
+				};
+				match block_builder.push_with_outcome(extrinsic) {
+					Ok(Ok(())) => ExtrinsicResult::Applied,
+					Ok(Err(e)) => ExtrinsicResult::DispatchFailed(format!("{:?}", e)),
+					Err(e) => ExtrinsicResult::Rejected(e.to_string()),
+				}
+			})
+			.collect();
+
+		// fill the rest of the block from the pool, stopping once the block is full or the
+		// deadline is reached like the proposer does.
+		if include_pool {
+			for tx in self.pool.ready() {
+				if Instant::now() > deadline {
+					log::debug!(target: crate::LOG_TARGET, "Deadline reached, block is full.");
+					break
+				}
+
+				let block_size = block_builder.estimate_block_size(PR::ENABLED);
+				if block_size + tx.data().encoded_size() > MAX_BLOCK_SIZE {
+					log::debug!(target: crate::LOG_TARGET, "Block size limit reached.");
+					break
+				}
+
+				match block_builder.push(tx.data().clone()) {
+					Ok(()) => {},
+					Err(ApplyExtrinsicFailed(Validity(e))) if e.exhausted_resources() => {
+						log::debug!(target: crate::LOG_TARGET, "Block resources exhausted.");
+						break
+					},
+					Err(e) => {
+						log::debug!(target: crate::LOG_TARGET, "Skipping pool transaction: {}", e)
+					},
+				}
+			}
+		}
+
+		let (block, storage_changes, proof) = block_builder.build()?.into_inner();
+		let proof = PR::into_proof(proof).map_err(|e| Error::StringError(e.to_string()))?;
+
+		Ok(ExtrinsicsBlock {
+			block,
+			storage_changes: sc_consensus::StorageChanges::Changes(storage_changes),
+			proof,
+			results,
+		})
+	}
+}
+
+/// params for sealing a new block with an exact list of extrinsics
+pub struct SealExtrinsicsParams<'a, B: BlockT, BI, SC, C: ProvideRuntimeApi<B>, CIDP, P> {
+	/// encoded extrinsics, applied in the given order right after the inherents.
+	pub extrinsics: Vec<Bytes>,
+	/// if true, the ready transactions of the pool are applied after the given extrinsics.
+	pub include_pool: bool,
+	/// instantly finalize this block?
+	pub finalize: bool,
+	/// specify the parent hash of the about-to-created block
+	pub parent_hash: Option<<B as BlockT>::Hash>,
+	/// sender to report errors/success to the rpc.
+	pub sender: rpc::Sender<CreatedBlock<<B as BlockT>::Hash>>,
+	/// header backend
+	pub client: Arc<C>,
+	/// builds the block with the given extrinsics
+	pub builder: &'a dyn BuildExtrinsicsBlock<B, TransactionFor<C, B>, P>,
+	/// SelectChain object
+	pub select_chain: &'a SC,
+	/// Digest provider for inclusion in blocks.
+	pub consensus_data_provider:
+		Option<&'a dyn ConsensusDataProvider<B, Proof = P, Transaction = TransactionFor<C, B>>>,
+	/// block import object
+	pub block_import: &'a mut BI,
+	/// Something that can create the inherent data providers.
+	pub create_inherent_data_providers: &'a CIDP,
+}
+
+/// seals a new block containing exactly the given extrinsics, reporting how each of them
+/// was applied.
+///
+/// Unlike [`seal_block`] this doesn't go through the proposer, the block is built directly on
+/// top of the parent so that the order of the extrinsics is preserved. Extrinsics that can't
+/// be applied are left out of the block.
+pub async fn seal_extrinsics<B, BI, SC, C, CIDP, P>(
+	SealExtrinsicsParams {
+		extrinsics,
+		include_pool,
+		finalize,
+		parent_hash,
+		client,
+		builder,
+		select_chain,
+		block_import,
+		create_inherent_data_providers,
+		consensus_data_provider: digest_provider,
+		mut sender,
+	}: SealExtrinsicsParams<'_, B, BI, SC, C, CIDP, P>,
+) where
+	B: BlockT,
+	BI: BlockImport<B, Error = sp_consensus::Error, Transaction = sp_api::TransactionFor<C, B>>
+		+ Send
+		+ Sync
+		+ 'static,
+	C: HeaderBackend<B> + ProvideRuntimeApi<B>,
+	SC: SelectChain<B>,
+	TransactionFor<C, B>: 'static,
+	CIDP: CreateInherentDataProviders<B, ()>,
+	P: Send + Sync + 'static,
+{
+	let future = async {
+		let parent = match parent_hash {
+			Some(hash) =>
+				client.header(hash)?.ok_or_else(|| Error::BlockNotFound(format!("{}", hash)))?,
+			None => select_chain.best_chain().await?,
+		};
+
+		let inherent_data_providers = create_inherent_data_providers
+			.create_inherent_data_providers(parent.hash(), ())
+			.await
+			.map_err(|e| Error::Other(e))?;
+
+		let inherent_data = inherent_data_providers.create_inherent_data().await?;
+
+		let digest = if let Some(digest_provider) = digest_provider {
+			digest_provider.create_digest(&parent, &inherent_data)?
+		} else {
+			Default::default()
+		};
+
+		// the block builder isn't `Send`, so the block is built without awaiting anything.
+		let ExtrinsicsBlock { block, storage_changes, proof, results } =
+			builder.build(&parent, digest, inherent_data.clone(), extrinsics, include_pool)?;
+
+		let (header, body) = block.deconstruct();
+		let mut params = BlockImportParams::new(BlockOrigin::Own, header.clone());
+		params.body = Some(body);
+		params.finalized = finalize;
+		params.fork_choice = Some(ForkChoiceStrategy::LongestChain);
+		params.state_action = StateAction::ApplyChanges(storage_changes);
+
+		if let Some(digest_provider) = digest_provider {
+			digest_provider.append_block_import(&parent, &mut params, &inherent_data, proof)?;
+		}
+
+		let mut post_header = header.clone();
+		post_header.digest_mut().logs.extend(params.post_digests.iter().cloned());
+
+		match block_import.import_block(params).await? {
+			ImportResult::Imported(aux) => Ok(CreatedBlock {
+				hash: <B as BlockT>::Header::hash(&post_header),
+				aux,
+				extrinsics: Some(results),
+			}),
 			other => Err(other.into()),
 		}
 	};
@@ -38,10 +38,20 @@
 //! for the auxiliary storage. It is also possible to just use the runtime
 //! as the storage, but it is not recommended as it won't work well with light
 //! clients.
+//!
+//! Chains accepting blocks sealed with several algorithms can combine them into a
+//! [`MultiAlgorithm`], which selects the algorithm of a block by its pre-runtime digest. The
+//! [`Lwma`] difficulty adjustment can be used to retarget the difficulty of each of them.
 
+mod lwma;
+mod multi;
 mod worker;
 
-pub use crate::worker::{MiningBuild, MiningHandle, MiningMetadata};
+pub use crate::{
+	lwma::{next_difficulty, BlockRecord, Lwma, LwmaParams},
+	multi::{BoxPowAlgorithm, MultiAlgorithm},
+	worker::{MiningBuild, MiningHandle, MiningMetadata},
+};
 
 use crate::worker::UntilImportedOrTimeout;
 use codec::{Decode, Encode};
@@ -57,7 +67,7 @@ use sp_api::ProvideRuntimeApi;
 use sp_block_builder::BlockBuilder as BlockBuilderApi;
 use sp_blockchain::HeaderBackend;
 use sp_consensus::{Environment, Error as ConsensusError, Proposer, SelectChain, SyncOracle};
-use sp_consensus_pow::{Seal, TotalDifficulty, POW_ENGINE_ID};
+use sp_consensus_pow::{AlgorithmId, Seal, TotalDifficulty, POW_ENGINE_ID};
 use sp_inherents::{CreateInherentDataProviders, InherentDataProvider};
 use sp_runtime::{
 	generic::{BlockId, Digest, DigestItem},
@@ -103,6 +113,10 @@ pub enum Error<B: BlockT> {
 	CheckInherentsUnknownError(sp_inherents::InherentIdentifier),
 	#[error("Multiple pre-runtime digests")]
 	MultiplePreRuntimeDigests,
+	#[error("Missing pre-runtime digest selecting the PoW algorithm")]
+	NoPreRuntimeDigest,
+	#[error("Unknown PoW algorithm {0:?}")]
+	UnknownAlgorithm(AlgorithmId),
 	#[error(transparent)]
 	Client(sp_blockchain::Error),
 	#[error(transparent)]
@@ -146,12 +160,30 @@ pub struct PowIntermediate<Difficulty> {
 pub static INTERMEDIATE_KEY: &[u8] = b"pow1";
 
 /// Auxiliary storage data for PoW.
-#[derive(Encode, Decode, Clone, Debug, Default)]
+#[derive(Encode, Clone, Debug, Default)]
 pub struct PowAux<Difficulty> {
 	/// Difficulty of the current block.
 	pub difficulty: Difficulty,
 	/// Total difficulty up to current block.
 	pub total_difficulty: Difficulty,
+	/// Timestamp of the current block, in milliseconds.
+	///
+	/// Recorded on import if the algorithm provides it, see [`PowAlgorithm::timestamp`].
+	pub timestamp: Option<u64>,
+}
+
+impl<Difficulty: Decode> Decode for PowAux<Difficulty> {
+	fn decode<I: codec::Input>(input: &mut I) -> Result<Self, codec::Error> {
+		let difficulty = Difficulty::decode(input)?;
+		let total_difficulty = Difficulty::decode(input)?;
+		// auxiliary data written before the timestamp was introduced ends here.
+		let timestamp = match input.remaining_len()? {
+			Some(0) => None,
+			_ => Option::<u64>::decode(input)?,
+		};
+
+		Ok(Self { difficulty, total_difficulty, timestamp })
+	}
 }
 
 impl<Difficulty> PowAux<Difficulty>
@@ -179,6 +211,17 @@ pub trait PowAlgorithm<B: BlockT> {
 	/// This function will be called twice during the import process, so the implementation
 	/// should be properly cached.
 	fn difficulty(&self, parent: B::Hash) -> Result<Self::Difficulty, Error<B>>;
+	/// Get the next block's difficulty, given the pre-runtime digest of the block.
+	///
+	/// Only algorithms whose difficulty depends on the pre-runtime digest, like
+	/// [`MultiAlgorithm`], need to override this.
+	fn difficulty_for(
+		&self,
+		parent: B::Hash,
+		_pre_digest: Option<&[u8]>,
+	) -> Result<Self::Difficulty, Error<B>> {
+		self.difficulty(parent)
+	}
 	/// Verify that the seal is valid against given pre hash when parent block is not yet imported.
 	///
 	/// None means that preliminary verify is not available for this algorithm.
@@ -198,6 +241,18 @@ pub trait PowAlgorithm<B: BlockT> {
 	fn break_tie(&self, _own_seal: &Seal, _new_seal: &Seal) -> bool {
 		false
 	}
+	/// Get the timestamp of the block with the given hash, in milliseconds.
+	///
+	/// Called right after the block is imported, while its state is available, to record the
+	/// timestamp in [`PowAux::timestamp`]. Only timestamp-based difficulty adjustments, like
+	/// [`Lwma`], need to override this.
+	fn timestamp(
+		&self,
+		_hash: B::Hash,
+		_pre_digest: Option<&[u8]>,
+	) -> Result<Option<u64>, Error<B>> {
+		Ok(None)
+	}
 	/// Verify that the difficulty is valid against given seal.
 	fn verify(
 		&self,
@@ -358,13 +413,15 @@ where
 		let intermediate = block
 			.remove_intermediate::<PowIntermediate<Algorithm::Difficulty>>(INTERMEDIATE_KEY)?;
 
+		let pre_hash = block.header.hash();
+		let pre_digest = find_pre_digest::<B>(&block.header)?;
+
 		let difficulty = match intermediate.difficulty {
 			Some(difficulty) => difficulty,
-			None => self.algorithm.difficulty(parent_hash)?,
+			None => self
+				.algorithm
+				.difficulty_for(parent_hash, pre_digest.as_ref().map(|v| &v[..]))?,
 		};
-
-		let pre_hash = block.header.hash();
-		let pre_digest = find_pre_digest::<B>(&block.header)?;
 		if !self.algorithm.verify(
 			&BlockId::hash(parent_hash),
 			&pre_hash,
@@ -377,9 +434,11 @@ where
 
 		aux.difficulty = difficulty;
 		aux.total_difficulty.increment(difficulty);
+		aux.timestamp = None;
 
-		let key = aux_key(&block.post_hash());
-		block.auxiliary.push((key, Some(aux.encode())));
+		let hash = block.post_hash();
+		let key = aux_key(&hash);
+		block.auxiliary.push((key.clone(), Some(aux.encode())));
 		if block.fork_choice.is_none() {
 			block.fork_choice = Some(ForkChoiceStrategy::Custom(
 				match aux.total_difficulty.cmp(&best_aux.total_difficulty) {
@@ -395,7 +454,25 @@ where
 			));
 		}
 
-		self.inner.import_block(block).await.map_err(Into::into)
+		let result = self.inner.import_block(block).await.map_err(Into::<ConsensusError>::into)?;
+
+		// the state of the block is only around now that it's imported.
+		if let ImportResult::Imported(_) = result {
+			match self.algorithm.timestamp(hash, pre_digest.as_ref().map(|v| &v[..])) {
+				Ok(Some(timestamp)) => {
+					aux.timestamp = Some(timestamp);
+					self.client
+						.insert_aux(&[(&key[..], &aux.encode()[..])], &[])
+						.map_err(|e| ConsensusError::ClientImport(e.to_string()))?;
+				},
+				Ok(None) => {},
+				Err(e) => {
+					debug!(target: LOG_TARGET, "Timestamp of block {:?} is unknown: {}", hash, e)
+				},
+			}
+		}
+
+		Ok(result)
 	}
 }
 
@@ -556,7 +633,7 @@ where
 			// The worker is locked for the duration of the whole proposing period. Within this
 			// period, the mining target is outdated and useless anyway.
 
-			let difficulty = match algorithm.difficulty(best_hash) {
+			let difficulty = match algorithm.difficulty_for(best_hash, pre_runtime.as_deref()) {
 				Ok(x) => x,
 				Err(err) => {
 					warn!(
@@ -0,0 +1,527 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Linearly weighted moving average (LWMA) difficulty adjustment.
+
+use crate::{find_pre_digest, Error, PowAux};
+use codec::Decode;
+use parking_lot::Mutex;
+use sc_client_api::backend::AuxStore;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_consensus_pow::{AlgorithmId, MultiAlgorithmPreDigest, TimestampApi};
+use sp_core::U256;
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT, Zero};
+use std::{marker::PhantomData, sync::Arc};
+
+/// Solve times are clamped to this multiple of the target block time, so that a single block
+/// with a bogus timestamp can't swing the difficulty too much.
+const MAX_SOLVE_TIME_FACTOR: u64 = 6;
+
+/// Number of blocks looked at per block of the window when only the blocks of one algorithm
+/// are retargeted over.
+const MAX_LOOKBACK_FACTOR: usize = 16;
+
+/// Parameters of the LWMA difficulty adjustment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LwmaParams {
+	/// Targeted time between two blocks, in milliseconds.
+	pub target_block_time: u64,
+	/// Number of recent solve times the difficulty is averaged over.
+	pub window: usize,
+}
+
+/// Timestamp and difficulty of a block in the history retargeted over.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlockRecord<D> {
+	/// Timestamp of the block, in milliseconds.
+	pub timestamp: u64,
+	/// Difficulty the block was sealed with.
+	pub difficulty: D,
+}
+
+/// Compute the next difficulty from the recent `history`, ordered from oldest to newest.
+///
+/// The solve times are weighted linearly, the most recent one weighing the most, and the
+/// average difficulty is scaled by how far their weighted average is off the target block time.
+/// Only the last `window` solve times are taken into account.
+///
+/// Returns `None` if the history holds less than two blocks, so there is no solve time yet.
+/// If the next difficulty doesn't fit `D`, the last difficulty is kept.
+pub fn next_difficulty<D>(params: &LwmaParams, history: &[BlockRecord<D>]) -> Option<D>
+where
+	D: Copy + Into<U256> + TryFrom<U256>,
+{
+	let history = &history[history.len().saturating_sub(params.window + 1)..];
+	if history.len() < 2 {
+		return None
+	}
+
+	let target = params.target_block_time.max(1);
+	let mut weighted_solve_times = U256::zero();
+	let mut sum_difficulties = U256::zero();
+	for (weight, pair) in history.windows(2).enumerate() {
+		let solve_time = pair[1]
+			.timestamp
+			.saturating_sub(pair[0].timestamp)
+			.clamp(1, target.saturating_mul(MAX_SOLVE_TIME_FACTOR));
+		weighted_solve_times += U256::from(weight as u64 + 1) * U256::from(solve_time);
+		sum_difficulties += pair[1].difficulty.into();
+	}
+
+	// with `n` solve times all on target, the weighted sum is `target * n * (n + 1) / 2` and
+	// the next difficulty the average difficulty.
+	let n = U256::from(history.len() as u64 - 1);
+	let next = sum_difficulties * U256::from(target) * (n + 1) / (weighted_solve_times * 2);
+	let last = history[history.len() - 1].difficulty;
+
+	Some(D::try_from(next.max(U256::one())).unwrap_or(last))
+}
+
+/// LWMA difficulty adjustment over the chain.
+///
+/// The timestamps and difficulties of the recent blocks are read from the auxiliary data of the
+/// PoW engine. The timestamps are recorded there on import, with [`Lwma::timestamp`] used in the
+/// implementation of [`crate::PowAlgorithm::timestamp`]. Meant to be used in the implementation
+/// of [`crate::PowAlgorithm::difficulty`].
+///
+/// The difficulty can't be computed if the timestamp of a block of the window is neither
+/// recorded nor readable from its state.
+pub struct Lwma<B: BlockT, C, D> {
+	client: Arc<C>,
+	params: LwmaParams,
+	initial_difficulty: D,
+	algorithm: Option<AlgorithmId>,
+	// the difficulty is requested several times for the same parent, see
+	// `PowAlgorithm::difficulty`.
+	cache: Mutex<Option<(B::Hash, D)>>,
+	_marker: PhantomData<B>,
+}
+
+impl<B, C, D> Lwma<B, C, D>
+where
+	B: BlockT,
+	C: HeaderBackend<B> + AuxStore + ProvideRuntimeApi<B>,
+	C::Api: TimestampApi<B, u64>,
+	D: Decode + Default + Copy + Into<U256> + TryFrom<U256>,
+{
+	/// Create a new LWMA difficulty adjustment, starting from `initial_difficulty`.
+	pub fn new(client: Arc<C>, params: LwmaParams, initial_difficulty: D) -> Self {
+		Self {
+			client,
+			params,
+			initial_difficulty,
+			algorithm: None,
+			cache: Mutex::new(None),
+			_marker: PhantomData,
+		}
+	}
+
+	/// Only retarget over the blocks sealed with `algorithm`, for use with
+	/// [`crate::MultiAlgorithm`].
+	///
+	/// The target block time should then be the one of the blocks sealed with `algorithm`.
+	pub fn with_algorithm(mut self, algorithm: AlgorithmId) -> Self {
+		self.algorithm = Some(algorithm);
+		self
+	}
+
+	/// Get the difficulty of the block built on top of `parent`.
+	pub fn difficulty(&self, parent: B::Hash) -> Result<D, Error<B>> {
+		if let Some((hash, difficulty)) = *self.cache.lock() {
+			if hash == parent {
+				return Ok(difficulty)
+			}
+		}
+
+		let history = self.history(parent)?;
+		let difficulty = next_difficulty(&self.params, &history).unwrap_or(self.initial_difficulty);
+		*self.cache.lock() = Some((parent, difficulty));
+
+		Ok(difficulty)
+	}
+
+	/// Read the timestamp of the block with the given hash from its state, with the
+	/// [`TimestampApi`].
+	pub fn timestamp(&self, hash: B::Hash) -> Result<u64, Error<B>> {
+		self.client
+			.runtime_api()
+			.timestamp(hash)
+			.map_err(|e| Error::Other(format!("Timestamp of block {:?} is unknown: {}", hash, e)))
+	}
+
+	/// Collect the history of the blocks up to `parent`, ordered from oldest to newest.
+	fn history(&self, parent: B::Hash) -> Result<Vec<BlockRecord<D>>, Error<B>> {
+		let len = self.params.window + 1;
+		let max_depth = match self.algorithm {
+			Some(_) => len.saturating_mul(MAX_LOOKBACK_FACTOR),
+			None => len,
+		};
+
+		let mut history = Vec::with_capacity(len);
+		let mut hash = parent;
+		for _ in 0..max_depth {
+			if history.len() == len {
+				break
+			}
+
+			let header = self
+				.client
+				.header(hash)
+				.map_err(Error::Client)?
+				.ok_or_else(|| Error::Other(format!("Header {:?} not found", hash)))?;
+			// the genesis block isn't sealed.
+			if header.number().is_zero() {
+				break
+			}
+
+			if self.is_retargeted_over(&header)? {
+				history.push(self.record(hash)?);
+			}
+
+			hash = *header.parent_hash();
+		}
+
+		history.reverse();
+		Ok(history)
+	}
+
+	/// Read the timestamp and difficulty of the block with the given hash.
+	///
+	/// Blocks imported without recording their timestamp fall back to reading it from their
+	/// state, which fails once it's pruned.
+	fn record(&self, hash: B::Hash) -> Result<BlockRecord<D>, Error<B>> {
+		let aux = PowAux::<D>::read::<_, B>(&*self.client, &hash)?;
+		let timestamp = match aux.timestamp {
+			Some(timestamp) => timestamp,
+			None => self.timestamp(hash)?,
+		};
+
+		Ok(BlockRecord { timestamp, difficulty: aux.difficulty })
+	}
+
+	fn is_retargeted_over(&self, header: &B::Header) -> Result<bool, Error<B>> {
+		let Some(algorithm) = self.algorithm else { return Ok(true) };
+
+		Ok(match find_pre_digest::<B>(header)? {
+			Some(pre_digest) =>
+				MultiAlgorithmPreDigest::decode(&mut &pre_digest[..])
+					.map_err(Error::Codec)?
+					.algorithm == algorithm,
+			None => false,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::aux_key;
+	use codec::Encode;
+	use sp_api::{ApiError, ApiRef};
+	use sp_blockchain::{BlockStatus, Info};
+	use sp_core::H256;
+	use sp_runtime::testing::{Block as RawBlock, ExtrinsicWrapper, Header};
+	use std::collections::HashMap;
+
+	type Block = RawBlock<ExtrinsicWrapper<u64>>;
+
+	const TARGET: u64 = 60_000;
+
+	/// Number of blocks whose state is kept.
+	const STATE_PRUNING: usize = 16;
+
+	#[derive(Default)]
+	struct Chain {
+		hashes: Vec<H256>,
+		headers: HashMap<H256, Header>,
+		aux: HashMap<Vec<u8>, Vec<u8>>,
+		// timestamps of the blocks whose state isn't pruned.
+		state: HashMap<H256, u64>,
+		runtime_calls: usize,
+	}
+
+	#[derive(Clone, Default)]
+	struct TestClient {
+		chain: Arc<Mutex<Chain>>,
+	}
+
+	impl TestClient {
+		/// Import a block with the given difficulty and timestamp on top of the best block,
+		/// recording its timestamp like `PowBlockImport` does.
+		fn import(&self, lwma: &Lwma<Block, Self, u128>, difficulty: u128, timestamp: u64) {
+			let hash = self.import_unrecorded(difficulty, timestamp);
+			let aux = PowAux {
+				difficulty,
+				total_difficulty: 0,
+				timestamp: Some(lwma.timestamp(hash).unwrap()),
+			};
+			self.chain.lock().aux.insert(aux_key(&hash), aux.encode());
+		}
+
+		/// Import a block without recording its timestamp.
+		fn import_unrecorded(&self, difficulty: u128, timestamp: u64) -> H256 {
+			let mut chain = self.chain.lock();
+			let parent = chain.hashes.last().copied().unwrap_or_default();
+			let header = Header::new(
+				chain.hashes.len() as u64,
+				Default::default(),
+				Default::default(),
+				parent,
+				Default::default(),
+			);
+			let hash = header.hash();
+			let aux = PowAux { difficulty, total_difficulty: 0, timestamp: None };
+
+			chain.hashes.push(hash);
+			chain.headers.insert(hash, header);
+			chain.aux.insert(aux_key(&hash), aux.encode());
+			chain.state.insert(hash, timestamp);
+			if let Some(pruned) = chain.hashes.len().checked_sub(STATE_PRUNING + 1) {
+				let pruned = chain.hashes[pruned];
+				chain.state.remove(&pruned);
+			}
+			hash
+		}
+
+		fn best_hash(&self) -> H256 {
+			*self.chain.lock().hashes.last().unwrap()
+		}
+
+		fn runtime_calls(&self) -> usize {
+			self.chain.lock().runtime_calls
+		}
+
+		fn aux(&self) -> HashMap<Vec<u8>, Vec<u8>> {
+			self.chain.lock().aux.clone()
+		}
+	}
+
+	impl HeaderBackend<Block> for TestClient {
+		fn header(&self, hash: H256) -> sp_blockchain::Result<Option<Header>> {
+			Ok(self.chain.lock().headers.get(&hash).cloned())
+		}
+
+		fn info(&self) -> Info<Block> {
+			let chain = self.chain.lock();
+			Info {
+				best_hash: *chain.hashes.last().unwrap(),
+				best_number: chain.hashes.len() as u64 - 1,
+				genesis_hash: chain.hashes[0],
+				finalized_hash: chain.hashes[0],
+				finalized_number: 0,
+				finalized_state: None,
+				number_leaves: 1,
+				block_gap: None,
+			}
+		}
+
+		fn status(&self, hash: H256) -> sp_blockchain::Result<BlockStatus> {
+			Ok(match self.chain.lock().headers.contains_key(&hash) {
+				true => BlockStatus::InChain,
+				false => BlockStatus::Unknown,
+			})
+		}
+
+		fn number(&self, hash: H256) -> sp_blockchain::Result<Option<u64>> {
+			Ok(self.chain.lock().headers.get(&hash).map(|header| header.number))
+		}
+
+		fn hash(&self, number: u64) -> sp_blockchain::Result<Option<H256>> {
+			Ok(self.chain.lock().hashes.get(number as usize).copied())
+		}
+	}
+
+	impl AuxStore for TestClient {
+		fn insert_aux<
+			'a,
+			'b: 'a,
+			'c: 'a,
+			I: IntoIterator<Item = &'a (&'c [u8], &'c [u8])>,
+			D: IntoIterator<Item = &'a &'b [u8]>,
+		>(
+			&self,
+			insert: I,
+			delete: D,
+		) -> sp_blockchain::Result<()> {
+			let mut chain = self.chain.lock();
+			for (key, value) in insert {
+				chain.aux.insert(key.to_vec(), value.to_vec());
+			}
+			for key in delete {
+				chain.aux.remove(*key);
+			}
+			Ok(())
+		}
+
+		fn get_aux(&self, key: &[u8]) -> sp_blockchain::Result<Option<Vec<u8>>> {
+			Ok(self.chain.lock().aux.get(key).cloned())
+		}
+	}
+
+	struct RuntimeApi {
+		chain: Arc<Mutex<Chain>>,
+	}
+
+	impl ProvideRuntimeApi<Block> for TestClient {
+		type Api = RuntimeApi;
+
+		fn runtime_api(&self) -> ApiRef<Self::Api> {
+			RuntimeApi { chain: self.chain.clone() }.into()
+		}
+	}
+
+	sp_api::mock_impl_runtime_apis! {
+		impl TimestampApi<Block, u64> for RuntimeApi {
+			#[advanced]
+			fn timestamp(at: <Block as BlockT>::Hash) -> Result<u64, ApiError> {
+				let mut chain = self.chain.lock();
+				chain.runtime_calls += 1;
+				chain.state.get(&at).copied().ok_or_else(|| {
+					(Box::from("State is pruned") as Box<dyn std::error::Error + Send + Sync>)
+						.into()
+				})
+			}
+		}
+	}
+
+	fn params() -> LwmaParams {
+		LwmaParams { target_block_time: TARGET, window: 45 }
+	}
+
+	/// Mines `blocks` blocks on top of `history` with the given hash rate (in hashes per
+	/// millisecond), so that each block takes `difficulty / hash_rate` on average.
+	fn mine(history: &mut Vec<BlockRecord<u128>>, hash_rate: u128, blocks: usize) {
+		for _ in 0..blocks {
+			let last = history.last().copied().unwrap();
+			let difficulty = next_difficulty(&params(), history).unwrap_or(last.difficulty);
+			let solve_time = (difficulty / hash_rate) as u64;
+			history.push(BlockRecord { timestamp: last.timestamp + solve_time, difficulty });
+		}
+	}
+
+	fn assert_close(difficulty: u128, expected: u128) {
+		let off = difficulty.abs_diff(expected);
+		assert!(off * 100 <= expected, "difficulty {} too far off {}", difficulty, expected);
+	}
+
+	#[test]
+	fn needs_a_solve_time() {
+		assert_eq!(next_difficulty::<u128>(&params(), &[]), None);
+		let genesis = BlockRecord { timestamp: 0, difficulty: 10u128 };
+		assert_eq!(next_difficulty(&params(), &[genesis]), None);
+	}
+
+	#[test]
+	fn keeps_difficulty_on_target() {
+		let history: Vec<_> = (0..100)
+			.map(|i| BlockRecord { timestamp: i * TARGET, difficulty: U256::from(1_000_000) })
+			.collect();
+
+		assert_eq!(next_difficulty(&params(), &history), Some(U256::from(1_000_000)));
+	}
+
+	#[test]
+	fn solve_times_are_clamped() {
+		let history = vec![
+			BlockRecord { timestamp: 0, difficulty: 1_000u128 },
+			BlockRecord { timestamp: 1_000 * TARGET, difficulty: 1_000 },
+		];
+
+		assert_eq!(
+			next_difficulty(&params(), &history),
+			Some(1_000 / MAX_SOLVE_TIME_FACTOR as u128)
+		);
+	}
+
+	#[test]
+	fn retargeting_converges() {
+		let hash_rate = 500;
+		let mut history = vec![BlockRecord { timestamp: 0, difficulty: 1_000u128 }];
+
+		// starting from a difficulty that's far too low.
+		mine(&mut history, hash_rate, 300);
+		assert_close(history.last().unwrap().difficulty, hash_rate * TARGET as u128);
+
+		// the hash rate doubles.
+		mine(&mut history, hash_rate * 2, 300);
+		assert_close(history.last().unwrap().difficulty, hash_rate * 2 * TARGET as u128);
+
+		// and drops to a fifth.
+		mine(&mut history, hash_rate / 5, 300);
+		assert_close(history.last().unwrap().difficulty, hash_rate / 5 * TARGET as u128);
+
+		// blocks of the last window are on target.
+		let window = &history[history.len() - params().window..];
+		let average_solve_time =
+			(window.last().unwrap().timestamp - window[0].timestamp) / (window.len() as u64 - 1);
+		assert!(average_solve_time.abs_diff(TARGET) * 100 <= TARGET);
+	}
+
+	#[test]
+	fn retargeting_over_imported_blocks_converges() {
+		let hash_rate = 500;
+		let client = Arc::new(TestClient::default());
+		let lwma = Lwma::new(client.clone(), params(), 1_000u128);
+
+		// genesis
+		client.import_unrecorded(0, 0);
+		let mut timestamp = 0;
+		for _ in 0..300 {
+			let difficulty = lwma.difficulty(client.best_hash()).unwrap();
+			timestamp += (difficulty / hash_rate) as u64;
+			client.import(&lwma, difficulty, timestamp);
+		}
+
+		// the window reaches far beyond the blocks whose state is kept.
+		assert!(params().window > STATE_PRUNING);
+		assert_close(lwma.difficulty(client.best_hash()).unwrap(), hash_rate * TARGET as u128);
+		// the timestamps were only read from the runtime when recording them on import.
+		assert_eq!(client.runtime_calls(), 300);
+	}
+
+	#[test]
+	fn unrecorded_timestamps_are_read_from_state() {
+		let client = Arc::new(TestClient::default());
+		client.import_unrecorded(0, 0);
+		for i in 1..=STATE_PRUNING as u64 {
+			client.import_unrecorded(1_000_000, i * TARGET / 2);
+		}
+		let aux = client.aux();
+
+		let lwma = Lwma::new(client.clone(), params(), 1_000u128);
+		assert_eq!(lwma.difficulty(client.best_hash()).unwrap(), 2_000_000);
+		assert_eq!(client.runtime_calls(), STATE_PRUNING);
+		// computing the difficulty doesn't record anything.
+		assert_eq!(client.aux(), aux);
+	}
+
+	#[test]
+	fn fails_on_unknown_timestamps() {
+		let client = Arc::new(TestClient::default());
+		client.import_unrecorded(0, 0);
+		for i in 1..=STATE_PRUNING as u64 * 2 {
+			client.import_unrecorded(1_000_000, i * TARGET / 2);
+		}
+
+		// the timestamps of the blocks whose state is pruned were never recorded, the window
+		// isn't shortened to the remaining blocks.
+		let lwma = Lwma::new(client.clone(), params(), 1_000u128);
+		assert!(lwma.difficulty(client.best_hash()).is_err());
+	}
+}
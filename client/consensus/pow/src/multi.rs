@@ -0,0 +1,215 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Support for chains accepting blocks sealed with several PoW algorithms.
+
+use crate::{Error, PowAlgorithm};
+use codec::{Decode, Encode};
+use sp_consensus_pow::{AlgorithmId, MultiAlgorithmPreDigest, Seal, TotalDifficulty};
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+use std::sync::Arc;
+
+/// Shared PoW algorithm trait object, as combined by [`MultiAlgorithm`].
+pub type BoxPowAlgorithm<B, D> = Arc<dyn PowAlgorithm<B, Difficulty = D> + Send + Sync>;
+
+/// PoW algorithm dispatching to one of several algorithms, selected by the
+/// [`MultiAlgorithmPreDigest`] of the block.
+///
+/// Each algorithm computes its own difficulty, e.g. by only retargeting over the blocks sealed
+/// with it (see [`crate::Lwma::with_algorithm`]). The difficulties of all algorithms add up to
+/// the total difficulty used for the fork choice, so they need to be on a comparable scale.
+///
+/// As the difficulty depends on the algorithm, blocks without a [`MultiAlgorithmPreDigest`]
+/// are rejected and [`PowAlgorithm::difficulty`] always fails. The import and mining paths ask
+/// for the difficulty with [`PowAlgorithm::difficulty_for`] instead.
+pub struct MultiAlgorithm<B: BlockT, D> {
+	algorithms: Vec<(AlgorithmId, BoxPowAlgorithm<B, D>)>,
+}
+
+impl<B: BlockT, D> Clone for MultiAlgorithm<B, D> {
+	fn clone(&self) -> Self {
+		Self { algorithms: self.algorithms.clone() }
+	}
+}
+
+impl<B: BlockT, D> Default for MultiAlgorithm<B, D> {
+	fn default() -> Self {
+		Self { algorithms: Vec::new() }
+	}
+}
+
+impl<B: BlockT, D> MultiAlgorithm<B, D> {
+	/// Create a new `MultiAlgorithm` without any algorithm.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Accept blocks sealed with `algorithm`, selected by `id`.
+	///
+	/// Replaces the algorithm previously registered for `id`, if any.
+	pub fn with_algorithm<A>(mut self, id: AlgorithmId, algorithm: A) -> Self
+	where
+		A: PowAlgorithm<B, Difficulty = D> + Send + Sync + 'static,
+	{
+		let algorithm: BoxPowAlgorithm<B, D> = Arc::new(algorithm);
+		match self.algorithms.iter_mut().find(|(known, _)| *known == id) {
+			Some(entry) => entry.1 = algorithm,
+			None => self.algorithms.push((id, algorithm)),
+		}
+		self
+	}
+
+	/// Identifiers of the accepted algorithms.
+	pub fn algorithm_ids(&self) -> impl Iterator<Item = AlgorithmId> + '_ {
+		self.algorithms.iter().map(|(id, _)| *id)
+	}
+
+	fn select(
+		&self,
+		pre_digest: Option<&[u8]>,
+	) -> Result<(&BoxPowAlgorithm<B, D>, Option<Vec<u8>>), Error<B>> {
+		let mut pre_digest = pre_digest.ok_or(Error::NoPreRuntimeDigest)?;
+		let MultiAlgorithmPreDigest { algorithm, inner } =
+			MultiAlgorithmPreDigest::decode(&mut pre_digest).map_err(Error::Codec)?;
+
+		self.algorithms
+			.iter()
+			.find(|(id, _)| *id == algorithm)
+			.map(|(_, selected)| (selected, inner))
+			.ok_or(Error::UnknownAlgorithm(algorithm))
+	}
+}
+
+impl<B, D> PowAlgorithm<B> for MultiAlgorithm<B, D>
+where
+	B: BlockT,
+	D: TotalDifficulty + Default + Encode + Decode + Ord + Clone + Copy,
+{
+	type Difficulty = D;
+
+	// there is no difficulty independent of the algorithm, which is only known from the
+	// pre-runtime digest passed to `difficulty_for`.
+	fn difficulty(&self, _parent: B::Hash) -> Result<D, Error<B>> {
+		Err(Error::NoPreRuntimeDigest)
+	}
+
+	fn difficulty_for(&self, parent: B::Hash, pre_digest: Option<&[u8]>) -> Result<D, Error<B>> {
+		let (algorithm, inner) = self.select(pre_digest)?;
+		algorithm.difficulty_for(parent, inner.as_ref().map(|v| &v[..]))
+	}
+
+	fn verify(
+		&self,
+		parent: &BlockId<B>,
+		pre_hash: &B::Hash,
+		pre_digest: Option<&[u8]>,
+		seal: &Seal,
+		difficulty: D,
+	) -> Result<bool, Error<B>> {
+		let (algorithm, inner) = self.select(pre_digest)?;
+		algorithm.verify(parent, pre_hash, inner.as_ref().map(|v| &v[..]), seal, difficulty)
+	}
+
+	fn timestamp(&self, hash: B::Hash, pre_digest: Option<&[u8]>) -> Result<Option<u64>, Error<B>> {
+		let (algorithm, inner) = self.select(pre_digest)?;
+		algorithm.timestamp(hash, inner.as_ref().map(|v| &v[..]))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::H256;
+	use sp_runtime::testing::{Block as RawBlock, ExtrinsicWrapper};
+
+	type Block = RawBlock<ExtrinsicWrapper<u64>>;
+
+	/// Accepts seals equal to its own difficulty.
+	struct Fixed(u128);
+
+	impl PowAlgorithm<Block> for Fixed {
+		type Difficulty = u128;
+
+		fn difficulty(&self, _parent: H256) -> Result<u128, Error<Block>> {
+			Ok(self.0)
+		}
+
+		fn verify(
+			&self,
+			_parent: &BlockId<Block>,
+			_pre_hash: &H256,
+			_pre_digest: Option<&[u8]>,
+			seal: &Seal,
+			difficulty: u128,
+		) -> Result<bool, Error<Block>> {
+			Ok(difficulty == self.0 && seal[..] == self.0.encode()[..])
+		}
+	}
+
+	fn pre_digest(algorithm: AlgorithmId) -> Vec<u8> {
+		MultiAlgorithmPreDigest { algorithm, inner: None }.encode()
+	}
+
+	#[test]
+	fn dispatches_by_pre_digest() {
+		let multi = MultiAlgorithm::new()
+			.with_algorithm(*b"sha3", Fixed(10))
+			.with_algorithm(*b"blk3", Fixed(20));
+		let parent = H256::zero();
+
+		assert_eq!(multi.difficulty_for(parent, Some(&pre_digest(*b"sha3"))).unwrap(), 10);
+		assert_eq!(multi.difficulty_for(parent, Some(&pre_digest(*b"blk3"))).unwrap(), 20);
+
+		let verify = |algorithm, seal: u128, difficulty| {
+			multi.verify(
+				&BlockId::Hash(parent),
+				&parent,
+				Some(&pre_digest(algorithm)),
+				&seal.encode(),
+				difficulty,
+			)
+		};
+		assert!(verify(*b"sha3", 10, 10).unwrap());
+		assert!(verify(*b"blk3", 20, 20).unwrap());
+		// a seal of one algorithm isn't valid for the other.
+		assert!(!verify(*b"blk3", 10, 10).unwrap());
+	}
+
+	#[test]
+	fn rejects_unknown_or_missing_algorithm() {
+		let multi = MultiAlgorithm::new().with_algorithm(*b"sha3", Fixed(10));
+		let parent = H256::zero();
+
+		assert!(matches!(
+			multi.difficulty_for(parent, Some(&pre_digest(*b"blk3"))),
+			Err(Error::UnknownAlgorithm(id)) if id == *b"blk3",
+		));
+		assert!(matches!(multi.difficulty_for(parent, None), Err(Error::NoPreRuntimeDigest)));
+		assert!(matches!(multi.difficulty(parent), Err(Error::NoPreRuntimeDigest)));
+	}
+
+	#[test]
+	fn registering_an_algorithm_again_replaces_it() {
+		let multi = MultiAlgorithm::new()
+			.with_algorithm(*b"sha3", Fixed(10))
+			.with_algorithm(*b"sha3", Fixed(30));
+
+		assert_eq!(multi.algorithm_ids().collect::<Vec<_>>(), vec![*b"sha3"]);
+		assert_eq!(multi.difficulty_for(H256::zero(), Some(&pre_digest(*b"sha3"))).unwrap(), 30);
+	}
+}
@@ -29,8 +29,8 @@ mod aux_schema;
 mod slots;
 
 pub use aux_schema::{check_equivocation, MAX_SLOT_CAPACITY, PRUNING_BOUND};
-pub use slots::SlotInfo;
 use slots::Slots;
+pub use slots::{SlotInfo, SlotScheduleProvider};
 
 use futures::{future::Either, Future, TryFutureExt};
 use futures_timer::Delay;
@@ -39,7 +39,7 @@ use sc_consensus::{BlockImport, JustificationSyncLink};
 use sc_telemetry::{telemetry, TelemetryHandle, CONSENSUS_DEBUG, CONSENSUS_INFO, CONSENSUS_WARN};
 use sp_arithmetic::traits::BaseArithmetic;
 use sp_consensus::{Proposal, Proposer, SelectChain, SyncOracle};
-use sp_consensus_slots::{Slot, SlotDuration};
+use sp_consensus_slots::Slot;
 use sp_inherents::CreateInherentDataProviders;
 use sp_runtime::traits::{Block as BlockT, HashFor, Header as HeaderT};
 use std::{
@@ -515,8 +515,12 @@ impl_inherent_data_provider_ext_tuple!(S, A, B, C, D, E, F, G, H, I, J);
 ///
 /// Every time a new slot is triggered, `worker.on_slot` is called and the future it returns is
 /// polled until completion, unless we are major syncing.
-pub async fn start_slot_worker<B, C, W, SO, CIDP, Proof>(
-	slot_duration: SlotDuration,
+///
+/// The schedule of the slots is queried from `slot_schedule` for every new slot, passing the
+/// current best block, so that slot duration changes are followed. A plain
+/// [`SlotDuration`](sp_consensus_slots::SlotDuration) can be passed for a fixed slot duration.
+pub async fn start_slot_worker<B, C, W, SO, CIDP, SS, Proof>(
+	slot_schedule: SS,
 	client: C,
 	mut worker: W,
 	sync_oracle: SO,
@@ -528,8 +532,9 @@ pub async fn start_slot_worker<B, C, W, SO, CIDP, Proof>(
 	SO: SyncOracle + Send,
 	CIDP: CreateInherentDataProviders<B, ()> + Send + 'static,
 	CIDP::InherentDataProviders: InherentDataProviderExt + Send,
+	SS: SlotScheduleProvider<B> + Send,
 {
-	let mut slots = Slots::new(slot_duration.as_duration(), create_inherent_data_providers, client);
+	let mut slots = Slots::new(slot_schedule, create_inherent_data_providers, client);
 
 	loop {
 		let slot_info = slots.next_slot().await;
@@ -822,8 +827,12 @@ impl<N> BackoffAuthoringBlocksStrategy<N> for () {
 #[cfg(test)]
 mod test {
 	use super::*;
+	use sp_consensus_slots::{SlotDuration, SlotSchedule};
 	use sp_runtime::traits::NumberFor;
-	use std::time::{Duration, Instant};
+	use std::{
+		sync::{Arc, Mutex},
+		time::{Duration, Instant},
+	};
 	use substrate_test_runtime_client::runtime::{Block, Header};
 
 	const SLOT_DURATION: Duration = Duration::from_millis(6000);
@@ -1252,4 +1261,101 @@ mod test {
 		assert_eq!((block_for_max_interval, time_to_reach_limit), expected);
 		assert_eq!((block_for_max_interval, time_to_reach_limit), (250, 60906));
 	}
+
+	#[derive(Clone)]
+	struct TestChain(Header);
+
+	#[async_trait::async_trait]
+	impl SelectChain<Block> for TestChain {
+		async fn leaves(&self) -> Result<Vec<<Block as BlockT>::Hash>, sp_consensus::Error> {
+			Ok(vec![self.0.hash()])
+		}
+
+		async fn best_chain(&self) -> Result<Header, sp_consensus::Error> {
+			Ok(self.0.clone())
+		}
+	}
+
+	/// A slot schedule that is changed by the test, like a runtime upgrade would.
+	struct TestSchedule(Arc<Mutex<SlotSchedule>>);
+
+	impl SlotScheduleProvider<Block> for TestSchedule {
+		fn slot_schedule(&self, _parent: &Header) -> Option<SlotSchedule> {
+			Some(*self.0.lock().unwrap())
+		}
+	}
+
+	struct TestSlot(Slot);
+
+	impl Deref for TestSlot {
+		type Target = Slot;
+
+		fn deref(&self) -> &Slot {
+			&self.0
+		}
+	}
+
+	#[async_trait::async_trait]
+	impl sp_inherents::InherentDataProvider for TestSlot {
+		async fn provide_inherent_data(
+			&self,
+			_inherent_data: &mut sp_inherents::InherentData,
+		) -> Result<(), sp_inherents::Error> {
+			Ok(())
+		}
+
+		async fn try_handle_error(
+			&self,
+			_identifier: &sp_inherents::InherentIdentifier,
+			_error: &[u8],
+		) -> Option<Result<(), sp_inherents::Error>> {
+			None
+		}
+	}
+
+	#[test]
+	fn slots_keep_increasing_when_slot_duration_changes() {
+		let slot_schedule =
+			Arc::new(Mutex::new(SlotSchedule::from_slot_duration(SlotDuration::from_millis(50))));
+		let create_inherent_data_providers = {
+			let slot_schedule = slot_schedule.clone();
+			move |_: <Block as BlockT>::Hash, ()| {
+				let now = super::slots::duration_now().as_millis() as u64;
+				let slot = slot_schedule.lock().unwrap().slot_at(now);
+				async move { Ok::<_, Box<dyn std::error::Error + Send + Sync>>((TestSlot(slot),)) }
+			}
+		};
+		let chain_head = slot(1).chain_head;
+		let mut slots = Slots::new(
+			TestSchedule(slot_schedule.clone()),
+			create_inherent_data_providers,
+			TestChain(chain_head),
+		);
+
+		futures::executor::block_on(async {
+			let mut last_slot = Slot::from(0);
+			for n in 0..6 {
+				// Counting slots of the new duration from the unix epoch would move them backwards
+				// and no further slots would be yielded.
+				if n == 3 {
+					let mut slot_schedule = slot_schedule.lock().unwrap();
+					*slot_schedule = slot_schedule
+						.change_slot_duration(last_slot, SlotDuration::from_millis(150));
+				}
+
+				let timeout = Delay::new(Duration::from_secs(5));
+				let slot_info =
+					match futures::future::select(Box::pin(slots.next_slot()), timeout).await {
+						Either::Left((slot_info, _)) => slot_info,
+						Either::Right(_) => panic!("No slot after {} slots", n),
+					};
+
+				let slot_schedule = *slot_schedule.lock().unwrap();
+				assert!(slot_info.slot > last_slot);
+				assert!(slot_info.slot >= slot_schedule.slot);
+				assert_eq!(slot_info.duration, slot_schedule.slot_duration.as_duration());
+				last_slot = slot_info.slot;
+			}
+		});
+	}
 }
@@ -22,12 +22,16 @@
 
 use super::{InherentDataProviderExt, Slot, LOG_TARGET};
 use sp_consensus::SelectChain;
+use sp_consensus_slots::{SlotDuration, SlotSchedule};
 use sp_inherents::{CreateInherentDataProviders, InherentDataProvider};
 use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
 
 use futures_timer::Delay;
 use std::time::{Duration, Instant};
 
+/// Delay before retrying to determine the slot schedule, if it isn't known yet.
+const SLOT_SCHEDULE_RETRY_DELAY: Duration = Duration::from_secs(1);
+
 /// Returns current duration since unix epoch.
 pub fn duration_now() -> Duration {
 	use std::time::SystemTime;
@@ -46,6 +50,35 @@ pub fn time_until_next_slot(slot_duration: Duration) -> Duration {
 	Duration::from_millis(remaining_millis as u64)
 }
 
+/// Returns the duration until the next slot of the given schedule from now.
+pub fn time_until_next_scheduled_slot(slot_schedule: &SlotSchedule) -> Duration {
+	let now = duration_now().as_millis() as u64;
+
+	let next_slot_start = if now < slot_schedule.timestamp {
+		slot_schedule.timestamp
+	} else {
+		slot_schedule.slot_start(slot_schedule.slot_at(now) + 1)
+	};
+	Duration::from_millis(next_slot_start.saturating_sub(now))
+}
+
+/// Provides the schedule of the slots built on top of a block.
+///
+/// This allows following slot duration changes made by runtime upgrades, instead of assuming a
+/// fixed slot duration for the lifetime of the chain.
+pub trait SlotScheduleProvider<B: BlockT> {
+	/// Returns the schedule of the slots built on top of `parent`, or `None` if it can't be
+	/// determined, in which case the previous slot schedule is kept.
+	fn slot_schedule(&self, parent: &B::Header) -> Option<SlotSchedule>;
+}
+
+/// A fixed slot duration, with slots counted from the unix epoch.
+impl<B: BlockT> SlotScheduleProvider<B> for SlotDuration {
+	fn slot_schedule(&self, _parent: &B::Header) -> Option<SlotSchedule> {
+		Some(SlotSchedule::from_slot_duration(*self))
+	}
+}
+
 /// Information about a slot.
 pub struct SlotInfo<B: BlockT> {
 	/// The slot number as found in the inherent data.
@@ -87,25 +120,27 @@ impl<B: BlockT> SlotInfo<B> {
 }
 
 /// A stream that returns every time there is a new slot.
-pub(crate) struct Slots<Block, SC, IDP> {
+pub(crate) struct Slots<Block, SC, IDP, SS> {
 	last_slot: Slot,
-	slot_duration: Duration,
+	slot_schedule: Option<SlotSchedule>,
+	slot_schedule_provider: SS,
 	until_next_slot: Option<Delay>,
 	create_inherent_data_providers: IDP,
 	select_chain: SC,
 	_phantom: std::marker::PhantomData<Block>,
 }
 
-impl<Block, SC, IDP> Slots<Block, SC, IDP> {
+impl<Block, SC, IDP, SS> Slots<Block, SC, IDP, SS> {
 	/// Create a new `Slots` stream.
 	pub fn new(
-		slot_duration: Duration,
+		slot_schedule_provider: SS,
 		create_inherent_data_providers: IDP,
 		select_chain: SC,
 	) -> Self {
 		Slots {
 			last_slot: 0.into(),
-			slot_duration,
+			slot_schedule: None,
+			slot_schedule_provider,
 			until_next_slot: None,
 			create_inherent_data_providers,
 			select_chain,
@@ -114,29 +149,64 @@ impl<Block, SC, IDP> Slots<Block, SC, IDP> {
 	}
 }
 
-impl<Block, SC, IDP> Slots<Block, SC, IDP>
+impl<Block, SC, IDP, SS> Slots<Block, SC, IDP, SS>
 where
 	Block: BlockT,
 	SC: SelectChain<Block>,
 	IDP: CreateInherentDataProviders<Block, ()> + 'static,
 	IDP::InherentDataProviders: crate::InherentDataProviderExt,
+	SS: SlotScheduleProvider<Block>,
 {
+	/// Update the slot schedule to the one in effect for the children of `chain_head`.
+	fn update_slot_schedule(&mut self, chain_head: &Block::Header) {
+		let Some(slot_schedule) = self.slot_schedule_provider.slot_schedule(chain_head) else {
+			return
+		};
+
+		if let Some(previous) = self
+			.slot_schedule
+			.filter(|previous| previous.slot_duration != slot_schedule.slot_duration)
+		{
+			log::info!(
+				target: LOG_TARGET,
+				"Slot duration changed from {:?} to {:?} at slot {} by block {:?}.",
+				previous.slot_duration.as_duration(),
+				slot_schedule.slot_duration.as_duration(),
+				slot_schedule.slot,
+				chain_head.hash(),
+			);
+		}
+		self.slot_schedule = Some(slot_schedule);
+	}
+
 	/// Returns a future that fires when the next slot starts.
 	pub async fn next_slot(&mut self) -> SlotInfo<Block> {
 		loop {
 			// Wait for slot timeout
-			self.until_next_slot
-				.take()
-				.unwrap_or_else(|| {
-					// Schedule first timeout.
-					let wait_dur = time_until_next_slot(self.slot_duration);
-					Delay::new(wait_dur)
-				})
-				.await;
-
-			// Schedule delay for next slot.
-			let wait_dur = time_until_next_slot(self.slot_duration);
-			self.until_next_slot = Some(Delay::new(wait_dur));
+			let delay = match self.until_next_slot.take() {
+				Some(delay) => delay,
+				None => {
+					// Schedule first timeout, once the slot schedule is known.
+					if self.slot_schedule.is_none() {
+						if let Ok(chain_head) = self.select_chain.best_chain().await {
+							self.update_slot_schedule(&chain_head);
+						}
+					}
+					match self.slot_schedule {
+						Some(slot_schedule) =>
+							Delay::new(time_until_next_scheduled_slot(&slot_schedule)),
+						None => {
+							log::warn!(
+								target: LOG_TARGET,
+								"Unable to determine the slot schedule, retrying.",
+							);
+							Delay::new(SLOT_SCHEDULE_RETRY_DELAY).await;
+							continue
+						},
+					}
+				},
+			};
+			delay.await;
 
 			let chain_head = match self.select_chain.best_chain().await {
 				Ok(x) => x,
@@ -151,6 +221,13 @@ where
 				},
 			};
 
+			// Schedule delay for next slot, following slot duration changes.
+			self.update_slot_schedule(&chain_head);
+			let Some(slot_schedule) = self.slot_schedule else { continue };
+			let wait_dur = time_until_next_scheduled_slot(&slot_schedule);
+			let ends_at = Instant::now() + wait_dur;
+			self.until_next_slot = Some(Delay::new(wait_dur));
+
 			let inherent_data_providers = match self
 				.create_inherent_data_providers
 				.create_inherent_data_providers(chain_head.hash(), ())
@@ -174,13 +251,14 @@ where
 			if slot > self.last_slot {
 				self.last_slot = slot;
 
-				break SlotInfo::new(
+				break SlotInfo {
 					slot,
-					Box::new(inherent_data_providers),
-					self.slot_duration,
+					ends_at,
+					create_inherent_data: Box::new(inherent_data_providers),
+					duration: slot_schedule.slot_duration.as_duration(),
 					chain_head,
-					None,
-				)
+					block_size_limit: None,
+				}
 			}
 		}
 	}
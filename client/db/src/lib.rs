@@ -90,10 +90,12 @@ use sp_state_machine::{
 	StateMachineStats, StorageCollection, StorageIterator, StorageKey, StorageValue,
 	UsageInfo as StateUsageInfo,
 };
-use sp_trie::{cache::SharedTrieCache, prefixed_key, MemoryDB, PrefixedMemoryDB};
+use sp_trie::{
+	cache::SharedTrieCache, prefixed_key, MemoryDB, MerkleValue, PrefixedMemoryDB, EMPTY_PREFIX,
+};
 
 // Re-export the Database trait so that one can pass an implementation of it.
-pub use sc_state_db::PruningMode;
+pub use sc_state_db::{Constraints as PruningConstraints, PruningMode};
 pub use sp_database::Database;
 
 pub use bench::BenchmarkingState;
@@ -215,6 +217,21 @@ impl<B: BlockT> StateBackend<HashFor<B>> for RefTrackingState<B> {
 		self.state.child_storage_hash(child_info, key)
 	}
 
+	fn closest_merkle_value(
+		&self,
+		key: &[u8],
+	) -> Result<Option<MerkleValue<B::Hash>>, Self::Error> {
+		self.state.closest_merkle_value(key)
+	}
+
+	fn child_closest_merkle_value(
+		&self,
+		child_info: &ChildInfo,
+		key: &[u8],
+	) -> Result<Option<MerkleValue<B::Hash>>, Self::Error> {
+		self.state.child_closest_merkle_value(child_info, key)
+	}
+
 	fn exists_storage(&self, key: &[u8]) -> Result<bool, Self::Error> {
 		self.state.exists_storage(key)
 	}
@@ -2480,6 +2497,22 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 		)
 	}
 
+	fn trie_node(&self, hash: Block::Hash) -> ClientResult<Option<Vec<u8>>> {
+		// With prefixed keys, nodes are stored under their position in the trie, which is not
+		// known here.
+		if self.storage.prefix_keys {
+			return Err(sp_blockchain::Error::Backend(
+				"Looking up trie nodes by hash requires a database that doesn't prefix keys, \
+				 like ParityDb"
+					.into(),
+			))
+		}
+
+		// Without prefixed keys the prefix is ignored.
+		sp_state_machine::Storage::get(&*self.storage, &hash, EMPTY_PREFIX)
+			.map_err(sp_blockchain::Error::Backend)
+	}
+
 	fn pin_block(&self, hash: <Block as BlockT>::Hash) -> sp_blockchain::Result<()> {
 		let hint = || {
 			let header_metadata = self.blockchain.header_metadata(hash);
@@ -2765,6 +2798,62 @@ pub(crate) mod tests {
 		}
 	}
 
+	#[test]
+	fn trie_node_by_hash() {
+		let base_path = tempfile::TempDir::new().unwrap();
+		let db = Backend::<Block>::new(
+			DatabaseSettings {
+				trie_cache_maximum_size: None,
+				state_pruning: Some(PruningMode::ArchiveAll),
+				source: DatabaseSource::ParityDb { path: base_path.path().into() },
+				blocks_pruning: BlocksPruning::KeepAll,
+			},
+			0,
+		)
+		.unwrap();
+
+		let mut op = db.begin_operation().unwrap();
+		let mut header = Header {
+			number: 0,
+			parent_hash: Default::default(),
+			state_root: Default::default(),
+			digest: Default::default(),
+			extrinsics_root: Default::default(),
+		};
+
+		// Values too big to be inlined into their parent node.
+		let storage = vec![(vec![1, 3, 5], vec![2; 64]), (vec![1, 2, 3], vec![9; 64])];
+
+		header.state_root = op
+			.old_state
+			.storage_root(storage.iter().map(|(x, y)| (&x[..], Some(&y[..]))), StateVersion::V1)
+			.0
+			.into();
+		let hash = header.hash();
+
+		op.reset_storage(
+			Storage { top: storage.into_iter().collect(), children_default: Default::default() },
+			StateVersion::V1,
+		)
+		.unwrap();
+		op.set_block_data(header.clone(), Some(vec![]), None, None, NewBlockState::Best)
+			.unwrap();
+		db.commit_operation(op).unwrap();
+
+		let proof =
+			sp_state_machine::prove_read(db.state_at(hash).unwrap(), [&[1u8, 3, 5][..]]).unwrap();
+		let nodes = proof.into_iter_nodes().collect::<Vec<_>>();
+		assert!(nodes.len() > 1);
+
+		for node in nodes {
+			assert_eq!(db.trie_node(BlakeTwo256::hash(&node)).unwrap(), Some(node));
+		}
+		assert_eq!(db.trie_node(H256::random()).unwrap(), None);
+
+		// Databases prefixing keys only know nodes by their position in the trie.
+		assert!(Backend::<Block>::new_test(2, 0).trie_node(header.state_root).is_err());
+	}
+
 	#[test]
 	fn delete_only_when_negative_rc() {
 		sp_tracing::try_init_simple();
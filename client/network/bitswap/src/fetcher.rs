@@ -0,0 +1,178 @@
+// Copyright Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Bitswap client.
+//!
+//! Retrieves data from peers serving it with
+//! [`BitswapRequestHandler`](crate::BitswapRequestHandler) over the bitswap request-response
+//! protocol.
+
+use crate::{
+	schema::bitswap::{
+		message::{
+			wantlist::{Entry, WantType},
+			BlockPresenceType, Wantlist,
+		},
+		Message as BitswapMessage,
+	},
+	uses_blake2_256, BitswapError, BLOCK_BODY_CODEC, BLOCK_HEADER_CODEC, LOG_TARGET, PROTOCOL_NAME,
+	TRIE_NODE_CODEC,
+};
+use cid::{
+	multihash::{Code, Multihash},
+	Cid,
+};
+use libp2p_identity::PeerId;
+use log::{debug, trace};
+use prost::Message;
+use sc_network::{request_responses::IfDisconnected, types::ProtocolName, NetworkRequest};
+use sp_runtime::{
+	codec::{Decode, Encode},
+	traits::{BlakeTwo256, Block as BlockT, Hash, HashFor, Header as HeaderT},
+	StateVersion,
+};
+use std::{marker::PhantomData, sync::Arc};
+
+/// Codec of CIDs referencing indexed transactions.
+const RAW_CODEC: u64 = 0x55;
+
+/// Fetches blocks from peers over bitswap.
+pub struct BitswapFetcher<B> {
+	network: Arc<dyn NetworkRequest + Send + Sync>,
+	_phantom: PhantomData<B>,
+}
+
+impl<B: BlockT> BitswapFetcher<B> {
+	/// Create a new [`BitswapFetcher`].
+	pub fn new(network: Arc<dyn NetworkRequest + Send + Sync>) -> Self {
+		Self { network, _phantom: PhantomData }
+	}
+
+	/// CID with the given `codec` referencing `hash`.
+	pub fn cid(codec: u64, hash: B::Hash) -> Cid {
+		let multihash = Multihash::wrap(u64::from(Code::Blake2b256), hash.as_ref())
+			.expect("Block hashes fit into a multihash; qed");
+		Cid::new_v1(codec, multihash)
+	}
+
+	/// Fetch the block referenced by `cid` from `peer`.
+	///
+	/// The data is checked to hash to the digest of `cid`, except for block bodies, see
+	/// [`BLOCK_BODY_CODEC`]. Blocks and trie nodes can only be fetched for chains hashing them
+	/// with Blake2b-256.
+	pub async fn fetch(&self, peer: PeerId, cid: &Cid) -> Result<Vec<u8>, BitswapError> {
+		if cid.codec() != RAW_CODEC && !uses_blake2_256::<B>() {
+			return Err(BitswapError::UnsupportedHasher)
+		}
+
+		let request = BitswapMessage {
+			wantlist: Some(Wantlist {
+				entries: vec![Entry {
+					block: cid.to_bytes(),
+					priority: 1,
+					cancel: false,
+					want_type: WantType::Block as i32,
+					send_dont_have: true,
+				}],
+				full: true,
+			}),
+			..Default::default()
+		};
+
+		trace!(target: LOG_TARGET, "Fetching {} from {}", cid, peer);
+		let response = self
+			.network
+			.request(
+				peer,
+				ProtocolName::from(PROTOCOL_NAME),
+				request.encode_to_vec(),
+				IfDisconnected::TryConnect,
+			)
+			.await?;
+		let response = BitswapMessage::decode(&response[..])?;
+
+		let dont_have = response.block_presences.iter().any(|presence| {
+			presence.r#type == BlockPresenceType::DontHave as i32 && presence.cid == cid.to_bytes()
+		});
+		let data = match response.payload.into_iter().next() {
+			Some(block) if !dont_have => block.data,
+			_ => {
+				debug!(target: LOG_TARGET, "Peer {} doesn't have {}", peer, cid);
+				return Err(BitswapError::NotFound)
+			},
+		};
+
+		if cid.codec() != BLOCK_BODY_CODEC &&
+			BlakeTwo256::hash(&data).as_ref() != cid.hash().digest()
+		{
+			debug!(target: LOG_TARGET, "Peer {} sent invalid data for {}", peer, cid);
+			return Err(BitswapError::InvalidBlock)
+		}
+		Ok(data)
+	}
+
+	/// Fetch the indexed transaction with the given content hash from `peer`.
+	pub async fn fetch_indexed_transaction(
+		&self,
+		peer: PeerId,
+		hash: B::Hash,
+	) -> Result<Vec<u8>, BitswapError> {
+		self.fetch(peer, &Self::cid(RAW_CODEC, hash)).await
+	}
+
+	/// Fetch the header of the block with the given hash from `peer`.
+	pub async fn fetch_header(
+		&self,
+		peer: PeerId,
+		hash: B::Hash,
+	) -> Result<B::Header, BitswapError> {
+		let data = self.fetch(peer, &Self::cid(BLOCK_HEADER_CODEC, hash)).await?;
+		Ok(B::Header::decode(&mut &data[..])?)
+	}
+
+	/// Fetch the body of the block with the given `header` from `peer`.
+	///
+	/// The body is checked against the extrinsics root of `header`.
+	pub async fn fetch_body(
+		&self,
+		peer: PeerId,
+		header: &B::Header,
+	) -> Result<Vec<B::Extrinsic>, BitswapError> {
+		let data = self.fetch(peer, &Self::cid(BLOCK_BODY_CODEC, header.hash())).await?;
+		let body = Vec::<B::Extrinsic>::decode(&mut &data[..])?;
+
+		// The state version used for the extrinsics root depends on the runtime.
+		let extrinsics = body.iter().map(Encode::encode).collect::<Vec<_>>();
+		let valid = [StateVersion::V0, StateVersion::V1].into_iter().any(|version| {
+			HashFor::<B>::ordered_trie_root(extrinsics.clone(), version) ==
+				*header.extrinsics_root()
+		});
+		if !valid {
+			debug!(target: LOG_TARGET, "Peer {} sent invalid body for {}", peer, header.hash());
+			return Err(BitswapError::InvalidBlock)
+		}
+		Ok(body)
+	}
+
+	/// Fetch the state trie node with the given hash from `peer`.
+	pub async fn fetch_trie_node(
+		&self,
+		peer: PeerId,
+		hash: B::Hash,
+	) -> Result<Vec<u8>, BitswapError> {
+		self.fetch(peer, &Self::cid(TRIE_NODE_CODEC, hash)).await
+	}
+}
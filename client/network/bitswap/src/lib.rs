@@ -14,30 +14,47 @@
 // You should have received a copy of the GNU General Public License
 // along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
 
-//! Bitswap server for Substrate.
+//! Bitswap server and client for Substrate.
 //!
 //! Allows querying transactions by hash over standard bitswap protocol
 //! Only supports bitswap 1.2.0.
-//! CID is expected to reference 256-bit Blake2b transaction hash.
+//! CID is expected to reference a 256-bit Blake2b hash.
+//!
+//! Besides indexed transactions, the server can optionally serve block headers, block bodies and
+//! state trie nodes, see [`BitswapConfig`]. These are told apart by the codec of the CID, see
+//! [`BLOCK_HEADER_CODEC`], [`BLOCK_BODY_CODEC`] and [`TRIE_NODE_CODEC`]. CIDs with any other codec
+//! reference indexed transactions.
+//!
+//! [`BitswapFetcher`] retrieves this data from peers serving it.
+//!
+//! Block headers, block bodies and state trie nodes are only served and fetched for chains hashing
+//! them with Blake2b-256, as the CIDs referencing them claim.
 
 use cid::{self, Version};
 use futures::StreamExt;
 use libp2p_identity::PeerId;
-use log::{debug, error, trace};
+use log::{debug, error, trace, warn};
 use prost::Message;
 use sc_client_api::BlockBackend;
 use sc_network::{
-	request_responses::{IncomingRequest, OutgoingResponse, ProtocolConfig},
+	request_responses::{IncomingRequest, OutgoingResponse, ProtocolConfig, RequestFailure},
 	types::ProtocolName,
 };
 use schema::bitswap::{
 	message::{wantlist::WantType, Block as MessageBlock, BlockPresence, BlockPresenceType},
 	Message as BitswapMessage,
 };
-use sp_runtime::traits::Block as BlockT;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{
+	codec::Encode,
+	traits::{BlakeTwo256, Block as BlockT, Hash as HashT, HashFor},
+};
 use std::{io, sync::Arc, time::Duration};
 use unsigned_varint::encode as varint_encode;
 
+pub use fetcher::BitswapFetcher;
+
+mod fetcher;
 mod schema;
 
 const LOG_TARGET: &str = "bitswap";
@@ -57,6 +74,45 @@ const MAX_WANTED_BLOCKS: usize = 16;
 /// Bitswap protocol name
 const PROTOCOL_NAME: &'static str = "/ipfs/bitswap/1.2.0";
 
+/// Codec of CIDs referencing SCALE-encoded block headers by block hash.
+///
+/// Taken from the private use range of the multicodec table.
+pub const BLOCK_HEADER_CODEC: u64 = 0x30_0000;
+
+/// Codec of CIDs referencing SCALE-encoded block bodies by block hash.
+///
+/// Taken from the private use range of the multicodec table. Unlike all other data, a body does
+/// not hash to the digest of its CID and has to be checked against the extrinsics root of the
+/// block header instead.
+pub const BLOCK_BODY_CODEC: u64 = 0x30_0001;
+
+/// Codec of CIDs referencing state trie nodes by node hash.
+///
+/// Taken from the private use range of the multicodec table.
+pub const TRIE_NODE_CODEC: u64 = 0x30_0002;
+
+/// Client data served over bitswap.
+pub trait BitswapClient<B: BlockT>: BlockBackend<B> + HeaderBackend<B> {}
+
+impl<B: BlockT, T: BlockBackend<B> + HeaderBackend<B>> BitswapClient<B> for T {}
+
+/// Data served by [`BitswapRequestHandler`] in addition to indexed transactions.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BitswapConfig {
+	/// Serve block headers and bodies.
+	pub serve_blocks: bool,
+	/// Serve state trie nodes.
+	///
+	/// Only works with backends able to look up trie nodes by hash, see
+	/// [`sc_client_api::backend::Backend::trie_node`].
+	pub serve_trie_nodes: bool,
+}
+
+/// Whether `B` hashes its blocks and trie nodes with Blake2b-256, like the CIDs referencing them.
+pub(crate) fn uses_blake2_256<B: BlockT>() -> bool {
+	HashFor::<B>::hash(&[]).as_ref() == BlakeTwo256::hash(&[]).as_ref()
+}
+
 /// Prefix represents all metadata of a CID, without the actual content.
 #[derive(PartialEq, Eq, Clone, Debug)]
 struct Prefix {
@@ -92,16 +148,36 @@ impl Prefix {
 
 /// Bitswap request handler
 pub struct BitswapRequestHandler<B> {
-	client: Arc<dyn BlockBackend<B> + Send + Sync>,
+	client: Arc<dyn BitswapClient<B>>,
+	config: BitswapConfig,
 	request_receiver: async_channel::Receiver<IncomingRequest>,
 }
 
 impl<B: BlockT> BitswapRequestHandler<B> {
-	/// Create a new [`BitswapRequestHandler`].
-	pub fn new(client: Arc<dyn BlockBackend<B> + Send + Sync>) -> (Self, ProtocolConfig) {
+	/// Create a new [`BitswapRequestHandler`] serving indexed transactions only.
+	pub fn new(client: Arc<dyn BitswapClient<B>>) -> (Self, ProtocolConfig) {
+		Self::with_config(client, BitswapConfig::default())
+	}
+
+	/// Create a new [`BitswapRequestHandler`] serving the data enabled in `config`.
+	pub fn with_config(
+		client: Arc<dyn BitswapClient<B>>,
+		config: BitswapConfig,
+	) -> (Self, ProtocolConfig) {
 		let (tx, request_receiver) = async_channel::bounded(MAX_REQUEST_QUEUE);
 
-		let config = ProtocolConfig {
+		let config = if (config.serve_blocks || config.serve_trie_nodes) && !uses_blake2_256::<B>()
+		{
+			warn!(
+				target: LOG_TARGET,
+				"Not serving blocks and trie nodes, as they aren't hashed with Blake2b-256",
+			);
+			BitswapConfig::default()
+		} else {
+			config
+		};
+
+		let protocol_config = ProtocolConfig {
 			name: ProtocolName::from(PROTOCOL_NAME),
 			fallback_names: vec![],
 			max_request_size: MAX_PACKET_SIZE,
@@ -110,7 +186,7 @@ impl<B: BlockT> BitswapRequestHandler<B> {
 			inbound_queue: Some(tx),
 		};
 
-		(Self { client, request_receiver }, config)
+		(Self { client, config, request_receiver }, protocol_config)
 	}
 
 	/// Run [`BitswapRequestHandler`].
@@ -204,16 +280,16 @@ impl<B: BlockT> BitswapRequestHandler<B> {
 
 			let mut hash = B::Hash::default();
 			hash.as_mut().copy_from_slice(&cid.hash().digest()[0..32]);
-			let transaction = match self.client.indexed_transaction(hash) {
-				Ok(ex) => ex,
+			let data = match self.data(cid.codec(), hash) {
+				Ok(data) => data,
 				Err(e) => {
-					error!(target: LOG_TARGET, "Error retrieving transaction {}: {}", hash, e);
+					error!(target: LOG_TARGET, "Error retrieving data {}: {}", hash, e);
 					None
 				},
 			};
 
-			match transaction {
-				Some(transaction) => {
+			match data {
+				Some(data) => {
 					trace!(target: LOG_TARGET, "Found CID {:?}, hash {:?}", cid, hash);
 
 					if entry.want_type == WantType::Block as i32 {
@@ -223,9 +299,7 @@ impl<B: BlockT> BitswapRequestHandler<B> {
 							mh_type: cid.hash().code(),
 							mh_len: cid.hash().size(),
 						};
-						response
-							.payload
-							.push(MessageBlock { prefix: prefix.to_bytes(), data: transaction });
+						response.payload.push(MessageBlock { prefix: prefix.to_bytes(), data });
 					} else {
 						response.block_presences.push(BlockPresence {
 							r#type: BlockPresenceType::Have as i32,
@@ -248,6 +322,19 @@ impl<B: BlockT> BitswapRequestHandler<B> {
 
 		Ok(response.encode_to_vec())
 	}
+
+	/// Get the data referenced by a CID with the given codec and hash, if it is served.
+	fn data(&self, codec: u64, hash: B::Hash) -> Result<Option<Vec<u8>>, BitswapError> {
+		Ok(match codec {
+			BLOCK_HEADER_CODEC if self.config.serve_blocks =>
+				self.client.header(hash)?.map(|header| header.encode()),
+			BLOCK_BODY_CODEC if self.config.serve_blocks =>
+				self.client.block_body(hash)?.map(|body| body.encode()),
+			TRIE_NODE_CODEC if self.config.serve_trie_nodes => self.client.trie_node(hash)?,
+			BLOCK_HEADER_CODEC | BLOCK_BODY_CODEC | TRIE_NODE_CODEC => None,
+			_ => self.client.indexed_transaction(hash)?,
+		})
+	}
 }
 
 /// Bitswap protocol error.
@@ -284,6 +371,26 @@ pub enum BitswapError {
 	/// Too many blocks requested.
 	#[error("Too many block entries in the request.")]
 	TooManyEntries,
+
+	/// Request to a peer failed.
+	#[error(transparent)]
+	Request(#[from] RequestFailure),
+
+	/// Peer doesn't have the requested block.
+	#[error("Peer doesn't have the requested block.")]
+	NotFound,
+
+	/// Peer sent a block not matching the requested CID.
+	#[error("Received block doesn't match the requested CID.")]
+	InvalidBlock,
+
+	/// SCALE decoding error.
+	#[error("Failed to decode block: {0}.")]
+	DecodeScale(#[from] sp_runtime::codec::Error),
+
+	/// Blocks and trie nodes of the chain aren't hashed with Blake2b-256.
+	#[error("Blocks and trie nodes aren't hashed with Blake2b-256.")]
+	UnsupportedHasher,
 }
 
 #[cfg(test)]
@@ -291,14 +398,81 @@ mod tests {
 	use super::*;
 	use futures::channel::oneshot;
 	use sc_block_builder::BlockBuilderProvider;
+	use sc_network::{request_responses::IfDisconnected, NetworkRequest};
 	use schema::bitswap::{
 		message::{wantlist::Entry, Wantlist},
 		Message as BitswapMessage,
 	};
 	use sp_consensus::BlockOrigin;
-	use sp_runtime::codec::Encode;
+	use sp_runtime::traits::Header as _;
 	use substrate_test_runtime::ExtrinsicBuilder;
-	use substrate_test_runtime_client::{self, prelude::*, TestClientBuilder};
+	use substrate_test_runtime_client::{
+		self, prelude::*, runtime::Block, sc_client_db, TestClientBuilder,
+	};
+
+	/// Network sending all requests to a bitswap request handler.
+	struct TestNetwork(async_channel::Sender<IncomingRequest>);
+
+	#[async_trait::async_trait]
+	impl NetworkRequest for TestNetwork {
+		async fn request(
+			&self,
+			target: PeerId,
+			_protocol: ProtocolName,
+			request: Vec<u8>,
+			_connect: IfDisconnected,
+		) -> Result<Vec<u8>, RequestFailure> {
+			let (tx, rx) = oneshot::channel();
+			self.0
+				.send(IncomingRequest { peer: target, payload: request, pending_response: tx })
+				.await
+				.unwrap();
+			rx.await.unwrap().result.map_err(|()| RequestFailure::Refused)
+		}
+
+		fn start_request(
+			&self,
+			_target: PeerId,
+			_protocol: ProtocolName,
+			_request: Vec<u8>,
+			_tx: oneshot::Sender<Result<Vec<u8>, RequestFailure>>,
+			_connect: IfDisconnected,
+		) {
+			unimplemented!()
+		}
+	}
+
+	async fn request_block(
+		config: &ProtocolConfig,
+		codec: u64,
+		hash: <Block as BlockT>::Hash,
+	) -> BitswapMessage {
+		let (tx, rx) = oneshot::channel();
+		config
+			.inbound_queue
+			.as_ref()
+			.unwrap()
+			.send(IncomingRequest {
+				peer: PeerId::random(),
+				payload: BitswapMessage {
+					wantlist: Some(Wantlist {
+						entries: vec![Entry {
+							block: BitswapFetcher::<Block>::cid(codec, hash).to_bytes(),
+							..Default::default()
+						}],
+						full: false,
+					}),
+					..Default::default()
+				}
+				.encode_to_vec(),
+				pending_response: tx,
+			})
+			.await
+			.unwrap();
+
+		let response = rx.await.unwrap().result.expect("fetch to succeed");
+		schema::bitswap::Message::decode(&response[..]).unwrap()
+	}
 
 	#[tokio::test]
 	async fn undecodeable_message() {
@@ -524,4 +698,89 @@ mod tests {
 			panic!("invalid event received");
 		}
 	}
+
+	#[tokio::test]
+	async fn blocks_and_trie_nodes_served_when_enabled() {
+		// Trie nodes can only be looked up by hash in databases that don't prefix keys.
+		let base_path = tempfile::TempDir::new().unwrap();
+		let backend = sc_client_db::Backend::new(
+			sc_client_db::DatabaseSettings {
+				trie_cache_maximum_size: None,
+				state_pruning: Some(sc_client_db::PruningMode::ArchiveAll),
+				source: sc_client_db::DatabaseSource::ParityDb { path: base_path.path().into() },
+				blocks_pruning: sc_client_db::BlocksPruning::KeepAll,
+			},
+			0,
+		)
+		.unwrap();
+		let client = Arc::new(TestClientBuilder::with_backend(Arc::new(backend)).build());
+		let genesis_hash = client.info().genesis_hash;
+		let genesis_header = client.header(genesis_hash).unwrap().unwrap();
+		let state_root = *genesis_header.state_root();
+
+		let (bitswap, config) = BitswapRequestHandler::new(client.clone());
+		tokio::spawn(async move { bitswap.run().await });
+
+		assert!(request_block(&config, BLOCK_HEADER_CODEC, genesis_hash)
+			.await
+			.payload
+			.is_empty());
+		assert!(request_block(&config, TRIE_NODE_CODEC, state_root).await.payload.is_empty());
+
+		let (bitswap, config) = BitswapRequestHandler::with_config(
+			client,
+			BitswapConfig { serve_blocks: true, serve_trie_nodes: true },
+		);
+		tokio::spawn(async move { bitswap.run().await });
+
+		let response = request_block(&config, BLOCK_HEADER_CODEC, genesis_hash).await;
+		assert_eq!(response.payload[0].data, genesis_header.encode());
+
+		let response = request_block(&config, BLOCK_BODY_CODEC, genesis_hash).await;
+		assert_eq!(response.payload[0].data, Vec::<<Block as BlockT>::Extrinsic>::new().encode());
+
+		let response = request_block(&config, TRIE_NODE_CODEC, state_root).await;
+		assert_eq!(sp_core::hashing::blake2_256(&response.payload[0].data), state_root.0);
+	}
+
+	#[tokio::test]
+	async fn fetcher_retrieves_served_blocks() {
+		let mut client = TestClientBuilder::with_tx_storage(u32::MAX).build();
+		let mut block_builder = client.new_block(Default::default()).unwrap();
+
+		let ext = ExtrinsicBuilder::new_indexed_call(vec![0x13, 0x37, 0x13, 0x38]).build();
+		block_builder.push(ext).unwrap();
+		let block = block_builder.build().unwrap().block;
+		client.import(BlockOrigin::File, block.clone()).await.unwrap();
+
+		let (bitswap, config) = BitswapRequestHandler::with_config(
+			Arc::new(client),
+			BitswapConfig { serve_blocks: true, serve_trie_nodes: false },
+		);
+		tokio::spawn(async move { bitswap.run().await });
+
+		let fetcher =
+			BitswapFetcher::<Block>::new(Arc::new(TestNetwork(config.inbound_queue.unwrap())));
+		let peer = PeerId::random();
+
+		let transaction = fetcher
+			.fetch_indexed_transaction(
+				peer,
+				sp_core::hashing::blake2_256(&[0x13, 0x37, 0x13, 0x38]).into(),
+			)
+			.await
+			.unwrap();
+		assert_eq!(transaction, vec![0x13, 0x37, 0x13, 0x38]);
+
+		let header = fetcher.fetch_header(peer, block.header.hash()).await.unwrap();
+		assert_eq!(header, block.header);
+
+		let body = fetcher.fetch_body(peer, &header).await.unwrap();
+		assert_eq!(body.encode(), block.extrinsics.encode());
+
+		assert!(matches!(
+			fetcher.fetch_trie_node(peer, *header.state_root()).await,
+			Err(BitswapError::NotFound),
+		));
+	}
 }
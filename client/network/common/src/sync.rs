@@ -36,7 +36,7 @@ use sp_runtime::{
 };
 use warp::WarpSyncProgress;
 
-use std::{any::Any, fmt, fmt::Formatter, pin::Pin, sync::Arc, task::Poll};
+use std::{any::Any, fmt, fmt::Formatter, num::NonZeroU32, pin::Pin, sync::Arc, task::Poll};
 
 /// The sync status of a peer we are trying to sync with
 #[derive(Debug)]
@@ -85,6 +85,19 @@ pub struct StateDownloadProgress {
 	pub size: u64,
 }
 
+/// Progress of the block history download after warp or checkpoint sync.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct GapSyncProgress<N> {
+	/// First block of the history being downloaded.
+	pub start: N,
+	/// Highest block of the history queued for import.
+	pub best_queued: N,
+	/// Highest block of the history imported.
+	pub best_imported: N,
+	/// Last block of the history to download.
+	pub target: N,
+}
+
 /// Syncing status and statistics.
 #[derive(Debug, Clone)]
 pub struct SyncStatus<Block: BlockT> {
@@ -102,6 +115,8 @@ pub struct SyncStatus<Block: BlockT> {
 	pub state_sync: Option<StateDownloadProgress>,
 	/// Warp sync in progress, if any.
 	pub warp_sync: Option<WarpSyncProgress<Block>>,
+	/// Block history download in progress, if any.
+	pub gap_sync: Option<GapSyncProgress<NumberFor<Block>>>,
 }
 
 /// A peer did not behave as expected and should be reported.
@@ -203,6 +218,9 @@ pub enum SyncMode {
 	},
 	/// Warp sync - verify authority set transitions and the latest state.
 	Warp,
+	/// Checkpoint sync - trust the finalized block given by [`CheckpointSyncConfig`], download its
+	/// state and continue with full sync from there.
+	Checkpoint,
 }
 
 impl SyncMode {
@@ -215,6 +233,11 @@ impl SyncMode {
 	pub fn light_state(&self) -> bool {
 		matches!(self, Self::LightState { .. })
 	}
+
+	/// Returns `true` if `self` is [`Self::Checkpoint`].
+	pub fn is_checkpoint(&self) -> bool {
+		matches!(self, Self::Checkpoint)
+	}
 }
 
 impl Default for SyncMode {
@@ -222,6 +245,43 @@ impl Default for SyncMode {
 		Self::Full
 	}
 }
+
+/// Trusted finalized block that [`SyncMode::Checkpoint`] starts syncing from.
+///
+/// The fields are SCALE-encoded, as the block types are not known at this level.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CheckpointSyncConfig {
+	/// Hash of the trusted block.
+	pub hash: Vec<u8>,
+	/// Header of the trusted block.
+	pub header: Vec<u8>,
+	/// State root of the trusted block.
+	pub state_root: Vec<u8>,
+}
+
+/// Configuration of the block history download after warp or checkpoint sync.
+///
+/// These sync modes import the state of a recent block without its ancestors. The missing
+/// blocks are downloaded in the background afterwards, without executing them.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GapSyncConfig {
+	/// Download the missing block history at all.
+	pub enabled: bool,
+	/// Last block of the history to download, `None` for the whole history.
+	///
+	/// The history is always downloaded upwards from the first missing block, as blocks can only
+	/// be imported on top of their parent.
+	pub target: Option<u64>,
+	/// Maximum number of history blocks requested per second, `None` for no limit.
+	pub max_blocks_per_second: Option<NonZeroU32>,
+}
+
+impl Default for GapSyncConfig {
+	fn default() -> Self {
+		Self { enabled: true, target: None, max_blocks_per_second: None }
+	}
+}
+
 #[derive(Debug)]
 pub struct Metrics {
 	pub queued_blocks: u32,
@@ -28,17 +28,18 @@ use futures::prelude::*;
 use libp2p_identity::PeerId;
 use log::{debug, trace};
 use prost::Message;
-use sc_client_api::{BlockBackend, ProofProvider};
+use sc_client_api::{BlockBackend, CompactProof, ProofProvider, StorageProof};
 use sc_network::{
 	config::ProtocolId,
 	request_responses::{IncomingRequest, OutgoingResponse, ProtocolConfig},
 	ReputationChange,
 };
+use sp_blockchain::HeaderBackend;
 use sp_core::{
 	hexdisplay::HexDisplay,
 	storage::{ChildInfo, ChildType, PrefixedStorageKey},
 };
-use sp_runtime::traits::Block;
+use sp_runtime::traits::{Block, HashFor, Header};
 use std::{marker::PhantomData, sync::Arc};
 
 const LOG_TARGET: &str = "light-client-request-handler";
@@ -47,6 +48,16 @@ const LOG_TARGET: &str = "light-client-request-handler";
 /// handling in production systems, this value is chosen to match the block request limit.
 const MAX_LIGHT_REQUEST_QUEUE: usize = 20;
 
+/// Maximum number of entries in a batched read request.
+const MAX_READ_BATCH_ENTRIES: usize = 64;
+
+/// Maximum number of keys over all entries of a batched read request.
+const MAX_READ_BATCH_KEYS: usize = 1024;
+
+/// Size limit of the proofs in a response. This is half the maximum response size, as proofs may
+/// exceed the limit by the last value read.
+const MAX_RESPONSE_PROOF_SIZE: usize = 8 * 1024 * 1024;
+
 /// Handler for incoming light client requests from a remote peer.
 pub struct LightClientRequestHandler<B, Client> {
 	request_receiver: async_channel::Receiver<IncomingRequest>,
@@ -58,7 +69,7 @@ pub struct LightClientRequestHandler<B, Client> {
 impl<B, Client> LightClientRequestHandler<B, Client>
 where
 	B: Block,
-	Client: BlockBackend<B> + ProofProvider<B> + Send + Sync + 'static,
+	Client: BlockBackend<B> + HeaderBackend<B> + ProofProvider<B> + Send + Sync + 'static,
 {
 	/// Create a new [`LightClientRequestHandler`].
 	pub fn new(
@@ -155,6 +166,10 @@ where
 				self.on_remote_read_request(&peer, r)?,
 			Some(schema::v1::light::request::Request::RemoteReadChildRequest(r)) =>
 				self.on_remote_read_child_request(&peer, r)?,
+			Some(schema::v1::light::request::Request::RemoteReadBatchRequest(r)) =>
+				self.on_remote_read_batch_request(&peer, r)?,
+			Some(schema::v1::light::request::Request::RemoteReadDescendantsRequest(r)) =>
+				self.on_remote_read_descendants_request(&peer, r)?,
 			None =>
 				return Err(HandleRequestError::BadRequest("Remote request without request data.")),
 		};
@@ -252,12 +267,7 @@ where
 
 		let block = Decode::decode(&mut request.block.as_ref())?;
 
-		let prefixed_key = PrefixedStorageKey::new_ref(&request.storage_key);
-		let child_info = match ChildType::from_prefixed_key(prefixed_key) {
-			Some((ChildType::ParentKeyId, storage_key)) => Ok(ChildInfo::new_default(storage_key)),
-			None => Err(sp_blockchain::Error::InvalidChildStorageKey),
-		};
-		let response = match child_info.and_then(|child_info| {
+		let response = match child_info(&request.storage_key).and_then(|child_info| {
 			self.client.read_child_proof(
 				block,
 				&child_info,
@@ -282,6 +292,188 @@ where
 			response: Some(schema::v1::light::response::Response::RemoteReadResponse(response)),
 		})
 	}
+
+	fn on_remote_read_batch_request(
+		&mut self,
+		peer: &PeerId,
+		request: &schema::v1::light::RemoteReadBatchRequest,
+	) -> Result<schema::v1::light::Response, HandleRequestError> {
+		if request.entries.is_empty() || request.entries.iter().any(|entry| entry.keys.is_empty()) {
+			debug!("Invalid remote read batch request sent by {}.", peer);
+			return Err(HandleRequestError::BadRequest("Remote read batch request without keys."))
+		}
+		if request.entries.len() > MAX_READ_BATCH_ENTRIES ||
+			request.entries.iter().map(|entry| entry.keys.len()).sum::<usize>() >
+				MAX_READ_BATCH_KEYS
+		{
+			debug!("Too large remote read batch request sent by {}.", peer);
+			return Err(HandleRequestError::BadRequest("Remote read batch request too large."))
+		}
+
+		trace!("Remote read batch request from {} ({} entries).", peer, request.entries.len());
+
+		// Group the entries by block, keeping the order in which the blocks first appear.
+		let mut blocks: Vec<(&Vec<u8>, Vec<&schema::v1::light::BlockKeys>)> = Vec::new();
+		for entry in &request.entries {
+			match blocks.iter_mut().find(|(block, _)| **block == entry.block) {
+				Some((_, entries)) => entries.push(entry),
+				None => blocks.push((&entry.block, vec![entry])),
+			}
+		}
+
+		let mut proofs = Vec::with_capacity(blocks.len());
+		let mut proofs_size = 0;
+		for (block, entries) in blocks {
+			let hash = Decode::decode(&mut block.as_ref())?;
+			let size_limit = MAX_RESPONSE_PROOF_SIZE.saturating_sub(proofs_size);
+			let proof = match self.read_block_proof(hash, &entries, size_limit) {
+				Ok(Some(proof)) => Some(proof.encode()),
+				// the keys of a single block don't fit in a response, fail that block.
+				Ok(None) if proofs.is_empty() => {
+					trace!(
+						"remote read batch request from {} (at {:?}) exceeds the response size.",
+						peer,
+						block,
+					);
+					proofs.push(schema::v1::light::BlockReadProof {
+						block: block.clone(),
+						proof: None,
+					});
+					break
+				},
+				Ok(None) => {
+					trace!(
+						"Remote read batch response to {} truncated to {} blocks.",
+						peer,
+						proofs.len(),
+					);
+					break
+				},
+				Err(error) => {
+					trace!(
+						"remote read batch request from {} (at {:?}) failed with: {}",
+						peer,
+						block,
+						error,
+					);
+					None
+				},
+			};
+
+			proofs_size += proof.as_ref().map_or(0, Vec::len);
+			proofs.push(schema::v1::light::BlockReadProof { block: block.clone(), proof });
+		}
+
+		Ok(schema::v1::light::Response {
+			response: Some(schema::v1::light::response::Response::RemoteReadBatchResponse(
+				schema::v1::light::RemoteReadBatchResponse { proofs },
+			)),
+		})
+	}
+
+	/// Compact read proof of the keys of all `entries` at block `hash`.
+	///
+	/// The keys are proven one by one, returns `None` as soon as the proofs read so far exceed
+	/// `size_limit`.
+	fn read_block_proof(
+		&self,
+		hash: B::Hash,
+		entries: &[&schema::v1::light::BlockKeys],
+		size_limit: usize,
+	) -> Result<Option<CompactProof>, sp_blockchain::Error> {
+		let state_root = *self
+			.client
+			.header(hash)?
+			.ok_or_else(|| sp_blockchain::Error::UnknownBlock(hash.to_string()))?
+			.state_root();
+
+		let mut proofs = Vec::new();
+		// nodes shared by the proofs of several keys are counted once per key, so this bounds
+		// the size of the merged proof.
+		let mut size = 0;
+		for entry in entries {
+			let child_info = entry.storage_key.as_ref().map(child_info).transpose()?;
+			for key in &entry.keys {
+				let keys = &mut std::iter::once(&key[..]);
+				let proof = match &child_info {
+					Some(child_info) => self.client.read_child_proof(hash, child_info, keys)?,
+					None => self.client.read_proof(hash, keys)?,
+				};
+
+				size += proof.encoded_size();
+				if size > size_limit {
+					return Ok(None)
+				}
+				proofs.push(proof);
+			}
+		}
+
+		StorageProof::merge(proofs)
+			.into_compact_proof::<HashFor<B>>(state_root)
+			.map(Some)
+			.map_err(|e| sp_blockchain::Error::from_state(Box::new(e)))
+	}
+
+	fn on_remote_read_descendants_request(
+		&mut self,
+		peer: &PeerId,
+		request: &schema::v1::light::RemoteReadDescendantsRequest,
+	) -> Result<schema::v1::light::Response, HandleRequestError> {
+		if request
+			.start_key
+			.as_ref()
+			.map_or(false, |key| !key.starts_with(&request.prefix))
+		{
+			debug!("Invalid remote read descendants request sent by {}.", peer);
+			return Err(HandleRequestError::BadRequest(
+				"Remote read descendants request with start key outside of prefix.",
+			))
+		}
+
+		trace!(
+			"Remote read descendants request from {} ({} at {:?}).",
+			peer,
+			HexDisplay::from(&request.prefix),
+			request.block,
+		);
+
+		let block = Decode::decode(&mut request.block.as_ref())?;
+
+		let child_info = request.storage_key.as_ref().map(child_info).transpose();
+		let response = match child_info.and_then(|child_info| {
+			self.client.read_descendants_proof(
+				block,
+				child_info.as_ref(),
+				&request.prefix,
+				request.start_key.as_deref(),
+				MAX_RESPONSE_PROOF_SIZE,
+			)
+		}) {
+			Ok((proof, _)) => schema::v1::light::RemoteReadResponse { proof: Some(proof.encode()) },
+			Err(error) => {
+				trace!(
+					"remote read descendants request from {} ({} at {:?}) failed with: {}",
+					peer,
+					HexDisplay::from(&request.prefix),
+					request.block,
+					error,
+				);
+				schema::v1::light::RemoteReadResponse { proof: None }
+			},
+		};
+
+		Ok(schema::v1::light::Response {
+			response: Some(schema::v1::light::response::Response::RemoteReadResponse(response)),
+		})
+	}
+}
+
+/// Child trie of the prefixed child storage key `storage_key`.
+fn child_info(storage_key: &Vec<u8>) -> Result<ChildInfo, sp_blockchain::Error> {
+	match ChildType::from_prefixed_key(PrefixedStorageKey::new_ref(storage_key)) {
+		Some((ChildType::ParentKeyId, storage_key)) => Ok(ChildInfo::new_default(storage_key)),
+		None => Err(sp_blockchain::Error::InvalidChildStorageKey),
+	}
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -311,3 +503,176 @@ fn fmt_keys(first: Option<&Vec<u8>>, last: Option<&Vec<u8>>) -> String {
 		String::from("n/a")
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_runtime::traits::BlakeTwo256;
+	use substrate_test_runtime_client::{
+		prelude::*, runtime::Block as TestBlock, TestClient, TestClientBuilder,
+	};
+
+	const CHILD_STORAGE_KEY: &[u8] = b"child";
+
+	fn test_client() -> Arc<TestClient> {
+		Arc::new(
+			TestClientBuilder::new()
+				.add_extra_storage(b":map:acc1".to_vec(), vec![1, 2])
+				.add_extra_storage(b":map:acc2".to_vec(), vec![1, 2, 3])
+				.add_extra_storage(b":other".to_vec(), vec![4])
+				.add_extra_child_storage(
+					&ChildInfo::new_default(CHILD_STORAGE_KEY),
+					b":map:acc1".to_vec(),
+					vec![5],
+				)
+				.build(),
+		)
+	}
+
+	fn handle_request(
+		client: Arc<TestClient>,
+		request: schema::v1::light::request::Request,
+	) -> Result<schema::v1::light::response::Response, HandleRequestError> {
+		let (mut handler, _) =
+			LightClientRequestHandler::<TestBlock, _>::new(&ProtocolId::from("test"), None, client);
+		let payload = schema::v1::light::Request { request: Some(request) }.encode_to_vec();
+		let response = handler.handle_request(PeerId::random(), payload)?;
+		Ok(schema::v1::light::Response::decode(&response[..]).unwrap().response.unwrap())
+	}
+
+	#[test]
+	fn batch_read_returns_compact_proof_per_block() {
+		let client = test_client();
+		let genesis_hash = client.info().genesis_hash;
+		let state_root = *client.header(genesis_hash).unwrap().unwrap().state_root();
+		let unknown_hash = <TestBlock as Block>::Hash::default();
+		let child_info = ChildInfo::new_default(CHILD_STORAGE_KEY);
+
+		let entry = |block: Vec<u8>, storage_key: Option<Vec<u8>>, key: &[u8]| {
+			schema::v1::light::BlockKeys { block, storage_key, keys: vec![key.to_vec()] }
+		};
+		let request = schema::v1::light::RemoteReadBatchRequest {
+			entries: vec![
+				entry(genesis_hash.encode(), None, b":map:acc1"),
+				entry(unknown_hash.encode(), None, b":map:acc1"),
+				entry(
+					genesis_hash.encode(),
+					Some(child_info.prefixed_storage_key().into_inner()),
+					b":map:acc1",
+				),
+			],
+		};
+
+		let proofs = match handle_request(
+			client,
+			schema::v1::light::request::Request::RemoteReadBatchRequest(request),
+		) {
+			Ok(schema::v1::light::response::Response::RemoteReadBatchResponse(response)) =>
+				response.proofs,
+			_ => panic!("Unexpected response"),
+		};
+		assert_eq!(proofs.len(), 2);
+		assert_eq!(proofs[0].block, genesis_hash.encode());
+		assert_eq!(proofs[1].block, unknown_hash.encode());
+		assert!(proofs[1].proof.is_none());
+
+		let proof = CompactProof::decode(&mut &proofs[0].proof.as_ref().unwrap()[..])
+			.unwrap()
+			.to_storage_proof::<BlakeTwo256>(Some(&state_root))
+			.unwrap()
+			.0;
+		let values = sp_state_machine::read_proof_check::<BlakeTwo256, _>(
+			state_root,
+			proof.clone(),
+			[b":map:acc1"],
+		)
+		.unwrap();
+		assert_eq!(values[&b":map:acc1".to_vec()], Some(vec![1, 2]));
+		let values = sp_state_machine::read_child_proof_check::<BlakeTwo256, _>(
+			state_root,
+			proof,
+			&child_info,
+			[b":map:acc1"],
+		)
+		.unwrap();
+		assert_eq!(values[&b":map:acc1".to_vec()], Some(vec![5]));
+	}
+
+	#[test]
+	fn too_large_batch_read_is_rejected() {
+		let request = schema::v1::light::RemoteReadBatchRequest {
+			entries: (0..=MAX_READ_BATCH_ENTRIES)
+				.map(|_| schema::v1::light::BlockKeys {
+					block: Default::default(),
+					storage_key: None,
+					keys: vec![b":map:acc1".to_vec()],
+				})
+				.collect(),
+		};
+
+		assert!(matches!(
+			handle_request(
+				test_client(),
+				schema::v1::light::request::Request::RemoteReadBatchRequest(request),
+			),
+			Err(HandleRequestError::BadRequest(_)),
+		));
+	}
+
+	#[test]
+	fn batch_read_proof_is_bounded_while_building() {
+		let client = test_client();
+		let genesis_hash = client.info().genesis_hash;
+		let (handler, _) =
+			LightClientRequestHandler::<TestBlock, _>::new(&ProtocolId::from("test"), None, client);
+		let entry = schema::v1::light::BlockKeys {
+			block: genesis_hash.encode(),
+			storage_key: None,
+			keys: vec![b":map:acc1".to_vec(), b":map:acc2".to_vec()],
+		};
+
+		assert!(handler
+			.read_block_proof(genesis_hash, &[&entry], MAX_RESPONSE_PROOF_SIZE)
+			.unwrap()
+			.is_some());
+		// the proof isn't built in full before checking its size.
+		assert!(handler.read_block_proof(genesis_hash, &[&entry], 1).unwrap().is_none());
+	}
+
+	#[test]
+	fn descendants_read_proves_all_keys_with_prefix() {
+		let client = test_client();
+		let genesis_hash = client.info().genesis_hash;
+		let state_root = *client.header(genesis_hash).unwrap().unwrap().state_root();
+
+		let request = schema::v1::light::RemoteReadDescendantsRequest {
+			block: genesis_hash.encode(),
+			storage_key: None,
+			prefix: b":map:".to_vec(),
+			start_key: None,
+		};
+		let proof = match handle_request(
+			client,
+			schema::v1::light::request::Request::RemoteReadDescendantsRequest(request),
+		) {
+			Ok(schema::v1::light::response::Response::RemoteReadResponse(response)) =>
+				response.proof.unwrap(),
+			_ => panic!("Unexpected response"),
+		};
+
+		let (values, complete) = sp_state_machine::read_range_proof_check::<BlakeTwo256>(
+			state_root,
+			StorageProof::decode(&mut &proof[..]).unwrap(),
+			None,
+			Some(b":map:"),
+			None,
+			None,
+		)
+		.unwrap();
+		assert!(complete);
+		assert_eq!(
+			values,
+			vec![(b":map:acc1".to_vec(), vec![1, 2]), (b":map:acc2".to_vec(), vec![1, 2, 3])],
+		);
+	}
+}
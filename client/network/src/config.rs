@@ -38,7 +38,7 @@ use zeroize::Zeroize;
 
 pub use sc_network_common::{
 	role::{Role, Roles},
-	sync::{warp::WarpSyncProvider, SyncMode},
+	sync::{warp::WarpSyncProvider, CheckpointSyncConfig, GapSyncConfig, SyncMode},
 	ExHashT,
 };
 use sc_utils::mpsc::TracingUnboundedSender;
@@ -561,6 +561,12 @@ pub struct NetworkConfiguration {
 	/// Initial syncing mode.
 	pub sync_mode: SyncMode,
 
+	/// Trusted block to start from with [`SyncMode::Checkpoint`].
+	pub sync_checkpoint: Option<CheckpointSyncConfig>,
+
+	/// Download of the block history missing after warp or checkpoint sync.
+	pub gap_sync: GapSyncConfig,
+
 	/// True if Kademlia random discovery should be enabled.
 	///
 	/// If true, the node will automatically randomly walk the DHT in order to find new peers.
@@ -582,6 +588,12 @@ pub struct NetworkConfiguration {
 	/// Enable serving block data over IPFS bitswap.
 	pub ipfs_server: bool,
 
+	/// Also serve block headers and bodies over IPFS bitswap.
+	pub ipfs_serve_blocks: bool,
+
+	/// Also serve state trie nodes over IPFS bitswap.
+	pub ipfs_serve_trie_nodes: bool,
+
 	/// Size of Yamux receive window of all substreams. `None` for the default (256kiB).
 	/// Any value less than 256kiB is invalid.
 	///
@@ -627,6 +639,8 @@ impl NetworkConfiguration {
 			max_parallel_downloads: 5,
 			max_blocks_per_request: 64,
 			sync_mode: SyncMode::Full,
+			sync_checkpoint: None,
+			gap_sync: GapSyncConfig::default(),
 			enable_dht_random_walk: true,
 			allow_non_globals_in_dht: false,
 			kademlia_disjoint_query_paths: false,
@@ -634,6 +648,8 @@ impl NetworkConfiguration {
 				.expect("value is a constant; constant is non-zero; qed."),
 			yamux_window_size: None,
 			ipfs_server: false,
+			ipfs_serve_blocks: false,
+			ipfs_serve_trie_nodes: false,
 		}
 	}
 
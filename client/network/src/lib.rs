@@ -263,6 +263,8 @@ pub mod utils;
 pub use event::{DhtEvent, Event, SyncEvent};
 #[doc(inline)]
 pub use libp2p::{multiaddr, Multiaddr, PeerId};
+#[doc(inline)]
+pub use peer_store::PeerReputation;
 pub use request_responses::{Config, IfDisconnected, RequestFailure};
 pub use sc_network_common::{
 	role::ObservedRole,
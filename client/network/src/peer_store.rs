@@ -16,17 +16,21 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use libp2p::PeerId;
-use log::trace;
+use libp2p::{multiaddr::Protocol, Multiaddr, PeerId};
+use log::{debug, trace, warn};
 use parking_lot::Mutex;
 use partial_sort::PartialSort;
 use sc_network_common::types::ReputationChange;
+use serde::{Deserialize, Serialize};
 use std::{
 	cmp::{Ord, Ordering, PartialOrd},
 	collections::{hash_map::Entry, HashMap, HashSet},
 	fmt::Debug,
+	fs,
+	io::{self, Write},
+	path::{Path, PathBuf},
 	sync::Arc,
-	time::{Duration, Instant},
+	time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 use wasm_timer::Delay;
 
@@ -48,6 +52,10 @@ const INVERSE_DECREMENT: i32 = 50;
 /// Amount of time between the moment we last updated the [`PeerStore`] entry and the moment we
 /// remove it, once the reputation value reaches 0.
 const FORGET_AFTER: Duration = Duration::from_secs(3600);
+/// Maximum number of addresses remembered for a single peer.
+const MAX_ADDRESSES_PER_PEER: usize = 8;
+/// Interval between two writes of the [`PeerStore`] to disk.
+const PERSIST_INTERVAL: Duration = Duration::from_secs(60);
 
 pub trait PeerStoreProvider: Debug + Send {
 	/// Check whether the peer is banned.
@@ -113,17 +121,54 @@ impl PeerStoreHandle {
 	pub fn add_known_peer(&mut self, peer_id: PeerId) {
 		self.inner.lock().add_known_peer(peer_id);
 	}
+
+	/// Remember an address the peer was reached at.
+	pub fn add_known_address(&mut self, peer_id: PeerId, address: Multiaddr) {
+		self.inner.lock().add_known_address(peer_id, address);
+	}
+
+	/// Get the addresses remembered for known peers.
+	pub fn known_addresses(&self) -> Vec<(PeerId, Multiaddr)> {
+		self.inner.lock().known_addresses()
+	}
+
+	/// Get the reputations of all known peers, highest first.
+	pub fn peer_reputations(&self) -> Vec<PeerReputation> {
+		self.inner.lock().peer_reputations()
+	}
+}
+
+/// Reputation of a peer known to the [`PeerStore`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerReputation {
+	/// Peer ID.
+	pub peer_id: PeerId,
+	/// Current reputation value.
+	pub reputation: i32,
+	/// Whether the peer is banned, i.e. its reputation is below [`BANNED_THRESHOLD`].
+	pub banned: bool,
+	/// Reason of the report that got the peer banned, if it is banned.
+	pub ban_reason: Option<String>,
+	/// Addresses the peer was last reached at, most recent first.
+	pub addresses: Vec<Multiaddr>,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 struct PeerInfo {
 	reputation: i32,
 	last_updated: Instant,
+	ban_reason: Option<String>,
+	addresses: Vec<Multiaddr>,
 }
 
 impl Default for PeerInfo {
 	fn default() -> Self {
-		Self { reputation: 0, last_updated: Instant::now() }
+		Self {
+			reputation: 0,
+			last_updated: Instant::now(),
+			ban_reason: None,
+			addresses: Vec::new(),
+		}
 	}
 }
 
@@ -175,6 +220,16 @@ impl PeerInfo {
 				break
 			}
 		}
+
+		if !self.is_banned() {
+			self.ban_reason = None;
+		}
+	}
+
+	fn add_address(&mut self, address: Multiaddr) {
+		self.addresses.retain(|known| *known != address);
+		self.addresses.insert(0, address);
+		self.addresses.truncate(MAX_ADDRESSES_PER_PEER);
 	}
 
 	fn bump_last_updated(&mut self) {
@@ -215,6 +270,7 @@ impl PeerStoreInner {
 		peer_info.add_reputation(change.value);
 
 		if peer_info.reputation < BANNED_THRESHOLD {
+			peer_info.ban_reason.get_or_insert_with(|| change.reason.to_string());
 			self.protocols.iter().for_each(|handle| handle.disconnect_peer(peer_id));
 
 			log::trace!(
@@ -246,7 +302,7 @@ impl PeerStoreInner {
 			.peers
 			.iter()
 			.filter_map(|(peer_id, info)| {
-				(!info.is_banned() && !ignored.contains(peer_id)).then_some((*peer_id, *info))
+				(!info.is_banned() && !ignored.contains(peer_id)).then_some((*peer_id, info))
 			})
 			.collect::<Vec<_>>();
 		let count = std::cmp::min(count, candidates.len());
@@ -287,25 +343,201 @@ impl PeerStoreInner {
 			},
 		}
 	}
+
+	fn add_known_address(&mut self, peer_id: PeerId, address: Multiaddr) {
+		// Addresses are stored without the `/p2p/<peer id>` suffix, like all the other addresses
+		// known to the discovery.
+		let address: Multiaddr =
+			address.iter().filter(|p| !matches!(p, Protocol::P2p(_))).collect();
+		if address.is_empty() {
+			return
+		}
+
+		let peer_info = self.peers.entry(peer_id).or_default();
+		peer_info.add_address(address);
+		peer_info.bump_last_updated();
+	}
+
+	fn known_addresses(&self) -> Vec<(PeerId, Multiaddr)> {
+		self.peers
+			.iter()
+			.filter(|(_, info)| !info.is_banned())
+			.flat_map(|(peer_id, info)| {
+				info.addresses.iter().map(move |address| (*peer_id, address.clone()))
+			})
+			.collect()
+	}
+
+	fn peer_reputations(&self) -> Vec<PeerReputation> {
+		let mut reputations = self
+			.peers
+			.iter()
+			.map(|(peer_id, info)| PeerReputation {
+				peer_id: *peer_id,
+				reputation: info.reputation,
+				banned: info.is_banned(),
+				ban_reason: info.ban_reason.clone(),
+				addresses: info.addresses.clone(),
+			})
+			.collect::<Vec<_>>();
+		reputations.sort_by(|a, b| b.reputation.cmp(&a.reputation));
+		reputations
+	}
+
+	/// Snapshot the entries to be written to disk at UNIX time `now_unix`.
+	fn to_persisted(&self, now_unix: u64) -> PersistedPeerStore {
+		let now = Instant::now();
+		let peers = self
+			.peers
+			.iter()
+			.map(|(peer_id, info)| PersistedPeer {
+				peer_id: peer_id.to_base58(),
+				reputation: info.reputation,
+				last_updated_ago: now.saturating_duration_since(info.last_updated).as_secs(),
+				ban_reason: info.ban_reason.clone(),
+				addresses: info.addresses.iter().map(ToString::to_string).collect(),
+			})
+			.collect();
+
+		PersistedPeerStore { saved_at: now_unix, peers }
+	}
+
+	/// Restore the entries read from disk, decaying the reputations for the time elapsed between
+	/// the moment they were written and `now_unix`.
+	fn load_persisted(&mut self, persisted: PersistedPeerStore, now_unix: u64) {
+		let elapsed = now_unix.saturating_sub(persisted.saved_at);
+		let now = Instant::now();
+
+		for peer in persisted.peers {
+			let peer_id = match peer.peer_id.parse::<PeerId>() {
+				Ok(peer_id) => peer_id,
+				Err(err) => {
+					debug!(
+						target: LOG_TARGET,
+						"Skipping persisted peer with invalid ID {}: {err}",
+						peer.peer_id,
+					);
+					continue
+				},
+			};
+
+			let age = Duration::from_secs(peer.last_updated_ago.saturating_add(elapsed));
+			let mut info = PeerInfo {
+				reputation: peer.reputation,
+				last_updated: now.checked_sub(age).unwrap_or(now),
+				ban_reason: peer.ban_reason,
+				addresses: peer
+					.addresses
+					.iter()
+					.filter_map(|address| address.parse().ok())
+					.take(MAX_ADDRESSES_PER_PEER)
+					.collect(),
+			};
+			info.decay_reputation(elapsed);
+
+			if info.reputation == 0 && age >= FORGET_AFTER {
+				continue
+			}
+
+			self.peers.insert(peer_id, info);
+		}
+
+		trace!(
+			target: LOG_TARGET,
+			"Loaded persisted peer store written {elapsed} seconds ago, {} peers known.",
+			self.peers.len(),
+		);
+	}
+}
+
+/// On-disk representation of the [`PeerStore`].
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PersistedPeerStore {
+	/// UNIX time in seconds the file was written at.
+	saved_at: u64,
+	/// Known peers.
+	peers: Vec<PersistedPeer>,
+}
+
+/// On-disk representation of a single [`PeerStore`] entry.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PersistedPeer {
+	/// Base58-encoded peer ID.
+	peer_id: String,
+	/// Reputation value at the moment the file was written.
+	reputation: i32,
+	/// Seconds elapsed since the last update of the entry at the moment the file was written.
+	#[serde(default)]
+	last_updated_ago: u64,
+	/// Reason the peer was banned for, if it is banned.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	ban_reason: Option<String>,
+	/// Addresses the peer was last reached at, most recent first.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	addresses: Vec<String>,
+}
+
+/// Current UNIX time in seconds.
+fn unix_time() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+/// Read the persisted peer store from `path`. Returns `Ok(None)` if the file doesn't exist.
+fn read_persisted(path: &Path) -> io::Result<Option<PersistedPeerStore>> {
+	let file = match fs::File::open(path) {
+		Ok(file) => file,
+		Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+		Err(err) => return Err(err),
+	};
+
+	Ok(Some(serde_json::from_reader(io::BufReader::new(file))?))
+}
+
+/// Write the persisted peer store to `path`, replacing the previous file atomically.
+fn write_persisted(path: &Path, persisted: &PersistedPeerStore) -> io::Result<()> {
+	let tmp_path = path.with_extension("tmp");
+	let mut writer = io::BufWriter::new(fs::File::create(&tmp_path)?);
+	serde_json::to_writer(&mut writer, persisted)?;
+	writer.flush()?;
+	drop(writer);
+
+	fs::rename(tmp_path, path)
 }
 
 #[derive(Debug)]
 pub struct PeerStore {
 	inner: Arc<Mutex<PeerStoreInner>>,
+	/// File the peer store is periodically written to, if persistence is enabled.
+	persistence_path: Option<PathBuf>,
 }
 
 impl PeerStore {
 	/// Create a new peer store from the list of bootnodes.
-	pub fn new(bootnodes: Vec<PeerId>) -> Self {
-		PeerStore {
-			inner: Arc::new(Mutex::new(PeerStoreInner {
-				peers: bootnodes
-					.into_iter()
-					.map(|peer_id| (peer_id, PeerInfo::default()))
-					.collect(),
-				protocols: Vec::new(),
-			})),
+	///
+	/// If `persistence_path` is set, entries previously written to this file are loaded with
+	/// their reputations decayed for the time the node was offline, and the peer store is
+	/// periodically written back to it.
+	pub fn new(bootnodes: Vec<PeerId>, persistence_path: Option<PathBuf>) -> Self {
+		let mut inner = PeerStoreInner {
+			peers: bootnodes.into_iter().map(|peer_id| (peer_id, PeerInfo::default())).collect(),
+			protocols: Vec::new(),
+		};
+
+		if let Some(path) = &persistence_path {
+			match read_persisted(path) {
+				Ok(Some(persisted)) => inner.load_persisted(persisted, unix_time()),
+				Ok(None) => {},
+				Err(err) => warn!(
+					target: LOG_TARGET,
+					"Failed to load persisted peer store from {}: {err}",
+					path.display(),
+				),
+			}
 		}
+
+		PeerStore { inner: Arc::new(Mutex::new(inner)), persistence_path }
 	}
 
 	/// Get `PeerStoreHandle`.
@@ -313,10 +545,51 @@ impl PeerStore {
 		PeerStoreHandle { inner: self.inner.clone() }
 	}
 
-	/// Drive the `PeerStore`, decaying reputation values over time and removing expired entries.
+	/// Write the peer store to disk, if persistence is enabled.
+	///
+	/// Serialization and file IO run on the blocking thread pool so that they don't stall the
+	/// network worker polling this future.
+	async fn persist(&self) {
+		let Some(path) = self.persistence_path.clone() else { return };
+
+		let persisted = self.inner.lock().to_persisted(unix_time());
+		let result = tokio::task::spawn_blocking(move || {
+			let result = write_persisted(&path, &persisted);
+			(path, result)
+		})
+		.await;
+
+		match result {
+			Ok((_, Ok(()))) => {},
+			Ok((path, Err(err))) => warn!(
+				target: LOG_TARGET,
+				"Failed to persist peer store to {}: {err}",
+				path.display(),
+			),
+			Err(err) => warn!(target: LOG_TARGET, "Peer store persistence task failed: {err}"),
+		}
+	}
+
+	/// Write the peer store to disk from the current thread, if persistence is enabled.
+	fn persist_blocking(&self) {
+		let Some(path) = &self.persistence_path else { return };
+
+		let persisted = self.inner.lock().to_persisted(unix_time());
+		if let Err(err) = write_persisted(path, &persisted) {
+			warn!(
+				target: LOG_TARGET,
+				"Failed to persist peer store to {}: {err}",
+				path.display(),
+			);
+		}
+	}
+
+	/// Drive the `PeerStore`, decaying reputation values over time, removing expired entries and
+	/// periodically persisting it to disk.
 	pub async fn run(self) {
 		let started = Instant::now();
 		let mut latest_time_update = started;
+		let mut latest_persist = started;
 
 		loop {
 			let now = Instant::now();
@@ -330,14 +603,31 @@ impl PeerStore {
 			};
 
 			self.inner.lock().progress_time(seconds_passed);
+
+			if now - latest_persist >= PERSIST_INTERVAL {
+				latest_persist = now;
+				self.persist().await;
+			}
+
 			let _ = Delay::new(Duration::from_secs(1)).await;
 		}
 	}
 }
 
+impl Drop for PeerStore {
+	/// The peer store future is dropped when the node shuts down, so write the latest state to
+	/// disk one last time. Reputation changes since the previous periodic write would be lost
+	/// otherwise.
+	fn drop(&mut self) {
+		self.persist_blocking();
+	}
+}
+
 #[cfg(test)]
 mod tests {
-	use super::PeerInfo;
+	use super::{PeerInfo, PeerStore, PeerStoreProvider, BANNED_THRESHOLD};
+	use libp2p::{multiaddr::Protocol, Multiaddr, PeerId};
+	use sc_network_common::types::ReputationChange;
 
 	#[test]
 	fn decaying_zero_reputation_yields_zero() {
@@ -404,4 +694,104 @@ mod tests {
 		peer_info.decay_reputation(SECONDS / 2);
 		assert_eq!(peer_info.reputation, 0);
 	}
+
+	#[test]
+	fn banning_peer_records_reason() {
+		let peer_id = PeerId::random();
+		let mut handle = PeerStore::new(Vec::new(), None).handle();
+
+		handle.report_peer(peer_id, ReputationChange::new(-10, "Minor misbehavior"));
+		let reputations = handle.peer_reputations();
+		assert!(!reputations[0].banned);
+		assert_eq!(reputations[0].ban_reason, None);
+
+		handle.report_peer(peer_id, ReputationChange::new_fatal("Bad block"));
+		handle.report_peer(peer_id, ReputationChange::new(-10, "Minor misbehavior"));
+		let reputations = handle.peer_reputations();
+		assert!(reputations[0].banned);
+		assert_eq!(reputations[0].ban_reason.as_deref(), Some("Bad block"));
+
+		// The reason is forgotten once the reputation has recovered.
+		handle.inner.lock().progress_time(100);
+		let reputations = handle.peer_reputations();
+		assert!(reputations[0].reputation >= BANNED_THRESHOLD);
+		assert_eq!(reputations[0].ban_reason, None);
+	}
+
+	#[test]
+	fn persisted_peer_store_is_restored_with_decay() {
+		const SAVED_AT: u64 = 1_000_000;
+
+		let banned = PeerId::random();
+		let good = PeerId::random();
+		let address: Multiaddr = "/ip4/127.0.0.1/tcp/30333".parse().unwrap();
+
+		let mut handle = PeerStore::new(Vec::new(), None).handle();
+		handle.report_peer(banned, ReputationChange::new_fatal("Bad block"));
+		handle.report_peer(good, ReputationChange::new(1000, "Good block"));
+		handle.add_known_address(good, address.clone().with(Protocol::P2p(good.into())));
+		let persisted = handle.inner.lock().to_persisted(SAVED_AT);
+
+		// Round-trip through the on-disk format.
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("peer_store.json");
+		super::write_persisted(&path, &persisted).unwrap();
+		let persisted = super::read_persisted(&path).unwrap().unwrap();
+
+		let restored = PeerStore::new(Vec::new(), None).handle();
+		restored.inner.lock().load_persisted(persisted, SAVED_AT + 5);
+
+		let banned_reputation = restored.peer_reputation(&banned);
+		assert!(banned_reputation < BANNED_THRESHOLD);
+		assert!(banned_reputation > i32::MIN);
+		assert!(restored.is_banned(&banned));
+
+		let good_reputation = restored.peer_reputation(&good);
+		assert!(good_reputation > 0 && good_reputation < 1000);
+		assert_eq!(restored.known_addresses(), vec![(good, address)]);
+	}
+
+	#[test]
+	fn expired_persisted_entries_are_dropped() {
+		const SAVED_AT: u64 = 1_000_000;
+
+		let peer_id = PeerId::random();
+		let mut handle = PeerStore::new(Vec::new(), None).handle();
+		handle.report_peer(peer_id, ReputationChange::new(100, "Good block"));
+		let persisted = handle.inner.lock().to_persisted(SAVED_AT);
+
+		let restored = PeerStore::new(Vec::new(), None).handle();
+		restored.inner.lock().load_persisted(persisted, SAVED_AT + 2 * 3600);
+		assert_eq!(restored.num_known_peers(), 0);
+	}
+
+	#[tokio::test]
+	async fn missing_persisted_file_is_ignored() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("peer_store.json");
+		let bootnode = PeerId::random();
+
+		let store = PeerStore::new(vec![bootnode], Some(path.clone()));
+		assert_eq!(store.handle().num_known_peers(), 1);
+
+		store.persist().await;
+		let restored = PeerStore::new(Vec::new(), Some(path));
+		assert_eq!(restored.handle().peer_reputations()[0].peer_id, bootnode);
+	}
+
+	#[test]
+	fn peer_store_is_persisted_on_drop() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("peer_store.json");
+		let peer_id = PeerId::random();
+
+		let store = PeerStore::new(Vec::new(), Some(path.clone()));
+		let mut handle = store.handle();
+		handle.report_peer(peer_id, ReputationChange::new(-100, "test"));
+		assert!(!path.exists());
+
+		drop(store);
+		let restored = PeerStore::new(Vec::new(), Some(path)).handle();
+		assert!(restored.peer_reputation(&peer_id) < 0);
+	}
 }
@@ -33,7 +33,7 @@
 //! will at all time try to maintain a connection with.
 
 use crate::{
-	peer_store::{PeerStore, PeerStoreHandle, PeerStoreProvider},
+	peer_store::{PeerReputation, PeerStore, PeerStoreHandle, PeerStoreProvider},
 	protocol_controller::{ProtocolController, ProtocolHandle},
 };
 
@@ -49,11 +49,12 @@ use sc_utils::mpsc::{tracing_unbounded, TracingUnboundedReceiver, TracingUnbound
 use serde_json::json;
 use std::{
 	collections::HashSet,
+	path::PathBuf,
 	pin::Pin,
 	task::{Context, Poll},
 };
 
-use libp2p::PeerId;
+use libp2p::{Multiaddr, PeerId};
 
 pub const LOG_TARGET: &str = "peerset";
 
@@ -66,6 +67,8 @@ enum Action {
 	ReportPeer(PeerId, ReputationChange),
 	AddKnownPeer(PeerId),
 	PeerReputation(PeerId, oneshot::Sender<i32>),
+	AddKnownAddress(PeerId, Multiaddr),
+	PeerReputations(oneshot::Sender<Vec<PeerReputation>>),
 }
 
 /// Identifier of a set in the peerset.
@@ -150,6 +153,21 @@ impl PeersetHandle {
 		// The channel can only be closed if the peerset no longer exists.
 		rx.await.map_err(|_| ())
 	}
+
+	/// Remember an address we successfully reached the peer at.
+	pub fn add_known_address(&self, peer_id: PeerId, address: Multiaddr) {
+		let _ = self.tx.unbounded_send(Action::AddKnownAddress(peer_id, address));
+	}
+
+	/// Returns the reputations of all known peers, highest first.
+	pub async fn peer_reputations(self) -> Result<Vec<PeerReputation>, ()> {
+		let (tx, rx) = oneshot::channel();
+
+		let _ = self.tx.unbounded_send(Action::PeerReputations(tx));
+
+		// The channel can only be closed if the peerset no longer exists.
+		rx.await.map_err(|_| ())
+	}
 }
 
 /// Message that can be sent by the peer set manager (PSM).
@@ -194,6 +212,9 @@ impl From<u64> for IncomingIndex {
 pub struct PeersetConfig {
 	/// List of sets of nodes the peerset manages.
 	pub sets: Vec<SetConfig>,
+
+	/// File to persist peer reputations and addresses to. Persistence is disabled if `None`.
+	pub peer_store_path: Option<PathBuf>,
 }
 
 /// Configuration for a single set of nodes.
@@ -245,7 +266,8 @@ impl Peerset {
 	/// Builds a new peerset from the given configuration.
 	pub fn from_config(config: PeersetConfig) -> (Peerset, PeersetHandle) {
 		let default_set_config = &config.sets[0];
-		let peer_store = PeerStore::new(default_set_config.bootnodes.clone());
+		let peer_store =
+			PeerStore::new(default_set_config.bootnodes.clone(), config.peer_store_path);
 
 		let (to_notifications, from_controllers) =
 			tracing_unbounded("mpsc_protocol_controllers_to_notifications", 10_000);
@@ -323,6 +345,11 @@ impl Peerset {
 	pub fn num_discovered_peers(&self) -> usize {
 		self.peer_store_handle.num_known_peers()
 	}
+
+	/// Returns the addresses remembered for known peers that are not banned.
+	pub fn known_addresses(&self) -> Vec<(PeerId, Multiaddr)> {
+		self.peer_store_handle.known_addresses()
+	}
 }
 
 impl Stream for Peerset {
@@ -359,6 +386,11 @@ impl Stream for Peerset {
 						let _ =
 							pending_response.send(self.peer_store_handle.peer_reputation(&peer_id));
 					},
+					Action::AddKnownAddress(peer_id, address) =>
+						self.peer_store_handle.add_known_address(peer_id, address),
+					Action::PeerReputations(pending_response) => {
+						let _ = pending_response.send(self.peer_store_handle.peer_reputations());
+					},
 				}
 			} else {
 				debug!(target: LOG_TARGET, "`PeersetHandle` was dropped, terminating `Peerset`.");
@@ -66,6 +66,8 @@ const HARDCODED_PEERSETS_SYNC: crate::peerset::SetId = crate::peerset::SetId::fr
 /// Number of hardcoded peersets (the constants right above). Any set whose identifier is equal or
 /// superior to this value corresponds to a user-defined protocol.
 const NUM_HARDCODED_PEERSETS: usize = 1;
+/// Name of the file, inside the network configuration directory, the peer store is persisted to.
+const PEER_STORE_FILE: &str = "peer_store.json";
 
 mod rep {
 	use crate::ReputationChange as Rep;
@@ -156,9 +158,18 @@ impl<B: BlockT> Protocol<B> {
 				});
 			}
 
-			crate::peerset::Peerset::from_config(crate::peerset::PeersetConfig { sets })
+			crate::peerset::Peerset::from_config(crate::peerset::PeersetConfig {
+				sets,
+				peer_store_path: network_config
+					.net_config_path
+					.as_ref()
+					.map(|path| path.join(PEER_STORE_FILE)),
+			})
 		};
 
+		// Let the discovery know where to find the peers remembered from previous runs.
+		known_addresses.extend(peerset.known_addresses());
+
 		let behaviour = {
 			Notifications::new(
 				peerset,
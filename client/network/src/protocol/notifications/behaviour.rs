@@ -2166,7 +2166,10 @@ mod tests {
 				reserved_only: false,
 			});
 
-			crate::peerset::Peerset::from_config(crate::peerset::PeersetConfig { sets })
+			crate::peerset::Peerset::from_config(crate::peerset::PeersetConfig {
+				sets,
+				peer_store_path: None,
+			})
 		};
 
 		(
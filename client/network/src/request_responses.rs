@@ -1092,6 +1092,7 @@ mod tests {
 				reserved_nodes: Default::default(),
 				reserved_only: false,
 			}],
+			peer_store_path: None,
 		};
 
 		let (peerset, handle) = Peerset::from_config(config);
@@ -36,6 +36,7 @@ use crate::{
 	network_state::{
 		NetworkState, NotConnectedPeer as NetworkStateNotConnectedPeer, Peer as NetworkStatePeer,
 	},
+	peer_store::PeerReputation,
 	peerset::PeersetHandle,
 	protocol::{self, NotifsHandlerError, Protocol, Ready},
 	request_responses::{IfDisconnected, RequestFailure},
@@ -668,6 +669,13 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkService<B, H> {
 		rx.await.map_err(|_| ())
 	}
 
+	/// Get the reputations of all peers known to the peer store, highest first.
+	///
+	/// Returns an error if the `NetworkWorker` is no longer running.
+	pub async fn peer_reputations(&self) -> Result<Vec<PeerReputation>, ()> {
+		self.peerset.clone().peer_reputations().await
+	}
+
 	/// Utility function to extract `PeerId` from each `Multiaddr` for peer set updates.
 	///
 	/// Returns an `Err` if one of the given addresses is invalid or contains an
@@ -1561,6 +1569,12 @@ where
 						metrics.distinct_peers_connections_opened_total.inc();
 					}
 				}
+
+				// Only addresses we dialed are known to be reachable, the remote address of an
+				// inbound connection is usually an ephemeral port.
+				if let ConnectedPoint::Dialer { address, .. } = endpoint {
+					self.service.peerset.add_known_address(peer_id, address);
+				}
 			},
 			SwarmEvent::ConnectionClosed { peer_id, cause, endpoint, num_established } => {
 				debug!(target: "sub-libp2p", "Libp2p => Disconnected({:?}, {:?})", peer_id, cause);
@@ -360,6 +360,8 @@ where
 			max_parallel_downloads,
 			max_blocks_per_request,
 			warp_sync_params,
+			net_config.network_config.gap_sync.clone(),
+			net_config.network_config.sync_checkpoint.clone(),
 			metrics_registry,
 			network_service.clone(),
 			import_queue,
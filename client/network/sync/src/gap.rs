@@ -0,0 +1,328 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+//! Background download of the block history missing after warp or checkpoint sync.
+//!
+//! Warp and checkpoint sync import the state of a recent block without its ancestors, leaving a
+//! gap in the block history. The gap is filled upwards from its first block, with the blocks
+//! being imported without execution.
+
+use crate::{blocks::BlockCollection, PeerSync, MAX_DOWNLOAD_AHEAD};
+use libp2p::PeerId;
+use log::{debug, info};
+use prometheus_endpoint::{register, Counter, Gauge, PrometheusError, Registry, U64};
+use sc_network_common::sync::{
+	message::{BlockAttributes, BlockRequest, Direction, FromBlock},
+	GapSyncConfig, GapSyncProgress,
+};
+use sp_runtime::traits::{Block as BlockT, NumberFor, One, SaturatedConversion};
+use std::{
+	ops::Range,
+	time::{Duration, Instant},
+};
+
+/// Length of the window the request rate limit applies to.
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(1);
+
+/// Prometheus metrics of the block history download.
+#[derive(Clone)]
+pub(crate) struct GapSyncMetrics {
+	best_queued: Gauge<U64>,
+	best_imported: Gauge<U64>,
+	target: Gauge<U64>,
+	blocks_requested: Counter<U64>,
+}
+
+impl GapSyncMetrics {
+	pub(crate) fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			best_queued: register(
+				Gauge::new(
+					"substrate_sync_gap_best_queued",
+					"Highest block of the history download queued for import.",
+				)?,
+				registry,
+			)?,
+			best_imported: register(
+				Gauge::new(
+					"substrate_sync_gap_best_imported",
+					"Highest block of the history download imported.",
+				)?,
+				registry,
+			)?,
+			target: register(
+				Gauge::new("substrate_sync_gap_target", "Last block of the history download.")?,
+				registry,
+			)?,
+			blocks_requested: register(
+				Counter::new(
+					"substrate_sync_gap_blocks_requested",
+					"Number of history blocks requested from peers.",
+				)?,
+				registry,
+			)?,
+		})
+	}
+}
+
+/// Limits the number of blocks requested per `RATE_LIMIT_WINDOW`.
+struct RateLimit {
+	max_blocks: u32,
+	window_start: Instant,
+	requested: u32,
+}
+
+impl RateLimit {
+	/// Number of blocks that may still be requested at `now`.
+	fn allowance(&mut self, now: Instant) -> u32 {
+		if now.saturating_duration_since(self.window_start) >= RATE_LIMIT_WINDOW {
+			self.window_start = now;
+			self.requested = 0;
+		}
+		self.max_blocks.saturating_sub(self.requested)
+	}
+}
+
+/// Block history download state.
+pub(crate) struct GapSync<B: BlockT> {
+	blocks: BlockCollection<B>,
+	start: NumberFor<B>,
+	best_queued_number: NumberFor<B>,
+	best_imported_number: NumberFor<B>,
+	target: NumberFor<B>,
+	rate_limit: Option<RateLimit>,
+	metrics: Option<GapSyncMetrics>,
+}
+
+impl<B: BlockT> GapSync<B> {
+	/// Start downloading the blocks `start..=end`, as far as `config` allows.
+	///
+	/// Returns `None` if there is nothing to download.
+	pub(crate) fn new(
+		start: NumberFor<B>,
+		end: NumberFor<B>,
+		config: &GapSyncConfig,
+		metrics: Option<GapSyncMetrics>,
+	) -> Option<Self> {
+		if !config.enabled {
+			info!(target: "sync", "Block history download #{} - #{} is disabled", start, end);
+			return None
+		}
+		let target = match config.target {
+			Some(target) => std::cmp::min(end, target.saturated_into()),
+			None => end,
+		};
+		if target < start {
+			debug!(
+				target: "sync",
+				"Block history up to #{} is already downloaded, skipping #{} - #{}",
+				target,
+				start,
+				end,
+			);
+			return None
+		}
+
+		debug!(target: "sync", "Starting gap sync #{} - #{}", start, target);
+		let best_queued_number = start - One::one();
+		if let Some(metrics) = &metrics {
+			metrics.best_queued.set(best_queued_number.saturated_into());
+			metrics.best_imported.set(best_queued_number.saturated_into());
+			metrics.target.set(target.saturated_into());
+		}
+		Some(Self {
+			blocks: BlockCollection::new(),
+			start,
+			best_queued_number,
+			best_imported_number: best_queued_number,
+			target,
+			rate_limit: config.max_blocks_per_second.map(|max_blocks| RateLimit {
+				max_blocks: max_blocks.get(),
+				window_start: Instant::now(),
+				requested: 0,
+			}),
+			metrics,
+		})
+	}
+
+	/// Current progress of the download.
+	pub(crate) fn progress(&self) -> GapSyncProgress<NumberFor<B>> {
+		GapSyncProgress {
+			start: self.start,
+			best_queued: self.best_queued_number,
+			best_imported: self.best_imported_number,
+			target: self.target,
+		}
+	}
+
+	/// Highest block of the history queued for import.
+	pub(crate) fn best_queued_number(&self) -> NumberFor<B> {
+		self.best_queued_number
+	}
+
+	/// Downloaded blocks of the history.
+	pub(crate) fn blocks(&mut self) -> &mut BlockCollection<B> {
+		&mut self.blocks
+	}
+
+	/// Take the downloaded blocks that directly follow the best queued block.
+	pub(crate) fn ready_blocks(&mut self) -> Vec<crate::blocks::BlockData<B>> {
+		self.blocks.ready_blocks(self.best_queued_number + One::one())
+	}
+
+	/// Called when a block has been queued for import.
+	pub(crate) fn on_block_queued(&mut self, number: NumberFor<B>) {
+		if number > self.best_queued_number && number <= self.target {
+			self.best_queued_number = number;
+			if let Some(metrics) = &self.metrics {
+				metrics.best_queued.set(number.saturated_into());
+			}
+		}
+	}
+
+	/// Called when a block has been imported.
+	///
+	/// Returns `true` once the download is complete.
+	pub(crate) fn on_block_imported(&mut self, number: NumberFor<B>) -> bool {
+		if number > self.best_imported_number && number <= self.target {
+			self.best_imported_number = number;
+			if let Some(metrics) = &self.metrics {
+				metrics.best_imported.set(number.saturated_into());
+			}
+		}
+		number == self.target
+	}
+
+	/// Get a new block request for the peer if any.
+	pub(crate) fn block_request(
+		&mut self,
+		id: &PeerId,
+		peer: &PeerSync<B>,
+		attrs: BlockAttributes,
+		max_blocks_per_request: u32,
+	) -> Option<(Range<NumberFor<B>>, BlockRequest<B>)> {
+		let count = match &mut self.rate_limit {
+			Some(rate_limit) =>
+				std::cmp::min(max_blocks_per_request, rate_limit.allowance(Instant::now())),
+			None => max_blocks_per_request,
+		};
+		if count == 0 {
+			return None
+		}
+
+		let range = self.blocks.needed_blocks(
+			*id,
+			count,
+			std::cmp::min(peer.best_number, self.target),
+			self.best_queued_number,
+			1,
+			MAX_DOWNLOAD_AHEAD,
+		)?;
+		let len = (range.end - range.start).saturated_into::<u32>();
+		if let Some(rate_limit) = &mut self.rate_limit {
+			rate_limit.requested = rate_limit.requested.saturating_add(len);
+		}
+		if let Some(metrics) = &self.metrics {
+			metrics.blocks_requested.inc_by(len.into());
+		}
+
+		// The end is not part of the range.
+		let last = range.end.saturating_sub(One::one());
+		let request = BlockRequest::<B> {
+			id: 0,
+			fields: attrs,
+			from: FromBlock::Number(last),
+			direction: Direction::Descending,
+			max: Some(len),
+		};
+		Some((range, request))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::PeerSyncState;
+	use std::num::NonZeroU32;
+	use substrate_test_runtime_client::runtime::Block;
+
+	fn peer(best_number: u64) -> PeerSync<Block> {
+		PeerSync {
+			peer_id: PeerId::random(),
+			common_number: 0,
+			best_hash: Default::default(),
+			best_number,
+			state: PeerSyncState::Available,
+		}
+	}
+
+	#[test]
+	fn target_is_clamped_to_gap() {
+		let config = GapSyncConfig { target: Some(1000), ..Default::default() };
+		let gap = GapSync::<Block>::new(10, 100, &config, None).unwrap();
+		assert_eq!(gap.progress().target, 100);
+
+		let config = GapSyncConfig { target: Some(50), ..Default::default() };
+		let gap = GapSync::<Block>::new(10, 100, &config, None).unwrap();
+		assert_eq!(
+			gap.progress(),
+			GapSyncProgress { start: 10, best_queued: 9, best_imported: 9, target: 50 },
+		);
+
+		let config = GapSyncConfig { target: Some(5), ..Default::default() };
+		assert!(GapSync::<Block>::new(10, 100, &config, None).is_none());
+	}
+
+	#[test]
+	fn disabled_gap_sync_downloads_nothing() {
+		let config = GapSyncConfig { enabled: false, ..Default::default() };
+		assert!(GapSync::<Block>::new(10, 100, &config, None).is_none());
+	}
+
+	#[test]
+	fn completes_at_target() {
+		let config = GapSyncConfig { target: Some(20), ..Default::default() };
+		let mut gap = GapSync::<Block>::new(10, 100, &config, None).unwrap();
+
+		gap.on_block_queued(20);
+		gap.on_block_queued(21);
+		assert_eq!(gap.best_queued_number(), 20);
+		assert!(!gap.on_block_imported(19));
+		assert!(gap.on_block_imported(20));
+		assert_eq!(gap.progress().best_imported, 20);
+	}
+
+	#[test]
+	fn requests_are_rate_limited() {
+		let config =
+			GapSyncConfig { max_blocks_per_second: NonZeroU32::new(10), ..Default::default() };
+		let mut gap = GapSync::<Block>::new(1, 100, &config, None).unwrap();
+		let peer = peer(100);
+
+		let (range, request) =
+			gap.block_request(&peer.peer_id, &peer, BlockAttributes::HEADER, 64).unwrap();
+		assert_eq!(range, 1..11);
+		assert_eq!(request.max, Some(10));
+		assert!(gap
+			.block_request(&PeerId::random(), &peer, BlockAttributes::HEADER, 64)
+			.is_none());
+
+		let rate_limit = gap.rate_limit.as_mut().unwrap();
+		let now = rate_limit.window_start + RATE_LIMIT_WINDOW;
+		assert_eq!(rate_limit.allowance(now), 10);
+	}
+}
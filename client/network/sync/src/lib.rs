@@ -30,6 +30,7 @@
 
 use crate::{
 	blocks::BlockCollection,
+	gap::{GapSync, GapSyncMetrics},
 	schema::v1::{StateRequest, StateResponse},
 	state::StateSync,
 	warp::{WarpProofImportResult, WarpSync},
@@ -64,10 +65,10 @@ use sc_network_common::{
 			BlockResponse, Direction, FromBlock,
 		},
 		warp::{EncodedProof, WarpProofRequest, WarpSyncParams, WarpSyncPhase, WarpSyncProgress},
-		BadPeer, ChainSync as ChainSyncT, ImportResult, Metrics, OnBlockData, OnBlockJustification,
-		OnStateData, OpaqueBlockRequest, OpaqueBlockResponse, OpaqueStateRequest,
-		OpaqueStateResponse, PeerInfo, PeerRequest, PollBlockAnnounceValidation, SyncMode,
-		SyncState, SyncStatus,
+		BadPeer, ChainSync as ChainSyncT, CheckpointSyncConfig, GapSyncConfig, ImportResult,
+		Metrics, OnBlockData, OnBlockJustification, OnStateData, OpaqueBlockRequest,
+		OpaqueBlockResponse, OpaqueStateRequest, OpaqueStateResponse, PeerInfo, PeerRequest,
+		PollBlockAnnounceValidation, SyncMode, SyncState, SyncStatus,
 	},
 };
 use sp_arithmetic::traits::Saturating;
@@ -95,6 +96,7 @@ use std::{
 pub use service::chain_sync::SyncingService;
 
 mod extra_requests;
+mod gap;
 mod schema;
 
 pub mod block_request_handler;
@@ -242,6 +244,7 @@ impl Default for AllowedRequests {
 struct SyncingMetrics {
 	pub import_queue_blocks_submitted: Counter<U64>,
 	pub import_queue_justifications_submitted: Counter<U64>,
+	pub gap_sync: GapSyncMetrics,
 }
 
 impl SyncingMetrics {
@@ -261,16 +264,11 @@ impl SyncingMetrics {
 				)?,
 				registry,
 			)?,
+			gap_sync: GapSyncMetrics::register(registry)?,
 		})
 	}
 }
 
-struct GapSync<B: BlockT> {
-	blocks: BlockCollection<B>,
-	best_queued_number: NumberFor<B>,
-	target: NumberFor<B>,
-}
-
 type PendingResponse<B> = Pin<
 	Box<
 		dyn Future<
@@ -333,6 +331,10 @@ pub struct ChainSync<B: BlockT, Client> {
 	import_existing: bool,
 	/// Gap download process.
 	gap_sync: Option<GapSync<B>>,
+	/// Gap download configuration.
+	gap_sync_config: GapSyncConfig,
+	/// Trusted block to start from in [`SyncMode::Checkpoint`].
+	checkpoint: Option<CheckpointSyncConfig>,
 	/// Handle for communicating with `NetworkService`
 	network_service: service::network::NetworkServiceHandle,
 	/// Protocol name used for block announcements
@@ -512,7 +514,7 @@ where
 
 		let warp_sync_progress = match (&self.warp_sync, &self.mode, &self.gap_sync) {
 			(_, _, Some(gap_sync)) => Some(WarpSyncProgress {
-				phase: WarpSyncPhase::DownloadingBlocks(gap_sync.best_queued_number),
+				phase: WarpSyncPhase::DownloadingBlocks(gap_sync.best_queued_number()),
 				total_bytes: 0,
 			}),
 			(None, SyncMode::Warp, _) => Some(WarpSyncProgress {
@@ -533,6 +535,7 @@ where
 			queued_blocks: self.queue_blocks.len() as u32,
 			state_sync: self.state_sync.as_ref().map(|s| s.progress()),
 			warp_sync: warp_sync_progress,
+			gap_sync: self.gap_sync.as_ref().map(|s| s.progress()),
 		}
 	}
 
@@ -775,16 +778,15 @@ where
 					PeerSyncState::DownloadingGap(_) => {
 						peer.state = PeerSyncState::Available;
 						if let Some(gap_sync) = &mut self.gap_sync {
-							gap_sync.blocks.clear_peer_download(who);
+							gap_sync.blocks().clear_peer_download(who);
 							if let Some(start_block) =
 								validate_blocks::<B>(&blocks, who, Some(request))?
 							{
-								gap_sync.blocks.insert(start_block, blocks, *who);
+								gap_sync.blocks().insert(start_block, blocks, *who);
 							}
 							gap = true;
 							let blocks: Vec<_> = gap_sync
-								.blocks
-								.ready_blocks(gap_sync.best_queued_number + One::one())
+								.ready_blocks()
 								.into_iter()
 								.map(|block_data| {
 									let justifications =
@@ -807,7 +809,7 @@ where
 									}
 								})
 								.collect();
-							debug!(target: "sync", "Drained {} gap blocks from {}", blocks.len(), gap_sync.best_queued_number);
+							debug!(target: "sync", "Drained {} gap blocks from {}", blocks.len(), gap_sync.best_queued_number());
 							blocks
 						} else {
 							debug!(target: "sync", "Unexpected gap block response from {}", who);
@@ -1226,7 +1228,7 @@ where
 	fn peer_disconnected(&mut self, who: &PeerId) {
 		self.blocks.clear_peer_download(who);
 		if let Some(gap_sync) = &mut self.gap_sync {
-			gap_sync.blocks.clear_peer_download(who)
+			gap_sync.blocks().clear_peer_download(who)
 		}
 		self.peers.remove(who);
 		self.pending_responses.remove(who);
@@ -1399,6 +1401,8 @@ where
 		max_parallel_downloads: u32,
 		max_blocks_per_request: u32,
 		warp_sync_params: Option<WarpSyncParams<B>>,
+		gap_sync_config: GapSyncConfig,
+		checkpoint: Option<CheckpointSyncConfig>,
 		metrics_registry: Option<&Registry>,
 		network_service: service::network::NetworkServiceHandle,
 		import_queue: Box<dyn ImportQueueService<B>>,
@@ -1440,6 +1444,8 @@ where
 			warp_sync: None,
 			import_existing: false,
 			gap_sync: None,
+			gap_sync_config,
+			checkpoint,
 			network_service,
 			block_request_protocol_name,
 			state_request_protocol_name,
@@ -1486,7 +1492,9 @@ where
 		match self.mode {
 			SyncMode::Full =>
 				BlockAttributes::HEADER | BlockAttributes::JUSTIFICATION | BlockAttributes::BODY,
-			SyncMode::LightState { storage_chain_mode: false, .. } | SyncMode::Warp =>
+			SyncMode::LightState { storage_chain_mode: false, .. } |
+			SyncMode::Warp |
+			SyncMode::Checkpoint =>
 				BlockAttributes::HEADER | BlockAttributes::JUSTIFICATION | BlockAttributes::BODY,
 			SyncMode::LightState { storage_chain_mode: true, .. } =>
 				BlockAttributes::HEADER |
@@ -1500,6 +1508,7 @@ where
 			SyncMode::Full => false,
 			SyncMode::LightState { .. } => true,
 			SyncMode::Warp => true,
+			SyncMode::Checkpoint => true,
 		}
 	}
 
@@ -1556,9 +1565,7 @@ where
 			trace!(target: "sync", "Completed fork sync {:?}", hash);
 		}
 		if let Some(gap_sync) = &mut self.gap_sync {
-			if number > gap_sync.best_queued_number && number <= gap_sync.target {
-				gap_sync.best_queued_number = number;
-			}
+			gap_sync.on_block_queued(number);
 		}
 		if number > self.best_queued_number {
 			self.best_queued_number = number;
@@ -1818,6 +1825,13 @@ where
 			);
 			self.mode = SyncMode::Full;
 		}
+		if matches!(self.mode, SyncMode::Checkpoint) && info.finalized_state.is_some() {
+			warn!(
+				target: "sync",
+				"Can't use checkpoint sync mode with a partially synced database. Reverting to full sync mode."
+			);
+			self.mode = SyncMode::Full;
+		}
 		self.import_existing = false;
 		self.best_queued_hash = info.best_hash;
 		self.best_queued_number = info.best_number;
@@ -1839,12 +1853,20 @@ where
 		}
 
 		if let Some((start, end)) = info.block_gap {
-			debug!(target: "sync", "Starting gap sync #{} - #{}", start, end);
-			self.gap_sync = Some(GapSync {
-				best_queued_number: start - One::one(),
-				target: end,
-				blocks: BlockCollection::new(),
-			});
+			self.gap_sync = GapSync::new(
+				start,
+				end,
+				&self.gap_sync_config,
+				self.metrics.as_ref().map(|metrics| metrics.gap_sync.clone()),
+			);
+		}
+		if self.mode == SyncMode::Checkpoint && self.warp_sync.is_none() {
+			let checkpoint = self.checkpoint.as_ref().ok_or_else(|| {
+				ClientError::Backend("Checkpoint sync mode requires a sync checkpoint".into())
+			})?;
+			let target = checkpoint_target::<B>(checkpoint)?;
+			debug!(target: "sync", "Starting checkpoint sync at #{}", target.number());
+			self.warp_sync = Some(WarpSync::new_with_target(self.client.clone(), target));
 		}
 		trace!(target: "sync", "Restarted sync at #{} ({:?})", self.best_queued_number, self.best_queued_hash);
 		Ok(())
@@ -2336,7 +2358,7 @@ where
 	}
 
 	fn block_requests(&mut self) -> Vec<(PeerId, BlockRequest<B>)> {
-		if self.mode == SyncMode::Warp {
+		if matches!(self.mode, SyncMode::Warp | SyncMode::Checkpoint) {
 			return self
 				.warp_target_block_request()
 				.map_or_else(|| Vec::new(), |req| Vec::from([req]))
@@ -2433,17 +2455,10 @@ where
 					trace!(target: "sync", "Downloading fork {:?} from {}", hash, id);
 					peer.state = PeerSyncState::DownloadingStale(hash);
 					Some((id, req))
-				} else if let Some((range, req)) = gap_sync.as_mut().and_then(|sync| {
-					peer_gap_block_request(
-						&id,
-						peer,
-						&mut sync.blocks,
-						attrs,
-						sync.target,
-						sync.best_queued_number,
-						max_blocks_per_request,
-					)
-				}) {
+				} else if let Some((range, req)) = gap_sync
+					.as_mut()
+					.and_then(|sync| sync.block_request(&id, peer, attrs, max_blocks_per_request))
+				{
 					peer.state = PeerSyncState::DownloadingGap(range.start);
 					trace!(
 						target: "sync",
@@ -2677,7 +2692,7 @@ where
 			self.queue_blocks.remove(hash);
 			self.blocks.clear_queued(hash);
 			if let Some(gap_sync) = &mut self.gap_sync {
-				gap_sync.blocks.clear_queued(hash);
+				gap_sync.blocks().clear_queued(hash);
 			}
 		}
 		for (result, hash) in results {
@@ -2746,7 +2761,7 @@ where
 						output.extend(self.restart());
 					}
 					let gap_sync_complete =
-						self.gap_sync.as_ref().map_or(false, |s| s.target == number);
+						self.gap_sync.as_mut().map_or(false, |s| s.on_block_imported(number));
 					if gap_sync_complete {
 						info!(
 							target: "sync",
@@ -2818,6 +2833,33 @@ fn legacy_justification_mapping(
 	justification.map(|just| (*b"FRNK", just).into())
 }
 
+/// Decode the trusted block header of [`SyncMode::Checkpoint`], making sure it matches the hash
+/// and state root of the checkpoint.
+fn checkpoint_target<B: BlockT>(
+	checkpoint: &CheckpointSyncConfig,
+) -> Result<B::Header, ClientError> {
+	let CheckpointSyncConfig { hash, header, state_root } = checkpoint;
+	let header = B::Header::decode_all(&mut &header[..]).map_err(|e| {
+		ClientError::Backend(format!("Invalid sync checkpoint, failed to decode header: {e}"))
+	})?;
+
+	if header.hash().as_ref() != &hash[..] {
+		return Err(ClientError::Backend(format!(
+			"Invalid sync checkpoint, header hash {:?} doesn't match the checkpoint hash",
+			header.hash(),
+		)))
+	}
+
+	if header.state_root().as_ref() != &state_root[..] {
+		return Err(ClientError::Backend(format!(
+			"Invalid sync checkpoint, header state root {:?} doesn't match the checkpoint state root",
+			header.state_root(),
+		)))
+	}
+
+	Ok(header)
+}
+
 /// Request the ancestry for a block. Sends a request for header and justification for the given
 /// block number. Used during ancestry search.
 fn ancestry_request<B: BlockT>(block: NumberFor<B>) -> BlockRequest<B> {
@@ -2948,39 +2990,6 @@ fn peer_block_request<B: BlockT>(
 	Some((range, request))
 }
 
-/// Get a new block request for the peer if any.
-fn peer_gap_block_request<B: BlockT>(
-	id: &PeerId,
-	peer: &PeerSync<B>,
-	blocks: &mut BlockCollection<B>,
-	attrs: BlockAttributes,
-	target: NumberFor<B>,
-	common_number: NumberFor<B>,
-	max_blocks_per_request: u32,
-) -> Option<(Range<NumberFor<B>>, BlockRequest<B>)> {
-	let range = blocks.needed_blocks(
-		*id,
-		max_blocks_per_request,
-		std::cmp::min(peer.best_number, target),
-		common_number,
-		1,
-		MAX_DOWNLOAD_AHEAD,
-	)?;
-
-	// The end is not part of the range.
-	let last = range.end.saturating_sub(One::one());
-	let from = FromBlock::Number(last);
-
-	let request = BlockRequest::<B> {
-		id: 0,
-		fields: attrs,
-		from,
-		direction: Direction::Descending,
-		max: Some((range.end - range.start).saturated_into::<u32>()),
-	};
-	Some((range, request))
-}
-
 /// Get pending fork sync targets for a peer.
 fn fork_sync_request<B: BlockT>(
 	id: &PeerId,
@@ -3199,6 +3208,8 @@ mod test {
 			1,
 			64,
 			None,
+			GapSyncConfig::default(),
+			None,
 			None,
 			chain_sync_network_handle,
 			import_queue,
@@ -3266,6 +3277,8 @@ mod test {
 			1,
 			64,
 			None,
+			GapSyncConfig::default(),
+			None,
 			None,
 			chain_sync_network_handle,
 			import_queue,
@@ -3448,6 +3461,8 @@ mod test {
 			5,
 			64,
 			None,
+			GapSyncConfig::default(),
+			None,
 			None,
 			chain_sync_network_handle,
 			import_queue,
@@ -3575,6 +3590,8 @@ mod test {
 			5,
 			64,
 			None,
+			GapSyncConfig::default(),
+			None,
 			None,
 			chain_sync_network_handle,
 			import_queue,
@@ -3733,6 +3750,8 @@ mod test {
 			5,
 			64,
 			None,
+			GapSyncConfig::default(),
+			None,
 			None,
 			chain_sync_network_handle,
 			import_queue,
@@ -3876,6 +3895,8 @@ mod test {
 			5,
 			64,
 			None,
+			GapSyncConfig::default(),
+			None,
 			None,
 			chain_sync_network_handle,
 			import_queue,
@@ -4021,6 +4042,8 @@ mod test {
 			1,
 			64,
 			None,
+			GapSyncConfig::default(),
+			None,
 			None,
 			chain_sync_network_handle,
 			import_queue,
@@ -4067,6 +4090,8 @@ mod test {
 			1,
 			64,
 			None,
+			GapSyncConfig::default(),
+			None,
 			None,
 			chain_sync_network_handle,
 			import_queue,
@@ -4121,6 +4146,8 @@ mod test {
 			1,
 			64,
 			None,
+			GapSyncConfig::default(),
+			None,
 			None,
 			chain_sync_network_handle,
 			import_queue,
@@ -4188,4 +4215,78 @@ mod test {
 		sync.peer_disconnected(&peers[1]);
 		assert_eq!(sync.pending_responses.len(), 0);
 	}
+
+	fn checkpoint_sync(
+		client: Arc<TestClient>,
+		header: &Header,
+		state_root: Hash,
+	) -> Result<ChainSync<Block, TestClient>, ClientError> {
+		let import_queue = Box::new(sc_consensus::import_queue::mock::MockImportQueueHandle::new());
+		let (_chain_sync_network_provider, chain_sync_network_handle) =
+			NetworkServiceProvider::new();
+
+		ChainSync::new(
+			SyncMode::Checkpoint,
+			client,
+			ProtocolId::from("test-protocol-name"),
+			&Some(String::from("test-fork-id")),
+			Roles::from(&Role::Full),
+			Box::new(DefaultBlockAnnounceValidator),
+			1,
+			64,
+			None,
+			GapSyncConfig::default(),
+			Some(CheckpointSyncConfig {
+				hash: header.hash().encode(),
+				header: header.encode(),
+				state_root: state_root.encode(),
+			}),
+			None,
+			chain_sync_network_handle,
+			import_queue,
+			ProtocolName::from("block-request"),
+			ProtocolName::from("state-request"),
+			None,
+		)
+		.map(|(sync, _)| sync)
+	}
+
+	#[test]
+	fn checkpoint_sync_downloads_trusted_block() {
+		let mut source = Arc::new(TestClientBuilder::new().build());
+		for _ in 0..3 {
+			let block = source.new_block(Default::default()).unwrap().build().unwrap().block;
+			block_on(source.import(BlockOrigin::Own, block)).unwrap();
+		}
+		let header = source.header(source.info().best_hash).unwrap().unwrap();
+
+		let client = Arc::new(TestClientBuilder::new().set_no_genesis().build());
+		let mut sync = checkpoint_sync(client, &header, header.state_root).unwrap();
+
+		assert_eq!(
+			sync.status().warp_sync.map(|progress| progress.phase),
+			Some(WarpSyncPhase::DownloadingTargetBlock),
+		);
+
+		// The trusted block is requested from a peer that has it, instead of syncing from genesis.
+		let peer_id = PeerId::random();
+		sync.new_peer(peer_id, header.hash(), header.number).unwrap();
+
+		let requests = sync.block_requests();
+		assert_eq!(requests.len(), 1);
+		assert_eq!(requests[0].0, peer_id);
+		assert_eq!(requests[0].1.from, FromBlock::Hash(header.hash()));
+		assert_eq!(sync.peers[&peer_id].state, PeerSyncState::DownloadingWarpTargetBlock);
+	}
+
+	#[test]
+	fn checkpoint_sync_rejects_mismatching_state_root() {
+		let mut source = Arc::new(TestClientBuilder::new().build());
+		let block = source.new_block(Default::default()).unwrap().build().unwrap().block;
+		block_on(source.import(BlockOrigin::Own, block.clone())).unwrap();
+
+		let client = Arc::new(TestClientBuilder::new().set_no_genesis().build());
+
+		assert!(checkpoint_sync(client, &block.header, Hash::random()).is_err());
+	}
 }
@@ -103,6 +103,13 @@ where
 		}
 	}
 
+	/// Create a new instance starting from a trusted target block.
+	///
+	/// Warp proofs are not downloaded, only the body and the state of the target block.
+	pub fn new_with_target(client: Arc<Client>, target: B::Header) -> Self {
+		Self { client, phase: Phase::TargetBlock(target), total_proof_bytes: 0 }
+	}
+
 	/// Poll to make progress.
 	///
 	/// This only makes progress when `phase = Phase::PendingTargetBlock` and the pending block was
@@ -50,8 +50,8 @@ use sc_consensus::{
 };
 use sc_network::{
 	config::{
-		FullNetworkConfiguration, MultiaddrWithPeerId, NetworkConfiguration, NonDefaultSetConfig,
-		NonReservedPeerMode, ProtocolId, Role, SyncMode, TransportConfig,
+		CheckpointSyncConfig, FullNetworkConfiguration, MultiaddrWithPeerId, NetworkConfiguration,
+		NonDefaultSetConfig, NonReservedPeerMode, ProtocolId, Role, SyncMode, TransportConfig,
 	},
 	request_responses::ProtocolConfig as RequestResponseConfig,
 	types::ProtocolName,
@@ -702,6 +702,8 @@ pub struct FullPeerConfig {
 	pub is_authority: bool,
 	/// Syncing mode
 	pub sync_mode: SyncMode,
+	/// Trusted block to start from with [`SyncMode::Checkpoint`].
+	pub sync_checkpoint: Option<CheckpointSyncConfig>,
 	/// Extra genesis storage.
 	pub extra_storage: Option<sp_core::storage::Storage>,
 	/// Enable transaction indexing.
@@ -770,7 +772,10 @@ where
 			*genesis_extra_storage = storage;
 		}
 
-		if matches!(config.sync_mode, SyncMode::LightState { .. } | SyncMode::Warp) {
+		if matches!(
+			config.sync_mode,
+			SyncMode::LightState { .. } | SyncMode::Warp | SyncMode::Checkpoint
+		) {
 			test_client_builder = test_client_builder.set_no_genesis();
 		}
 		let backend = test_client_builder.backend();
@@ -797,6 +802,7 @@ where
 		let mut network_config =
 			NetworkConfiguration::new("test-node", "test-client", Default::default(), None);
 		network_config.sync_mode = config.sync_mode;
+		network_config.sync_checkpoint = config.sync_checkpoint;
 		network_config.transport = TransportConfig::MemoryOnly;
 		network_config.listen_addresses = vec![listen_addr.clone()];
 		network_config.allow_non_globals_in_dht = true;
@@ -1290,6 +1290,38 @@ async fn warp_sync_to_target_block() {
 	.await;
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn checkpoint_sync() {
+	sp_tracing::try_init_simple();
+	let mut net = TestNet::new(0);
+	// Create 3 synced peers and 1 peer starting from a checkpoint.
+	net.add_full_peer_with_config(Default::default());
+	net.add_full_peer_with_config(Default::default());
+	net.add_full_peer_with_config(Default::default());
+
+	let blocks = net.peer(0).push_blocks(64, false);
+	net.peer(1).push_blocks(64, false);
+	net.peer(2).push_blocks(64, false);
+
+	let checkpoint = net.peer(0).client.header(blocks[31]).unwrap().unwrap();
+
+	net.add_full_peer_with_config(FullPeerConfig {
+		sync_mode: SyncMode::Checkpoint,
+		sync_checkpoint: Some(CheckpointSyncConfig {
+			hash: checkpoint.hash().encode(),
+			header: checkpoint.encode(),
+			state_root: checkpoint.state_root.encode(),
+		}),
+		..Default::default()
+	});
+
+	// The state of the checkpoint is downloaded and imported, the blocks after it are synced.
+	net.run_until_sync().await;
+	assert!(net.peer(3).client().has_state_at(&BlockId::Number(32)));
+	assert!(net.peer(3).client().has_state_at(&BlockId::Number(64)));
+	assert!(blocks[32..].iter().all(|b| net.peer(3).has_body(*b)));
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn syncs_huge_blocks() {
 	use sp_core::storage::well_known_keys::HEAP_PAGES;
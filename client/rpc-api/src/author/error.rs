@@ -54,6 +54,12 @@ pub enum Error {
 	/// Call to an unsafe RPC was denied.
 	#[error(transparent)]
 	UnsafeRpcCalled(#[from] crate::policy::UnsafeRpcError),
+	/// Extrinsics that were not submitted locally can't be cancelled.
+	#[error("Extrinsics were not submitted locally: {}", .0)]
+	NotLocal(String),
+	/// The transaction pool doesn't support cancelling extrinsics.
+	#[error("The transaction pool doesn't support cancelling extrinsics")]
+	CancelUnsupported,
 }
 
 /// Base code for all authorship errors.
@@ -62,6 +68,8 @@ const BASE_ERROR: i32 = crate::error::base::AUTHOR;
 const BAD_FORMAT: i32 = BASE_ERROR + 1;
 /// Error during transaction verification in runtime.
 const VERIFICATION_ERROR: i32 = BASE_ERROR + 2;
+/// Extrinsic can't be cancelled, since it was not submitted locally.
+const NOT_LOCAL: i32 = BASE_ERROR + 3;
 
 /// Pool rejected the transaction as invalid
 const POOL_INVALID_TX: i32 = BASE_ERROR + 10;
@@ -136,9 +144,9 @@ impl From<Error> for JsonRpseeError {
 				"Transaction Already Imported",
 				Some(format!("{:?}", hash)),
 			)),
-			Error::Pool(PoolError::TooLowPriority { old, new }) => CallError::Custom(ErrorObject::owned(
+			Error::Pool(PoolError::TooLowPriority { old, new, required }) => CallError::Custom(ErrorObject::owned(
 				POOL_TOO_LOW_PRIORITY,
-				format!("Priority is too low: ({} vs {})", old, new),
+				format!("Priority is too low: ({} vs {}, at least {} required)", old, new, required),
 				Some("The transaction has too low priority to replace another transaction already in the pool.")
 			)),
 			Error::Pool(PoolError::CycleDetected) =>
@@ -176,6 +184,11 @@ impl From<Error> for JsonRpseeError {
 					None::<()>,
 				))
 			},
+			Error::NotLocal(hashes) => CallError::Custom(ErrorObject::owned(
+				NOT_LOCAL,
+				"Only locally submitted extrinsics can be cancelled",
+				Some(hashes),
+			)),
 			Error::UnsafeRpcCalled(e) => e.into(),
 			e => CallError::Failed(e.into()),
 		}.into()
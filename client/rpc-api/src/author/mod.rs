@@ -65,6 +65,20 @@ pub trait AuthorApi<Hash, BlockHash> {
 		bytes_or_hash: Vec<hash::ExtrinsicOrHash<Hash>>,
 	) -> RpcResult<Vec<Hash>>;
 
+	/// Cancel given extrinsic, removing it and the extrinsics depending on it from the pool.
+	///
+	/// Only extrinsics submitted locally by the node, e.g. by offchain workers, can be cancelled.
+	/// If any of the given extrinsics came from elsewhere, nothing is cancelled.
+	///
+	/// Unlike `author_removeExtrinsic` the extrinsic is not banned, so it can be resubmitted
+	/// (e.g. with a higher tip) right away. Watchers of the cancelled extrinsics are notified
+	/// that they were dropped.
+	#[method(name = "author_cancelExtrinsic")]
+	fn cancel_extrinsic(
+		&self,
+		bytes_or_hash: Vec<hash::ExtrinsicOrHash<Hash>>,
+	) -> RpcResult<Vec<Hash>>;
+
 	/// Submit an extrinsic to watch.
 	///
 	/// See [`TransactionStatus`](sc_transaction_pool_api::TransactionStatus) for details on
@@ -19,10 +19,45 @@
 //! Substrate Statement Store RPC API.
 
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use serde::{Deserialize, Serialize};
 use sp_core::Bytes;
 
 pub mod error;
 
+/// Topic filter for statement subscriptions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TopicFilter {
+	/// Match statements that include all of the topics.
+	MatchAll(Vec<[u8; 32]>),
+	/// Match statements that include any of the topics. An empty list matches all statements.
+	MatchAny(Vec<[u8; 32]>),
+}
+
+/// Statement store query.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementQuery {
+	/// Only statements of this account.
+	pub account: Option<[u8; 32]>,
+	/// Only statements in this channel.
+	pub channel: Option<[u8; 32]>,
+	/// Only statements with at least this priority.
+	pub min_priority: Option<u32>,
+	/// Only statements with at most this priority.
+	pub max_priority: Option<u32>,
+}
+
+/// A page of statements returned by `statement_query`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementPage {
+	/// SCALE-encoded statements, ordered by hash.
+	pub statements: Vec<Bytes>,
+	/// Cursor to fetch the next page. `None` if this is the last page.
+	pub next_cursor: Option<[u8; 32]>,
+}
+
 /// Substrate statement RPC API
 #[rpc(client, server)]
 pub trait StatementApi {
@@ -50,6 +85,18 @@ pub trait StatementApi {
 		dest: [u8; 32],
 	) -> RpcResult<Vec<Bytes>>;
 
+	/// Return a page of statements matching the query, SCALE-encoded.
+	///
+	/// Statements are ordered by hash. Only statements with a hash greater than `cursor` are
+	/// returned. The number of returned statements is capped by the node.
+	#[method(name = "statement_query")]
+	fn query(
+		&self,
+		query: StatementQuery,
+		cursor: Option<[u8; 32]>,
+		limit: Option<u32>,
+	) -> RpcResult<StatementPage>;
+
 	/// Submit a pre-encoded statement.
 	#[method(name = "statement_submit")]
 	fn submit(&self, encoded: Bytes) -> RpcResult<()>;
@@ -57,4 +104,17 @@ pub trait StatementApi {
 	/// Remove a statement from the store.
 	#[method(name = "statement_remove")]
 	fn remove(&self, statement_hash: [u8; 32]) -> RpcResult<()>;
+
+	/// Subscribe to statements matching the topic filter, SCALE-encoded.
+	///
+	/// Only statements whose decryption key is identified as `dest` are reported. When `dest` is
+	/// not provided, only statements without a `DecryptionKey` field are reported. All known
+	/// matching statements are reported first, followed by the statements accepted by the store
+	/// afterwards.
+	#[subscription(
+		name = "statement_subscribe" => "statement_statement",
+		unsubscribe = "statement_unsubscribe",
+		item = Bytes,
+	)]
+	fn subscribe(&self, topic_filter: TopicFilter, dest: Option<[u8; 32]>);
 }
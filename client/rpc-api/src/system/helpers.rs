@@ -71,6 +71,22 @@ pub struct PeerInfo<Hash, Number> {
 	pub best_number: Number,
 }
 
+/// Reputation of a peer known to the node
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerReputation {
+	/// Peer ID
+	pub peer_id: String,
+	/// Reputation value
+	pub reputation: i32,
+	/// Whether the peer is banned
+	pub banned: bool,
+	/// Reason the peer was banned for, if it is banned
+	pub ban_reason: Option<String>,
+	/// Addresses the peer was last reached at, most recent first
+	pub addresses: Vec<String>,
+}
+
 /// The role the node is running as
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub enum NodeRole {
@@ -90,6 +106,21 @@ pub struct SyncState<Number> {
 	pub current_block: Number,
 	/// Height of the highest block in the network.
 	pub highest_block: Number,
+	/// Progress of the block history download after warp or checkpoint sync, if any.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub gap_sync: Option<GapSyncState<Number>>,
+}
+
+/// Progress of the block history download after warp or checkpoint sync.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GapSyncState<Number> {
+	/// Height of the first block of the history being downloaded.
+	pub starting_block: Number,
+	/// Height of the highest block of the history imported.
+	pub current_block: Number,
+	/// Height of the last block of the history to download.
+	pub target_block: Number,
 }
 
 #[cfg(test)]
@@ -130,6 +161,7 @@ mod tests {
 				starting_block: 12u32,
 				current_block: 50u32,
 				highest_block: 128u32,
+				gap_sync: None,
 			})
 			.unwrap(),
 			r#"{"startingBlock":12,"currentBlock":50,"highestBlock":128}"#,
@@ -140,9 +172,25 @@ mod tests {
 				starting_block: 12u32,
 				current_block: 50u32,
 				highest_block: 50u32,
+				gap_sync: None,
 			})
 			.unwrap(),
 			r#"{"startingBlock":12,"currentBlock":50,"highestBlock":50}"#,
 		);
+
+		assert_eq!(
+			::serde_json::to_string(&SyncState {
+				starting_block: 12u32,
+				current_block: 50u32,
+				highest_block: 50u32,
+				gap_sync: Some(GapSyncState {
+					starting_block: 1u32,
+					current_block: 5u32,
+					target_block: 10u32,
+				}),
+			})
+			.unwrap(),
+			r#"{"startingBlock":12,"currentBlock":50,"highestBlock":50,"gapSync":{"startingBlock":1,"currentBlock":5,"targetBlock":10}}"#,
+		);
 	}
 }
@@ -23,7 +23,9 @@ use jsonrpsee::{
 	proc_macros::rpc,
 };
 
-pub use self::helpers::{Health, NodeRole, PeerInfo, SyncState, SystemInfo};
+pub use self::helpers::{
+	GapSyncState, Health, NodeRole, PeerInfo, PeerReputation, SyncState, SystemInfo,
+};
 
 pub mod error;
 pub mod helpers;
@@ -74,6 +76,11 @@ pub trait SystemApi<Hash, Number> {
 	#[method(name = "system_peers")]
 	async fn system_peers(&self) -> RpcResult<Vec<PeerInfo<Hash, Number>>>;
 
+	/// Returns the reputations of all peers known to the node, highest first, including the
+	/// reason of the ban for banned peers.
+	#[method(name = "system_peerReputations")]
+	async fn system_peer_reputations(&self) -> RpcResult<Vec<PeerReputation>>;
+
 	/// Returns current state of the network.
 	///
 	/// **Warning**: This API is not stable. Please do not programmatically interpret its output,
@@ -0,0 +1,149 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Implementation of the `archive_storage` method.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use sc_client_api::{Backend, ChildInfo, StorageKey, StorageProvider};
+use sp_core::storage::well_known_keys;
+use sp_runtime::traits::Block as BlockT;
+
+use crate::{
+	archive::event::ArchiveStorageResult,
+	chain_head::{
+		event::{StorageQuery, StorageQueryType, StorageResult, StorageResultType},
+		hex_string,
+	},
+};
+
+/// Generates the results of the `archive_storage` method.
+pub struct ArchiveStorage<Client, Block, BE> {
+	/// Substrate client.
+	client: Arc<Client>,
+	_phantom: PhantomData<(Block, BE)>,
+}
+
+impl<Client, Block, BE> ArchiveStorage<Client, Block, BE> {
+	/// Constructs a new [`ArchiveStorage`].
+	pub fn new(client: Arc<Client>) -> Self {
+		Self { client, _phantom: PhantomData }
+	}
+}
+
+/// Checks if the provided key (main or child key) is valid
+/// for queries.
+///
+/// Keys that are identical to `:child_storage:` or `:child_storage:default:`
+/// are not queryable.
+fn is_key_queryable(key: &[u8]) -> bool {
+	!well_known_keys::is_default_child_storage_key(key) &&
+		!well_known_keys::is_child_storage_key(key)
+}
+
+impl<Client, Block, BE> ArchiveStorage<Client, Block, BE>
+where
+	Block: BlockT + 'static,
+	BE: Backend<Block> + 'static,
+	Client: StorageProvider<Block, BE> + 'static,
+{
+	/// Fetch the value from storage.
+	fn query_storage_value(
+		&self,
+		hash: Block::Hash,
+		key: &StorageKey,
+		child_key: Option<&ChildInfo>,
+	) -> Result<Option<StorageResult<String>>, String> {
+		let result = if let Some(child_key) = child_key {
+			self.client.child_storage(hash, child_key, key)
+		} else {
+			self.client.storage(hash, key)
+		};
+
+		result
+			.map(|opt| {
+				opt.map(|storage_data| StorageResult::<String> {
+					key: hex_string(&key.0),
+					result: StorageResultType::Value(hex_string(&storage_data.0)),
+				})
+			})
+			.map_err(|error| error.to_string())
+	}
+
+	/// Fetch the hash of a value from storage.
+	fn query_storage_hash(
+		&self,
+		hash: Block::Hash,
+		key: &StorageKey,
+		child_key: Option<&ChildInfo>,
+	) -> Result<Option<StorageResult<String>>, String> {
+		let result = if let Some(child_key) = child_key {
+			self.client.child_storage_hash(hash, child_key, key)
+		} else {
+			self.client.storage_hash(hash, key)
+		};
+
+		result
+			.map(|opt| {
+				opt.map(|storage_data| StorageResult::<String> {
+					key: hex_string(&key.0),
+					result: StorageResultType::Hash(hex_string(&storage_data.as_ref())),
+				})
+			})
+			.map_err(|error| error.to_string())
+	}
+
+	/// Generate the result of the `archive_storage` method.
+	///
+	/// The first error encountered for any of the query items aborts the whole query.
+	pub fn handle_query(
+		&self,
+		hash: Block::Hash,
+		items: Vec<StorageQuery<StorageKey>>,
+		child_key: Option<ChildInfo>,
+	) -> ArchiveStorageResult {
+		if let Some(child_key) = child_key.as_ref() {
+			if !is_key_queryable(child_key.storage_key()) {
+				return ArchiveStorageResult::ok(Vec::new())
+			}
+		}
+
+		let mut storage_results = Vec::with_capacity(items.len());
+		for item in items {
+			if !is_key_queryable(&item.key.0) {
+				continue
+			}
+
+			let result = match item.queue_type {
+				StorageQueryType::Value =>
+					self.query_storage_value(hash, &item.key, child_key.as_ref()),
+				StorageQueryType::Hash =>
+					self.query_storage_hash(hash, &item.key, child_key.as_ref()),
+				_ => continue,
+			};
+
+			match result {
+				Ok(Some(storage_result)) => storage_results.push(storage_result),
+				Ok(None) => continue,
+				Err(error) => return ArchiveStorageResult::err(error),
+			}
+		}
+
+		ArchiveStorageResult::ok(storage_results)
+	}
+}
@@ -0,0 +1,134 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! The archive's event returned as json compatible object.
+
+use crate::chain_head::event::StorageResult;
+use serde::{Deserialize, Serialize};
+
+/// The result of an RPC method.
+///
+/// On success, the `value` field holds the hexadecimal-encoded result.
+/// On failure, the `error` field holds a human-readable reason.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MethodResult {
+	/// Whether the method succeeded.
+	pub success: bool,
+	/// The result of the method, present on success.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub value: Option<String>,
+	/// The reason of the failure, present on error.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+}
+
+impl MethodResult {
+	/// Construct a successful result.
+	pub fn ok(value: impl Into<String>) -> MethodResult {
+		MethodResult { success: true, value: Some(value.into()), error: None }
+	}
+
+	/// Construct an error result.
+	pub fn err(error: impl Into<String>) -> MethodResult {
+		MethodResult { success: false, value: None, error: Some(error.into()) }
+	}
+}
+
+/// The result of the `archive_storage` method.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveStorageResult {
+	/// Whether the storage queries succeeded.
+	pub success: bool,
+	/// The resulting items.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub result: Vec<StorageResult<String>>,
+	/// The reason of the failure, present on error.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+}
+
+impl ArchiveStorageResult {
+	/// Construct a successful result.
+	pub fn ok(result: Vec<StorageResult<String>>) -> ArchiveStorageResult {
+		ArchiveStorageResult { success: true, result, error: None }
+	}
+
+	/// Construct an error result.
+	pub fn err(error: impl Into<String>) -> ArchiveStorageResult {
+		ArchiveStorageResult { success: false, result: Vec::new(), error: Some(error.into()) }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::chain_head::event::StorageResultType;
+
+	#[test]
+	fn method_result_ok() {
+		let ok = MethodResult::ok("hello");
+
+		let ser = serde_json::to_string(&ok).unwrap();
+		let exp = r#"{"success":true,"value":"hello"}"#;
+		assert_eq!(ser, exp);
+
+		let ok_dec: MethodResult = serde_json::from_str(exp).unwrap();
+		assert_eq!(ok_dec, ok);
+	}
+
+	#[test]
+	fn method_result_error() {
+		let err = MethodResult::err("hello");
+
+		let ser = serde_json::to_string(&err).unwrap();
+		let exp = r#"{"success":false,"error":"hello"}"#;
+		assert_eq!(ser, exp);
+
+		let err_dec: MethodResult = serde_json::from_str(exp).unwrap();
+		assert_eq!(err_dec, err);
+	}
+
+	#[test]
+	fn storage_result_ok() {
+		let ok = ArchiveStorageResult::ok(vec![StorageResult {
+			key: "0x1".into(),
+			result: StorageResultType::Value("0x2".into()),
+		}]);
+
+		let ser = serde_json::to_string(&ok).unwrap();
+		let exp = r#"{"success":true,"result":[{"key":"0x1","value":"0x2"}]}"#;
+		assert_eq!(ser, exp);
+
+		let ok_dec: ArchiveStorageResult = serde_json::from_str(exp).unwrap();
+		assert_eq!(ok_dec, ok);
+	}
+
+	#[test]
+	fn storage_result_error() {
+		let err = ArchiveStorageResult::err("State already discarded");
+
+		let ser = serde_json::to_string(&err).unwrap();
+		let exp = r#"{"success":false,"error":"State already discarded"}"#;
+		assert_eq!(ser, exp);
+
+		let err_dec: ArchiveStorageResult = serde_json::from_str(exp).unwrap();
+		assert_eq!(err_dec, err);
+	}
+}
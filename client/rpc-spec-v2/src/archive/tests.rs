@@ -0,0 +1,411 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::chain_head::{
+	event::{StorageQuery, StorageQueryType, StorageResultType},
+	hex_string,
+};
+
+use super::{archive::Archive, *};
+
+use assert_matches::assert_matches;
+use codec::{Decode, Encode};
+use jsonrpsee::{
+	core::error::Error,
+	rpc_params,
+	types::{error::CallError, EmptyServerParams as EmptyParams},
+	RpcModule,
+};
+use sc_block_builder::BlockBuilderProvider;
+use sp_blockchain::HeaderBackend;
+use sp_consensus::BlockOrigin;
+use sp_core::{Blake2Hasher, Hasher};
+use sp_runtime::traits::Header as HeaderT;
+use std::sync::Arc;
+use substrate_test_runtime::Transfer;
+use substrate_test_runtime_client::{
+	prelude::*, runtime, Backend, BlockBuilderExt, Client, ClientBlockImportExt,
+};
+
+type Header = substrate_test_runtime_client::runtime::Header;
+type Block = substrate_test_runtime_client::runtime::Block;
+const CHAIN_GENESIS: [u8; 32] = [0; 32];
+const INVALID_HASH: [u8; 32] = [1; 32];
+const KEY: &[u8] = b":mock";
+const VALUE: &[u8] = b"hello world";
+
+type ArchiveRpc = RpcModule<Archive<Backend, Block, Client<Backend>>>;
+
+fn setup_api() -> (Arc<Client<Backend>>, ArchiveRpc) {
+	let builder = TestClientBuilder::new();
+	let backend = builder.backend();
+	let client = Arc::new(builder.build());
+
+	let api = Archive::new(client.clone(), backend, CHAIN_GENESIS).into_rpc();
+
+	(client, api)
+}
+
+#[tokio::test]
+async fn archive_genesis() {
+	let (_client, api) = setup_api();
+
+	let genesis: String =
+		api.call("archive_unstable_genesisHash", EmptyParams::new()).await.unwrap();
+	assert_eq!(genesis, hex_string(&CHAIN_GENESIS));
+}
+
+#[tokio::test]
+async fn archive_body() {
+	let (mut client, api) = setup_api();
+
+	// Invalid block hash.
+	let invalid_hash = hex_string(&INVALID_HASH);
+	let body: Option<Vec<String>> =
+		api.call("archive_unstable_body", [invalid_hash]).await.unwrap();
+	assert!(body.is_none());
+
+	// Import a new block with an extrinsic.
+	let mut builder = client.new_block(Default::default()).unwrap();
+	builder
+		.push_transfer(runtime::Transfer {
+			from: AccountKeyring::Alice.into(),
+			to: AccountKeyring::Ferdie.into(),
+			amount: 42,
+			nonce: 0,
+		})
+		.unwrap();
+	let block = builder.build().unwrap().block;
+	let block_hash = format!("{:?}", block.header.hash());
+	client.import(BlockOrigin::Own, block.clone()).await.unwrap();
+
+	let expected_tx = hex_string(&block.extrinsics[0].encode());
+
+	let body: Vec<String> = api.call("archive_unstable_body", [block_hash]).await.unwrap();
+	assert_eq!(vec![expected_tx], body);
+}
+
+#[tokio::test]
+async fn archive_header() {
+	let (mut client, api) = setup_api();
+
+	// Invalid block hash.
+	let invalid_hash = hex_string(&INVALID_HASH);
+	let header: Option<String> = api.call("archive_unstable_header", [invalid_hash]).await.unwrap();
+	assert!(header.is_none());
+
+	// Import a new block.
+	let block = client.new_block(Default::default()).unwrap().build().unwrap().block;
+	let block_hash = format!("{:?}", block.header.hash());
+	client.import(BlockOrigin::Own, block.clone()).await.unwrap();
+
+	let header: String = api.call("archive_unstable_header", [block_hash]).await.unwrap();
+	let bytes = array_bytes::hex2bytes(&header).unwrap();
+	let header: Header = Decode::decode(&mut &bytes[..]).unwrap();
+	assert_eq!(header, block.header);
+}
+
+#[tokio::test]
+async fn archive_finalized_height() {
+	let (mut client, api) = setup_api();
+
+	let client_height: u64 = client.info().finalized_number;
+
+	let height: u64 =
+		api.call("archive_unstable_finalizedHeight", EmptyParams::new()).await.unwrap();
+	assert_eq!(client_height, height);
+
+	// Import and finalize a new block.
+	let block = client.new_block(Default::default()).unwrap().build().unwrap().block;
+	let block_hash = block.header.hash();
+	client.import(BlockOrigin::Own, block.clone()).await.unwrap();
+
+	// The block is not finalized yet.
+	let height: u64 =
+		api.call("archive_unstable_finalizedHeight", EmptyParams::new()).await.unwrap();
+	assert_eq!(client_height, height);
+
+	client.finalize_block(block_hash, None).unwrap();
+
+	let height: u64 =
+		api.call("archive_unstable_finalizedHeight", EmptyParams::new()).await.unwrap();
+	assert_eq!(client_height + 1, height);
+}
+
+#[tokio::test]
+async fn archive_hash_by_height() {
+	let (mut client, api) = setup_api();
+
+	// Genesis height.
+	let hashes: Vec<String> = api.call("archive_unstable_hashByHeight", [0]).await.unwrap();
+	assert_eq!(hashes, vec![format!("{:?}", client.info().genesis_hash)]);
+
+	// Block tree:
+	// genesis -> block 1 -> block 2 -> block 3
+	//         -> block 1 -> block 4
+	//
+	//            ^^^ h = N
+	//                       ^^^ h = N + 1
+	//                                  ^^^ h = N + 2
+	let block_1 = client.new_block(Default::default()).unwrap().build().unwrap().block;
+	let block_1_hash = block_1.header.hash();
+	client.import(BlockOrigin::Own, block_1.clone()).await.unwrap();
+
+	let block_2 = client.new_block(Default::default()).unwrap().build().unwrap().block;
+	let block_2_hash = block_2.header.hash();
+	client.import(BlockOrigin::Own, block_2.clone()).await.unwrap();
+	let block_3 = client.new_block(Default::default()).unwrap().build().unwrap().block;
+	let block_3_hash = block_3.header.hash();
+	client.import(BlockOrigin::Own, block_3.clone()).await.unwrap();
+
+	// Import block 4 fork.
+	let mut block_builder = client.new_block_at(block_1_hash, Default::default(), false).unwrap();
+	// This push is required as otherwise block 4 has the same hash as block 2 and won't get
+	// imported
+	block_builder
+		.push_transfer(Transfer {
+			from: AccountKeyring::Alice.into(),
+			to: AccountKeyring::Ferdie.into(),
+			amount: 41,
+			nonce: 0,
+		})
+		.unwrap();
+	let block_4 = block_builder.build().unwrap().block;
+	let block_4_hash = block_4.header.hash();
+	client.import(BlockOrigin::Own, block_4.clone()).await.unwrap();
+
+	// Check finalized height.
+	let hashes: Vec<String> = api.call("archive_unstable_hashByHeight", [0]).await.unwrap();
+	assert_eq!(hashes, vec![format!("{:?}", client.info().genesis_hash)]);
+
+	// Test nonfinalized heights.
+	// Height N must include block 1.
+	let mut height = block_1.header.number;
+	let hashes: Vec<String> = api.call("archive_unstable_hashByHeight", [height]).await.unwrap();
+	assert_eq!(hashes, vec![format!("{:?}", block_1_hash)]);
+
+	// Height (N + 1) must include block 2 and 4.
+	height += 1;
+	let mut hashes: Vec<String> =
+		api.call("archive_unstable_hashByHeight", [height]).await.unwrap();
+	let mut expected = vec![format!("{:?}", block_2_hash), format!("{:?}", block_4_hash)];
+	hashes.sort();
+	expected.sort();
+	assert_eq!(hashes, expected);
+
+	// Height (N + 2) must include block 3.
+	height += 1;
+	let hashes: Vec<String> = api.call("archive_unstable_hashByHeight", [height]).await.unwrap();
+	assert_eq!(hashes, vec![format!("{:?}", block_3_hash)]);
+
+	// Height (N + 3) has no blocks.
+	height += 1;
+	let hashes: Vec<String> = api.call("archive_unstable_hashByHeight", [height]).await.unwrap();
+	assert!(hashes.is_empty());
+
+	// Finalizing block 3 reports only the canonical block at height (N + 1).
+	client.finalize_block(block_3_hash, None).unwrap();
+	let hashes: Vec<String> = api
+		.call("archive_unstable_hashByHeight", [block_2.header.number])
+		.await
+		.unwrap();
+	assert_eq!(hashes, vec![format!("{:?}", block_2_hash)]);
+}
+
+#[tokio::test]
+async fn archive_call() {
+	let (mut client, api) = setup_api();
+	let invalid_hash = hex_string(&INVALID_HASH);
+
+	// Pass an invalid parameters that cannot be decode.
+	let err = api
+		.call::<_, serde_json::Value>(
+			"archive_unstable_call",
+			// 0x0 is invalid.
+			[&invalid_hash, "BabeApi_current_epoch", "0x0"],
+		)
+		.await
+		.unwrap_err();
+	assert_matches!(err,
+		Error::Call(CallError::Custom(ref err)) if err.code() == 3001 && err.message().contains("Invalid parameter")
+	);
+
+	// Invalid hash.
+	let result: MethodResult = api
+		.call("archive_unstable_call", [&invalid_hash, "BabeApi_current_epoch", "0x00"])
+		.await
+		.unwrap();
+	assert!(!result.success);
+
+	let block_1 = client.new_block(Default::default()).unwrap().build().unwrap().block;
+	let block_1_hash = block_1.header.hash();
+	client.import(BlockOrigin::Own, block_1.clone()).await.unwrap();
+
+	// Valid call.
+	let alice_id = AccountKeyring::Alice.to_account_id();
+	// Hex encoded scale encoded bytes representing the call parameters.
+	let call_parameters = hex_string(&alice_id.encode());
+	let result: MethodResult = api
+		.call(
+			"archive_unstable_call",
+			[&format!("{:?}", block_1_hash), "AccountNonceApi_account_nonce", &call_parameters],
+		)
+		.await
+		.unwrap();
+	assert_eq!(result, MethodResult::ok("0x0000000000000000"));
+
+	// The `current_epoch` takes no parameters and not draining the input buffer
+	// will cause the execution to fail.
+	let result: MethodResult = api
+		.call(
+			"archive_unstable_call",
+			[&format!("{:?}", block_1_hash), "BabeApi_current_epoch", "0x00"],
+		)
+		.await
+		.unwrap();
+	assert_matches!(result.error, Some(ref error) if error.contains("Execution failed"));
+}
+
+#[tokio::test]
+async fn archive_storage() {
+	let (mut client, api) = setup_api();
+	let key = hex_string(&KEY);
+
+	// Import a new block with storage changes.
+	let mut builder = client.new_block(Default::default()).unwrap();
+	builder.push_storage_change(KEY.to_vec(), Some(VALUE.to_vec())).unwrap();
+	let block = builder.build().unwrap().block;
+	let block_hash = format!("{:?}", block.header.hash());
+	let parent_hash = format!("{:?}", block.header.parent_hash());
+	client.import(BlockOrigin::Own, block.clone()).await.unwrap();
+
+	// The key is not set at the parent block.
+	let result: ArchiveStorageResult = api
+		.call(
+			"archive_unstable_storage",
+			rpc_params![
+				&parent_hash,
+				vec![StorageQuery { key: key.clone(), queue_type: StorageQueryType::Value }]
+			],
+		)
+		.await
+		.unwrap();
+	assert_eq!(result, ArchiveStorageResult::ok(vec![]));
+
+	// Query both the value and the hash at the new block.
+	let expected_value = hex_string(&VALUE);
+	let expected_hash = format!("{:?}", Blake2Hasher::hash(&VALUE));
+	let result: ArchiveStorageResult = api
+		.call(
+			"archive_unstable_storage",
+			rpc_params![
+				&block_hash,
+				vec![
+					StorageQuery { key: key.clone(), queue_type: StorageQueryType::Value },
+					StorageQuery { key: key.clone(), queue_type: StorageQueryType::Hash },
+				]
+			],
+		)
+		.await
+		.unwrap();
+	assert!(result.success);
+	assert_eq!(result.result.len(), 2);
+	assert_eq!(result.result[0].key, key);
+	assert_eq!(result.result[0].result, StorageResultType::Value(expected_value));
+	assert_eq!(result.result[1].key, key);
+	assert_eq!(result.result[1].result, StorageResultType::Hash(expected_hash));
+
+	// Unknown block hash reports an error.
+	let result: ArchiveStorageResult = api
+		.call(
+			"archive_unstable_storage",
+			rpc_params![
+				hex_string(&INVALID_HASH),
+				vec![StorageQuery { key: key.clone(), queue_type: StorageQueryType::Value }]
+			],
+		)
+		.await
+		.unwrap();
+	assert!(!result.success);
+	assert!(result.error.is_some());
+
+	// Unsupported query types are rejected.
+	let err = api
+		.call::<_, serde_json::Value>(
+			"archive_unstable_storage",
+			rpc_params![
+				&block_hash,
+				vec![StorageQuery { key, queue_type: StorageQueryType::DescendantsValues }]
+			],
+		)
+		.await
+		.unwrap_err();
+	assert_matches!(err,
+		Error::Call(CallError::Custom(ref err)) if err.code() == 3001 && err.message().contains("Invalid parameter")
+	);
+}
+
+#[tokio::test]
+async fn archive_pruned_state() {
+	let builder = TestClientBuilder::with_pruning_window(1);
+	let backend = builder.backend();
+	let mut client = Arc::new(builder.build());
+	let api = Archive::new(client.clone(), backend, CHAIN_GENESIS).into_rpc();
+
+	let genesis_hash = format!("{:?}", client.genesis_hash());
+	let mut best_hash = String::new();
+	for _ in 0..3 {
+		let block = client.new_block(Default::default()).unwrap().build().unwrap().block;
+		best_hash = format!("{:?}", block.header.hash());
+		client.import_as_final(BlockOrigin::Own, block).await.unwrap();
+	}
+
+	// The state of the genesis block is pruned, queries at it fail.
+	let alice_id = AccountKeyring::Alice.to_account_id();
+	let call_parameters = hex_string(&alice_id.encode());
+	let result: MethodResult = api
+		.call(
+			"archive_unstable_call",
+			[&genesis_hash, "AccountNonceApi_account_nonce", &call_parameters],
+		)
+		.await
+		.unwrap();
+	assert_matches!(result.error, Some(ref error) if error.contains("pruned"));
+
+	let result: ArchiveStorageResult = api
+		.call(
+			"archive_unstable_storage",
+			rpc_params![
+				&genesis_hash,
+				vec![StorageQuery { key: hex_string(&KEY), queue_type: StorageQueryType::Value }]
+			],
+		)
+		.await
+		.unwrap();
+	assert_matches!(result.error, Some(ref error) if error.contains("pruned"));
+
+	// The state of the latest block is still available.
+	let result: MethodResult = api
+		.call(
+			"archive_unstable_call",
+			[&best_hash, "AccountNonceApi_account_nonce", &call_parameters],
+		)
+		.await
+		.unwrap();
+	assert_eq!(result, MethodResult::ok("0x0000000000000000"));
+}
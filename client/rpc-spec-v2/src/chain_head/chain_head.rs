@@ -49,7 +49,7 @@ use std::{marker::PhantomData, sync::Arc, time::Duration};
 
 use super::{
 	chain_head_storage::ChainHeadStorage,
-	event::{ChainHeadStorageEvent, StorageQuery, StorageQueryType},
+	event::{ChainHeadStorageEvent, StorageQuery},
 };
 
 pub(crate) const LOG_TARGET: &str = "rpc-spec-v2";
@@ -300,16 +300,6 @@ where
 		let items = items
 			.into_iter()
 			.map(|query| {
-				if query.queue_type != StorageQueryType::Value &&
-					query.queue_type != StorageQueryType::Hash
-				{
-					// Note: remove this once all types are implemented.
-					let _ = sink.reject(ChainHeadRpcError::InvalidParam(
-						"Storage query type not supported".into(),
-					));
-					return Err(SubscriptionEmptyError)
-				}
-
 				Ok(StorageQuery {
 					key: StorageKey(parse_hex_param(&mut sink, query.key)?),
 					queue_type: query.queue_type,
@@ -345,12 +335,33 @@ where
 			},
 		};
 
+		// The storage subscription ID identifies the operation for the `continue` calls.
+		let operation_id = match self.accept_subscription(&mut sink) {
+			Ok(operation_id) => operation_id,
+			Err(err) => {
+				sink.close(ChainHeadRpcError::InvalidSubscriptionID);
+				return Err(err)
+			},
+		};
+
+		let operation =
+			match subscriptions.register_operation(&follow_subscription, operation_id.clone()) {
+				Ok(operation) => operation,
+				Err(error) => {
+					let _ = sink.send(&ChainHeadStorageEvent::<String>::Error(ErrorEvent {
+						error: error.to_string(),
+					}));
+					return Ok(())
+				},
+			};
+
 		let storage_client = ChainHeadStorage::<Client, Block, BE>::new(client);
 
 		let fut = async move {
 			let _block_guard = block_guard;
 
-			storage_client.generate_events(sink, hash, items, child_trie);
+			storage_client.generate_events(sink, operation, hash, items, child_trie).await;
+			subscriptions.remove_operation(&follow_subscription, &operation_id);
 		};
 
 		self.executor.spawn("substrate-rpc-subscription", Some("rpc"), fut.boxed());
@@ -436,4 +447,19 @@ where
 			Err(_) => Err(ChainHeadRpcError::InvalidBlock.into()),
 		}
 	}
+
+	fn chain_head_unstable_continue(
+		&self,
+		follow_subscription: String,
+		operation_id: String,
+	) -> RpcResult<()> {
+		match self.subscriptions.continue_operation(&follow_subscription, &operation_id) {
+			Ok(()) => Ok(()),
+			Err(SubscriptionManagementError::SubscriptionAbsent) => {
+				// Invalid invalid subscription ID.
+				Ok(())
+			},
+			Err(_) => Err(ChainHeadRpcError::InvalidContinue.into()),
+		}
+	}
 }
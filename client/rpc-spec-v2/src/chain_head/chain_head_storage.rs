@@ -21,7 +21,7 @@
 use std::{marker::PhantomData, sync::Arc};
 
 use jsonrpsee::SubscriptionSink;
-use sc_client_api::{Backend, ChildInfo, StorageKey, StorageProvider};
+use sc_client_api::{Backend, ChildInfo, MerkleValue, StorageKey, StorageProvider};
 use sp_api::BlockT;
 use sp_core::storage::well_known_keys;
 
@@ -30,9 +30,18 @@ use super::{
 		ChainHeadStorageEvent, ItemsEvent, StorageQuery, StorageQueryType, StorageResult,
 		StorageResultType,
 	},
-	hex_string, ErrorEvent,
+	hex_string,
+	subscription::RegisteredOperation,
+	ErrorEvent,
 };
 
+/// The maximum number of items reported by a single `Items` event.
+///
+/// When a query produces more items (ie `descendantsValues`), the `WaitForContinue`
+/// event is generated and the remaining items are reported after the
+/// `chainHead_unstable_continue` call.
+pub(crate) const MAX_ITER_ITEMS: usize = 10;
+
 /// Generates the events of the `chainHead_storage` method.
 pub struct ChainHeadStorage<Client, Block, BE> {
 	/// Substrate client.
@@ -60,6 +69,75 @@ fn is_key_queryable(key: &[u8]) -> bool {
 /// The result of making a query call.
 type QueryResult = Result<StorageResult<String>, ChainHeadStorageEvent<String>>;
 
+/// Accumulates the results of the storage queries and reports them in batches
+/// of at most [`MAX_ITER_ITEMS`] items.
+struct ItemsSender {
+	/// The subscription sink.
+	sink: SubscriptionSink,
+	/// Pauses the reporting between batches.
+	operation: RegisteredOperation,
+	/// The results not yet reported.
+	items: Vec<StorageResult<String>>,
+}
+
+impl ItemsSender {
+	/// Queue a new result.
+	///
+	/// If the current batch is full, the batch is reported and the method waits for
+	/// the `continue` call before queueing the result.
+	///
+	/// Returns `false` if the operation cannot continue.
+	async fn push(&mut self, item: StorageResult<String>) -> bool {
+		if self.items.len() >= MAX_ITER_ITEMS {
+			if !self.flush() {
+				return false
+			}
+
+			// Mark the operation as waiting before sending the event, such that a prompt
+			// `continue` call is not rejected.
+			let wait = self.operation.wait_for_continue();
+			if !matches!(
+				self.sink.send(&ChainHeadStorageEvent::<String>::WaitForContinue),
+				Ok(true)
+			) {
+				return false
+			}
+
+			if !wait.await {
+				return false
+			}
+		}
+
+		self.items.push(item);
+		true
+	}
+
+	/// Report the queued results.
+	///
+	/// Returns `false` if the sink is closed.
+	fn flush(&mut self) -> bool {
+		if self.items.is_empty() {
+			return true
+		}
+
+		let items = std::mem::take(&mut self.items);
+		let event = ChainHeadStorageEvent::Items(ItemsEvent { items });
+		matches!(self.sink.send(&event), Ok(true))
+	}
+
+	/// Report the error and stop the operation.
+	fn error(mut self, event: ChainHeadStorageEvent<String>) {
+		let _ = self.sink.send(&event);
+	}
+
+	/// Report the queued results followed by the `Done` event.
+	fn done(mut self) {
+		if self.flush() {
+			let _ = self.sink.send(&ChainHeadStorageEvent::<String>::Done);
+		}
+	}
+}
+
 impl<Client, Block, BE> ChainHeadStorage<Client, Block, BE>
 where
 	Block: BlockT + 'static,
@@ -124,28 +202,132 @@ where
 			})
 	}
 
+	/// Fetch the closest merkle value.
+	fn query_storage_merkle_value(
+		&self,
+		hash: Block::Hash,
+		key: &StorageKey,
+		child_key: Option<&ChildInfo>,
+	) -> Option<QueryResult> {
+		let result = if let Some(child_key) = child_key {
+			self.client.child_closest_merkle_value(hash, child_key, key)
+		} else {
+			self.client.closest_merkle_value(hash, key)
+		};
+
+		result
+			.map(|opt| {
+				opt.map(|merkle_value| {
+					let result = match merkle_value {
+						MerkleValue::Node(data) => hex_string(&data),
+						MerkleValue::Hash(hash) => hex_string(&hash.as_ref()),
+					};
+
+					QueryResult::Ok(StorageResult::<String> {
+						key: hex_string(&key.0),
+						result: StorageResultType::ClosestDescendantMerkleValue(result),
+					})
+				})
+			})
+			.unwrap_or_else(|err| {
+				Some(QueryResult::Err(ChainHeadStorageEvent::<String>::Error(ErrorEvent {
+					error: err.to_string(),
+				})))
+			})
+	}
+
+	/// Fetch the next batch of keys under the provided prefix, starting after `start_key`.
+	fn query_descendant_keys(
+		&self,
+		hash: Block::Hash,
+		prefix: &StorageKey,
+		start_key: Option<&StorageKey>,
+		child_key: Option<&ChildInfo>,
+	) -> Result<Vec<StorageKey>, ChainHeadStorageEvent<String>> {
+		let keys_iter = if let Some(child_key) = child_key {
+			self.client.child_storage_keys(hash, child_key.clone(), Some(prefix), start_key)
+		} else {
+			self.client.storage_keys(hash, Some(prefix), start_key)
+		};
+
+		keys_iter
+			.map(|iter| iter.take(MAX_ITER_ITEMS).collect())
+			.map_err(|err| ChainHeadStorageEvent::Error(ErrorEvent { error: err.to_string() }))
+	}
+
+	/// Iterate over all the descendants of the provided key.
+	///
+	/// Returns `false` if the operation cannot continue.
+	async fn query_storage_descendants(
+		&self,
+		sender: &mut ItemsSender,
+		hash: Block::Hash,
+		query: &StorageQuery<StorageKey>,
+		child_key: Option<&ChildInfo>,
+	) -> Result<bool, ChainHeadStorageEvent<String>> {
+		// The iteration is resumed from the last reported key, such that the
+		// state is not kept alive while waiting for the `continue` call.
+		let mut start_key: Option<StorageKey> = None;
+
+		loop {
+			let keys =
+				self.query_descendant_keys(hash, &query.key, start_key.as_ref(), child_key)?;
+			let Some(last_key) = keys.last().cloned() else { return Ok(true) };
+
+			for key in keys {
+				let result = match query.queue_type {
+					StorageQueryType::DescendantsValues =>
+						self.query_storage_value(hash, &key, child_key),
+					_ => self.query_storage_hash(hash, &key, child_key),
+				};
+
+				match result {
+					Some(QueryResult::Ok(item)) =>
+						if !sender.push(item).await {
+							return Ok(false)
+						},
+					Some(QueryResult::Err(event)) => return Err(event),
+					None => continue,
+				}
+			}
+
+			start_key = Some(last_key);
+		}
+	}
+
 	/// Make the storage query.
-	fn query_storage(
+	async fn query_storage(
 		&self,
+		sender: &mut ItemsSender,
 		hash: Block::Hash,
 		query: &StorageQuery<StorageKey>,
 		child_key: Option<&ChildInfo>,
-	) -> Option<QueryResult> {
+	) -> Result<bool, ChainHeadStorageEvent<String>> {
 		if !is_key_queryable(&query.key.0) {
-			return None
+			return Ok(true)
 		}
 
-		match query.queue_type {
+		let result = match query.queue_type {
 			StorageQueryType::Value => self.query_storage_value(hash, &query.key, child_key),
 			StorageQueryType::Hash => self.query_storage_hash(hash, &query.key, child_key),
-			_ => None,
+			StorageQueryType::ClosestDescendantMerkleValue =>
+				self.query_storage_merkle_value(hash, &query.key, child_key),
+			StorageQueryType::DescendantsValues | StorageQueryType::DescendantsHashes =>
+				return self.query_storage_descendants(sender, hash, query, child_key).await,
+		};
+
+		match result {
+			Some(QueryResult::Ok(item)) => Ok(sender.push(item).await),
+			Some(QueryResult::Err(event)) => Err(event),
+			None => Ok(true),
 		}
 	}
 
 	/// Generate the block events for the `chainHead_storage` method.
-	pub fn generate_events(
+	pub async fn generate_events(
 		&self,
 		mut sink: SubscriptionSink,
+		operation: RegisteredOperation,
 		hash: Block::Hash,
 		items: Vec<StorageQuery<StorageKey>>,
 		child_key: Option<ChildInfo>,
@@ -157,28 +339,21 @@ where
 			}
 		}
 
-		let mut storage_results = Vec::with_capacity(items.len());
+		let mut sender = ItemsSender { sink, operation, items: Vec::new() };
 		for item in items {
-			let Some(result) = self.query_storage(hash, &item, child_key.as_ref()) else {
-                continue
-            };
-
-			match result {
-				QueryResult::Ok(storage_result) => storage_results.push(storage_result),
-				QueryResult::Err(event) => {
-					let _ = sink.send(&event);
+			match self.query_storage(&mut sender, hash, &item, child_key.as_ref()).await {
+				Ok(true) => continue,
+				// The sink was closed or the subscription was dropped.
+				Ok(false) => return,
+				Err(event) => {
 					// If an error is encountered for any of the query items
 					// do not produce any other events.
+					sender.error(event);
 					return
 				},
 			}
 		}
 
-		if !storage_results.is_empty() {
-			let event = ChainHeadStorageEvent::Items(ItemsEvent { items: storage_results });
-			let _ = sink.send(&event);
-		}
-
-		let _ = sink.send(&ChainHeadStorageEvent::<String>::Done);
+		sender.done();
 	}
 }
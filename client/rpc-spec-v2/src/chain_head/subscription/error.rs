@@ -38,6 +38,12 @@ pub enum SubscriptionManagementError {
 	/// The specified subscription ID is not present.
 	#[error("Subscription is absent")]
 	SubscriptionAbsent,
+	/// The specified operation ID is not present.
+	#[error("Operation is absent")]
+	OperationAbsent,
+	/// The operation did not generate the `WaitForContinue` event.
+	#[error("Operation is not waiting for continue")]
+	OperationNotWaiting,
 	/// Custom error.
 	#[error("Subscription error {0}")]
 	Custom(String),
@@ -52,7 +58,9 @@ impl PartialEq for SubscriptionManagementError {
 			(Self::Blockchain(_), Self::Blockchain(_)) |
 			(Self::BlockHashAbsent, Self::BlockHashAbsent) |
 			(Self::BlockHeaderAbsent, Self::BlockHeaderAbsent) |
-			(Self::SubscriptionAbsent, Self::SubscriptionAbsent) => true,
+			(Self::SubscriptionAbsent, Self::SubscriptionAbsent) |
+			(Self::OperationAbsent, Self::OperationAbsent) |
+			(Self::OperationNotWaiting, Self::OperationNotWaiting) => true,
 			(Self::Custom(lhs), Self::Custom(rhs)) => lhs == rhs,
 			_ => false,
 		}
@@ -16,12 +16,18 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use futures::channel::oneshot;
+use futures::{
+	channel::{mpsc, oneshot},
+	Future, FutureExt, StreamExt,
+};
 use sc_client_api::Backend;
 use sp_runtime::traits::Block as BlockT;
 use std::{
 	collections::{hash_map::Entry, HashMap},
-	sync::Arc,
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		Arc,
+	},
 	time::{Duration, Instant},
 };
 
@@ -110,6 +116,44 @@ struct BlockState {
 	timestamp: Instant,
 }
 
+/// The state of an operation that produces its results in multiple steps.
+///
+/// The operation pauses after generating the `WaitForContinue` event and
+/// resumes when the user calls the `continue` method.
+struct OperationState {
+	/// True if the operation generated the `WaitForContinue` event and
+	/// did not receive the `continue` call yet.
+	is_waiting: Arc<AtomicBool>,
+	/// Signals the operation to resume.
+	tx_continue: mpsc::Sender<()>,
+}
+
+/// The handle of an operation registered for a subscription.
+///
+/// The operation is dropped when the subscription is removed, in which case
+/// [`RegisteredOperation::wait_for_continue`] returns `false`.
+#[derive(Debug)]
+pub struct RegisteredOperation {
+	/// Shared with the [`OperationState`] of the subscription.
+	is_waiting: Arc<AtomicBool>,
+	/// Triggered by the `continue` method.
+	rx_continue: mpsc::Receiver<()>,
+}
+
+impl RegisteredOperation {
+	/// Wait for the user to call the `continue` method.
+	///
+	/// The operation is marked as waiting when this method is called, not when the returned
+	/// future is first polled. This allows the `WaitForContinue` event to be sent in between,
+	/// without rejecting a `continue` call that arrives before the future is polled.
+	///
+	/// The future returns `false` if the operation was removed before the `continue` call.
+	pub fn wait_for_continue(&mut self) -> impl Future<Output = bool> + '_ {
+		self.is_waiting.store(true, Ordering::Release);
+		self.rx_continue.next().map(|item| item.is_some())
+	}
+}
+
 /// The state of a single subscription ID.
 struct SubscriptionState<Block: BlockT> {
 	/// The `with_runtime` parameter flag of the subscription.
@@ -127,6 +171,8 @@ struct SubscriptionState<Block: BlockT> {
 	/// - extra space: an extra BTreeMap<Instant, Hash> to older hashes by oldest insertion
 	/// - extra time: O(log(N)) for insert/remove/find each `pin` block time per subscriptions
 	blocks: HashMap<Block::Hash, BlockState>,
+	/// Operations that can be paused and resumed, indexed by their operation ID.
+	operations: HashMap<String, OperationState>,
 }
 
 impl<Block: BlockT> SubscriptionState<Block> {
@@ -318,6 +364,7 @@ impl<Block: BlockT, BE: Backend<Block>> SubscriptionsInner<Block, BE> {
 				with_runtime,
 				tx_stop: Some(tx_stop),
 				blocks: Default::default(),
+				operations: Default::default(),
 			};
 			entry.insert(state);
 			Some(rx_stop)
@@ -501,11 +548,62 @@ impl<Block: BlockT, BE: Backend<Block>> SubscriptionsInner<Block, BE> {
 
 		BlockGuard::new(hash, sub.with_runtime, self.backend.clone())
 	}
+
+	pub fn register_operation(
+		&mut self,
+		sub_id: &str,
+		operation_id: String,
+	) -> Result<RegisteredOperation, SubscriptionManagementError> {
+		let Some(sub) = self.subs.get_mut(sub_id) else {
+			return Err(SubscriptionManagementError::SubscriptionAbsent)
+		};
+
+		let Entry::Vacant(entry) = sub.operations.entry(operation_id) else {
+			return Err(SubscriptionManagementError::Custom("Operation already registered".into()))
+		};
+
+		let is_waiting = Arc::new(AtomicBool::new(false));
+		let (tx_continue, rx_continue) = mpsc::channel(1);
+		entry.insert(OperationState { is_waiting: is_waiting.clone(), tx_continue });
+
+		Ok(RegisteredOperation { is_waiting, rx_continue })
+	}
+
+	pub fn continue_operation(
+		&mut self,
+		sub_id: &str,
+		operation_id: &str,
+	) -> Result<(), SubscriptionManagementError> {
+		let Some(sub) = self.subs.get_mut(sub_id) else {
+			return Err(SubscriptionManagementError::SubscriptionAbsent)
+		};
+
+		let Some(operation) = sub.operations.get_mut(operation_id) else {
+			return Err(SubscriptionManagementError::OperationAbsent)
+		};
+
+		// The `continue` method is valid only after the `WaitForContinue` event.
+		if !operation.is_waiting.swap(false, Ordering::AcqRel) {
+			return Err(SubscriptionManagementError::OperationNotWaiting)
+		}
+
+		// The channel has capacity for one message, which is consumed before
+		// the operation can wait again.
+		let _ = operation.tx_continue.try_send(());
+		Ok(())
+	}
+
+	pub fn remove_operation(&mut self, sub_id: &str, operation_id: &str) {
+		if let Some(sub) = self.subs.get_mut(sub_id) {
+			sub.operations.remove(operation_id);
+		}
+	}
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use futures::FutureExt;
 	use sc_block_builder::BlockBuilderProvider;
 	use sc_service::client::new_in_mem;
 	use sp_consensus::BlockOrigin;
@@ -608,6 +706,7 @@ mod tests {
 			with_runtime: false,
 			tx_stop: None,
 			blocks: Default::default(),
+			operations: Default::default(),
 		};
 
 		let hash = H256::random();
@@ -633,6 +732,7 @@ mod tests {
 			with_runtime: false,
 			tx_stop: None,
 			blocks: Default::default(),
+			operations: Default::default(),
 		};
 
 		let hash = H256::random();
@@ -913,6 +1013,55 @@ mod tests {
 		assert_eq!(subs.global_blocks.len(), 0);
 	}
 
+	#[test]
+	fn subscription_operations() {
+		let builder = TestClientBuilder::new();
+		let backend = builder.backend();
+		let mut subs = SubscriptionsInner::new(10, Duration::from_secs(10), backend);
+
+		let id = "abc".to_string();
+		let operation_id = "op".to_string();
+
+		let err = subs.register_operation(&id, operation_id.clone()).unwrap_err();
+		assert_eq!(err, SubscriptionManagementError::SubscriptionAbsent);
+
+		let _stop = subs.insert_subscription(id.clone(), true).unwrap();
+		let mut operation = subs.register_operation(&id, operation_id.clone()).unwrap();
+
+		// Operation IDs are unique per subscription.
+		assert!(subs.register_operation(&id, operation_id.clone()).is_err());
+
+		let err = subs.continue_operation(&id, "invalid").unwrap_err();
+		assert_eq!(err, SubscriptionManagementError::OperationAbsent);
+
+		// The operation did not produce the `WaitForContinue` event.
+		let err = subs.continue_operation(&id, &operation_id).unwrap_err();
+		assert_eq!(err, SubscriptionManagementError::OperationNotWaiting);
+
+		{
+			let mut wait = Box::pin(operation.wait_for_continue());
+			assert!((&mut wait).now_or_never().is_none());
+
+			subs.continue_operation(&id, &operation_id).unwrap();
+			// The operation resumes only once.
+			let err = subs.continue_operation(&id, &operation_id).unwrap_err();
+			assert_eq!(err, SubscriptionManagementError::OperationNotWaiting);
+
+			assert_eq!(wait.now_or_never(), Some(true));
+		}
+
+		{
+			// The operation can be resumed before the waiting future is polled.
+			let wait = operation.wait_for_continue();
+			subs.continue_operation(&id, &operation_id).unwrap();
+			assert_eq!(wait.now_or_never(), Some(true));
+		}
+
+		// Removing the subscription drops the operations.
+		subs.remove_subscription(&id);
+		assert_eq!(operation.wait_for_continue().now_or_never(), Some(false));
+	}
+
 	#[test]
 	fn subscription_check_stop_event() {
 		let builder = TestClientBuilder::new();
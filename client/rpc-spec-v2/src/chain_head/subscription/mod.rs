@@ -26,8 +26,8 @@ mod error;
 mod inner;
 
 pub use error::SubscriptionManagementError;
-pub use inner::BlockGuard;
 use inner::SubscriptionsInner;
+pub use inner::{BlockGuard, RegisteredOperation};
 
 /// Manage block pinning / unpinning for subscription IDs.
 pub struct SubscriptionManagement<Block: BlockT, BE: Backend<Block>> {
@@ -122,4 +122,37 @@ impl<Block: BlockT, BE: Backend<Block>> SubscriptionManagement<Block, BE> {
 		let mut inner = self.inner.write();
 		inner.lock_block(sub_id, hash)
 	}
+
+	/// Register an operation that produces its results in multiple steps.
+	///
+	/// Returns a [`RegisteredOperation`] used to wait for the `continue` calls.
+	/// Returns an error if the subscription ID is invalid or the operation ID
+	/// is already registered.
+	pub fn register_operation(
+		&self,
+		sub_id: &str,
+		operation_id: String,
+	) -> Result<RegisteredOperation, SubscriptionManagementError> {
+		let mut inner = self.inner.write();
+		inner.register_operation(sub_id, operation_id)
+	}
+
+	/// Resume an operation that generated the `WaitForContinue` event.
+	///
+	/// Returns an error if the subscription or operation ID is invalid, or if
+	/// the operation is not waiting for the `continue` call.
+	pub fn continue_operation(
+		&self,
+		sub_id: &str,
+		operation_id: &str,
+	) -> Result<(), SubscriptionManagementError> {
+		let mut inner = self.inner.write();
+		inner.continue_operation(sub_id, operation_id)
+	}
+
+	/// Remove the operation from the subscription.
+	pub fn remove_operation(&self, sub_id: &str, operation_id: &str) {
+		let mut inner = self.inner.write();
+		inner.remove_operation(sub_id, operation_id)
+	}
 }
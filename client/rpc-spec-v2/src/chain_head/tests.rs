@@ -1,4 +1,5 @@
 use crate::chain_head::{
+	chain_head_storage::MAX_ITER_ITEMS,
 	event::{ChainHeadStorageEvent, StorageQuery, StorageQueryType, StorageResultType},
 	test_utils::ChainHeadMockClient,
 };
@@ -817,6 +818,177 @@ async fn get_storage_wrong_key() {
 	assert_matches!(event, ChainHeadStorageEvent::Done);
 }
 
+#[tokio::test]
+async fn get_storage_descendants_paginated() {
+	let (mut client, api, mut block_sub, sub_id, _block) = setup_api().await;
+
+	// Import a new block with more descendants than reported by a single `Items` event.
+	let num_keys = MAX_ITER_ITEMS + 5;
+	let mut builder = client.new_block(Default::default()).unwrap();
+	for i in 0..num_keys {
+		let key = format!(":mock_{:02}", i).into_bytes();
+		builder.push_storage_change(key, Some(VALUE.to_vec())).unwrap();
+	}
+	let block = builder.build().unwrap().block;
+	let block_hash = format!("{:?}", block.header.hash());
+	client.import(BlockOrigin::Own, block.clone()).await.unwrap();
+
+	// Ensure the imported block is propagated and pinned for this subscription.
+	assert_matches!(
+		get_next_event::<FollowEvent<String>>(&mut block_sub).await,
+		FollowEvent::NewBlock(_)
+	);
+	assert_matches!(
+		get_next_event::<FollowEvent<String>>(&mut block_sub).await,
+		FollowEvent::BestBlockChanged(_)
+	);
+
+	let key = hex_string(&KEY);
+	let expected_value = hex_string(&VALUE);
+	let mut sub = api
+		.subscribe(
+			"chainHead_unstable_storage",
+			rpc_params![
+				&sub_id,
+				&block_hash,
+				vec![StorageQuery { key, queue_type: StorageQueryType::DescendantsValues }]
+			],
+		)
+		.await
+		.unwrap();
+	let operation_id = serde_json::to_string(&sub.subscription_id()).unwrap();
+
+	// The first batch contains `MAX_ITER_ITEMS` items.
+	let event: ChainHeadStorageEvent<String> = get_next_event(&mut sub).await;
+	assert_matches!(event, ChainHeadStorageEvent::<String>::Items(res) if res.items.len() == MAX_ITER_ITEMS &&
+		res.items[0].key == hex_string(b":mock_00") &&
+		res.items.iter().all(|item| item.result == StorageResultType::Value(expected_value.clone()))
+	);
+	let event: ChainHeadStorageEvent<String> = get_next_event(&mut sub).await;
+	assert_matches!(event, ChainHeadStorageEvent::WaitForContinue);
+
+	// Invalid operation ID.
+	let err = api
+		.call::<_, ()>("chainHead_unstable_continue", [&sub_id, "invalid_operation_id"])
+		.await
+		.unwrap_err();
+	assert_matches!(err,
+		Error::Call(CallError::Custom(ref err)) if err.code() == 2005
+	);
+
+	// Stale subscription ID is ignored.
+	let _res: () = api
+		.call("chainHead_unstable_continue", ["invalid_sub_id", &operation_id])
+		.await
+		.unwrap();
+
+	let _res: () = api.call("chainHead_unstable_continue", [&sub_id, &operation_id]).await.unwrap();
+
+	// The remaining items are reported after the `continue` call.
+	let event: ChainHeadStorageEvent<String> = get_next_event(&mut sub).await;
+	assert_matches!(event, ChainHeadStorageEvent::<String>::Items(res) if res.items.len() == num_keys - MAX_ITER_ITEMS &&
+		res.items[0].key == hex_string(&format!(":mock_{:02}", MAX_ITER_ITEMS).into_bytes())
+	);
+	let event: ChainHeadStorageEvent<String> = get_next_event(&mut sub).await;
+	assert_matches!(event, ChainHeadStorageEvent::Done);
+
+	// The operation is no longer waiting for the `continue` call.
+	let err = api
+		.call::<_, ()>("chainHead_unstable_continue", [&sub_id, &operation_id])
+		.await
+		.unwrap_err();
+	assert_matches!(err,
+		Error::Call(CallError::Custom(ref err)) if err.code() == 2005 && err.message() == "Wait for continue event was not generated for the subscription"
+	);
+
+	// Descendants hashes are reported in the same manner.
+	let key = hex_string(&KEY);
+	let expected_hash = format!("{:?}", Blake2Hasher::hash(&VALUE));
+	let mut sub = api
+		.subscribe(
+			"chainHead_unstable_storage",
+			rpc_params![
+				&sub_id,
+				&block_hash,
+				vec![StorageQuery { key, queue_type: StorageQueryType::DescendantsHashes }]
+			],
+		)
+		.await
+		.unwrap();
+	let event: ChainHeadStorageEvent<String> = get_next_event(&mut sub).await;
+	assert_matches!(event, ChainHeadStorageEvent::<String>::Items(res) if res.items.len() == MAX_ITER_ITEMS &&
+		res.items.iter().all(|item| item.result == StorageResultType::Hash(expected_hash.clone()))
+	);
+	let event: ChainHeadStorageEvent<String> = get_next_event(&mut sub).await;
+	assert_matches!(event, ChainHeadStorageEvent::WaitForContinue);
+}
+
+#[tokio::test]
+async fn get_storage_closest_merkle_value() {
+	let (mut client, api, mut block_sub, sub_id, block) = setup_api().await;
+	let block_hash = format!("{:?}", block.header.hash());
+	let key = hex_string(&KEY);
+
+	// No descendants of the key.
+	let mut sub = api
+		.subscribe(
+			"chainHead_unstable_storage",
+			rpc_params![
+				&sub_id,
+				&block_hash,
+				vec![StorageQuery {
+					key: key.clone(),
+					queue_type: StorageQueryType::ClosestDescendantMerkleValue
+				}]
+			],
+		)
+		.await
+		.unwrap();
+	let event: ChainHeadStorageEvent<String> = get_next_event(&mut sub).await;
+	assert_matches!(event, ChainHeadStorageEvent::Done);
+
+	// Import a new block with a descendant of the key.
+	let mut builder = client.new_block(Default::default()).unwrap();
+	builder
+		.push_storage_change(b":mock_descendant".to_vec(), Some(VALUE.to_vec()))
+		.unwrap();
+	let block = builder.build().unwrap().block;
+	let block_hash = format!("{:?}", block.header.hash());
+	client.import(BlockOrigin::Own, block.clone()).await.unwrap();
+
+	// Ensure the imported block is propagated and pinned for this subscription.
+	assert_matches!(
+		get_next_event::<FollowEvent<String>>(&mut block_sub).await,
+		FollowEvent::NewBlock(_)
+	);
+	assert_matches!(
+		get_next_event::<FollowEvent<String>>(&mut block_sub).await,
+		FollowEvent::BestBlockChanged(_)
+	);
+
+	let mut sub = api
+		.subscribe(
+			"chainHead_unstable_storage",
+			rpc_params![
+				&sub_id,
+				&block_hash,
+				vec![StorageQuery {
+					key: key.clone(),
+					queue_type: StorageQueryType::ClosestDescendantMerkleValue
+				}]
+			],
+		)
+		.await
+		.unwrap();
+	let event: ChainHeadStorageEvent<String> = get_next_event(&mut sub).await;
+	assert_matches!(event, ChainHeadStorageEvent::<String>::Items(res) if res.items.len() == 1 &&
+		res.items[0].key == key &&
+		matches!(res.items[0].result, StorageResultType::ClosestDescendantMerkleValue(_))
+	);
+	let event: ChainHeadStorageEvent<String> = get_next_event(&mut sub).await;
+	assert_matches!(event, ChainHeadStorageEvent::Done);
+}
+
 #[tokio::test]
 async fn follow_generates_initial_blocks() {
 	let builder = TestClientBuilder::new();
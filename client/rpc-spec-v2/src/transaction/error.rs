@@ -21,6 +21,10 @@
 //! Errors are interpreted as transaction events for subscriptions.
 
 use crate::transaction::event::{TransactionError, TransactionEvent};
+use jsonrpsee::{
+	core::Error as RpcError,
+	types::error::{CallError, ErrorObject},
+};
 use sc_transaction_pool_api::error::Error as PoolError;
 use sp_runtime::transaction_validity::InvalidTransaction;
 
@@ -65,11 +69,12 @@ impl<Hash> From<Error> for TransactionEvent<Hash> {
 				TransactionEvent::Invalid(TransactionError {
 					error: "Transaction is already imported".into(),
 				}),
-			Error::Pool(PoolError::TooLowPriority { old, new }) =>
+			Error::Pool(PoolError::TooLowPriority { old, new, required }) =>
 				TransactionEvent::Invalid(TransactionError {
 					error: format!(
-						"The priority of the transaction is too low (pool {} > current {})",
-						old, new
+						"The priority of the transaction is too low (pool {} > current {}, at least \
+						{} required)",
+						old, new, required
 					),
 				}),
 			Error::Pool(PoolError::CycleDetected) => TransactionEvent::Invalid(TransactionError {
@@ -98,3 +103,38 @@ impl<Hash> From<Error> for TransactionEvent<Hash> {
 		}
 	}
 }
+
+/// The transaction broadcast errors.
+#[derive(Debug, thiserror::Error)]
+pub enum ErrorBroadcast {
+	/// The provided operation ID is invalid.
+	#[error("Invalid operation id")]
+	InvalidOperationID,
+	/// The maximum number of broadcast operations is reached.
+	#[error("Too many broadcast operations")]
+	TooManyOperations,
+}
+
+/// Invalid parameter error, as defined by the JSON-RPC specification.
+const INVALID_PARAM_ERROR: i32 = -32602;
+/// The server reached the limit of operations it can run.
+const REACHED_LIMITS_ERROR: i32 = -32800;
+
+impl From<ErrorBroadcast> for ErrorObject<'static> {
+	fn from(e: ErrorBroadcast) -> Self {
+		let msg = e.to_string();
+
+		match e {
+			ErrorBroadcast::InvalidOperationID =>
+				ErrorObject::owned(INVALID_PARAM_ERROR, msg, None::<()>),
+			ErrorBroadcast::TooManyOperations =>
+				ErrorObject::owned(REACHED_LIMITS_ERROR, msg, None::<()>),
+		}
+	}
+}
+
+impl From<ErrorBroadcast> for RpcError {
+	fn from(e: ErrorBroadcast) -> Self {
+		CallError::Custom(e.into()).into()
+	}
+}
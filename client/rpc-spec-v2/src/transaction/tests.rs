@@ -0,0 +1,157 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use super::*;
+use crate::chain_head::hex_string;
+use assert_matches::assert_matches;
+use codec::Encode;
+use futures::StreamExt;
+use jsonrpsee::{core::error::Error, types::error::CallError, RpcModule};
+use parking_lot::RwLock;
+use sc_transaction_pool::{BasicPool, FullChainApi};
+use sc_transaction_pool_api::TransactionPool;
+use sp_core::testing::TaskExecutor;
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use substrate_test_runtime_client::{
+	prelude::*,
+	runtime::{Block, Extrinsic, ExtrinsicBuilder, Transfer},
+	AccountKeyring, Backend, Client,
+};
+
+type FullTransactionPool = BasicPool<FullChainApi<Client<Backend>, Block>, Block>;
+
+fn uxt(sender: AccountKeyring, nonce: u64) -> Extrinsic {
+	let tx = Transfer {
+		amount: Default::default(),
+		nonce,
+		from: sender.into(),
+		to: AccountKeyring::Bob.into(),
+	};
+	ExtrinsicBuilder::new_transfer(tx).build()
+}
+
+type BroadcastIds = Arc<RwLock<HashMap<String, transaction_broadcast::BroadcastState>>>;
+
+fn setup_api() -> (
+	Arc<FullTransactionPool>,
+	BroadcastIds,
+	RpcModule<TransactionBroadcast<FullTransactionPool, Client<Backend>>>,
+) {
+	let client = Arc::new(TestClientBuilder::new().build());
+	let spawner = TaskExecutor::new();
+	let pool = BasicPool::new_full(Default::default(), true.into(), None, spawner, client.clone());
+
+	let tx_api = TransactionBroadcast::new(client, pool.clone(), Arc::new(TaskExecutor::default()));
+	let broadcast_ids = tx_api.broadcast_ids.clone();
+
+	(pool, broadcast_ids, tx_api.into_rpc())
+}
+
+#[tokio::test]
+async fn tx_broadcast_enters_pool() {
+	let (pool, _, api) = setup_api();
+	let mut pool_imports = pool.import_notification_stream();
+
+	let uxt = uxt(AccountKeyring::Alice, 0);
+	let xt = hex_string(&uxt.encode());
+
+	let operation_id: Option<String> =
+		api.call("transaction_unstable_broadcast", [&xt]).await.unwrap();
+	let operation_id = operation_id.unwrap();
+
+	// The transaction is submitted to the pool, which propagates it to peers.
+	let imported = tokio::time::timeout(Duration::from_secs(60), pool_imports.next())
+		.await
+		.unwrap()
+		.unwrap();
+	assert_eq!(imported, pool.hash_of(&uxt));
+	assert_eq!(pool.status().ready, 1);
+
+	let _res: () = api.call("transaction_unstable_stop", [&operation_id]).await.unwrap();
+
+	// The operation ID is no longer valid after the broadcast is stopped.
+	let err = api
+		.call::<_, ()>("transaction_unstable_stop", [&operation_id])
+		.await
+		.unwrap_err();
+	assert_matches!(err,
+		Error::Call(CallError::Custom(ref err)) if err.code() == -32602 && err.message() == "Invalid operation id"
+	);
+}
+
+#[tokio::test]
+async fn tx_broadcast_invalid_format() {
+	let (pool, _, api) = setup_api();
+
+	// Extrinsics that cannot be decoded are not broadcasted.
+	let operation_id: Option<String> =
+		api.call("transaction_unstable_broadcast", ["0xdeadbeef"]).await.unwrap();
+	assert!(operation_id.is_none());
+	assert_eq!(pool.status().ready, 0);
+
+	let err = api
+		.call::<_, ()>("transaction_unstable_stop", ["invalid_operation_id"])
+		.await
+		.unwrap_err();
+	assert_matches!(err,
+		Error::Call(CallError::Custom(ref err)) if err.code() == -32602 && err.message() == "Invalid operation id"
+	);
+}
+
+#[tokio::test]
+async fn tx_broadcast_stops_on_invalid_transaction() {
+	let (pool, broadcast_ids, api) = setup_api();
+
+	// Unsigned calls to `include_data` are rejected by the runtime.
+	let uxt = ExtrinsicBuilder::new_include_data(vec![]).unsigned().build();
+	let xt = hex_string(&uxt.encode());
+
+	let operation_id: Option<String> =
+		api.call("transaction_unstable_broadcast", [&xt]).await.unwrap();
+	assert!(operation_id.is_some());
+
+	// The broadcast ends on its own instead of resubmitting the transaction forever.
+	tokio::time::timeout(Duration::from_secs(60), async {
+		while !broadcast_ids.read().is_empty() {
+			tokio::time::sleep(Duration::from_millis(10)).await;
+		}
+	})
+	.await
+	.unwrap();
+	assert_eq!(pool.status().ready, 0);
+}
+
+#[tokio::test]
+async fn tx_broadcast_limits_operations() {
+	let (_pool, _, api) = setup_api();
+
+	let xt = hex_string(&uxt(AccountKeyring::Alice, 0).encode());
+	for _ in 0..transaction_broadcast::MAX_BROADCAST_OPERATIONS {
+		let operation_id: Option<String> =
+			api.call("transaction_unstable_broadcast", [&xt]).await.unwrap();
+		assert!(operation_id.is_some());
+	}
+
+	let err = api
+		.call::<_, Option<String>>("transaction_unstable_broadcast", [&xt])
+		.await
+		.unwrap_err();
+	assert_matches!(err,
+		Error::Call(CallError::Custom(ref err)) if err.code() == -32800 && err.message() == "Too many broadcast operations"
+	);
+}
@@ -0,0 +1,220 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! API implementation for broadcasting transactions.
+
+use crate::{
+	transaction::{api::TransactionBroadcastApiServer, error::ErrorBroadcast},
+	SubscriptionTaskExecutor,
+};
+use codec::Decode;
+use futures::{
+	future::{self, AbortHandle},
+	FutureExt, StreamExt,
+};
+use jsonrpsee::core::{async_trait, RpcResult};
+use log::debug;
+use parking_lot::RwLock;
+use rand::{distributions::Alphanumeric, Rng};
+use sc_client_api::BlockchainEvents;
+use sc_transaction_pool_api::{
+	error::{Error as PoolError, IntoPoolError},
+	BlockHash, TransactionFor, TransactionPool, TransactionSource, TransactionStatus,
+};
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_runtime::{generic, traits::Block as BlockT};
+use std::{
+	collections::{hash_map::Entry, HashMap},
+	sync::Arc,
+};
+
+/// Log target for this file.
+const LOG_TARGET: &str = "rpc-spec-v2";
+
+/// Currently we treat all RPC transactions as externals.
+const TX_SOURCE: TransactionSource = TransactionSource::External;
+
+/// The length of the generated operation IDs.
+const OPERATION_ID_LEN: usize = 16;
+
+/// The maximum number of broadcast operations running at the same time.
+pub(crate) const MAX_BROADCAST_OPERATIONS: usize = 64;
+
+/// An API for transaction RPC calls.
+pub struct TransactionBroadcast<Pool, Client> {
+	/// Substrate client.
+	client: Arc<Client>,
+	/// Transactions pool.
+	pool: Arc<Pool>,
+	/// Executor to spawn subscriptions.
+	executor: SubscriptionTaskExecutor,
+	/// The broadcast operation IDs.
+	pub(crate) broadcast_ids: Arc<RwLock<HashMap<String, BroadcastState>>>,
+}
+
+/// The state of a broadcast operation.
+pub(crate) struct BroadcastState {
+	/// Handle to abort the running broadcast task.
+	handle: AbortHandle,
+}
+
+impl<Pool, Client> TransactionBroadcast<Pool, Client> {
+	/// Creates a new [`TransactionBroadcast`].
+	pub fn new(client: Arc<Client>, pool: Arc<Pool>, executor: SubscriptionTaskExecutor) -> Self {
+		TransactionBroadcast { client, pool, executor, broadcast_ids: Default::default() }
+	}
+
+	/// Register a broadcast operation under a newly generated unique ID.
+	///
+	/// Returns `None` if the maximum number of broadcast operations is reached.
+	fn register_operation(&self, state: BroadcastState) -> Option<String> {
+		let mut broadcast_ids = self.broadcast_ids.write();
+		if broadcast_ids.len() >= MAX_BROADCAST_OPERATIONS {
+			return None
+		}
+
+		loop {
+			let id: String = rand::thread_rng()
+				.sample_iter(&Alphanumeric)
+				.take(OPERATION_ID_LEN)
+				.map(char::from)
+				.collect();
+
+			if let Entry::Vacant(entry) = broadcast_ids.entry(id.clone()) {
+				entry.insert(state);
+				return Some(id)
+			}
+		}
+	}
+}
+
+#[async_trait]
+impl<Pool, Client> TransactionBroadcastApiServer for TransactionBroadcast<Pool, Client>
+where
+	Pool: TransactionPool + Sync + Send + 'static,
+	Pool::Hash: Unpin,
+	<Pool::Block as BlockT>::Hash: Unpin,
+	Client: HeaderBackend<Pool::Block> + BlockchainEvents<Pool::Block> + Send + Sync + 'static,
+{
+	fn broadcast(&self, bytes: Bytes) -> RpcResult<Option<String>> {
+		// There is nothing we could do with an extrinsic of invalid format.
+		let Ok(decoded_extrinsic) = TransactionFor::<Pool>::decode(&mut &bytes[..]) else {
+			return Ok(None)
+		};
+
+		let client = self.client.clone();
+		let pool = self.pool.clone();
+		// The transaction is resubmitted on new best blocks after it leaves the pool.
+		let mut best_block_import_stream =
+			Box::pin(self.client.import_notification_stream().filter_map(
+				|notification| async move { notification.is_new_best.then_some(notification.hash) },
+			));
+
+		let broadcast_transaction_fut = async move {
+			loop {
+				let best_block_hash = client.info().best_hash;
+				let submit = pool.submit_and_watch(
+					&generic::BlockId::hash(best_block_hash),
+					TX_SOURCE,
+					decoded_extrinsic.clone(),
+				);
+
+				// Ready transactions of the pool are periodically propagated to
+				// peers by the transactions protocol of the network.
+				match submit.await {
+					Ok(mut stream) =>
+						while let Some(event) = stream.next().await {
+							if is_broadcast_complete::<Pool>(&event) {
+								return
+							}
+						},
+					Err(err) => {
+						debug!(
+							target: LOG_TARGET,
+							"[broadcast] Cannot submit transaction at {:?}: {:?}",
+							best_block_hash,
+							err
+						);
+
+						if is_permanently_invalid::<Pool>(err) {
+							return
+						}
+					},
+				}
+
+				// Wait for a new best block before submitting the transaction again.
+				if best_block_import_stream.next().await.is_none() {
+					return
+				}
+			}
+		};
+
+		let (fut, handle) = future::abortable(broadcast_transaction_fut);
+
+		let Some(id) = self.register_operation(BroadcastState { handle }) else {
+			return Err(ErrorBroadcast::TooManyOperations.into())
+		};
+
+		// Clean up the operation ID once the broadcast completed or was stopped.
+		let broadcast_ids = self.broadcast_ids.clone();
+		let drop_id = id.clone();
+		let fut = fut.map(move |_| {
+			broadcast_ids.write().remove(&drop_id);
+		});
+
+		self.executor.spawn("substrate-rpc-subscription", Some("rpc"), fut.boxed());
+		Ok(Some(id))
+	}
+
+	fn stop_broadcast(&self, operation_id: String) -> RpcResult<()> {
+		let Some(broadcast_state) = self.broadcast_ids.write().remove(&operation_id) else {
+			return Err(ErrorBroadcast::InvalidOperationID.into())
+		};
+
+		broadcast_state.handle.abort();
+		Ok(())
+	}
+}
+
+/// Returns true if the transaction no longer needs to be broadcasted.
+///
+/// The broadcast completes once the transaction is finalized, when its block was
+/// not finalized in time, or when it was replaced by a different transaction.
+/// Other terminal events cause the transaction to be submitted again on the
+/// next best block.
+fn is_broadcast_complete<Pool: TransactionPool>(
+	event: &TransactionStatus<Pool::Hash, BlockHash<Pool>>,
+) -> bool {
+	matches!(
+		event,
+		TransactionStatus::Finalized(_) |
+			TransactionStatus::FinalityTimeout(_) |
+			TransactionStatus::Usurped(_)
+	)
+}
+
+/// Returns true if the submission failed because the transaction can never become valid.
+///
+/// Transactions that merely exhausted the resources of the block may fit in a later one.
+fn is_permanently_invalid<Pool: TransactionPool>(err: Pool::Error) -> bool {
+	match err.into_pool_error() {
+		Ok(PoolError::InvalidTransaction(invalid)) => !invalid.exhausted_resources(),
+		_ => false,
+	}
+}
@@ -34,8 +34,8 @@ use jsonrpsee::{
 };
 use sc_rpc_api::DenyUnsafe;
 use sc_transaction_pool_api::{
-	error::IntoPoolError, BlockHash, InPoolTransaction, TransactionFor, TransactionPool,
-	TransactionSource, TxHash,
+	error::IntoPoolError, BlockHash, InPoolTransaction, RemoveLocalError, TransactionFor,
+	TransactionPool, TransactionSource, TxHash,
 };
 use sp_api::{ApiExt, ProvideRuntimeApi};
 use sp_blockchain::HeaderBackend;
@@ -75,6 +75,25 @@ impl<P, Client> Author<P, Client> {
 	}
 }
 
+impl<P: TransactionPool, Client> Author<P, Client> {
+	/// Resolves given extrinsics or extrinsic hashes to extrinsic hashes.
+	fn extrinsic_hashes(
+		&self,
+		bytes_or_hash: Vec<hash::ExtrinsicOrHash<TxHash<P>>>,
+	) -> Result<Vec<TxHash<P>>> {
+		bytes_or_hash
+			.into_iter()
+			.map(|x| match x {
+				hash::ExtrinsicOrHash::Hash(h) => Ok(h),
+				hash::ExtrinsicOrHash::Extrinsic(bytes) => {
+					let xt = Decode::decode(&mut &bytes[..])?;
+					Ok(self.pool.hash_of(&xt))
+				},
+			})
+			.collect()
+	}
+}
+
 /// Currently we treat all RPC transactions as externals.
 ///
 /// Possibly in the future we could allow opt-in for special treatment
@@ -162,16 +181,7 @@ where
 		bytes_or_hash: Vec<hash::ExtrinsicOrHash<TxHash<P>>>,
 	) -> RpcResult<Vec<TxHash<P>>> {
 		self.deny_unsafe.check_if_safe()?;
-		let hashes = bytes_or_hash
-			.into_iter()
-			.map(|x| match x {
-				hash::ExtrinsicOrHash::Hash(h) => Ok(h),
-				hash::ExtrinsicOrHash::Extrinsic(bytes) => {
-					let xt = Decode::decode(&mut &bytes[..])?;
-					Ok(self.pool.hash_of(&xt))
-				},
-			})
-			.collect::<Result<Vec<_>>>()?;
+		let hashes = self.extrinsic_hashes(bytes_or_hash)?;
 
 		Ok(self
 			.pool
@@ -181,6 +191,20 @@ where
 			.collect())
 	}
 
+	fn cancel_extrinsic(
+		&self,
+		bytes_or_hash: Vec<hash::ExtrinsicOrHash<TxHash<P>>>,
+	) -> RpcResult<Vec<TxHash<P>>> {
+		self.deny_unsafe.check_if_safe()?;
+		let hashes = self.extrinsic_hashes(bytes_or_hash)?;
+
+		let removed = self.pool.remove_local(&hashes).map_err(|err| match err {
+			RemoveLocalError::NotLocal(hashes) => Error::NotLocal(format!("{:?}", hashes)),
+			RemoveLocalError::Unsupported => Error::CancelUnsupported,
+		})?;
+		Ok(removed.into_iter().map(|tx| tx.hash().clone()).collect())
+	}
+
 	fn watch_extrinsic(&self, mut sink: SubscriptionSink, xt: Bytes) -> SubscriptionResult {
 		let best_block_hash = self.client.info().best_hash;
 		let dxt = match TransactionFor::<P>::decode(&mut &xt[..]).map_err(|e| Error::from(e)) {
@@ -215,6 +215,52 @@ async fn author_should_remove_extrinsics() {
 	assert_eq!(removed, vec![xt1_hash, xt2_hash, xt3_hash]);
 }
 
+#[tokio::test]
+async fn author_should_cancel_extrinsics() {
+	let setup = TestSetup::default();
+	let api = setup.author().into_rpc();
+
+	let xt_bytes = uxt(AccountKeyring::Alice, 0).encode();
+	let xt = to_hex(&xt_bytes, true);
+	let xt_hash = setup
+		.pool
+		.submit_one(
+			&generic::BlockId::hash(setup.client.info().best_hash),
+			TransactionSource::Local,
+			uxt(AccountKeyring::Alice, 0),
+		)
+		.await
+		.unwrap();
+	assert_eq!(setup.pool.status().ready, 1);
+
+	let cancelled: Vec<H256> = api
+		.call("author_cancelExtrinsic", vec![vec![hash::ExtrinsicOrHash::Hash(xt_hash)]])
+		.await
+		.unwrap();
+	assert_eq!(cancelled, vec![xt_hash]);
+	assert_eq!(setup.pool.status().ready, 0);
+
+	// Cancelled extrinsics are not banned and can be resubmitted.
+	let resubmitted: H256 = api.call("author_submitExtrinsic", [xt]).await.unwrap();
+	assert_eq!(resubmitted, xt_hash);
+}
+
+#[tokio::test]
+async fn author_should_not_cancel_external_extrinsics() {
+	let setup = TestSetup::default();
+	let api = setup.author().into_rpc();
+
+	let xt = to_hex(&uxt(AccountKeyring::Alice, 0).encode(), true);
+	let xt_hash: H256 = api.call("author_submitExtrinsic", [xt]).await.unwrap();
+	assert_eq!(setup.pool.status().ready, 1);
+
+	assert_matches!(
+		api.call::<_, Vec<H256>>("author_cancelExtrinsic", vec![vec![hash::ExtrinsicOrHash::Hash(xt_hash)]]).await,
+		Err(RpcError::Call(CallError::Custom(err))) if err.message().contains("Only locally submitted") && err.code() == 1003
+	);
+	assert_eq!(setup.pool.status().ready, 1);
+}
+
 #[tokio::test]
 async fn author_should_insert_key() {
 	let setup = TestSetup::default();
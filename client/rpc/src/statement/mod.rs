@@ -18,19 +18,34 @@
 
 //! Substrate statement store API.
 
+#[cfg(test)]
+mod tests;
+
+use crate::SubscriptionTaskExecutor;
 use codec::{Decode, Encode};
-use jsonrpsee::core::{async_trait, RpcResult};
+use futures::{FutureExt, StreamExt};
+use jsonrpsee::{
+	core::{async_trait, Error as JsonRpseeError, RpcResult},
+	types::SubscriptionResult,
+	SubscriptionSink,
+};
 /// Re-export the API for backward compatibility.
-pub use sc_rpc_api::statement::{error::Error, StatementApiServer};
+pub use sc_rpc_api::statement::{
+	error::Error, StatementApiServer, StatementPage, StatementQuery, TopicFilter,
+};
 use sc_rpc_api::DenyUnsafe;
 use sp_core::Bytes;
-use sp_statement_store::{StatementSource, SubmitResult};
+use sp_statement_store::{StatementFilter, StatementSource, SubmitResult};
 use std::sync::Arc;
 
+/// Maximum number of statements returned by a single `statement_query` call.
+const MAX_QUERY_LIMIT: u32 = 256;
+
 /// Statement store API
 pub struct StatementStore {
 	store: Arc<dyn sp_statement_store::StatementStore>,
 	deny_unsafe: DenyUnsafe,
+	executor: SubscriptionTaskExecutor,
 }
 
 impl StatementStore {
@@ -38,8 +53,9 @@ impl StatementStore {
 	pub fn new(
 		store: Arc<dyn sp_statement_store::StatementStore>,
 		deny_unsafe: DenyUnsafe,
+		executor: SubscriptionTaskExecutor,
 	) -> Self {
-		StatementStore { store, deny_unsafe }
+		StatementStore { store, deny_unsafe, executor }
 	}
 }
 
@@ -87,6 +103,31 @@ impl StatementApiServer for StatementStore {
 			.collect())
 	}
 
+	fn query(
+		&self,
+		query: StatementQuery,
+		cursor: Option<[u8; 32]>,
+		limit: Option<u32>,
+	) -> RpcResult<StatementPage> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let query = sp_statement_store::StatementQuery {
+			account: query.account,
+			channel: query.channel,
+			min_priority: query.min_priority,
+			max_priority: query.max_priority,
+		};
+		let limit = limit.unwrap_or(MAX_QUERY_LIMIT).clamp(1, MAX_QUERY_LIMIT);
+		let page = self
+			.store
+			.query(&query, cursor, limit as usize)
+			.map_err(|e| Error::StatementStore(e.to_string()))?;
+		Ok(StatementPage {
+			statements: page.statements.into_iter().map(|(_, s)| s.encode().into()).collect(),
+			next_cursor: page.next_cursor,
+		})
+	}
+
 	fn submit(&self, encoded: Bytes) -> RpcResult<()> {
 		let statement = Decode::decode(&mut &*encoded)
 			.map_err(|e| Error::StatementStore(format!("Eror decoding statement: {:?}", e)))?;
@@ -105,4 +146,33 @@ impl StatementApiServer for StatementStore {
 	fn remove(&self, hash: [u8; 32]) -> RpcResult<()> {
 		Ok(self.store.remove(&hash).map_err(|e| Error::StatementStore(e.to_string()))?)
 	}
+
+	fn subscribe(
+		&self,
+		mut sink: SubscriptionSink,
+		topic_filter: TopicFilter,
+		dest: Option<[u8; 32]>,
+	) -> SubscriptionResult {
+		let topics = match topic_filter {
+			TopicFilter::MatchAll(topics) => sp_statement_store::TopicFilter::MatchAll(topics),
+			TopicFilter::MatchAny(topics) => sp_statement_store::TopicFilter::MatchAny(topics),
+		};
+		let (existing, new) = match self.store.subscribe(StatementFilter { topics, dest }) {
+			Ok(subscription) => subscription,
+			Err(e) => {
+				let _ = sink.reject(JsonRpseeError::from(Error::StatementStore(e.to_string())));
+				return Ok(())
+			},
+		};
+
+		let stream = futures::stream::iter(existing)
+			.chain(new)
+			.map(|statement| Bytes::from(statement.encode()));
+		let fut = async move {
+			sink.pipe_from_stream(stream.boxed()).await;
+		};
+
+		self.executor.spawn("substrate-rpc-subscription", Some("rpc"), fut.boxed());
+		Ok(())
+	}
 }
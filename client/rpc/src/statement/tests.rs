@@ -0,0 +1,190 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use super::*;
+use crate::testing::{test_executor, timeout_secs};
+use futures::channel::mpsc;
+use parking_lot::Mutex;
+use sp_statement_store::{
+	Hash, NetworkPriority, Statement, StatementStore as _, StatementStream, Topic,
+};
+
+/// In-memory statement store that accepts all statements.
+#[derive(Default)]
+struct TestStore {
+	statements: Mutex<Vec<Statement>>,
+	subscribers: Mutex<Vec<(StatementFilter, mpsc::Sender<Statement>)>>,
+}
+
+impl sp_statement_store::StatementStore for TestStore {
+	fn statements(&self) -> sp_statement_store::Result<Vec<(Hash, Statement)>> {
+		Ok(self.statements.lock().iter().map(|s| (s.hash(), s.clone())).collect())
+	}
+
+	fn statement(&self, hash: &Hash) -> sp_statement_store::Result<Option<Statement>> {
+		Ok(self.statements.lock().iter().find(|s| &s.hash() == hash).cloned())
+	}
+
+	fn broadcasts(&self, _match_all_topics: &[Topic]) -> sp_statement_store::Result<Vec<Vec<u8>>> {
+		Ok(Vec::new())
+	}
+
+	fn posted(
+		&self,
+		_match_all_topics: &[Topic],
+		_dest: [u8; 32],
+	) -> sp_statement_store::Result<Vec<Vec<u8>>> {
+		Ok(Vec::new())
+	}
+
+	fn posted_clear(
+		&self,
+		_match_all_topics: &[Topic],
+		_dest: [u8; 32],
+	) -> sp_statement_store::Result<Vec<Vec<u8>>> {
+		Ok(Vec::new())
+	}
+
+	fn submit(&self, statement: Statement, _source: StatementSource) -> SubmitResult {
+		self.subscribers.lock().retain(|(filter, sender)| {
+			!filter.matches(&statement) || sender.try_send(statement.clone()).is_ok()
+		});
+		self.statements.lock().push(statement);
+		SubmitResult::New(NetworkPriority::High)
+	}
+
+	fn remove(&self, _hash: &Hash) -> sp_statement_store::Result<()> {
+		Ok(())
+	}
+
+	fn query(
+		&self,
+		query: &sp_statement_store::StatementQuery,
+		cursor: Option<Hash>,
+		limit: usize,
+	) -> sp_statement_store::Result<sp_statement_store::StatementPage> {
+		let mut statements: Vec<_> = self
+			.statements
+			.lock()
+			.iter()
+			.filter(|s| {
+				query.min_priority.map_or(true, |min| s.priority().unwrap_or(0) >= min) &&
+					query.max_priority.map_or(true, |max| s.priority().unwrap_or(0) <= max)
+			})
+			.map(|s| (s.hash(), s.clone()))
+			.filter(|(hash, _)| cursor.map_or(true, |cursor| hash > &cursor))
+			.collect();
+		statements.sort_by_key(|(hash, _)| *hash);
+		let next_cursor = if statements.len() > limit {
+			statements.truncate(limit);
+			statements.last().map(|(hash, _)| *hash)
+		} else {
+			None
+		};
+		Ok(sp_statement_store::StatementPage { statements, next_cursor })
+	}
+
+	fn subscribe(
+		&self,
+		filter: StatementFilter,
+	) -> sp_statement_store::Result<(Vec<Statement>, StatementStream)> {
+		let existing =
+			self.statements.lock().iter().filter(|s| filter.matches(s)).cloned().collect();
+		let (sender, receiver) = mpsc::channel(16);
+		self.subscribers.lock().push((filter, sender));
+		Ok((existing, receiver))
+	}
+}
+
+fn statement(data: u8, topics: &[Topic]) -> Statement {
+	let mut statement = Statement::new();
+	statement.set_plain_data(vec![data]);
+	for (i, topic) in topics.iter().enumerate() {
+		statement.set_topic(i, *topic);
+	}
+	statement
+}
+
+#[tokio::test]
+async fn query_is_paginated() {
+	let store = Arc::new(TestStore::default());
+	for i in 0..10 {
+		let mut s = statement(i, &[]);
+		s.set_priority(i as u32);
+		store.submit(s, StatementSource::Local);
+	}
+	let api = StatementStore::new(store, DenyUnsafe::No, test_executor()).into_rpc();
+
+	let query = StatementQuery { min_priority: Some(2), ..Default::default() };
+	let mut cursor: Option<[u8; 32]> = None;
+	let mut statements = Vec::new();
+	loop {
+		let page: StatementPage =
+			api.call("statement_query", (query.clone(), cursor, Some(3))).await.unwrap();
+		assert!(page.statements.len() <= 3);
+		statements.extend(page.statements);
+		cursor = page.next_cursor;
+		if cursor.is_none() {
+			break
+		}
+	}
+	assert_eq!(statements.len(), 8);
+}
+
+#[tokio::test]
+async fn query_is_unsafe() {
+	let store = Arc::new(TestStore::default());
+	let api = StatementStore::new(store, DenyUnsafe::Yes, test_executor()).into_rpc();
+
+	let res = api
+		.call::<_, StatementPage>(
+			"statement_query",
+			(StatementQuery::default(), None::<[u8; 32]>, None::<u32>),
+		)
+		.await;
+	assert!(res.is_err());
+}
+
+#[tokio::test]
+async fn subscribe_reports_existing_and_new_statements() {
+	let store = Arc::new(TestStore::default());
+	let api = StatementStore::new(store.clone(), DenyUnsafe::No, test_executor()).into_rpc();
+
+	let existing = statement(0, &[[0; 32]]);
+	store.submit(existing.clone(), StatementSource::Local);
+
+	let mut sub = api
+		.subscribe(
+			"statement_subscribe",
+			(TopicFilter::MatchAny(vec![[0; 32], [1; 32]]), None::<[u8; 32]>),
+		)
+		.await
+		.unwrap();
+
+	let (item, _) = timeout_secs(10, sub.next::<Bytes>()).await.unwrap().unwrap().unwrap();
+	assert_eq!(item, Bytes::from(existing.encode()));
+
+	// Statements not matching the filter are not reported.
+	store.submit(statement(1, &[[2; 32]]), StatementSource::Local);
+	let new = statement(2, &[[1; 32]]);
+	let encoded: Bytes = new.encode().into();
+	let _: () = api.call("statement_submit", [encoded.clone()]).await.unwrap();
+
+	let (item, _) = timeout_secs(10, sub.next::<Bytes>()).await.unwrap().unwrap().unwrap();
+	assert_eq!(item, encoded);
+}
@@ -33,7 +33,9 @@ use sp_runtime::traits::{self, Header as HeaderT};
 
 use self::error::Result;
 
-pub use self::helpers::{Health, NodeRole, PeerInfo, SyncState, SystemInfo};
+pub use self::helpers::{
+	GapSyncState, Health, NodeRole, PeerInfo, PeerReputation, SyncState, SystemInfo,
+};
 pub use sc_rpc_api::system::*;
 
 /// System API implementation
@@ -54,6 +56,8 @@ pub enum Request<B: traits::Block> {
 	LocalListenAddresses(oneshot::Sender<Vec<String>>),
 	/// Must return information about the peers we are connected to.
 	Peers(oneshot::Sender<Vec<PeerInfo<B::Hash, <B::Header as HeaderT>::Number>>>),
+	/// Must return the reputations of the peers known to the node.
+	PeerReputations(oneshot::Sender<Vec<PeerReputation>>),
 	/// Must return the state of the network.
 	NetworkState(oneshot::Sender<serde_json::Value>),
 	/// Must return any potential parse error.
@@ -131,6 +135,13 @@ impl<B: traits::Block> SystemApiServer<B::Hash, <B::Header as HeaderT>::Number>
 		rx.await.map_err(|e| JsonRpseeError::to_call_error(e))
 	}
 
+	async fn system_peer_reputations(&self) -> RpcResult<Vec<PeerReputation>> {
+		self.deny_unsafe.check_if_safe()?;
+		let (tx, rx) = oneshot::channel();
+		let _ = self.send_back.unbounded_send(Request::PeerReputations(tx));
+		rx.await.map_err(|e| JsonRpseeError::to_call_error(e))
+	}
+
 	async fn system_network_state(&self) -> RpcResult<JsonValue> {
 		self.deny_unsafe.check_if_safe()?;
 		let (tx, rx) = oneshot::channel();
@@ -25,7 +25,7 @@ use jsonrpsee::{
 	RpcModule,
 };
 use sc_network::{self, config::Role, PeerId};
-use sc_rpc_api::system::helpers::PeerInfo;
+use sc_rpc_api::system::helpers::{PeerInfo, PeerReputation};
 use sc_utils::mpsc::tracing_unbounded;
 use sp_core::H256;
 use std::{
@@ -85,6 +85,15 @@ fn api<T: Into<Option<Status>>>(sync: T) -> RpcModule<System<Block>> {
 					}
 					let _ = sender.send(peers);
 				},
+				Request::PeerReputations(sender) => {
+					let _ = sender.send(vec![PeerReputation {
+						peer_id: status.peer_id.to_base58(),
+						reputation: i32::MIN,
+						banned: true,
+						ban_reason: Some("Bad block".into()),
+						addresses: vec!["/ip4/198.51.100.19/tcp/30333".into()],
+					}]);
+				},
 				Request::NetworkState(sender) => {
 					let _ = sender.send(
 						serde_json::to_value(&sc_network::network_state::NetworkState {
@@ -124,6 +133,7 @@ fn api<T: Into<Option<Status>>>(sync: T) -> RpcModule<System<Block>> {
 						starting_block: 1,
 						current_block: 2,
 						highest_block: 3,
+						gap_sync: None,
 					});
 				},
 			};
@@ -267,6 +277,27 @@ async fn system_peers() {
 	);
 }
 
+#[tokio::test]
+async fn system_peer_reputations() {
+	let peer_id = PeerId::random();
+	let reputations: Vec<PeerReputation> =
+		api(Status { peer_id, peers: 1, is_syncing: false, is_dev: true })
+			.call("system_peerReputations", EmptyParams::new())
+			.await
+			.unwrap();
+
+	assert_eq!(
+		reputations,
+		vec![PeerReputation {
+			peer_id: peer_id.to_base58(),
+			reputation: i32::MIN,
+			banned: true,
+			ban_reason: Some("Bad block".into()),
+			addresses: vec!["/ip4/198.51.100.19/tcp/30333".into()],
+		}]
+	);
+}
+
 #[tokio::test]
 async fn system_network_state() {
 	use sc_network::network_state::NetworkState;
@@ -297,7 +328,10 @@ async fn system_node_roles() {
 async fn system_sync_state() {
 	let sync_state: SyncState<i32> =
 		api(None).call("system_syncState", EmptyParams::new()).await.unwrap();
-	assert_eq!(sync_state, SyncState { starting_block: 1, current_block: 2, highest_block: 3 });
+	assert_eq!(
+		sync_state,
+		SyncState { starting_block: 1, current_block: 2, highest_block: 3, gap_sync: None }
+	);
 }
 
 #[tokio::test]
@@ -45,7 +45,7 @@ use sc_network::{
 	config::{FullNetworkConfiguration, SyncMode},
 	NetworkService, NetworkStateInfo, NetworkStatusProvider,
 };
-use sc_network_bitswap::BitswapRequestHandler;
+use sc_network_bitswap::{BitswapConfig, BitswapRequestHandler};
 use sc_network_common::{role::Roles, sync::warp::WarpSyncParams};
 use sc_network_light::light_client_requests::handler::LightClientRequestHandler;
 use sc_network_sync::{
@@ -61,7 +61,11 @@ use sc_rpc::{
 	system::SystemApiServer,
 	DenyUnsafe, SubscriptionTaskExecutor,
 };
-use sc_rpc_spec_v2::{chain_head::ChainHeadApiServer, transaction::TransactionApiServer};
+use sc_rpc_spec_v2::{
+	archive::ArchiveApiServer,
+	chain_head::ChainHeadApiServer,
+	transaction::{TransactionApiServer, TransactionBroadcastApiServer},
+};
 use sc_telemetry::{telemetry, ConnectionMessage, Telemetry, TelemetryHandle, SUBSTRATE_INFO};
 use sc_transaction_pool_api::{MaintainedTransactionPool, TransactionPool};
 use sc_utils::mpsc::{tracing_unbounded, TracingUnboundedSender};
@@ -224,7 +228,7 @@ where
 				wasm_runtime_overrides: config.wasm_runtime_overrides.clone(),
 				no_genesis: matches!(
 					config.network.sync_mode,
-					SyncMode::LightState { .. } | SyncMode::Warp { .. }
+					SyncMode::LightState { .. } | SyncMode::Warp { .. } | SyncMode::Checkpoint
 				),
 				wasm_runtime_substitutes,
 			},
@@ -635,6 +639,13 @@ where
 	)
 	.into_rpc();
 
+	let transaction_broadcast_v2 = sc_rpc_spec_v2::transaction::TransactionBroadcast::new(
+		client.clone(),
+		transaction_pool.clone(),
+		task_executor.clone(),
+	)
+	.into_rpc();
+
 	// Maximum pinned blocks across all connections.
 	// This number is large enough to consider immediate blocks.
 	// Note: This should never exceed the `PINNING_CACHE_SIZE` from client/db.
@@ -656,6 +667,13 @@ where
 	)
 	.into_rpc();
 
+	let archive_v2 = sc_rpc_spec_v2::archive::Archive::new(
+		client.clone(),
+		backend.clone(),
+		client.info().genesis_hash,
+	)
+	.into_rpc();
+
 	let author = sc_rpc::author::Author::new(
 		client.clone(),
 		transaction_pool,
@@ -675,7 +693,11 @@ where
 
 	// Part of the RPC v2 spec.
 	rpc_api.merge(transaction_v2).map_err(|e| Error::Application(e.into()))?;
+	rpc_api
+		.merge(transaction_broadcast_v2)
+		.map_err(|e| Error::Application(e.into()))?;
 	rpc_api.merge(chain_head_v2).map_err(|e| Error::Application(e.into()))?;
+	rpc_api.merge(archive_v2).map_err(|e| Error::Application(e.into()))?;
 
 	// Part of the old RPC spec.
 	rpc_api.merge(chain).map_err(|e| Error::Application(e.into()))?;
@@ -758,6 +780,8 @@ where
 			SyncMode::LightState { .. } =>
 				return Err("Fast sync doesn't work for archive nodes".into()),
 			SyncMode::Warp => return Err("Warp sync doesn't work for archive nodes".into()),
+			SyncMode::Checkpoint =>
+				return Err("Checkpoint sync doesn't work for archive nodes".into()),
 			SyncMode::Full => {},
 		}
 	}
@@ -842,7 +866,13 @@ where
 	}
 
 	if config.network.ipfs_server {
-		let (handler, protocol_config) = BitswapRequestHandler::new(client.clone());
+		let (handler, protocol_config) = BitswapRequestHandler::with_config(
+			client.clone(),
+			BitswapConfig {
+				serve_blocks: config.network.ipfs_serve_blocks,
+				serve_trie_nodes: config.network.ipfs_serve_trie_nodes,
+			},
+		);
 		spawn_handle.spawn("bitswap-request-handler", Some("networking"), handler.run());
 		net_config.add_request_response_protocol(protocol_config);
 	}
@@ -18,7 +18,7 @@
 
 //! Service configuration.
 
-pub use sc_client_db::{BlocksPruning, Database, DatabaseSource, PruningMode};
+pub use sc_client_db::{BlocksPruning, Database, DatabaseSource, PruningConstraints, PruningMode};
 pub use sc_executor::{WasmExecutionMethod, WasmtimeInstantiationStrategy};
 pub use sc_network::{
 	config::{
@@ -39,6 +39,7 @@ use sp_core::crypto::SecretString;
 use std::{
 	io, iter,
 	net::SocketAddr,
+	num::NonZeroUsize,
 	path::{Path, PathBuf},
 };
 use tempfile::TempDir;
@@ -129,6 +130,10 @@ pub struct Configuration {
 	pub max_runtime_instances: usize,
 	/// Announce block automatically after they have been imported
 	pub announce_block: bool,
+	/// Number of blocks the import queue verifies in parallel ahead of their import.
+	///
+	/// If `None` is given blocks are verified sequentially.
+	pub import_verification_parallelism: Option<NonZeroUsize>,
 	/// Data path root for the configured chain.
 	pub data_path: PathBuf,
 	/// Base path of the configuration. This is shared between chains.
@@ -231,7 +236,10 @@ impl Configuration {
 	/// Returns true if the genesis state writting will be skipped while initializing the genesis
 	/// block.
 	pub fn no_genesis(&self) -> bool {
-		matches!(self.network.sync_mode, SyncMode::LightState { .. } | SyncMode::Warp { .. })
+		matches!(
+			self.network.sync_mode,
+			SyncMode::LightState { .. } | SyncMode::Warp { .. } | SyncMode::Checkpoint
+		)
 	}
 
 	/// Returns the database config for creating the backend.
@@ -70,11 +70,12 @@ pub use sc_chain_spec::{
 };
 
 pub use config::{
-	BasePath, BlocksPruning, Configuration, DatabaseSource, PruningMode, Role, RpcMethods, TaskType,
+	BasePath, BlocksPruning, Configuration, DatabaseSource, PruningConstraints, PruningMode, Role,
+	RpcMethods, TaskType,
 };
 pub use sc_chain_spec::{
 	ChainSpec, ChainType, Extension as ChainSpecExtension, GenericChainSpec, NoExtension,
-	Properties, RuntimeGenesis,
+	Properties, RuntimeGenesis, SyncCheckpoint,
 };
 
 pub use sc_consensus::ImportQueue;
@@ -279,6 +280,25 @@ pub async fn build_system_rpc_future<
 				},
 				Err(_) => log::error!("`SyncingEngine` shut down"),
 			},
+			sc_rpc::system::Request::PeerReputations(sender) => {
+				let reputations = network_service.peer_reputations().await;
+				if let Ok(reputations) = reputations {
+					let _ = sender.send(
+						reputations
+							.into_iter()
+							.map(|r| sc_rpc::system::PeerReputation {
+								peer_id: r.peer_id.to_base58(),
+								reputation: r.reputation,
+								banned: r.banned,
+								ban_reason: r.ban_reason,
+								addresses: r.addresses.iter().map(ToString::to_string).collect(),
+							})
+							.collect(),
+					);
+				} else {
+					break
+				}
+			},
 			sc_rpc::system::Request::NetworkState(sender) => {
 				let network_state = network_service.network_state().await;
 				if let Ok(network_state) = network_state {
@@ -329,15 +349,20 @@ pub async fn build_system_rpc_future<
 				let _ = sender.send(vec![node_role]);
 			},
 			sc_rpc::system::Request::SyncState(sender) => {
-				use sc_rpc::system::SyncState;
+				use sc_rpc::system::{GapSyncState, SyncState};
 
-				match sync_service.best_seen_block().await {
-					Ok(best_seen_block) => {
+				match sync_service.status().await {
+					Ok(status) => {
 						let best_number = client.info().best_number;
 						let _ = sender.send(SyncState {
 							starting_block,
 							current_block: best_number,
-							highest_block: best_seen_block.unwrap_or(best_number),
+							highest_block: status.best_seen_block.unwrap_or(best_number),
+							gap_sync: status.gap_sync.map(|gap_sync| GapSyncState {
+								starting_block: gap_sync.start,
+								current_block: gap_sync.best_imported,
+								target_block: gap_sync.target,
+							}),
 						});
 					},
 					Err(_) => log::error!("`SyncingEngine` shut down"),
@@ -265,6 +265,7 @@ fn node_config<
 		tracing_receiver: Default::default(),
 		max_runtime_instances: 8,
 		announce_block: true,
+		import_verification_parallelism: None,
 		base_path: BasePath::new(root.clone()),
 		data_path: root,
 		informant_output_format: Default::default(),
@@ -54,6 +54,7 @@ use pruning::{HaveBlock, RefWindow};
 use std::{
 	collections::{hash_map::Entry, HashMap},
 	fmt,
+	time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 const LOG_TARGET: &str = "state-db";
@@ -218,12 +219,34 @@ pub struct CommitSet<H: Hash> {
 	pub meta: ChangeSet<Vec<u8>>,
 }
 
-/// Pruning constraints. If none are specified pruning is
+/// Pruning constraints. A canonical block is pruned as soon as any of the specified constraints
+/// is violated. If none are specified pruning is as aggressive as possible, keeping only
+/// non-canonical states.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Constraints {
-	/// Maximum blocks. Defaults to 0 when unspecified, effectively keeping only non-canonical
-	/// states.
+	/// Maximum blocks. Defaults to 0 when unspecified and no other constraint is set, effectively
+	/// keeping only non-canonical states.
 	pub max_blocks: Option<u32>,
+	/// Maximum age of a block in the pruning window, measured from the time it was
+	/// canonicalized.
+	pub max_age: Option<Duration>,
+	/// Maximum size in bytes of the state kept besides the finalized state: the state inserted
+	/// by the non-canonical blocks plus the state inserted by the canonical blocks in the pruning
+	/// window. Only canonical blocks can be pruned, so the non-canonical state alone may exceed
+	/// this limit. This is an estimate based on the size of the inserted trie nodes; nodes shared
+	/// with older state are counted for every block that re-inserts them.
+	pub max_size: Option<u64>,
+}
+
+impl Constraints {
+	/// The block count constraint in effect, if any.
+	fn effective_max_blocks(&self) -> Option<u32> {
+		match self.max_blocks {
+			Some(max_blocks) => Some(max_blocks),
+			None if self.max_age.is_none() && self.max_size.is_none() => Some(0),
+			None => None,
+		}
+	}
 }
 
 /// Pruning mode.
@@ -240,7 +263,26 @@ pub enum PruningMode {
 impl PruningMode {
 	/// Create a mode that keeps given number of blocks.
 	pub fn blocks_pruning(n: u32) -> PruningMode {
-		PruningMode::Constrained(Constraints { max_blocks: Some(n) })
+		PruningMode::Constrained(Constraints { max_blocks: Some(n), ..Default::default() })
+	}
+
+	/// Create a mode that keeps the state of blocks canonicalized within the given duration.
+	pub fn age_pruning(max_age: Duration) -> PruningMode {
+		PruningMode::Constrained(Constraints {
+			max_blocks: None,
+			max_age: Some(max_age),
+			max_size: None,
+		})
+	}
+
+	/// Create a mode that keeps at most the given number of bytes of non-canonical state and
+	/// canonical state in the pruning window.
+	pub fn size_pruning(max_size: u64) -> PruningMode {
+		PruningMode::Constrained(Constraints {
+			max_blocks: None,
+			max_age: None,
+			max_size: Some(max_size),
+		})
 	}
 
 	/// Is this an archive (either ArchiveAll or ArchiveCanonical) pruning mode?
@@ -278,7 +320,7 @@ impl Default for PruningMode {
 
 impl Default for Constraints {
 	fn default() -> Self {
-		Self { max_blocks: Some(DEFAULT_MAX_BLOCK_CONSTRAINT) }
+		Self { max_blocks: Some(DEFAULT_MAX_BLOCK_CONSTRAINT), max_age: None, max_size: None }
 	}
 }
 
@@ -305,6 +347,8 @@ pub struct StateDbSync<BlockHash: Hash, Key: Hash, D: MetaDb> {
 	pruning: Option<RefWindow<BlockHash, Key, D>>,
 	pinned: HashMap<BlockHash, u32>,
 	ref_counting: bool,
+	/// Fixed unix time in seconds used instead of the system clock, for testing.
+	time_override: Option<u64>,
 }
 
 impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
@@ -316,13 +360,39 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
 		trace!(target: LOG_TARGET, "StateDb settings: {:?}. Ref-counting: {}", mode, ref_counting);
 
 		let non_canonical: NonCanonicalOverlay<BlockHash, Key> = NonCanonicalOverlay::new(&db)?;
-		let pruning: Option<RefWindow<BlockHash, Key, D>> = match mode {
-			PruningMode::Constrained(Constraints { max_blocks }) =>
-				Some(RefWindow::new(db, max_blocks.unwrap_or(0), ref_counting)?),
+		let pruning: Option<RefWindow<BlockHash, Key, D>> = match &mode {
+			PruningMode::Constrained(constraints) => {
+				if ref_counting && (constraints.max_age.is_some() || constraints.max_size.is_some())
+				{
+					log::warn!(
+						target: LOG_TARGET,
+						"Age or size based state pruning keeps the whole pruning window in memory \
+						with this database. Consider adding a block count constraint or switching \
+						your database to paritydb."
+					);
+				}
+				let window_size =
+					constraints.effective_max_blocks().unwrap_or(DEFAULT_MAX_BLOCK_CONSTRAINT);
+				Some(RefWindow::new(db, window_size, ref_counting)?)
+			},
 			PruningMode::ArchiveAll | PruningMode::ArchiveCanonical => None,
 		};
 
-		Ok(StateDbSync { mode, non_canonical, pruning, pinned: Default::default(), ref_counting })
+		Ok(StateDbSync {
+			mode,
+			non_canonical,
+			pruning,
+			pinned: Default::default(),
+			ref_counting,
+			time_override: None,
+		})
+	}
+
+	/// Current unix time in seconds.
+	fn timestamp(&self) -> u64 {
+		self.time_override.unwrap_or_else(|| {
+			SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+		})
 	}
 
 	fn insert_block(
@@ -358,8 +428,9 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
 		if self.mode == PruningMode::ArchiveCanonical {
 			commit.data.deleted.clear();
 		}
+		let now = self.timestamp();
 		if let Some(ref mut pruning) = self.pruning {
-			pruning.note_canonical(hash, number, &mut commit)?;
+			pruning.note_canonical(hash, number, now, &mut commit)?;
 		}
 		self.prune(&mut commit)?;
 		Ok(commit)
@@ -408,25 +479,41 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
 	}
 
 	fn prune(&mut self, commit: &mut CommitSet<Key>) -> Result<(), Error<D::Error>> {
+		let now = self.timestamp();
 		if let (&mut Some(ref mut pruning), PruningMode::Constrained(constraints)) =
 			(&mut self.pruning, &self.mode)
 		{
+			let max_blocks = constraints.effective_max_blocks();
+			let non_canonical_bytes = self.non_canonical.inserted_size();
 			loop {
-				if pruning.window_size() <= constraints.max_blocks.unwrap_or(0) as u64 {
+				let exceeds_blocks =
+					max_blocks.map_or(false, |max| pruning.window_size() > max as u64);
+				let exceeds_size = constraints.max_size.map_or(false, |max| {
+					pruning.window_bytes().saturating_add(non_canonical_bytes) > max
+				});
+				if !exceeds_blocks && !exceeds_size && constraints.max_age.is_none() {
 					break
 				}
 
-				let pinned = &self.pinned;
-				match pruning.next_hash() {
+				let (next_hash, canonicalized_at) = match pruning.next_block() {
 					// the block record is temporary unavailable, break and try next time
 					Err(Error::StateDb(StateDbError::BlockUnavailable)) => break,
-					res =>
-						if res?.map_or(false, |h| pinned.contains_key(&h)) {
-							break
-						},
+					res => match res? {
+						Some(next) => next,
+						None => break,
+					},
+				};
+				if !exceeds_blocks && !exceeds_size {
+					let age = now.saturating_sub(canonicalized_at);
+					if constraints.max_age.map_or(true, |max| age <= max.as_secs()) {
+						break
+					}
+				}
+				if self.pinned.contains_key(&next_hash) {
+					break
 				}
 				match pruning.prune_one(commit) {
-					// this branch should not reach as previous `next_hash` don't return error
+					// this branch should not reach as previous `next_block` don't return error
 					// keeping it for robustness
 					Err(Error::StateDb(StateDbError::BlockUnavailable)) => break,
 					res => res?,
@@ -658,6 +745,11 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDb<BlockHash, Key, D> {
 		*state_db = StateDbSync::new(state_db.mode.clone(), state_db.ref_counting, db)?;
 		Ok(())
 	}
+
+	#[cfg(test)]
+	fn set_time(&self, now: u64) {
+		self.db.write().time_override = Some(now);
+	}
 }
 
 /// The result return by `StateDb::is_pruned`
@@ -709,6 +801,7 @@ mod tests {
 		Constraints, Error, IsPruned, PruningMode, StateDb, StateDbError,
 	};
 	use sp_core::H256;
+	use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 	fn make_test_db(settings: PruningMode) -> (TestDb, StateDb<H256, H256, TestDb>) {
 		let mut db = make_db(&[91, 921, 922, 93, 94]);
@@ -788,8 +881,10 @@ mod tests {
 
 	#[test]
 	fn block_record_unavailable() {
-		let (mut db, state_db) =
-			make_test_db(PruningMode::Constrained(Constraints { max_blocks: Some(1) }));
+		let (mut db, state_db) = make_test_db(PruningMode::Constrained(Constraints {
+			max_blocks: Some(1),
+			..Default::default()
+		}));
 		// import 2 blocks
 		for i in &[5, 6] {
 			db.commit(
@@ -823,13 +918,19 @@ mod tests {
 
 	#[test]
 	fn prune_window_0() {
-		let (db, _) = make_test_db(PruningMode::Constrained(Constraints { max_blocks: Some(0) }));
+		let (db, _) = make_test_db(PruningMode::Constrained(Constraints {
+			max_blocks: Some(0),
+			..Default::default()
+		}));
 		assert!(db.data_eq(&make_db(&[21, 3, 922, 94])));
 	}
 
 	#[test]
 	fn prune_window_1() {
-		let (db, sdb) = make_test_db(PruningMode::Constrained(Constraints { max_blocks: Some(1) }));
+		let (db, sdb) = make_test_db(PruningMode::Constrained(Constraints {
+			max_blocks: Some(1),
+			..Default::default()
+		}));
 		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(0), 0), IsPruned::Pruned);
 		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(1), 1), IsPruned::Pruned);
 		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(21), 2), IsPruned::Pruned);
@@ -839,7 +940,10 @@ mod tests {
 
 	#[test]
 	fn prune_window_2() {
-		let (db, sdb) = make_test_db(PruningMode::Constrained(Constraints { max_blocks: Some(2) }));
+		let (db, sdb) = make_test_db(PruningMode::Constrained(Constraints {
+			max_blocks: Some(2),
+			..Default::default()
+		}));
 		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(0), 0), IsPruned::Pruned);
 		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(1), 1), IsPruned::Pruned);
 		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(21), 2), IsPruned::NotPruned);
@@ -847,6 +951,54 @@ mod tests {
 		assert!(db.data_eq(&make_db(&[1, 21, 3, 921, 922, 93, 94])));
 	}
 
+	#[test]
+	fn prune_by_size() {
+		// Every test block inserts a single 32 byte value. The state of the non-canonical block 4
+		// counts towards the limit, leaving room for a single canonical block.
+		let (db, sdb) = make_test_db(PruningMode::size_pruning(64));
+		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(1), 1), IsPruned::Pruned);
+		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(21), 2), IsPruned::Pruned);
+		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(3), 3), IsPruned::NotPruned);
+		assert!(db.data_eq(&make_db(&[21, 3, 922, 93, 94])));
+	}
+
+	#[test]
+	fn prune_by_size_without_non_canonical_state() {
+		let (mut db, sdb) = make_test_db(PruningMode::size_pruning(64));
+		// Once block 4 is canonicalized, two canonical blocks fit in the limit.
+		db.commit(&sdb.canonicalize_block(&H256::from_low_u64_be(4)).unwrap());
+		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(3), 3), IsPruned::NotPruned);
+		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(4), 4), IsPruned::NotPruned);
+		assert!(db.data_eq(&make_db(&[21, 3, 4, 922, 93, 94])));
+	}
+
+	#[test]
+	fn prune_by_age() {
+		let (mut db, sdb) = make_test_db(PruningMode::age_pruning(Duration::from_secs(60)));
+		// all blocks were canonicalized just now, nothing is old enough to be pruned
+		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(1), 1), IsPruned::NotPruned);
+		assert!(db.data_eq(&make_db(&[1, 21, 3, 91, 921, 922, 93, 94])));
+
+		let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+		sdb.set_time(now + 3600);
+		db.commit(&sdb.canonicalize_block(&H256::from_low_u64_be(4)).unwrap());
+		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(3), 3), IsPruned::Pruned);
+		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(4), 4), IsPruned::NotPruned);
+		assert!(db.data_eq(&make_db(&[21, 3, 4, 922, 94])));
+	}
+
+	#[test]
+	fn prune_by_any_violated_constraint() {
+		let (db, sdb) = make_test_db(PruningMode::Constrained(Constraints {
+			max_blocks: Some(2),
+			max_age: Some(Duration::from_secs(3600)),
+			max_size: Some(1024),
+		}));
+		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(1), 1), IsPruned::Pruned);
+		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(21), 2), IsPruned::NotPruned);
+		assert!(db.data_eq(&make_db(&[1, 21, 3, 921, 922, 93, 94])));
+	}
+
 	#[test]
 	fn detects_incompatible_mode() {
 		let mut db = make_db(&[]);
@@ -863,7 +1015,8 @@ mod tests {
 				)
 				.unwrap(),
 		);
-		let new_mode = PruningMode::Constrained(Constraints { max_blocks: Some(2) });
+		let new_mode =
+			PruningMode::Constrained(Constraints { max_blocks: Some(2), ..Default::default() });
 		let state_db_open_result: Result<(_, StateDb<H256, H256, TestDb>), _> =
 			StateDb::open(db.clone(), Some(new_mode), false, false);
 		assert!(state_db_open_result.is_err());
@@ -88,6 +88,12 @@ struct BlockOverlay<BlockHash: Hash, Key: Hash> {
 	journal_key: Vec<u8>,
 	inserted: Vec<Key>,
 	deleted: Vec<Key>,
+	/// Total size of the inserted values, in bytes.
+	inserted_size: u64,
+}
+
+fn inserted_size<Key>(inserted: &[(Key, DBValue)]) -> u64 {
+	inserted.iter().map(|(_, v)| v.len() as u64).sum()
 }
 
 fn insert_values<Key: Hash>(
@@ -202,6 +208,7 @@ impl<BlockHash: Hash, Key: Hash> NonCanonicalOverlay<BlockHash, Key> {
 							journal_key,
 							inserted,
 							deleted: record.deleted,
+							inserted_size: inserted_size(&record.inserted),
 						};
 						insert_values(&mut values, record.inserted);
 						trace!(
@@ -317,6 +324,7 @@ impl<BlockHash: Hash, Key: Hash> NonCanonicalOverlay<BlockHash, Key> {
 			journal_key: journal_key.clone(),
 			inserted,
 			deleted: changeset.deleted.clone(),
+			inserted_size: inserted_size(&changeset.inserted),
 		};
 		level.push(overlay);
 		self.parents.insert(hash.clone(), parent_hash.clone());
@@ -469,6 +477,16 @@ impl<BlockHash: Hash, Key: Hash> NonCanonicalOverlay<BlockHash, Key> {
 		self.values.get(key).map(|v| v.1.clone())
 	}
 
+	/// Total size in bytes of the values inserted by the non-canonical blocks. Values shared by
+	/// several blocks are counted for each of them.
+	pub fn inserted_size(&self) -> u64 {
+		self.levels
+			.iter()
+			.flat_map(|level| level.blocks.iter())
+			.map(|overlay| overlay.inserted_size)
+			.sum()
+	}
+
 	/// Check if the block is in the canonicalization queue.
 	pub fn have_block(&self, hash: &BlockHash) -> bool {
 		self.parents.contains_key(hash)
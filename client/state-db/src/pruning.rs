@@ -22,7 +22,8 @@
 //! There is also a global index of node key to block number.
 //! If a node is re-inserted into the window it gets removed from
 //! the death list.
-//! The changes are journaled in the DB.
+//! The changes are journaled in the DB, together with the time each block was canonicalized
+//! and the size of the state it inserted, which are used by the age and size constraints.
 
 use crate::{
 	noncanonical::LAST_CANONICAL, to_meta_key, CommitSet, Error, Hash, MetaDb, StateDbError,
@@ -30,10 +31,14 @@ use crate::{
 };
 use codec::{Decode, Encode};
 use log::trace;
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::{
+	collections::{HashMap, HashSet, VecDeque},
+	time::{SystemTime, UNIX_EPOCH},
+};
 
 pub(crate) const LAST_PRUNED: &[u8] = b"last_pruned";
 const PRUNING_JOURNAL: &[u8] = b"pruning_journal";
+const PRUNING_JOURNAL_INFO: &[u8] = b"pruning_journal_info";
 
 /// See module documentation.
 pub struct RefWindow<BlockHash: Hash, Key: Hash, D: MetaDb> {
@@ -42,6 +47,11 @@ pub struct RefWindow<BlockHash: Hash, Key: Hash, D: MetaDb> {
 	queue: DeathRowQueue<BlockHash, Key, D>,
 	/// Block number that is next to be pruned.
 	base: u64,
+	/// Total size of the state inserted by the blocks in the pruning window.
+	window_bytes: u64,
+	/// Time the window was opened, used as the canonicalization time of blocks journaled
+	/// without a `BlockInfo` record.
+	opened_at: u64,
 }
 
 /// `DeathRowQueue` used to keep track of blocks in the pruning window, there are two flavors:
@@ -97,7 +107,8 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> DeathRowQueue<BlockHash, Key, D> {
 						record.inserted.len(),
 						record.deleted.len(),
 					);
-					queue.import(base, block, record);
+					let info = load_block_info_from_db::<D>(db, block)?;
+					queue.import(base, block, record, info);
 				},
 				None => break,
 			}
@@ -127,7 +138,13 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> DeathRowQueue<BlockHash, Key, D> {
 	}
 
 	/// import a new block to the back of the queue
-	fn import(&mut self, base: u64, num: u64, journal_record: JournalRecord<BlockHash, Key>) {
+	fn import(
+		&mut self,
+		base: u64,
+		num: u64,
+		journal_record: JournalRecord<BlockHash, Key>,
+		info: Option<BlockInfo>,
+	) {
 		let JournalRecord { hash, inserted, deleted } = journal_record;
 		trace!(target: LOG_TARGET, "Importing {}, base={}", num, base);
 		match self {
@@ -136,7 +153,11 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> DeathRowQueue<BlockHash, Key, D> {
 				// cache.
 				if num == base + cache.len() as u64 && cache.len() < *cache_capacity {
 					trace!(target: LOG_TARGET, "Adding to DB backed cache {:?} (#{})", hash, num);
-					cache.push_back(DeathRow { hash, deleted: deleted.into_iter().collect() });
+					cache.push_back(DeathRow {
+						hash,
+						deleted: deleted.into_iter().collect(),
+						info,
+					});
 				}
 				*last = Some(num);
 			},
@@ -152,7 +173,11 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> DeathRowQueue<BlockHash, Key, D> {
 				for k in deleted.iter() {
 					death_index.insert(k.clone(), imported_block);
 				}
-				death_rows.push_back(DeathRow { hash, deleted: deleted.into_iter().collect() });
+				death_rows.push_back(DeathRow {
+					hash,
+					deleted: deleted.into_iter().collect(),
+					info,
+				});
 			},
 		}
 	}
@@ -219,6 +244,28 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> DeathRowQueue<BlockHash, Key, D> {
 		}
 	}
 
+	/// Return the total size of the state inserted by the blocks in the pruning window
+	fn window_bytes(&self, base: u64) -> Result<u64, Error<D::Error>> {
+		match self {
+			DeathRowQueue::DbBacked { db, last, .. } => {
+				let mut window_bytes = 0;
+				if let Some(last) = *last {
+					for block in base..=last {
+						if let Some(info) = load_block_info_from_db::<D>(db, block)? {
+							window_bytes += info.size;
+						}
+					}
+				}
+				Ok(window_bytes)
+			},
+			DeathRowQueue::Mem { death_rows, .. } => Ok(death_rows
+				.iter()
+				.filter_map(|row| row.info.as_ref())
+				.map(|info| info.size)
+				.sum()),
+		}
+	}
+
 	/// Return the number of block in the pruning window
 	fn len(&self, base: u64) -> u64 {
 		match self {
@@ -256,16 +303,29 @@ fn load_death_row_from_db<BlockHash: Hash, Key: Hash, D: MetaDb>(
 	match db.get_meta(&journal_key).map_err(Error::Db)? {
 		Some(record) => {
 			let JournalRecord { hash, deleted, .. } = Decode::decode(&mut record.as_slice())?;
-			Ok(Some(DeathRow { hash, deleted: deleted.into_iter().collect() }))
+			let info = load_block_info_from_db::<D>(db, block)?;
+			Ok(Some(DeathRow { hash, deleted: deleted.into_iter().collect(), info }))
 		},
 		None => Ok(None),
 	}
 }
 
+fn load_block_info_from_db<D: MetaDb>(
+	db: &D,
+	block: u64,
+) -> Result<Option<BlockInfo>, Error<D::Error>> {
+	match db.get_meta(&to_block_info_key(block)).map_err(Error::Db)? {
+		Some(info) => Ok(Some(Decode::decode(&mut info.as_slice())?)),
+		None => Ok(None),
+	}
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 struct DeathRow<BlockHash: Hash, Key: Hash> {
 	hash: BlockHash,
 	deleted: HashSet<Key>,
+	/// `None` for blocks journaled before block info was recorded.
+	info: Option<BlockInfo>,
 }
 
 #[derive(Encode, Decode, Default)]
@@ -275,10 +335,24 @@ struct JournalRecord<BlockHash: Hash, Key: Hash> {
 	deleted: Vec<Key>,
 }
 
+/// Additional per-block data used by the age and size constraints. Stored separately from the
+/// `JournalRecord` so that existing journals remain readable.
+#[derive(Encode, Decode, Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct BlockInfo {
+	/// Unix time in seconds when the block was canonicalized.
+	canonicalized_at: u64,
+	/// Total size of the state values inserted by the block.
+	size: u64,
+}
+
 fn to_journal_key(block: u64) -> Vec<u8> {
 	to_meta_key(PRUNING_JOURNAL, &block)
 }
 
+fn to_block_info_key(block: u64) -> Vec<u8> {
+	to_meta_key(PRUNING_JOURNAL_INFO, &block)
+}
+
 /// The result return by `RefWindow::have_block`
 #[derive(Debug, PartialEq, Eq)]
 pub enum HaveBlock {
@@ -347,25 +421,40 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 			DeathRowQueue::new_db_backed(db, base, last, window_size)?
 		};
 
-		Ok(RefWindow { queue, base })
+		let window_bytes = queue.window_bytes(base)?;
+		let opened_at = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+
+		Ok(RefWindow { queue, base, window_bytes, opened_at })
 	}
 
 	pub fn window_size(&self) -> u64 {
 		self.queue.len(self.base) as u64
 	}
 
+	/// Total size of the state inserted by the blocks in the pruning window.
+	pub fn window_bytes(&self) -> u64 {
+		self.window_bytes
+	}
+
 	/// Get the hash of the next pruning block
+	#[cfg(test)]
 	pub fn next_hash(&mut self) -> Result<Option<BlockHash>, Error<D::Error>> {
-		let res = match &mut self.queue {
+		Ok(self.next_block()?.map(|(hash, _)| hash))
+	}
+
+	/// Get the hash and the canonicalization time of the next pruning block
+	pub fn next_block(&mut self) -> Result<Option<(BlockHash, u64)>, Error<D::Error>> {
+		let opened_at = self.opened_at;
+		let row = match &mut self.queue {
 			DeathRowQueue::DbBacked { db, cache, cache_capacity, .. } => {
 				if cache.is_empty() {
 					DeathRowQueue::load_batch_from_db(db, cache, self.base, *cache_capacity)?;
 				}
-				cache.front().map(|r| r.hash.clone())
+				cache.front()
 			},
-			DeathRowQueue::Mem { death_rows, .. } => death_rows.front().map(|r| r.hash.clone()),
+			DeathRowQueue::Mem { death_rows, .. } => death_rows.front(),
 		};
-		Ok(res)
+		Ok(row.map(|r| (r.hash.clone(), r.info.map_or(opened_at, |info| info.canonicalized_at))))
 	}
 
 	fn is_empty(&self) -> bool {
@@ -390,6 +479,10 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 			commit.data.deleted.extend(pruned.deleted.into_iter());
 			commit.meta.inserted.push((to_meta_key(LAST_PRUNED, &()), index.encode()));
 			commit.meta.deleted.push(to_journal_key(self.base));
+			commit.meta.deleted.push(to_block_info_key(self.base));
+			if let Some(info) = pruned.info {
+				self.window_bytes = self.window_bytes.saturating_sub(info.size);
+			}
 			self.base += 1;
 			Ok(())
 		} else {
@@ -398,11 +491,13 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 		}
 	}
 
-	/// Add a change set to the window. Creates a journal record and pushes it to `commit`
+	/// Add a change set to the window. Creates a journal record and pushes it to `commit`.
+	/// `now` is the current unix time in seconds.
 	pub fn note_canonical(
 		&mut self,
 		hash: &BlockHash,
 		number: u64,
+		now: u64,
 		commit: &mut CommitSet<Key>,
 	) -> Result<(), Error<D::Error>> {
 		if self.base == 0 && self.is_empty() && number > 0 {
@@ -417,21 +512,28 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 		} else {
 			Default::default()
 		};
+		let size = commit.data.inserted.iter().map(|(_, v)| v.len() as u64).sum();
+		let info = BlockInfo { canonicalized_at: now, size };
 		let deleted = std::mem::take(&mut commit.data.deleted);
 		let journal_record = JournalRecord { hash: hash.clone(), inserted, deleted };
 		commit.meta.inserted.push((to_journal_key(number), journal_record.encode()));
-		self.queue.import(self.base, number, journal_record);
+		commit.meta.inserted.push((to_block_info_key(number), info.encode()));
+		self.window_bytes += size;
+		self.queue.import(self.base, number, journal_record, Some(info));
 		Ok(())
 	}
 }
 
 #[cfg(test)]
 mod tests {
-	use super::{to_journal_key, DeathRowQueue, HaveBlock, JournalRecord, RefWindow, LAST_PRUNED};
+	use super::{
+		to_block_info_key, to_journal_key, DeathRowQueue, HaveBlock, JournalRecord, RefWindow,
+		LAST_PRUNED,
+	};
 	use crate::{
 		noncanonical::LAST_CANONICAL,
 		test::{make_commit, make_db, TestDb},
-		to_meta_key, CommitSet, Error, Hash, StateDbError, DEFAULT_MAX_BLOCK_CONSTRAINT,
+		to_meta_key, CommitSet, Error, Hash, MetaDb, StateDbError, DEFAULT_MAX_BLOCK_CONSTRAINT,
 	};
 	use codec::Encode;
 	use sp_core::H256;
@@ -441,6 +543,7 @@ mod tests {
 		let restored: RefWindow<H256, H256, TestDb> =
 			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, count_insertions).unwrap();
 		assert_eq!(pruning.base, restored.base);
+		assert_eq!(pruning.window_bytes, restored.window_bytes);
 		assert_eq!(pruning.queue.get_mem_queue_state(), restored.queue.get_mem_queue_state());
 	}
 
@@ -478,7 +581,7 @@ mod tests {
 			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, true).unwrap();
 		let mut commit = make_commit(&[4, 5], &[1, 3]);
 		let hash = H256::random();
-		pruning.note_canonical(&hash, 0, &mut commit).unwrap();
+		pruning.note_canonical(&hash, 0, 0, &mut commit).unwrap();
 		db.commit(&commit);
 		assert_eq!(pruning.have_block(&hash, 0), HaveBlock::Yes);
 		assert_eq!(pruning.have_block(&hash, 0), HaveBlock::Yes);
@@ -501,16 +604,42 @@ mod tests {
 		assert_eq!(pruning.base, 1);
 	}
 
+	#[test]
+	fn window_bytes_tracks_inserted_state() {
+		let mut db = make_db(&[1, 2, 3]);
+		let mut pruning: RefWindow<H256, H256, TestDb> =
+			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, false).unwrap();
+		let mut commit = make_commit(&[4, 5], &[1]);
+		pruning.note_canonical(&H256::random(), 0, 10, &mut commit).unwrap();
+		db.commit(&commit);
+		let mut commit = make_commit(&[6], &[2]);
+		pruning.note_canonical(&H256::random(), 1, 20, &mut commit).unwrap();
+		db.commit(&commit);
+		assert_eq!(pruning.window_bytes(), 96);
+		assert_eq!(pruning.next_block().unwrap().map(|(_, at)| at), Some(10));
+
+		let restored: RefWindow<H256, H256, TestDb> =
+			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, false).unwrap();
+		assert_eq!(restored.window_bytes(), 96);
+
+		let mut commit = CommitSet::default();
+		pruning.prune_one(&mut commit).unwrap();
+		db.commit(&commit);
+		assert_eq!(pruning.window_bytes(), 32);
+		assert_eq!(pruning.next_block().unwrap().map(|(_, at)| at), Some(20));
+		assert!(db.get_meta(&to_block_info_key(0)).unwrap().is_none());
+	}
+
 	#[test]
 	fn prune_two() {
 		let mut db = make_db(&[1, 2, 3]);
 		let mut pruning: RefWindow<H256, H256, TestDb> =
 			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, true).unwrap();
 		let mut commit = make_commit(&[4], &[1]);
-		pruning.note_canonical(&H256::random(), 0, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 0, 0, &mut commit).unwrap();
 		db.commit(&commit);
 		let mut commit = make_commit(&[5], &[2]);
-		pruning.note_canonical(&H256::random(), 1, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 1, 0, &mut commit).unwrap();
 		db.commit(&commit);
 		assert!(db.data_eq(&make_db(&[1, 2, 3, 4, 5])));
 
@@ -533,10 +662,10 @@ mod tests {
 		let mut pruning: RefWindow<H256, H256, TestDb> =
 			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, true).unwrap();
 		let mut commit = make_commit(&[4], &[1]);
-		pruning.note_canonical(&H256::random(), 0, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 0, 0, &mut commit).unwrap();
 		db.commit(&commit);
 		let mut commit = make_commit(&[5], &[2]);
-		pruning.note_canonical(&H256::random(), 1, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 1, 0, &mut commit).unwrap();
 		db.commit(&commit);
 		assert!(db.data_eq(&make_db(&[1, 2, 3, 4, 5])));
 		let mut commit = CommitSet::default();
@@ -556,13 +685,13 @@ mod tests {
 		let mut pruning: RefWindow<H256, H256, TestDb> =
 			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, true).unwrap();
 		let mut commit = make_commit(&[], &[2]);
-		pruning.note_canonical(&H256::random(), 0, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 0, 0, &mut commit).unwrap();
 		db.commit(&commit);
 		let mut commit = make_commit(&[2], &[]);
-		pruning.note_canonical(&H256::random(), 1, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 1, 0, &mut commit).unwrap();
 		db.commit(&commit);
 		let mut commit = make_commit(&[], &[2]);
-		pruning.note_canonical(&H256::random(), 2, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 2, 0, &mut commit).unwrap();
 		db.commit(&commit);
 		assert!(db.data_eq(&make_db(&[1, 2, 3])));
 
@@ -588,13 +717,13 @@ mod tests {
 		let mut pruning: RefWindow<H256, H256, TestDb> =
 			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, true).unwrap();
 		let mut commit = make_commit(&[], &[2]);
-		pruning.note_canonical(&H256::random(), 0, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 0, 0, &mut commit).unwrap();
 		db.commit(&commit);
 		let mut commit = make_commit(&[2], &[]);
-		pruning.note_canonical(&H256::random(), 1, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 1, 0, &mut commit).unwrap();
 		db.commit(&commit);
 		let mut commit = make_commit(&[], &[2]);
-		pruning.note_canonical(&H256::random(), 2, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 2, 0, &mut commit).unwrap();
 		db.commit(&commit);
 		assert!(db.data_eq(&make_db(&[1, 2, 3])));
 
@@ -618,13 +747,13 @@ mod tests {
 		let mut pruning: RefWindow<H256, H256, TestDb> =
 			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, false).unwrap();
 		let mut commit = make_commit(&[], &[2]);
-		pruning.note_canonical(&H256::random(), 0, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 0, 0, &mut commit).unwrap();
 		db.commit(&commit);
 		let mut commit = make_commit(&[2], &[]);
-		pruning.note_canonical(&H256::random(), 1, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 1, 0, &mut commit).unwrap();
 		db.commit(&commit);
 		let mut commit = make_commit(&[], &[2]);
-		pruning.note_canonical(&H256::random(), 2, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 2, 0, &mut commit).unwrap();
 		db.commit(&commit);
 		assert!(db.data_eq(&make_db(&[1, 2, 3])));
 
@@ -720,7 +849,7 @@ mod tests {
 		// queue size and content should match
 		for i in 0..(cache_capacity + 10) {
 			let mut commit = make_commit(&[], &[]);
-			pruning.note_canonical(&(i as u64), i as u64, &mut commit).unwrap();
+			pruning.note_canonical(&(i as u64), i as u64, 0, &mut commit).unwrap();
 			push_last_canonicalized(i as u64, &mut commit);
 			db.commit(&commit);
 			// blocks will fill the cache first
@@ -744,7 +873,12 @@ mod tests {
 		// won't keep the new block in memory
 		let mut commit = CommitSet::default();
 		pruning
-			.note_canonical(&(cache_capacity as u64 + 10), cache_capacity as u64 + 10, &mut commit)
+			.note_canonical(
+				&(cache_capacity as u64 + 10),
+				cache_capacity as u64 + 10,
+				0,
+				&mut commit,
+			)
 			.unwrap();
 		assert_eq!(pruning.window_size(), cache_capacity as u64 + 11);
 		let (cache, _) = pruning.queue.get_db_backed_queue_state().unwrap();
@@ -792,7 +926,7 @@ mod tests {
 		// import blocks
 		for i in 0..(cache_capacity as u64 * 2 + 10) {
 			let mut commit = make_commit(&[], &[]);
-			pruning.note_canonical(&i, i, &mut commit).unwrap();
+			pruning.note_canonical(&i, i, 0, &mut commit).unwrap();
 			push_last_canonicalized(i as u64, &mut commit);
 			db.commit(&commit);
 		}
@@ -842,14 +976,14 @@ mod tests {
 		// import blocks and commit to db
 		let mut commit = make_commit(&[], &[]);
 		for i in 0..(cache_capacity + 10) {
-			pruning.note_canonical(&i, i, &mut commit).unwrap();
+			pruning.note_canonical(&i, i, 0, &mut commit).unwrap();
 		}
 		db.commit(&commit);
 
 		// import a block but not commit to db yet
 		let mut pending_commit = make_commit(&[], &[]);
 		let index = cache_capacity + 10;
-		pruning.note_canonical(&index, index, &mut pending_commit).unwrap();
+		pruning.note_canonical(&index, index, 0, &mut pending_commit).unwrap();
 
 		let mut commit = make_commit(&[], &[]);
 		// prune blocks that had committed to db
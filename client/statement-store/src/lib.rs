@@ -43,6 +43,13 @@
 //! explicitly with the `remove` function) the statement is marked as expired. Expired statements
 //! can't be added to the store for `Options::purge_after_sec` seconds. This is to prevent old
 //! statements from being propagated on the network.
+//!
+//! Statement subscriptions.
+//!
+//! Subscribers provide a `StatementFilter` and receive all known statements matching it, followed
+//! by the matching statements accepted with `submit`. Subscribers are registered while the index
+//! is locked, so no accepted statement is missed or reported twice. Subscribers that fall more
+//! than `SUBSCRIPTION_BUFFER_SIZE` statements behind are dropped, which ends their stream.
 
 #![warn(missing_docs)]
 #![warn(unused_extern_crates)]
@@ -51,8 +58,9 @@ mod metrics;
 
 pub use sp_statement_store::{Error, StatementStore, MAX_TOPICS};
 
+use futures::channel::mpsc;
 use metrics::MetricsLink as PrometheusMetrics;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use prometheus_endpoint::Registry as PrometheusRegistry;
 use sc_keystore::LocalKeystore;
 use sp_api::ProvideRuntimeApi;
@@ -64,10 +72,12 @@ use sp_statement_store::{
 		InvalidStatement, StatementSource, StatementStoreExt, ValidStatement, ValidateStatement,
 	},
 	AccountId, BlockHash, Channel, DecryptionKey, Hash, NetworkPriority, Proof, Result, Statement,
-	SubmitResult, Topic,
+	StatementFilter, StatementPage, StatementQuery, StatementStream, SubmitResult, Topic,
+	TopicFilter,
 };
 use std::{
 	collections::{BTreeMap, HashMap, HashSet},
+	ops::Bound,
 	sync::Arc,
 };
 
@@ -81,6 +91,8 @@ const DEFAULT_MAX_TOTAL_STATEMENTS: usize = 8192;
 const DEFAULT_MAX_TOTAL_SIZE: usize = 64 * 1024 * 1024;
 
 const MAINTENANCE_PERIOD: std::time::Duration = std::time::Duration::from_secs(30);
+/// Number of statements buffered for a subscriber before it is dropped.
+const SUBSCRIPTION_BUFFER_SIZE: usize = 1024;
 
 mod col {
 	pub const META: u8 = 0;
@@ -154,13 +166,57 @@ struct Index {
 	by_topic: HashMap<Topic, HashSet<Hash>>,
 	by_dec_key: HashMap<Option<DecryptionKey>, HashSet<Hash>>,
 	topics_and_keys: HashMap<Hash, ([Option<Topic>; MAX_TOPICS], Option<DecryptionKey>)>,
-	entries: HashMap<Hash, (AccountId, Priority, usize)>,
+	// Ordered by hash to allow paginated queries to resume from a cursor.
+	entries: BTreeMap<Hash, (AccountId, Priority, usize)>,
 	expired: HashMap<Hash, u64>, // Value is expiration timestamp.
 	accounts: HashMap<AccountId, StatementsForAccount>,
 	options: Options,
 	total_size: usize,
 }
 
+/// Statement subscribers.
+struct Subscriptions {
+	subscribers: Vec<(StatementFilter, mpsc::Sender<Statement>)>,
+	/// Number of statements buffered for each subscriber.
+	buffer_size: usize,
+}
+
+impl Default for Subscriptions {
+	fn default() -> Self {
+		Subscriptions { subscribers: Vec::new(), buffer_size: SUBSCRIPTION_BUFFER_SIZE }
+	}
+}
+
+impl Subscriptions {
+	fn subscribe(&mut self, filter: StatementFilter) -> StatementStream {
+		let (sender, receiver) = mpsc::channel(self.buffer_size);
+		self.subscribers.push((filter, sender));
+		receiver
+	}
+
+	fn notify(&mut self, statement: &Statement) {
+		self.subscribers.retain_mut(|(filter, sender)| {
+			if !filter.matches(statement) {
+				// Drop subscribers that are no longer listening.
+				return !sender.is_closed()
+			}
+			match sender.try_send(statement.clone()) {
+				Ok(()) => true,
+				Err(e) => {
+					if e.is_full() {
+						log::debug!(
+							target: LOG_TARGET,
+							"Dropping lagging subscriber: {:?}",
+							filter,
+						);
+					}
+					false
+				},
+			}
+		});
+	}
+}
+
 struct ClientWrapper<Block, Client> {
 	client: Arc<Client>,
 	_block: std::marker::PhantomData<Block>,
@@ -206,6 +262,7 @@ pub struct Store {
 	// Used for testing
 	time_override: Option<u64>,
 	metrics: PrometheusMetrics,
+	subscriptions: Mutex<Subscriptions>,
 }
 
 enum IndexQuery {
@@ -305,6 +362,68 @@ impl Index {
 		Ok(())
 	}
 
+	fn query(
+		&self,
+		query: &StatementQuery,
+		cursor: Option<Hash>,
+		limit: usize,
+	) -> (Vec<Hash>, Option<Hash>) {
+		let in_range = |priority: &Priority| {
+			query.min_priority.map_or(true, |min| priority.0 >= min) &&
+				query.max_priority.map_or(true, |max| priority.0 <= max)
+		};
+		let after_cursor = |hash: &Hash| cursor.map_or(true, |cursor| hash > &cursor);
+
+		// Fetch one more item than requested to know whether there is a next page.
+		let mut hashes: Vec<Hash> = match query.account {
+			// The number of statements of an account is bounded by its allowance, sorting them is
+			// cheap.
+			Some(account) => {
+				let mut hashes: Vec<Hash> = self
+					.accounts
+					.get(&account)
+					.into_iter()
+					.flat_map(|account_rec| account_rec.by_priority.iter())
+					.filter(|(key, (c, _))| {
+						query.channel.map_or(true, |channel| c == &Some(channel)) &&
+							in_range(&key.priority)
+					})
+					.map(|(key, _)| key.hash)
+					.filter(after_cursor)
+					.collect();
+				hashes.sort_unstable();
+				hashes.truncate(limit.saturating_add(1));
+				hashes
+			},
+			// Resume the scan of the entries, ordered by hash, right after the cursor.
+			None => {
+				let start = cursor.map_or(Bound::Unbounded, Bound::Excluded);
+				self.entries
+					.range((start, Bound::Unbounded))
+					.filter(|(hash, (account, priority, _))| {
+						in_range(priority) &&
+							query.channel.map_or(true, |channel| {
+								self.accounts
+									.get(account)
+									.and_then(|account_rec| account_rec.channels.get(&channel))
+									.map_or(false, |entry| entry.hash == **hash)
+							})
+					})
+					.map(|(hash, _)| *hash)
+					.take(limit.saturating_add(1))
+					.collect()
+			},
+		};
+
+		if hashes.len() > limit {
+			hashes.truncate(limit);
+			let next_cursor = hashes.last().copied();
+			(hashes, next_cursor)
+		} else {
+			(hashes, None)
+		}
+	}
+
 	fn maintain(&mut self, current_time: u64) -> Vec<Hash> {
 		// Purge previously expired messages.
 		let mut purged = Vec::new();
@@ -573,6 +692,7 @@ impl Store {
 			keystore,
 			time_override: None,
 			metrics: PrometheusMetrics::new(prometheus),
+			subscriptions: Default::default(),
 		};
 		store.populate()?;
 		Ok(store)
@@ -634,10 +754,20 @@ impl Store {
 		&self,
 		key: Option<DecryptionKey>,
 		match_all_topics: &[Topic],
+		f: impl FnMut(Statement) -> Option<R>,
+	) -> Result<Vec<R>> {
+		let index = self.index.read();
+		self.collect_statements_with(&index, key, match_all_topics, f)
+	}
+
+	fn collect_statements_with<R>(
+		&self,
+		index: &Index,
+		key: Option<DecryptionKey>,
+		match_all_topics: &[Topic],
 		mut f: impl FnMut(Statement) -> Option<R>,
 	) -> Result<Vec<R>> {
 		let mut result = Vec::new();
-		let index = self.index.read();
 		index.iterate_with(key, match_all_topics, |hash| {
 			match self.db.get(col::STATEMENTS, hash).map_err(|e| Error::Db(e.to_string()))? {
 				Some(entry) => {
@@ -895,7 +1025,12 @@ impl StatementStore for Store {
 				);
 				return SubmitResult::InternalError(Error::Db(e.to_string()))
 			}
-		} // Release index lock
+			// Lock the subscriptions before releasing the index lock, such that subscribers
+			// registered after this statement was inserted don't receive it twice.
+			let mut subscriptions = self.subscriptions.lock();
+			drop(index);
+			subscriptions.notify(&statement);
+		} // Release subscriptions lock
 		self.metrics.report(|metrics| metrics.submitted_statements.inc());
 		let network_priority = NetworkPriority::High;
 		log::trace!(target: LOG_TARGET, "Statement submitted: {:?}", HexDisplay::from(&hash));
@@ -925,6 +1060,62 @@ impl StatementStore for Store {
 		}
 		Ok(())
 	}
+
+	/// Return statements matching the query, ordered by hash.
+	fn query(
+		&self,
+		query: &StatementQuery,
+		cursor: Option<Hash>,
+		limit: usize,
+	) -> Result<StatementPage> {
+		let index = self.index.read();
+		let (hashes, next_cursor) = index.query(query, cursor, limit);
+		let mut statements = Vec::with_capacity(hashes.len());
+		for hash in hashes {
+			let encoded =
+				self.db.get(col::STATEMENTS, &hash).map_err(|e| Error::Db(e.to_string()))?;
+			match encoded.map(|encoded| Statement::decode(&mut encoded.as_slice())) {
+				Some(Ok(statement)) => statements.push((hash, statement)),
+				// DB inconsistency
+				_ => log::warn!(
+					target: LOG_TARGET,
+					"Missing or corrupt statement {:?}",
+					HexDisplay::from(&hash)
+				),
+			}
+		}
+		Ok(StatementPage { statements, next_cursor })
+	}
+
+	/// Subscribe to statements matching the filter.
+	fn subscribe(&self, filter: StatementFilter) -> Result<(Vec<Statement>, StatementStream)> {
+		// Hold the index lock until the subscriber is registered. Statements are submitted with
+		// the index locked, so each of them is either reported now or sent to the stream.
+		let index = self.index.read();
+		let queries: Vec<&[Topic]> = match &filter.topics {
+			TopicFilter::MatchAll(topics) => vec![&topics[..]],
+			TopicFilter::MatchAny(topics) if topics.is_empty() => vec![&[][..]],
+			TopicFilter::MatchAny(topics) => topics.iter().map(std::slice::from_ref).collect(),
+		};
+		let mut seen = HashSet::new();
+		let mut statements = Vec::new();
+		for match_all_topics in queries {
+			statements.extend(self.collect_statements_with(
+				&index,
+				filter.dest,
+				match_all_topics,
+				|statement| seen.insert(statement.hash()).then_some(statement),
+			)?);
+		}
+		log::trace!(
+			target: LOG_TARGET,
+			"New subscription: {:?}, {} existing statements",
+			filter,
+			statements.len()
+		);
+		let stream = self.subscriptions.lock().subscribe(filter);
+		Ok((statements, stream))
+	}
 }
 
 #[cfg(test)]
@@ -935,7 +1126,8 @@ mod tests {
 	use sp_statement_store::{
 		runtime_api::{InvalidStatement, ValidStatement, ValidateStatement},
 		AccountId, Channel, DecryptionKey, NetworkPriority, Proof, SignatureVerificationResult,
-		Statement, StatementSource, StatementStore, SubmitResult, Topic,
+		Statement, StatementFilter, StatementQuery, StatementSource, StatementStore, SubmitResult,
+		Topic, TopicFilter,
 	};
 
 	type Extrinsic = sp_runtime::OpaqueExtrinsic;
@@ -1187,6 +1379,153 @@ mod tests {
 		assert_topics(&[0, 1, 2, 3, 42], None, &[]);
 	}
 
+	#[test]
+	fn subscriptions_receive_matching_statements() {
+		let (store, _temp) = test_store();
+		let statement0 = signed_statement_with_topics(0, &[topic(0)], None);
+		let statement1 = signed_statement_with_topics(1, &[topic(0), topic(1)], None);
+		let statement2 = signed_statement_with_topics(2, &[topic(1)], Some(dec_key(2)));
+		let statement3 = signed_statement_with_topics(3, &[topic(2)], None);
+		store.submit(statement0.clone(), StatementSource::Network);
+
+		let filter =
+			|topics: TopicFilter, dest: Option<DecryptionKey>| StatementFilter { topics, dest };
+		let (existing, mut match_all) = store
+			.subscribe(filter(TopicFilter::MatchAll(vec![topic(0), topic(1)]), None))
+			.unwrap();
+		assert!(existing.is_empty());
+		let (existing, mut match_any) = store
+			.subscribe(filter(TopicFilter::MatchAny(vec![topic(0), topic(2)]), None))
+			.unwrap();
+		assert_eq!(existing, vec![statement0.clone()]);
+		let (existing, mut posted) = store
+			.subscribe(filter(TopicFilter::MatchAll(vec![]), Some(dec_key(2))))
+			.unwrap();
+		assert!(existing.is_empty());
+
+		for s in [&statement1, &statement2, &statement3] {
+			store.submit(s.clone(), StatementSource::Network);
+		}
+		// Known statements are not reported again.
+		store.submit(statement0, StatementSource::Network);
+
+		let received = |stream: &mut sp_statement_store::StatementStream| {
+			std::iter::from_fn(|| stream.try_next().ok().flatten()).collect::<Vec<_>>()
+		};
+		assert_eq!(received(&mut match_all), vec![statement1.clone()]);
+		assert_eq!(received(&mut match_any), vec![statement1, statement3]);
+		assert_eq!(received(&mut posted), vec![statement2]);
+
+		// Dropped subscribers are removed on the next notification.
+		drop(match_all);
+		drop(match_any);
+		drop(posted);
+		store.submit(signed_statement(4), StatementSource::Network);
+		assert!(store.subscriptions.lock().subscribers.is_empty());
+	}
+
+	#[test]
+	fn lagging_subscribers_are_dropped() {
+		let mut subscriptions = crate::Subscriptions { subscribers: Vec::new(), buffer_size: 1 };
+		let match_all = StatementFilter { topics: TopicFilter::MatchAll(vec![]), dest: None };
+		let mut lagging = subscriptions.subscribe(match_all.clone());
+		let mut listening = subscriptions.subscribe(match_all);
+
+		// The channel capacity is the buffer size plus one slot for the sender.
+		let statements: Vec<_> = (0..3).map(signed_statement).collect();
+		subscriptions.notify(&statements[0]);
+		subscriptions.notify(&statements[1]);
+		assert_eq!(listening.try_next().unwrap(), Some(statements[0].clone()));
+		assert_eq!(listening.try_next().unwrap(), Some(statements[1].clone()));
+
+		subscriptions.notify(&statements[2]);
+		assert_eq!(subscriptions.subscribers.len(), 1);
+		assert_eq!(listening.try_next().unwrap(), Some(statements[2].clone()));
+
+		// The lagging subscriber receives the buffered statements, then its stream ends.
+		assert_eq!(lagging.try_next().unwrap(), Some(statements[0].clone()));
+		assert_eq!(lagging.try_next().unwrap(), Some(statements[1].clone()));
+		assert_eq!(lagging.try_next().unwrap(), None);
+	}
+
+	#[test]
+	fn query_by_account_channel_and_priority() {
+		let (store, _temp) = test_store();
+		let statements = vec![
+			statement(4, 1, Some(1), 10),
+			statement(4, 2, Some(2), 10),
+			statement(4, 3, None, 10),
+			statement(4, 4, None, 10),
+			statement(3, 1, Some(1), 10),
+			statement(3, 5, None, 10),
+		];
+		for s in &statements {
+			assert_eq!(
+				store.submit(s.clone(), StatementSource::Network),
+				SubmitResult::New(NetworkPriority::High)
+			);
+		}
+
+		let query_all = |query: StatementQuery| {
+			let mut result = store.query(&query, None, usize::MAX).unwrap();
+			assert_eq!(result.next_cursor, None);
+			result.statements.sort_by_key(|(_, s)| (s.priority(), s.account_id()));
+			result.statements.into_iter().map(|(_, s)| s).collect::<Vec<_>>()
+		};
+
+		assert_eq!(query_all(Default::default()).len(), statements.len());
+		assert_eq!(
+			query_all(StatementQuery { account: Some(account(4)), ..Default::default() }),
+			statements[0..4].to_vec(),
+		);
+		assert_eq!(
+			query_all(StatementQuery { channel: Some(channel(1)), ..Default::default() }),
+			vec![statements[4].clone(), statements[0].clone()],
+		);
+		assert_eq!(
+			query_all(StatementQuery {
+				account: Some(account(4)),
+				channel: Some(channel(2)),
+				..Default::default()
+			}),
+			vec![statements[1].clone()],
+		);
+		assert_eq!(
+			query_all(StatementQuery {
+				min_priority: Some(3),
+				max_priority: Some(4),
+				..Default::default()
+			}),
+			vec![statements[2].clone(), statements[3].clone()],
+		);
+		assert_eq!(
+			query_all(StatementQuery {
+				account: Some(account(3)),
+				min_priority: Some(2),
+				..Default::default()
+			}),
+			vec![statements[5].clone()],
+		);
+		assert!(query_all(StatementQuery { account: Some(account(42)), ..Default::default() })
+			.is_empty());
+
+		// Paginate through all the statements.
+		let mut cursor = None;
+		let mut hashes = Vec::new();
+		loop {
+			let page = store.query(&Default::default(), cursor, 4).unwrap();
+			assert!(page.statements.len() <= 4);
+			hashes.extend(page.statements.into_iter().map(|(hash, _)| hash));
+			cursor = page.next_cursor;
+			if cursor.is_none() {
+				break
+			}
+		}
+		let mut expected: Vec<_> = statements.iter().map(|s| s.hash()).collect();
+		expected.sort();
+		assert_eq!(hashes, expected);
+	}
+
 	#[test]
 	fn constraints() {
 		let (store, _temp) = test_store();
@@ -48,12 +48,14 @@ pub enum Error {
 	#[error("[{0:?}] Already imported")]
 	AlreadyImported(Box<dyn std::any::Any + Send + Sync>),
 
-	#[error("Too low priority ({} > {})", old, new)]
+	#[error("Too low priority ({} > {}, at least {} required)", old, new, required)]
 	TooLowPriority {
 		/// Transaction already in the pool.
 		old: Priority,
 		/// Transaction entering the pool.
 		new: Priority,
+		/// Minimal priority required to replace the transaction already in the pool.
+		required: Priority,
 	},
 	#[error("Transaction with cyclic dependency")]
 	CycleDetected,
@@ -156,6 +156,15 @@ pub type TxIndex = usize;
 /// Typical future type used in transaction pool api.
 pub type PoolFuture<T, E> = std::pin::Pin<Box<dyn Future<Output = Result<T, E>> + Send>>;
 
+/// Error returned by [`TransactionPool::remove_local`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoveLocalError<Hash> {
+	/// The given transactions were not submitted locally, nothing was removed.
+	NotLocal(Vec<Hash>),
+	/// The pool doesn't support removing locally submitted transactions.
+	Unsupported,
+}
+
 /// In-pool transaction interface.
 ///
 /// The pool is container of transactions that are implementing this trait.
@@ -246,6 +255,23 @@ pub trait TransactionPool: Send + Sync {
 	/// Remove transactions identified by given hashes (and dependent transactions) from the pool.
 	fn remove_invalid(&self, hashes: &[TxHash<Self>]) -> Vec<Arc<Self::InPoolTransaction>>;
 
+	// *** RPC
+	/// Remove locally submitted transactions identified by given hashes (and dependent
+	/// transactions) from the pool without banning them, so that they can be cancelled or
+	/// resubmitted with a higher priority.
+	///
+	/// Watchers of the removed transactions are notified that they were dropped. If any of the
+	/// given transactions was not submitted with [`TransactionSource::Local`], nothing is removed
+	/// and the hashes of such transactions are returned in [`RemoveLocalError::NotLocal`].
+	///
+	/// By default removing transactions is not supported.
+	fn remove_local(
+		&self,
+		_hashes: &[TxHash<Self>],
+	) -> Result<Vec<Arc<Self::InPoolTransaction>>, RemoveLocalError<TxHash<Self>>> {
+		Err(RemoveLocalError::Unsupported)
+	}
+
 	// *** logging
 	/// Returns pool status.
 	fn status(&self) -> PoolStatus;
@@ -20,7 +20,12 @@
 //!
 //! For a more full-featured pool, have a look at the `pool` module.
 
-use std::{cmp::Ordering, collections::HashSet, fmt, hash, sync::Arc};
+use std::{
+	cmp::{Ordering, Reverse},
+	collections::{HashMap, HashSet},
+	fmt, hash,
+	sync::Arc,
+};
 
 use crate::LOG_TARGET;
 use log::{debug, trace, warn};
@@ -219,6 +224,8 @@ pub struct BasePool<Hash: hash::Hash + Eq, Ex> {
 	/// transactions to future in case they were just stuck in verification.
 	recently_pruned: [HashSet<Tag>; RECENTLY_PRUNED_TAGS],
 	recently_pruned_index: usize,
+	/// Usage of the per-sender limits and of the reserved lane.
+	accounting: Accounting<Hash>,
 }
 
 impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> Default for BasePool<Hash, Ex> {
@@ -236,9 +243,25 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 			ready: Default::default(),
 			recently_pruned: Default::default(),
 			recently_pruned_index: 0,
+			accounting: Accounting::new(Default::default(), Default::default()),
 		}
 	}
 
+	/// Sets the percentage by which the priority of a replacement transaction has to exceed the
+	/// collective priority of the ready transactions it replaces.
+	pub fn set_replacement_bump(&mut self, percent: u32) {
+		self.ready.set_replacement_bump(percent);
+	}
+
+	/// Sets the per-sender limits and the reserved lane enforced by `enforce_limits`.
+	///
+	/// The usage is tracked as transactions enter and leave the queues, so this should be called
+	/// before any transaction is imported.
+	pub fn set_limits(&mut self, per_sender: SenderLimit, reserved: ReservedLane) {
+		debug_assert!(self.ready.len() == 0 && self.future.len() == 0);
+		self.accounting = Accounting::new(per_sender, reserved);
+	}
+
 	/// Temporary enables future transactions, runs closure and then restores
 	/// `reject_future_transactions` flag back to previous value.
 	///
@@ -288,6 +311,7 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 			}
 
 			let hash = tx.transaction.hash.clone();
+			self.accounting.added_to_future(&tx.transaction);
 			self.future.import(tx);
 			return Ok(Imported::Future { hash })
 		}
@@ -313,12 +337,17 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 		// take first transaction from the list
 		while let Some(tx) = to_import.pop() {
 			// find transactions in Future that it unlocks
-			to_import.append(&mut self.future.satisfy_tags(&tx.transaction.provides));
+			to_import.append(&mut self.satisfy_future_tags(&tx.transaction.provides));
 
 			// import this transaction
 			let current_hash = tx.transaction.hash.clone();
+			let transaction = tx.transaction.clone();
 			match self.ready.import(tx) {
 				Ok(mut replaced) => {
+					self.accounting.added_to_ready(&transaction);
+					for tx in &replaced {
+						self.accounting.removed_from_ready(tx);
+					}
 					if !first {
 						promoted.push(current_hash);
 					}
@@ -346,7 +375,9 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 		if removed.iter().any(|tx| tx.hash == hash) {
 			// We still need to remove all transactions that we promoted
 			// since they depend on each other and will never get to the best iterator.
-			self.ready.remove_subtree(&promoted);
+			for tx in self.ready.remove_subtree(&promoted) {
+				self.accounting.removed_from_ready(&tx);
+			}
 
 			debug!(target: LOG_TARGET, "[{:?}] Cycle detected, bailing.", hash);
 			return Err(error::Error::CycleDetected)
@@ -384,42 +415,52 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 	/// Makes sure that the transactions in the queues stay within provided limits.
 	///
 	/// Removes and returns worst transactions from the queues and all transactions that depend on
-	/// them. Technically the worst transaction should be evaluated by computing the entire pending
-	/// set. We use a simplified approach to remove transactions with the lowest priority first or
-	/// those that occupy the pool for the longest time in case priority is the same.
+	/// them, together with the reason of the eviction. Technically the worst transaction should be
+	/// evaluated by computing the entire pending set. We use a simplified approach to remove
+	/// transactions with the lowest priority first or those that occupy the pool for the longest
+	/// time in case priority is the same.
+	///
+	/// Per-sender limits are enforced first. Unlike the queue limits they evict the newest
+	/// transactions of a sender, since its older transactions are usually required by the newer
+	/// ones. Transactions in the reserved lane are only evicted to satisfy the ready queue limit
+	/// once the lane itself exceeds its limit.
 	pub fn enforce_limits(
 		&mut self,
 		ready: &Limit,
 		future: &Limit,
-	) -> Vec<Arc<Transaction<Hash, Ex>>> {
+	) -> Vec<(Arc<Transaction<Hash, Ex>>, EvictionReason)> {
 		let mut removed = vec![];
+		let mut evict = |pool: &mut Self, hash: Hash, reason: EvictionReason| {
+			let subtree = pool.remove_subtree(&[hash]);
+			let evicted = !subtree.is_empty();
+			removed.extend(subtree.into_iter().map(|tx| (tx, reason)));
+			evicted
+		};
+
+		while let Some(newest) = self.sender_ready_to_evict() {
+			if !evict(self, newest, EvictionReason::SenderReadyLimit) {
+				break
+			}
+		}
 
+		while let Some(newest) = self.sender_future_to_evict() {
+			if !evict(self, newest, EvictionReason::SenderFutureLimit) {
+				break
+			}
+		}
+
+		let reserved = self.accounting.reserved.clone();
 		while ready.is_exceeded(self.ready.len(), self.ready.bytes()) {
-			// find the worst transaction
-			let worst = self.ready.fold::<TransactionRef<Hash, Ex>, _>(|worst, current| {
-				let transaction = &current.transaction;
-				worst
-					.map(|worst| {
-						// Here we don't use `TransactionRef`'s ordering implementation because
-						// while it prefers priority like need here, it also prefers older
-						// transactions for inclusion purposes and limit enforcement needs to prefer
-						// newer transactions instead and drop the older ones.
-						match worst.transaction.priority.cmp(&transaction.transaction.priority) {
-							Ordering::Less => worst,
-							Ordering::Equal =>
-								if worst.insertion_id > transaction.insertion_id {
-									transaction.clone()
-								} else {
-									worst
-								},
-							Ordering::Greater => transaction.clone(),
-						}
-					})
-					.or_else(|| Some(transaction.clone()))
-			});
+			let (lane_count, lane_bytes) = self.accounting.reserved_usage;
+			let evict_reserved = reserved.limit.is_exceeded(lane_count, lane_bytes);
+
+			// find the worst transaction, preferring the ones outside of the reserved lane
+			let worst = self
+				.worst_ready(|tx| evict_reserved || !reserved.contains(tx))
+				.or_else(|| self.worst_ready(|_| true));
 
 			if let Some(worst) = worst {
-				removed.append(&mut self.remove_subtree(&[worst.transaction.hash.clone()]))
+				evict(self, worst.transaction.hash.clone(), EvictionReason::ReadyLimit);
 			} else {
 				break
 			}
@@ -434,7 +475,7 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 			});
 
 			if let Some(worst) = worst {
-				removed.append(&mut self.remove_subtree(&[worst.transaction.hash.clone()]))
+				evict(self, worst.transaction.hash.clone(), EvictionReason::FutureLimit);
 			} else {
 				break
 			}
@@ -443,6 +484,80 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 		removed
 	}
 
+	/// Returns true if any sender exceeds its ready or future transactions limit.
+	pub fn exceeds_sender_limits(&self) -> bool {
+		self.accounting.ready.is_exceeded() || self.accounting.future.is_exceeded()
+	}
+
+	/// Returns the ready transaction to be evicted first among the ones matching `filter`.
+	fn worst_ready(
+		&mut self,
+		filter: impl Fn(&Transaction<Hash, Ex>) -> bool,
+	) -> Option<TransactionRef<Hash, Ex>> {
+		self.ready.fold::<TransactionRef<Hash, Ex>, _>(|worst, current| {
+			let transaction = &current.transaction;
+			if !filter(&transaction.transaction) {
+				return worst
+			}
+			worst
+				.map(|worst| {
+					// Here we don't use `TransactionRef`'s ordering implementation because
+					// while it prefers priority like need here, it also prefers older
+					// transactions for inclusion purposes and limit enforcement needs to prefer
+					// newer transactions instead and drop the older ones.
+					match worst.transaction.priority.cmp(&transaction.transaction.priority) {
+						Ordering::Less => worst,
+						Ordering::Equal =>
+							if worst.insertion_id > transaction.insertion_id {
+								transaction.clone()
+							} else {
+								worst
+							},
+						Ordering::Greater => transaction.clone(),
+					}
+				})
+				.or_else(|| Some(transaction.clone()))
+		})
+	}
+
+	/// Returns the newest of the lowest priority ready transactions of a sender that exceeds its
+	/// ready transactions limit.
+	fn sender_ready_to_evict(&self) -> Option<Hash> {
+		self.accounting
+			.ready
+			.exceeding_sender()?
+			.iter()
+			.filter_map(|(hash, added)| {
+				let tx = self.ready.by_hash(hash)?;
+				Some(((tx.priority, Reverse(*added)), hash))
+			})
+			.min_by_key(|(key, _)| *key)
+			.map(|(_, hash)| hash.clone())
+	}
+
+	/// Returns the newest future transaction of a sender that exceeds its future transactions
+	/// limit.
+	fn sender_future_to_evict(&self) -> Option<Hash> {
+		self.accounting
+			.future
+			.exceeding_sender()?
+			.iter()
+			.max_by_key(|(_, added)| **added)
+			.map(|(hash, _)| hash.clone())
+	}
+
+	/// Satisfies given tags in the future queue, returning the transactions that became ready.
+	fn satisfy_future_tags<T: AsRef<Tag>>(
+		&mut self,
+		tags: impl IntoIterator<Item = T>,
+	) -> Vec<WaitingTransaction<Hash, Ex>> {
+		let unlocked = self.future.satisfy_tags(tags);
+		for tx in &unlocked {
+			self.accounting.removed_from_future(&tx.transaction);
+		}
+		unlocked
+	}
+
 	/// Removes all transactions represented by the hashes and all other transactions
 	/// that depend on them.
 	///
@@ -453,13 +568,24 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 	/// and you don't want them to be stored in the pool use `prune_tags` method.
 	pub fn remove_subtree(&mut self, hashes: &[Hash]) -> Vec<Arc<Transaction<Hash, Ex>>> {
 		let mut removed = self.ready.remove_subtree(hashes);
-		removed.extend(self.future.remove(hashes));
+		for tx in &removed {
+			self.accounting.removed_from_ready(tx);
+		}
+		let future = self.future.remove(hashes);
+		for tx in &future {
+			self.accounting.removed_from_future(tx);
+		}
+		removed.extend(future);
 		removed
 	}
 
 	/// Removes and returns all transactions from the future queue.
 	pub fn clear_future(&mut self) -> Vec<Arc<Transaction<Hash, Ex>>> {
-		self.future.clear()
+		let removed = self.future.clear();
+		for tx in &removed {
+			self.accounting.removed_from_future(tx);
+		}
+		removed
 	}
 
 	/// Prunes transactions that provide given list of tags.
@@ -471,17 +597,21 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 	pub fn prune_tags(&mut self, tags: impl IntoIterator<Item = Tag>) -> PruneStatus<Hash, Ex> {
 		let mut to_import = vec![];
 		let mut pruned = vec![];
-		let recently_pruned = &mut self.recently_pruned[self.recently_pruned_index];
+		let recently_pruned_index = self.recently_pruned_index;
 		self.recently_pruned_index = (self.recently_pruned_index + 1) % RECENTLY_PRUNED_TAGS;
-		recently_pruned.clear();
+		self.recently_pruned[recently_pruned_index].clear();
 
 		for tag in tags {
 			// make sure to promote any future transactions that could be unlocked
-			to_import.append(&mut self.future.satisfy_tags(std::iter::once(&tag)));
+			to_import.append(&mut self.satisfy_future_tags(std::iter::once(&tag)));
 			// and actually prune transactions in ready queue
-			pruned.append(&mut self.ready.prune_tags(tag.clone()));
+			let mut ready_pruned = self.ready.prune_tags(tag.clone());
+			for tx in &ready_pruned {
+				self.accounting.removed_from_ready(tx);
+			}
+			pruned.append(&mut ready_pruned);
 			// store the tags for next submission
-			recently_pruned.insert(tag);
+			self.recently_pruned[recently_pruned_index].insert(tag);
 		}
 
 		let mut promoted = vec![];
@@ -530,6 +660,200 @@ impl Limit {
 	}
 }
 
+/// Limits of the transactions of a single sender.
+///
+/// The pool does not know the sender of a transaction, so it is derived from the tags the
+/// transaction provides. The FRAME `CheckNonce` extension provides the encoded
+/// `(AccountId, Nonce)` pair, which is exactly `account_id_len + nonce_len` bytes long, so the
+/// sender is the account id prefix of the first provided tag of that length. Transactions that
+/// don't provide such a tag, like most unsigned transactions, are not subject to these limits.
+#[derive(Debug, Clone)]
+pub struct SenderLimit {
+	/// Maximal number of ready transactions of a single sender, unlimited if `None`.
+	pub ready: Option<usize>,
+	/// Maximal number of future transactions of a single sender, unlimited if `None`.
+	pub future: Option<usize>,
+	/// Length of the encoded account id identifying the sender.
+	pub account_id_len: usize,
+	/// Length of the encoded nonce following the account id in the provided tag.
+	pub nonce_len: usize,
+}
+
+impl Default for SenderLimit {
+	fn default() -> Self {
+		Self { ready: None, future: None, account_id_len: 32, nonce_len: 4 }
+	}
+}
+
+impl SenderLimit {
+	/// Returns the sender of given transaction, if it provides a tag identifying it.
+	pub fn sender_of<'a, Hash, Ex>(&self, tx: &'a Transaction<Hash, Ex>) -> Option<&'a [u8]> {
+		let tag_len = self.account_id_len + self.nonce_len;
+		tx.provides
+			.iter()
+			.find(|tag| tag.len() == tag_len)
+			.map(|tag| &tag[..self.account_id_len])
+	}
+}
+
+/// Part of the ready queue reserved for local and operational transactions.
+#[derive(Debug, Clone)]
+pub struct ReservedLane {
+	/// Limits of the reserved lane. Transactions within these limits are not evicted in favour of
+	/// the transactions outside of the lane.
+	pub limit: Limit,
+	/// Minimal priority of a transaction to be considered operational, if any.
+	pub operational_priority: Option<Priority>,
+}
+
+impl Default for ReservedLane {
+	fn default() -> Self {
+		Self { limit: Limit { count: 0, total_bytes: 0 }, operational_priority: None }
+	}
+}
+
+impl ReservedLane {
+	/// Returns true if given transaction belongs to the reserved lane.
+	pub fn contains<Hash, Ex>(&self, tx: &Transaction<Hash, Ex>) -> bool {
+		tx.source == Source::Local ||
+			self.operational_priority.map_or(false, |priority| tx.priority >= priority)
+	}
+}
+
+/// Reason of evicting a transaction when enforcing the pool limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EvictionReason {
+	/// The ready queue limit was exceeded.
+	ReadyLimit,
+	/// The future queue limit was exceeded.
+	FutureLimit,
+	/// The sender exceeded its ready transactions limit.
+	SenderReadyLimit,
+	/// The sender exceeded its future transactions limit.
+	SenderFutureLimit,
+}
+
+impl EvictionReason {
+	/// Returns the name of the reason, as used in metrics.
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			EvictionReason::ReadyLimit => "ready_limit",
+			EvictionReason::FutureLimit => "future_limit",
+			EvictionReason::SenderReadyLimit => "sender_ready_limit",
+			EvictionReason::SenderFutureLimit => "sender_future_limit",
+		}
+	}
+}
+
+/// Transactions of every sender in a single queue.
+#[derive(Debug)]
+struct SenderQueue<Hash> {
+	/// Maximal number of transactions of a single sender.
+	max: Option<usize>,
+	/// Transactions of every sender, along with the order they were added in.
+	transactions: HashMap<Vec<u8>, HashMap<Hash, u64>>,
+	/// Senders with more than `max` transactions.
+	exceeding: HashSet<Vec<u8>>,
+}
+
+impl<Hash: hash::Hash + Eq> SenderQueue<Hash> {
+	fn new(max: Option<usize>) -> Self {
+		Self { max, transactions: Default::default(), exceeding: Default::default() }
+	}
+
+	fn insert(&mut self, sender: &[u8], hash: Hash, added: u64) {
+		let transactions = self.transactions.entry(sender.to_vec()).or_default();
+		transactions.insert(hash, added);
+		if self.max.map_or(false, |max| transactions.len() > max) {
+			self.exceeding.insert(sender.to_vec());
+		}
+	}
+
+	fn remove(&mut self, sender: &[u8], hash: &Hash) {
+		if let Some(transactions) = self.transactions.get_mut(sender) {
+			transactions.remove(hash);
+			if !self.max.map_or(false, |max| transactions.len() > max) {
+				self.exceeding.remove(sender);
+			}
+			if transactions.is_empty() {
+				self.transactions.remove(sender);
+			}
+		}
+	}
+
+	fn is_exceeded(&self) -> bool {
+		!self.exceeding.is_empty()
+	}
+
+	/// Returns the transactions of any sender with more than `max` transactions.
+	fn exceeding_sender(&self) -> Option<&HashMap<Hash, u64>> {
+		self.exceeding.iter().next().and_then(|sender| self.transactions.get(sender))
+	}
+}
+
+/// Usage of the per-sender limits and of the reserved lane.
+///
+/// Updated whenever a transaction enters or leaves one of the queues, so that the limits can be
+/// enforced without going through all the transactions in the pool.
+#[derive(Debug)]
+struct Accounting<Hash> {
+	per_sender: SenderLimit,
+	reserved: ReservedLane,
+	ready: SenderQueue<Hash>,
+	future: SenderQueue<Hash>,
+	/// Number and total size of the ready transactions in the reserved lane.
+	reserved_usage: (usize, usize),
+	/// Counter used to order the transactions of a sender.
+	next_added: u64,
+}
+
+impl<Hash: hash::Hash + Eq + Clone> Accounting<Hash> {
+	fn new(per_sender: SenderLimit, reserved: ReservedLane) -> Self {
+		Self {
+			ready: SenderQueue::new(per_sender.ready),
+			future: SenderQueue::new(per_sender.future),
+			per_sender,
+			reserved,
+			reserved_usage: (0, 0),
+			next_added: 0,
+		}
+	}
+
+	fn added_to_ready<Ex>(&mut self, tx: &Transaction<Hash, Ex>) {
+		if self.reserved.contains(tx) {
+			self.reserved_usage.0 += 1;
+			self.reserved_usage.1 += tx.bytes;
+		}
+		if let Some(sender) = self.per_sender.sender_of(tx) {
+			self.next_added += 1;
+			self.ready.insert(sender, tx.hash.clone(), self.next_added);
+		}
+	}
+
+	fn removed_from_ready<Ex>(&mut self, tx: &Transaction<Hash, Ex>) {
+		if self.reserved.contains(tx) {
+			self.reserved_usage.0 = self.reserved_usage.0.saturating_sub(1);
+			self.reserved_usage.1 = self.reserved_usage.1.saturating_sub(tx.bytes);
+		}
+		if let Some(sender) = self.per_sender.sender_of(tx) {
+			self.ready.remove(sender, &tx.hash);
+		}
+	}
+
+	fn added_to_future<Ex>(&mut self, tx: &Transaction<Hash, Ex>) {
+		if let Some(sender) = self.per_sender.sender_of(tx) {
+			self.next_added += 1;
+			self.future.insert(sender, tx.hash.clone(), self.next_added);
+		}
+	}
+
+	fn removed_from_future<Ex>(&mut self, tx: &Transaction<Hash, Ex>) {
+		if let Some(sender) = self.per_sender.sender_of(tx) {
+			self.future.remove(sender, &tx.hash);
+		}
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -1019,6 +1343,176 @@ source: TransactionSource::External, requires: [03, 02], provides: [04], data: [
 		assert_eq!(pool.future.len(), 0);
 	}
 
+	#[test]
+	fn should_enforce_per_sender_ready_limit() {
+		// given
+		let mut pool = pool();
+		pool.set_limits(
+			SenderLimit { ready: Some(2), future: None, account_id_len: 1, nonce_len: 1 },
+			ReservedLane::default(),
+		);
+		for nonce in 0..3u8 {
+			pool.import(Transaction {
+				data: vec![nonce],
+				hash: nonce as u64,
+				requires: if nonce == 0 { vec![] } else { vec![vec![1, nonce - 1]] },
+				provides: vec![vec![1, nonce]],
+				..DEFAULT_TX.clone()
+			})
+			.unwrap();
+		}
+		pool.import(Transaction {
+			data: vec![10u8],
+			hash: 10,
+			provides: vec![vec![2, 0]],
+			..DEFAULT_TX.clone()
+		})
+		.unwrap();
+		assert!(pool.exceeds_sender_limits());
+		let limit = Limit { count: 100, total_bytes: 1000 };
+
+		// when
+		let removed = pool.enforce_limits(&limit, &limit);
+
+		// then
+		assert_eq!(
+			removed.iter().map(|(tx, reason)| (tx.hash, *reason)).collect::<Vec<_>>(),
+			vec![(2, EvictionReason::SenderReadyLimit)],
+		);
+		assert_eq!(pool.ready().map(|tx| tx.hash).collect::<Vec<_>>(), vec![0, 1, 10]);
+		assert!(!pool.exceeds_sender_limits());
+	}
+
+	#[test]
+	fn should_enforce_per_sender_future_limit() {
+		// given
+		let mut pool = pool();
+		pool.set_limits(
+			SenderLimit { ready: None, future: Some(1), account_id_len: 1, nonce_len: 1 },
+			ReservedLane::default(),
+		);
+		for nonce in 1..3u8 {
+			pool.import(Transaction {
+				data: vec![nonce],
+				hash: nonce as u64,
+				requires: vec![vec![1, 0]],
+				provides: vec![vec![1, nonce]],
+				..DEFAULT_TX.clone()
+			})
+			.unwrap();
+		}
+		assert!(pool.exceeds_sender_limits());
+		let limit = Limit { count: 100, total_bytes: 1000 };
+
+		// when
+		let removed = pool.enforce_limits(&limit, &limit);
+
+		// then
+		assert_eq!(removed.len(), 1);
+		assert_eq!(removed[0].1, EvictionReason::SenderFutureLimit);
+		assert_eq!(pool.future.len(), 1);
+		assert!(!pool.exceeds_sender_limits());
+	}
+
+	#[test]
+	fn should_track_senders_when_transactions_leave_the_pool() {
+		// given
+		let mut pool = pool();
+		pool.set_limits(
+			SenderLimit { ready: Some(1), future: None, account_id_len: 1, nonce_len: 1 },
+			ReservedLane::default(),
+		);
+		pool.import(Transaction {
+			data: vec![0u8],
+			hash: 0,
+			provides: vec![vec![1, 0]],
+			..DEFAULT_TX.clone()
+		})
+		.unwrap();
+		pool.import(Transaction {
+			data: vec![1u8],
+			hash: 1,
+			requires: vec![vec![1, 0]],
+			provides: vec![vec![1, 1]],
+			..DEFAULT_TX.clone()
+		})
+		.unwrap();
+		// transactions without a sender tag are not limited
+		pool.import(Transaction {
+			data: vec![2u8],
+			hash: 2,
+			provides: vec![vec![2]],
+			..DEFAULT_TX.clone()
+		})
+		.unwrap();
+		pool.import(Transaction {
+			data: vec![3u8],
+			hash: 3,
+			provides: vec![vec![3]],
+			..DEFAULT_TX.clone()
+		})
+		.unwrap();
+		assert!(pool.exceeds_sender_limits());
+
+		// when
+		let pruned = pool.prune_tags(vec![vec![1, 0]]);
+
+		// then
+		assert_eq!(pruned.pruned.len(), 1);
+		assert!(!pool.exceeds_sender_limits());
+	}
+
+	#[test]
+	fn should_not_evict_reserved_transactions_within_the_lane_limit() {
+		// given
+		let mut pool = pool();
+		pool.set_limits(
+			SenderLimit::default(),
+			ReservedLane {
+				limit: Limit { count: 1, total_bytes: 1000 },
+				operational_priority: None,
+			},
+		);
+		pool.import(Transaction {
+			data: vec![1u8],
+			hash: 1,
+			priority: 1,
+			provides: vec![vec![1]],
+			source: Source::Local,
+			..DEFAULT_TX.clone()
+		})
+		.unwrap();
+		pool.import(Transaction {
+			data: vec![2u8],
+			hash: 2,
+			priority: 10,
+			provides: vec![vec![2]],
+			..DEFAULT_TX.clone()
+		})
+		.unwrap();
+		pool.import(Transaction {
+			data: vec![3u8],
+			hash: 3,
+			priority: 20,
+			provides: vec![vec![3]],
+			..DEFAULT_TX.clone()
+		})
+		.unwrap();
+
+		// when
+		let removed = pool.enforce_limits(
+			&Limit { count: 2, total_bytes: 1000 },
+			&Limit { count: 100, total_bytes: 1000 },
+		);
+
+		// then
+		assert_eq!(
+			removed.iter().map(|(tx, reason)| (tx.hash, *reason)).collect::<Vec<_>>(),
+			vec![(2, EvictionReason::ReadyLimit)],
+		);
+		assert_eq!(pool.ready().map(|tx| tx.hash).collect::<Vec<_>>(), vec![3, 1]);
+	}
+
 	#[test]
 	fn should_accept_future_transactions_when_explicitly_asked_to() {
 		// given
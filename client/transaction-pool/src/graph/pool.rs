@@ -16,9 +16,9 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::{collections::HashMap, path::PathBuf, sync::Arc, time::Duration};
 
-use crate::LOG_TARGET;
+use crate::{metrics::MetricsLink, LOG_TARGET};
 use futures::{channel::mpsc::Receiver, Future};
 use sc_transaction_pool_api::error;
 use sp_blockchain::TreeRoute;
@@ -115,10 +115,21 @@ pub struct Options {
 	pub ready: base::Limit,
 	/// Future queue limits.
 	pub future: base::Limit,
+	/// Per-sender limits of the ready and future queues.
+	pub per_sender: base::SenderLimit,
+	/// Part of the ready queue reserved for local and operational transactions.
+	pub reserved: base::ReservedLane,
 	/// Reject future transactions.
 	pub reject_future_transactions: bool,
 	/// How long the extrinsic is banned for.
 	pub ban_time: Duration,
+	/// Percentage by which the priority of a transaction has to exceed the priority of the
+	/// transactions it replaces.
+	pub replacement_bump: u32,
+	/// File the pool transactions are journaled to, so that they survive a restart.
+	///
+	/// Journaling is disabled if `None`.
+	pub journal_path: Option<PathBuf>,
 }
 
 impl Default for Options {
@@ -126,8 +137,12 @@ impl Default for Options {
 		Self {
 			ready: base::Limit { count: 8192, total_bytes: 20 * 1024 * 1024 },
 			future: base::Limit { count: 512, total_bytes: 1 * 1024 * 1024 },
+			per_sender: Default::default(),
+			reserved: Default::default(),
 			reject_future_transactions: false,
 			ban_time: Duration::from_secs(60 * 30),
+			replacement_bump: 0,
+			journal_path: None,
 		}
 	}
 }
@@ -148,7 +163,17 @@ pub struct Pool<B: ChainApi> {
 impl<B: ChainApi> Pool<B> {
 	/// Create a new transaction pool.
 	pub fn new(options: Options, is_validator: IsValidator, api: Arc<B>) -> Self {
-		Self { validated_pool: Arc::new(ValidatedPool::new(options, is_validator, api)) }
+		Self::with_metrics(options, is_validator, api, Default::default())
+	}
+
+	/// Create a new transaction pool reporting to the given metrics.
+	pub(crate) fn with_metrics(
+		options: Options,
+		is_validator: IsValidator,
+		api: Arc<B>,
+		metrics: MetricsLink,
+	) -> Self {
+		Self { validated_pool: Arc::new(ValidatedPool::new(options, is_validator, api, metrics)) }
 	}
 
 	/// Imports a bunch of unverified extrinsics to the pool
@@ -118,6 +118,9 @@ pub struct ReadyTransactions<Hash: hash::Hash + Eq, Ex> {
 	/// Best transactions that are ready to be included to the block without any other previous
 	/// transaction.
 	best: BTreeSet<TransactionRef<Hash, Ex>>,
+	/// Percentage by which the priority of a replacement transaction has to exceed the
+	/// collective priority of the transactions it replaces.
+	replacement_bump: u32,
 }
 
 impl<Hash, Ex> tracked_map::Size for ReadyTx<Hash, Ex> {
@@ -133,11 +136,20 @@ impl<Hash: hash::Hash + Eq, Ex> Default for ReadyTransactions<Hash, Ex> {
 			provided_tags: Default::default(),
 			ready: Default::default(),
 			best: Default::default(),
+			replacement_bump: 0,
 		}
 	}
 }
 
 impl<Hash: hash::Hash + Member + Serialize, Ex> ReadyTransactions<Hash, Ex> {
+	/// Sets the percentage by which the priority of a replacement transaction has to exceed the
+	/// collective priority of the transactions it replaces.
+	///
+	/// With the default of `0` any strictly higher priority is enough.
+	pub fn set_replacement_bump(&mut self, percent: u32) {
+		self.replacement_bump = percent;
+	}
+
 	/// Borrows a map of tags that are provided by transactions in this queue.
 	pub fn provided_tags(&self) -> &HashMap<Tag, Hash> {
 		&self.provided_tags
@@ -435,7 +447,8 @@ impl<Hash: hash::Hash + Member + Serialize, Ex> ReadyTransactions<Hash, Ex> {
 				return Ok((vec![], vec![]))
 			}
 
-			// now check if collective priority is lower than the replacement transaction.
+			// now check if collective priority (bumped by the configured percentage) is lower
+			// than the replacement transaction.
 			let old_priority = {
 				let ready = self.ready.read();
 				replace_hashes
@@ -445,10 +458,16 @@ impl<Hash: hash::Hash + Member + Serialize, Ex> ReadyTransactions<Hash, Ex> {
 						total.saturating_add(tx.transaction.transaction.priority)
 					})
 			};
+			let bump = old_priority.saturating_mul(self.replacement_bump.into()) / 100;
+			let bumped_priority = old_priority.saturating_add(bump);
 
 			// bail - the transaction has too low priority to replace the old ones
-			if old_priority >= tx.priority {
-				return Err(error::Error::TooLowPriority { old: old_priority, new: tx.priority })
+			if bumped_priority >= tx.priority {
+				return Err(error::Error::TooLowPriority {
+					old: old_priority,
+					new: tx.priority,
+					required: bumped_priority.saturating_add(1),
+				})
 			}
 
 			// construct a list of unlocked transactions
@@ -589,6 +608,7 @@ fn remove_item<T: PartialEq>(vec: &mut Vec<T>, item: &T) {
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use assert_matches::assert_matches;
 	use sp_runtime::transaction_validity::TransactionSource as Source;
 
 	fn tx(id: u8) -> Transaction<u64, Vec<u8>> {
@@ -641,6 +661,34 @@ mod tests {
 		assert_eq!(ready.get().count(), 1);
 	}
 
+	#[test]
+	fn should_require_priority_bump_for_replacement() {
+		// given
+		let mut ready = ReadyTransactions::default();
+		ready.set_replacement_bump(50);
+		let mut tx1 = tx(1);
+		tx1.requires.clear();
+		tx1.priority = 10;
+		let mut tx2 = tx(2);
+		tx2.requires.clear();
+		tx2.provides = vec![vec![3]];
+		tx2.priority = 15;
+		import(&mut ready, tx1).unwrap();
+
+		// when
+		assert_matches!(
+			import(&mut ready, tx2.clone()),
+			Err(error::Error::TooLowPriority { old: 10, new: 15, required: 16 })
+		);
+		tx2.priority = 16;
+		let replaced = import(&mut ready, tx2).unwrap();
+
+		// then
+		assert_eq!(replaced.len(), 1);
+		assert_eq!(replaced[0].hash, 1);
+		assert_eq!(ready.get().map(|tx| tx.hash).collect::<Vec<_>>(), vec![2]);
+	}
+
 	#[test]
 	fn should_replace_multiple_transactions_correctly() {
 		// given
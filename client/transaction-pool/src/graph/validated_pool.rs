@@ -22,7 +22,7 @@ use std::{
 	sync::Arc,
 };
 
-use crate::LOG_TARGET;
+use crate::{metrics::MetricsLink, LOG_TARGET};
 use futures::channel::mpsc::{channel, Sender};
 use parking_lot::{Mutex, RwLock};
 use sc_transaction_pool_api::{error, PoolStatus, ReadyTransactions};
@@ -109,12 +109,20 @@ pub struct ValidatedPool<B: ChainApi> {
 	pool: RwLock<base::BasePool<ExtrinsicHash<B>, ExtrinsicFor<B>>>,
 	import_notification_sinks: Mutex<Vec<Sender<ExtrinsicHash<B>>>>,
 	rotator: PoolRotator<ExtrinsicHash<B>>,
+	metrics: MetricsLink,
 }
 
 impl<B: ChainApi> ValidatedPool<B> {
 	/// Create a new transaction pool.
-	pub fn new(options: Options, is_validator: IsValidator, api: Arc<B>) -> Self {
-		let base_pool = base::BasePool::new(options.reject_future_transactions);
+	pub fn new(
+		options: Options,
+		is_validator: IsValidator,
+		api: Arc<B>,
+		metrics: MetricsLink,
+	) -> Self {
+		let mut base_pool = base::BasePool::new(options.reject_future_transactions);
+		base_pool.set_replacement_bump(options.replacement_bump);
+		base_pool.set_limits(options.per_sender.clone(), options.reserved.clone());
 		let ban_time = options.ban_time;
 		Self {
 			is_validator,
@@ -124,6 +132,7 @@ impl<B: ChainApi> ValidatedPool<B> {
 			pool: RwLock::new(base_pool),
 			import_notification_sinks: Default::default(),
 			rotator: PoolRotator::new(ban_time),
+			metrics,
 		}
 	}
 
@@ -227,13 +236,17 @@ impl<B: ChainApi> ValidatedPool<B> {
 	}
 
 	fn enforce_limits(&self) -> HashSet<ExtrinsicHash<B>> {
-		let status = self.pool.read().status();
+		let (status, exceeds_sender_limits) = {
+			let pool = self.pool.read();
+			(pool.status(), pool.exceeds_sender_limits())
+		};
 		let ready_limit = &self.options.ready;
 		let future_limit = &self.options.future;
 
 		log::debug!(target: LOG_TARGET, "Pool Status: {:?}", status);
 		if ready_limit.is_exceeded(status.ready, status.ready_bytes) ||
-			future_limit.is_exceeded(status.future, status.future_bytes)
+			future_limit.is_exceeded(status.future, status.future_bytes) ||
+			exceeds_sender_limits
 		{
 			log::debug!(
 				target: LOG_TARGET,
@@ -247,11 +260,13 @@ impl<B: ChainApi> ValidatedPool<B> {
 			// clean up the pool
 			let removed = {
 				let mut pool = self.pool.write();
-				let removed = pool
-					.enforce_limits(ready_limit, future_limit)
-					.into_iter()
-					.map(|x| x.hash)
-					.collect::<HashSet<_>>();
+				let evicted = pool.enforce_limits(ready_limit, future_limit);
+				self.metrics.report(|metrics| {
+					for (_, reason) in &evicted {
+						metrics.evicted_transactions.with_label_values(&[reason.as_str()]).inc();
+					}
+				});
+				let removed = evicted.into_iter().map(|(tx, _)| tx.hash).collect::<HashSet<_>>();
 				// ban all removed transactions
 				self.rotator.ban(&Instant::now(), removed.iter().copied());
 				removed
@@ -447,6 +462,17 @@ impl<B: ChainApi> ValidatedPool<B> {
 		self.pool.read().ready_by_hash(hash)
 	}
 
+	/// Returns true if the transaction with given hash is in the ready or future queue.
+	pub fn is_imported(&self, hash: &ExtrinsicHash<B>) -> bool {
+		self.pool.read().is_imported(hash)
+	}
+
+	/// Returns the hashes of all ready and future transactions in the pool.
+	pub fn imported_hashes(&self) -> HashSet<ExtrinsicHash<B>> {
+		let pool = self.pool.read();
+		pool.ready().map(|tx| tx.hash).chain(pool.futures().map(|tx| tx.hash)).collect()
+	}
+
 	/// Prunes ready transactions that provide given list of tags.
 	pub fn prune_tags(
 		&self,
@@ -615,6 +641,46 @@ impl<B: ChainApi> ValidatedPool<B> {
 		invalid
 	}
 
+	/// Removes given locally submitted transactions and the transactions depending on them from
+	/// the pool.
+	///
+	/// Unlike [`Self::remove_invalid`] the transactions are not banned, so they can be
+	/// resubmitted right away, e.g. with a higher priority. If any of the given transactions was
+	/// not submitted locally, nothing is removed and the hashes of such transactions are returned
+	/// as an error.
+	pub fn remove_local(
+		&self,
+		hashes: &[ExtrinsicHash<B>],
+	) -> Result<Vec<TransactionFor<B>>, Vec<ExtrinsicHash<B>>> {
+		if hashes.is_empty() {
+			return Ok(vec![])
+		}
+
+		let removed = {
+			let mut pool = self.pool.write();
+			let not_local = pool
+				.by_hashes(hashes)
+				.into_iter()
+				.flatten()
+				.filter(|tx| tx.source != TransactionSource::Local)
+				.map(|tx| tx.hash)
+				.collect::<Vec<_>>();
+			if !not_local.is_empty() {
+				return Err(not_local)
+			}
+			pool.remove_subtree(hashes)
+		};
+
+		log::debug!(target: LOG_TARGET, "Removed transactions on request: {:?}", removed);
+
+		let mut listener = self.listener.write();
+		for tx in &removed {
+			listener.dropped(&tx.hash, None);
+		}
+
+		Ok(removed)
+	}
+
 	/// Get an iterator for ready transactions ordered by priority
 	pub fn ready(&self) -> impl ReadyTransactions<Item = TransactionFor<B>> + Send {
 		self.pool.read().ready()
@@ -0,0 +1,486 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Transaction pool journal.
+//!
+//! Keeps track of the transactions submitted to the pool and persists them to disk, so that they
+//! can be resubmitted after the node restarts. Transactions are removed from the journal once
+//! they are included in a block, found invalid or otherwise leave the pool.
+//!
+//! The journal is an append-only log of additions and removals, which is compacted once it
+//! grows much larger than the set of journaled transactions. Changes are only kept in memory
+//! by the pool and written to disk in batches by a background task, see [`run_writer`].
+
+use std::{
+	collections::{HashMap, HashSet},
+	fs, hash,
+	io::{self, Write as _},
+	path::{Path, PathBuf},
+	sync::{Arc, Weak},
+	time::Duration,
+};
+
+use codec::{Decode, Encode};
+use parking_lot::Mutex;
+use sp_runtime::transaction_validity::TransactionSource;
+
+use crate::{
+	graph::{ExtrinsicFor, ExtrinsicHash},
+	LOG_TARGET,
+};
+
+/// How often the changes to the journal are written to disk.
+pub(crate) const JOURNAL_WRITE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Minimal number of records in the log before it is compacted.
+const MIN_COMPACTION_RECORDS: usize = 1024;
+
+/// Shared journal of the transactions of given pool.
+pub(crate) type JournalFor<Api> = Arc<Mutex<Journal<ExtrinsicHash<Api>, ExtrinsicFor<Api>>>>;
+
+/// Single entry of the journal log.
+#[derive(Encode, Decode)]
+enum Record<Hash, Ex> {
+	/// Transaction was added to the journal.
+	Add(TransactionSource, Ex),
+	/// Transaction with given hash was removed from the journal.
+	Remove(Hash),
+}
+
+/// Pending change of the journal file.
+#[derive(Debug, PartialEq)]
+pub(crate) enum JournalWrite {
+	/// Encoded records to be appended to the log.
+	Append(Vec<u8>),
+	/// Encoded records replacing the whole log.
+	Replace(Vec<u8>),
+}
+
+/// Transactions journal persisted to a file.
+pub(crate) struct Journal<Hash: Encode, Ex: Encode> {
+	/// File the journal is persisted to.
+	path: PathBuf,
+	/// Journaled transactions.
+	transactions: HashMap<Hash, (TransactionSource, Ex)>,
+	/// Transactions restored from disk that are yet to be resubmitted to the pool.
+	pending: Vec<(Hash, TransactionSource, Ex)>,
+	/// Restored transactions that are being revalidated before being resubmitted to the pool.
+	restoring: HashSet<Hash>,
+	/// Records that are not written to disk yet.
+	unwritten: Vec<Record<Hash, Ex>>,
+	/// Number of records in the log on disk.
+	written: usize,
+}
+
+impl<Hash, Ex> Journal<Hash, Ex>
+where
+	Hash: hash::Hash + Eq + Clone + Encode + Decode,
+	Ex: Encode + Decode + Clone,
+{
+	/// Opens the journal at given path, restoring the transactions journaled previously.
+	///
+	/// The restored transactions are kept in the journal until they are resubmitted, see
+	/// [`Journal::take_pending`]. A journal that can not be read is discarded.
+	pub fn open(path: PathBuf, hash_of: impl Fn(&Ex) -> Hash) -> Self {
+		let records = match read_journal::<Hash, Ex>(&path) {
+			Ok(records) => records,
+			Err(e) => {
+				log::warn!(
+					target: LOG_TARGET,
+					"Discarding transaction pool journal at {}: {}",
+					path.display(),
+					e,
+				);
+				Vec::new()
+			},
+		};
+
+		let written = records.len();
+		let mut transactions = HashMap::new();
+		for record in records {
+			match record {
+				Record::Add(source, xt) => {
+					transactions.insert(hash_of(&xt), (source, xt));
+				},
+				Record::Remove(hash) => {
+					transactions.remove(&hash);
+				},
+			}
+		}
+		log::debug!(
+			target: LOG_TARGET,
+			"Restored {} transactions from the journal at {}",
+			transactions.len(),
+			path.display(),
+		);
+
+		let pending = transactions
+			.iter()
+			.map(|(hash, (source, xt))| (hash.clone(), *source, xt.clone()))
+			.collect();
+
+		Self {
+			path,
+			transactions,
+			pending,
+			restoring: Default::default(),
+			unwritten: Vec::new(),
+			written,
+		}
+	}
+
+	/// Takes the restored transactions that are yet to be resubmitted to the pool.
+	///
+	/// The transactions stay in the journal until [`Journal::restored`] is called for them.
+	pub fn take_pending(&mut self) -> Vec<(Hash, TransactionSource, Ex)> {
+		let pending = std::mem::take(&mut self.pending);
+		self.restoring.extend(pending.iter().map(|(hash, _, _)| hash.clone()));
+		pending
+	}
+
+	/// Notes that the restored transaction was either resubmitted to the pool or found invalid.
+	pub fn restored(&mut self, hash: &Hash) {
+		self.restoring.remove(hash);
+	}
+
+	/// Records a transaction successfully submitted to the pool.
+	///
+	/// Transactions coming from blocks are not journaled.
+	pub fn record(&mut self, hash: Hash, source: TransactionSource, xt: Ex) {
+		if source == TransactionSource::InBlock || self.transactions.contains_key(&hash) {
+			return
+		}
+		self.unwritten.push(Record::Add(source, xt.clone()));
+		self.transactions.insert(hash, (source, xt));
+	}
+
+	/// Removes given transactions from the journal.
+	pub fn remove<'a>(&mut self, hashes: impl IntoIterator<Item = &'a Hash>)
+	where
+		Hash: 'a,
+	{
+		for hash in hashes {
+			if self.transactions.remove(hash).is_some() {
+				self.unwritten.push(Record::Remove(hash.clone()));
+			}
+		}
+	}
+
+	/// Retains only the transactions for which the predicate returns true.
+	///
+	/// Restored transactions that are yet to be resubmitted are always retained.
+	pub fn retain(&mut self, mut f: impl FnMut(&Hash) -> bool) {
+		let pending = self.pending.iter().map(|(hash, _, _)| hash).collect::<HashSet<_>>();
+		let restoring = &self.restoring;
+		let unwritten = &mut self.unwritten;
+		self.transactions.retain(|hash, _| {
+			let retain = pending.contains(hash) || restoring.contains(hash) || f(hash);
+			if !retain {
+				unwritten.push(Record::Remove(hash.clone()));
+			}
+			retain
+		});
+	}
+
+	/// Number of journaled transactions.
+	pub fn len(&self) -> usize {
+		self.transactions.len()
+	}
+
+	/// Takes the changes that are yet to be written to disk.
+	///
+	/// The log is rewritten from scratch once it holds considerably more records than there are
+	/// journaled transactions.
+	pub fn take_write(&mut self) -> Option<JournalWrite> {
+		if self.unwritten.is_empty() {
+			return None
+		}
+
+		let records = self.written + self.unwritten.len();
+		if records > MIN_COMPACTION_RECORDS && records > 2 * self.transactions.len() {
+			self.unwritten.clear();
+			self.written = self.transactions.len();
+			let mut encoded = Vec::new();
+			for (source, xt) in self.transactions.values() {
+				Record::<Hash, _>::Add(*source, xt).encode_to(&mut encoded);
+			}
+			return Some(JournalWrite::Replace(encoded))
+		}
+
+		self.written = records;
+		let mut encoded = Vec::new();
+		for record in self.unwritten.drain(..) {
+			record.encode_to(&mut encoded);
+		}
+		Some(JournalWrite::Append(encoded))
+	}
+
+	/// File the journal is persisted to.
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+}
+
+impl<Hash: Encode, Ex: Encode> Drop for Journal<Hash, Ex> {
+	fn drop(&mut self) {
+		if self.unwritten.is_empty() {
+			return
+		}
+		let mut encoded = Vec::new();
+		for record in &self.unwritten {
+			record.encode_to(&mut encoded);
+		}
+		if let Err(e) = write_journal(&self.path, JournalWrite::Append(encoded)) {
+			log::warn!(target: LOG_TARGET, "Failed to write transaction pool journal: {}", e);
+		}
+	}
+}
+
+/// Periodically writes the changes to the journal to disk, until the journal is dropped.
+///
+/// The file is written on the blocking thread pool.
+pub(crate) async fn run_writer<Hash, Ex>(
+	journal: Weak<Mutex<Journal<Hash, Ex>>>,
+	interval: Duration,
+) where
+	Hash: hash::Hash + Eq + Clone + Encode + Decode + Send + 'static,
+	Ex: Encode + Decode + Clone + Send + 'static,
+{
+	loop {
+		futures_timer::Delay::new(interval).await;
+
+		let journal = match journal.upgrade() {
+			Some(journal) => journal,
+			None => break,
+		};
+		let (path, write) = {
+			let mut journal = journal.lock();
+			(journal.path().to_path_buf(), journal.take_write())
+		};
+		let Some(write) = write else { continue };
+
+		// Keep the journal alive while writing, so that the final write on drop can't race with
+		// this one.
+		let result = tokio::task::spawn_blocking(move || {
+			let result = write_journal(&path, write);
+			drop(journal);
+			result
+		})
+		.await;
+		match result {
+			Ok(Ok(())) => {},
+			Ok(Err(e)) =>
+				log::warn!(target: LOG_TARGET, "Failed to write transaction pool journal: {}", e),
+			Err(e) =>
+				log::warn!(target: LOG_TARGET, "Transaction pool journal writer failed: {}", e),
+		}
+	}
+}
+
+/// Writes the changes to the journal to given file.
+///
+/// A replaced journal is written to a temporary file first, which then replaces the previous
+/// journal, so that a crash never leaves a partially written journal behind.
+pub(crate) fn write_journal(path: &Path, write: JournalWrite) -> io::Result<()> {
+	match write {
+		JournalWrite::Append(encoded) => fs::OpenOptions::new()
+			.create(true)
+			.append(true)
+			.open(path)
+			.and_then(|mut file| file.write_all(&encoded)),
+		JournalWrite::Replace(encoded) => {
+			let tmp_path = path.with_extension("tmp");
+			fs::write(&tmp_path, encoded)?;
+			fs::rename(&tmp_path, path)
+		},
+	}
+}
+
+/// Reads the records of the journal from given file. A missing file is an empty journal.
+///
+/// A record that was only partially written, e.g. because of a crash, ends the journal.
+fn read_journal<Hash: Decode, Ex: Decode>(path: &Path) -> io::Result<Vec<Record<Hash, Ex>>> {
+	let encoded = match fs::read(path) {
+		Ok(encoded) => encoded,
+		Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+		Err(e) => return Err(e),
+	};
+
+	let mut input = &encoded[..];
+	let mut records = Vec::new();
+	while !input.is_empty() {
+		match Record::decode(&mut input) {
+			Ok(record) => records.push(record),
+			Err(e) if records.is_empty() =>
+				return Err(io::Error::new(io::ErrorKind::InvalidData, e.to_string())),
+			Err(e) => {
+				log::warn!(
+					target: LOG_TARGET,
+					"Ignoring the end of the transaction pool journal at {}: {}",
+					path.display(),
+					e,
+				);
+				break
+			},
+		}
+	}
+	Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::io::Write;
+
+	fn hash_of(xt: &Vec<u8>) -> u64 {
+		xt[0] as u64
+	}
+
+	fn flush(journal: &mut Journal<u64, Vec<u8>>) {
+		if let Some(write) = journal.take_write() {
+			write_journal(journal.path(), write).unwrap();
+		}
+	}
+
+	#[test]
+	fn journal_survives_reopening() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("txpool_journal");
+
+		let mut journal = Journal::<u64, Vec<u8>>::open(path.clone(), hash_of);
+		assert!(journal.take_pending().is_empty());
+		journal.record(1, TransactionSource::External, vec![1]);
+		journal.record(2, TransactionSource::Local, vec![2, 2]);
+		journal.record(3, TransactionSource::InBlock, vec![3]);
+		flush(&mut journal);
+		journal.record(4, TransactionSource::External, vec![4]);
+		journal.remove(&[4]);
+		flush(&mut journal);
+		drop(journal);
+
+		let mut journal = Journal::<u64, Vec<u8>>::open(path, hash_of);
+		let mut pending = journal.take_pending();
+		pending.sort_by_key(|(hash, _, _)| *hash);
+		assert_eq!(
+			pending,
+			vec![
+				(1, TransactionSource::External, vec![1]),
+				(2, TransactionSource::Local, vec![2, 2]),
+			],
+		);
+		assert_eq!(journal.len(), 2);
+		assert!(journal.take_pending().is_empty());
+	}
+
+	#[test]
+	fn unwritten_changes_are_written_on_drop() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("txpool_journal");
+
+		let mut journal = Journal::<u64, Vec<u8>>::open(path.clone(), hash_of);
+		journal.record(1, TransactionSource::External, vec![1]);
+		drop(journal);
+
+		let journal = Journal::<u64, Vec<u8>>::open(path, hash_of);
+		assert_eq!(journal.len(), 1);
+	}
+
+	#[test]
+	fn changes_are_appended_until_compaction() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("txpool_journal");
+
+		let mut journal = Journal::<u64, Vec<u8>>::open(path.clone(), hash_of);
+		journal.record(1, TransactionSource::External, vec![1]);
+		assert!(matches!(journal.take_write(), Some(JournalWrite::Append(_))));
+		assert_eq!(journal.take_write(), None);
+
+		for _ in 0..MIN_COMPACTION_RECORDS / 2 {
+			journal.record(2, TransactionSource::External, vec![2]);
+			journal.remove(&[2]);
+		}
+		assert_eq!(
+			journal.take_write(),
+			Some(JournalWrite::Replace(
+				Record::<u64, _>::Add(TransactionSource::External, vec![1u8]).encode()
+			)),
+		);
+	}
+
+	#[test]
+	fn retain_prunes_transactions() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("txpool_journal");
+
+		let mut journal = Journal::<u64, Vec<u8>>::open(path.clone(), hash_of);
+		journal.record(1, TransactionSource::External, vec![1]);
+		journal.record(2, TransactionSource::External, vec![2]);
+		journal.retain(|hash| *hash == 2);
+		flush(&mut journal);
+
+		let journal = Journal::<u64, Vec<u8>>::open(path, hash_of);
+		assert_eq!(journal.len(), 1);
+	}
+
+	#[test]
+	fn restoring_transactions_are_retained() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("txpool_journal");
+
+		let mut journal = Journal::<u64, Vec<u8>>::open(path.clone(), hash_of);
+		journal.record(1, TransactionSource::External, vec![1]);
+		flush(&mut journal);
+		drop(journal);
+
+		let mut journal = Journal::<u64, Vec<u8>>::open(path, hash_of);
+		assert_eq!(journal.take_pending().len(), 1);
+		journal.retain(|_| false);
+		assert_eq!(journal.len(), 1);
+
+		journal.restored(&1);
+		journal.retain(|_| false);
+		assert_eq!(journal.len(), 0);
+	}
+
+	#[test]
+	fn corrupted_journal_is_discarded() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("txpool_journal");
+		fs::write(&path, [0xff; 3]).unwrap();
+
+		let mut journal = Journal::<u64, Vec<u8>>::open(path, hash_of);
+		assert!(journal.take_pending().is_empty());
+		assert_eq!(journal.len(), 0);
+	}
+
+	#[test]
+	fn partially_written_record_is_ignored() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("txpool_journal");
+
+		let mut journal = Journal::<u64, Vec<u8>>::open(path.clone(), hash_of);
+		journal.record(1, TransactionSource::External, vec![1]);
+		flush(&mut journal);
+		drop(journal);
+		let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+		file.write_all(&[0x00]).unwrap();
+
+		let journal = Journal::<u64, Vec<u8>>::open(path, hash_of);
+		assert_eq!(journal.len(), 1);
+	}
+}
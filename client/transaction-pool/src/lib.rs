@@ -26,6 +26,7 @@ mod api;
 mod enactment_state;
 pub mod error;
 mod graph;
+mod journal;
 mod metrics;
 mod revalidation;
 #[cfg(test)]
@@ -40,8 +41,12 @@ use futures::{
 	prelude::*,
 };
 pub use graph::{
-	base_pool::Limit as PoolLimit, ChainApi, Options, Pool, Transaction, ValidatedTransaction,
+	base_pool::{
+		Limit as PoolLimit, ReservedLane as PoolReservedLane, SenderLimit as PoolSenderLimit,
+	},
+	ChainApi, Options, Pool, Transaction, ValidatedTransaction,
 };
+use journal::JournalFor;
 use parking_lot::Mutex;
 use std::{
 	collections::{HashMap, HashSet},
@@ -52,8 +57,8 @@ use std::{
 use graph::{ExtrinsicHash, IsValidator};
 use sc_transaction_pool_api::{
 	error::Error as TxPoolError, ChainEvent, ImportNotificationStream, MaintainedTransactionPool,
-	PoolFuture, PoolStatus, ReadyTransactions, TransactionFor, TransactionPool, TransactionSource,
-	TransactionStatusStreamFor, TxHash,
+	PoolFuture, PoolStatus, ReadyTransactions, RemoveLocalError, TransactionFor, TransactionPool,
+	TransactionSource, TransactionStatusStreamFor, TxHash,
 };
 use sp_core::traits::SpawnEssentialNamed;
 use sp_runtime::{
@@ -93,6 +98,7 @@ where
 	ready_poll: Arc<Mutex<ReadyPoll<ReadyIteratorFor<PoolApi>, Block>>>,
 	metrics: PrometheusMetrics,
 	enactment_state: Arc<Mutex<EnactmentState<Block>>>,
+	journal: Option<JournalFor<PoolApi>>,
 }
 
 struct ReadyPoll<T, Block: BlockT> {
@@ -180,6 +186,7 @@ where
 					best_block_hash,
 					finalized_hash,
 				))),
+				journal: None,
 			},
 			background_task,
 		)
@@ -198,7 +205,16 @@ where
 		best_block_hash: Block::Hash,
 		finalized_hash: Block::Hash,
 	) -> Self {
-		let pool = Arc::new(graph::Pool::new(options, is_validator, pool_api.clone()));
+		let metrics = PrometheusMetrics::new(prometheus);
+		let journal = options.journal_path.clone().map(|path| {
+			Arc::new(Mutex::new(journal::Journal::open(path, |xt| pool_api.hash_and_length(xt).0)))
+		});
+		let pool = Arc::new(graph::Pool::with_metrics(
+			options,
+			is_validator,
+			pool_api.clone(),
+			metrics.clone(),
+		));
 		let (revalidation_queue, background_task) = match revalidation_type {
 			RevalidationType::Light =>
 				(revalidation::RevalidationQueue::new(pool_api.clone(), pool.clone()), None),
@@ -213,6 +229,15 @@ where
 			spawner.spawn_essential("txpool-background", Some("transaction-pool"), background_task);
 		}
 
+		if let Some(journal) = &journal {
+			spawner.spawn_essential(
+				"txpool-journal",
+				Some("transaction-pool"),
+				journal::run_writer(Arc::downgrade(journal), journal::JOURNAL_WRITE_INTERVAL)
+					.boxed(),
+			);
+		}
+
 		Self {
 			api: pool_api,
 			pool,
@@ -223,11 +248,12 @@ where
 				RevalidationType::Full => RevalidationStrategy::Always,
 			})),
 			ready_poll: Arc::new(Mutex::new(ReadyPoll::new(best_block_number))),
-			metrics: PrometheusMetrics::new(prometheus),
+			metrics,
 			enactment_state: Arc::new(Mutex::new(EnactmentState::new(
 				best_block_hash,
 				finalized_hash,
 			))),
+			journal,
 		}
 	}
 
@@ -260,11 +286,24 @@ where
 	) -> PoolFuture<Vec<Result<TxHash<Self>, Self::Error>>, Self::Error> {
 		let pool = self.pool.clone();
 		let at = *at;
+		let journaled = self.journal.clone().map(|journal| (journal, xts.clone()));
 
 		self.metrics
 			.report(|metrics| metrics.submitted_transactions.inc_by(xts.len() as u64));
 
-		async move { pool.submit_at(&at, source, xts).await }.boxed()
+		async move {
+			let results = pool.submit_at(&at, source, xts).await?;
+			if let Some((journal, xts)) = journaled {
+				let mut journal = journal.lock();
+				for (xt, result) in xts.into_iter().zip(&results) {
+					if let Ok(hash) = result {
+						journal.record(*hash, source, xt);
+					}
+				}
+			}
+			Ok(results)
+		}
+		.boxed()
 	}
 
 	fn submit_one(
@@ -275,10 +314,18 @@ where
 	) -> PoolFuture<TxHash<Self>, Self::Error> {
 		let pool = self.pool.clone();
 		let at = *at;
+		let journaled = self.journal.clone().map(|journal| (journal, xt.clone()));
 
 		self.metrics.report(|metrics| metrics.submitted_transactions.inc());
 
-		async move { pool.submit_one(&at, source, xt).await }.boxed()
+		async move {
+			let hash = pool.submit_one(&at, source, xt).await?;
+			if let Some((journal, xt)) = journaled {
+				journal.lock().record(hash, source, xt);
+			}
+			Ok(hash)
+		}
+		.boxed()
 	}
 
 	fn submit_and_watch(
@@ -289,11 +336,15 @@ where
 	) -> PoolFuture<Pin<Box<TransactionStatusStreamFor<Self>>>, Self::Error> {
 		let at = *at;
 		let pool = self.pool.clone();
+		let journaled = self.journal.clone().map(|journal| (journal, xt.clone()));
 
 		self.metrics.report(|metrics| metrics.submitted_transactions.inc());
 
 		async move {
 			let watcher = pool.submit_and_watch(&at, source, xt).await?;
+			if let Some((journal, xt)) = journaled {
+				journal.lock().record(*watcher.hash(), source, xt);
+			}
 
 			Ok(watcher.into_stream().boxed())
 		}
@@ -302,11 +353,29 @@ where
 
 	fn remove_invalid(&self, hashes: &[TxHash<Self>]) -> Vec<Arc<Self::InPoolTransaction>> {
 		let removed = self.pool.validated_pool().remove_invalid(hashes);
+		if let Some(journal) = &self.journal {
+			journal.lock().remove(hashes);
+		}
 		self.metrics
 			.report(|metrics| metrics.validations_invalid.inc_by(removed.len() as u64));
 		removed
 	}
 
+	fn remove_local(
+		&self,
+		hashes: &[TxHash<Self>],
+	) -> Result<Vec<Arc<Self::InPoolTransaction>>, RemoveLocalError<TxHash<Self>>> {
+		let removed = self
+			.pool
+			.validated_pool()
+			.remove_local(hashes)
+			.map_err(RemoveLocalError::NotLocal)?;
+		if let Some(journal) = &self.journal {
+			journal.lock().remove(removed.iter().map(|tx| &tx.hash));
+		}
+		Ok(removed)
+	}
+
 	fn status(&self) -> PoolStatus {
 		self.pool.validated_pool().status()
 	}
@@ -445,6 +514,7 @@ where
 			.block_id_to_number(&BlockId::hash(at))?
 			.ok_or_else(|| error::Error::BlockIdConversion(format!("{:?}", at)))?;
 
+		let journaled = self.journal.clone().map(|journal| (journal, xt.clone()));
 		let validated = ValidatedTransaction::valid_at(
 			block_number.saturated_into::<u64>(),
 			hash,
@@ -454,7 +524,11 @@ where
 			validity,
 		);
 
-		self.pool.validated_pool().submit(vec![validated]).remove(0)
+		let hash = self.pool.validated_pool().submit(vec![validated]).remove(0)?;
+		if let Some((journal, xt)) = journaled {
+			journal.lock().record(hash, TransactionSource::Local, xt);
+		}
+		Ok(hash)
 	}
 }
 
@@ -641,6 +715,10 @@ where
 		self.metrics
 			.report(|metrics| metrics.block_transactions_pruned.inc_by(pruned_log.len() as u64));
 
+		if let Some(journal) = &self.journal {
+			journal.lock().remove(&pruned_log);
+		}
+
 		if next_action.resubmit {
 			let mut resubmit_transactions = Vec::new();
 
@@ -716,6 +794,38 @@ where
 			self.revalidation_strategy.lock().clear();
 		}
 	}
+
+	/// Sends the transactions restored from the journal to the revalidation queue on the first new
+	/// best block and drops the transactions that left the pool from the journal.
+	///
+	/// The journal is written to disk in the background, see [`journal::run_writer`].
+	async fn maintain_journal(
+		&self,
+		journal: &JournalFor<PoolApi>,
+		best_hash: Option<Block::Hash>,
+	) {
+		if let Some(hash) = best_hash {
+			match self.api.block_id_to_number(&BlockId::Hash(hash)) {
+				Ok(Some(number)) => {
+					let pending = journal.lock().take_pending();
+					self.revalidation_queue.restore_later(number, journal.clone(), pending).await;
+				},
+				result => log::debug!(
+					target: LOG_TARGET,
+					"[{:?}] Not restoring journaled transactions, unknown block number: {:?}",
+					hash,
+					result,
+				),
+			}
+		}
+
+		// The journal is locked before taking the snapshot, so transactions imported after the
+		// snapshot are only recorded after they were retained.
+		let mut journal = journal.lock();
+		let imported = self.pool.validated_pool().imported_hashes();
+		journal.retain(|hash| imported.contains(hash));
+		log::trace!(target: LOG_TARGET, "Journaled {} transactions", journal.len());
+	}
 }
 
 #[async_trait]
@@ -755,6 +865,11 @@ where
 			},
 		};
 
+		let best_hash = match &event {
+			ChainEvent::NewBestBlock { hash, .. } => Some(*hash),
+			ChainEvent::Finalized { .. } => None,
+		};
+
 		if let ChainEvent::Finalized { hash, tree_route } = event {
 			log::trace!(
 				target: LOG_TARGET,
@@ -772,6 +887,10 @@ where
 				}
 			}
 		}
+
+		if let Some(journal) = &self.journal {
+			self.maintain_journal(journal, best_hash).await;
+		}
 	}
 }
 
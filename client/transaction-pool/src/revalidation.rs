@@ -25,14 +25,15 @@ use std::{
 };
 
 use crate::{
-	graph::{ChainApi, ExtrinsicHash, NumberFor, Pool, ValidatedTransaction},
+	graph::{ChainApi, ExtrinsicFor, ExtrinsicHash, NumberFor, Pool, ValidatedTransaction},
+	journal::JournalFor,
 	LOG_TARGET,
 };
 use sc_utils::mpsc::{tracing_unbounded, TracingUnboundedReceiver, TracingUnboundedSender};
 use sp_runtime::{
 	generic::BlockId,
 	traits::{SaturatedConversion, Zero},
-	transaction_validity::TransactionValidityError,
+	transaction_validity::{TransactionSource, TransactionValidityError},
 };
 
 use futures::prelude::*;
@@ -42,10 +43,17 @@ const BACKGROUND_REVALIDATION_INTERVAL: Duration = Duration::from_millis(200);
 
 const MIN_BACKGROUND_REVALIDATION_BATCH_SIZE: usize = 20;
 
+const MAX_BACKGROUND_RESTORE_BATCH_SIZE: usize = 256;
+
+/// Transaction restored from the journal.
+pub(crate) type RestoredTransaction<Api> =
+	(ExtrinsicHash<Api>, TransactionSource, ExtrinsicFor<Api>);
+
 /// Payload from queue to worker.
 struct WorkerPayload<Api: ChainApi> {
 	at: NumberFor<Api>,
 	transactions: Vec<ExtrinsicHash<Api>>,
+	restored: Option<(JournalFor<Api>, Vec<RestoredTransaction<Api>>)>,
 }
 
 /// Async revalidation worker.
@@ -57,6 +65,8 @@ struct RevalidationWorker<Api: ChainApi> {
 	best_block: NumberFor<Api>,
 	block_ordered: BTreeMap<NumberFor<Api>, HashSet<ExtrinsicHash<Api>>>,
 	members: HashMap<ExtrinsicHash<Api>, NumberFor<Api>>,
+	journal: Option<JournalFor<Api>>,
+	restored: Vec<RestoredTransaction<Api>>,
 }
 
 impl<Api: ChainApi> Unpin for RevalidationWorker<Api> {}
@@ -134,6 +144,67 @@ async fn batch_revalidate<Api: ChainApi>(
 	}
 }
 
+/// Validate batch of transactions restored from the journal.
+///
+/// Valid transactions are imported to the `pool`, the others are dropped. All of them are then
+/// reported to the `journal` as restored.
+async fn batch_restore<Api: ChainApi>(
+	pool: Arc<Pool<Api>>,
+	api: Arc<Api>,
+	journal: JournalFor<Api>,
+	at: NumberFor<Api>,
+	batch: Vec<RestoredTransaction<Api>>,
+) {
+	let validation_results =
+		futures::future::join_all(batch.into_iter().map(|(ext_hash, source, ext)| {
+			api.validate_transaction(&BlockId::Number(at), source, ext.clone())
+				.map(move |validation_result| (validation_result, ext_hash, source, ext))
+		}))
+		.await;
+
+	let mut hashes = Vec::with_capacity(validation_results.len());
+	let mut validated = Vec::new();
+	for (validation_result, ext_hash, source, ext) in validation_results {
+		hashes.push(ext_hash);
+		match validation_result {
+			Ok(Ok(validity)) => {
+				let bytes = api.hash_and_length(&ext).1;
+				validated.push(ValidatedTransaction::valid_at(
+					at.saturated_into::<u64>(),
+					ext_hash,
+					source,
+					ext,
+					bytes,
+					validity,
+				));
+			},
+			Ok(Err(err)) => {
+				log::debug!(
+					target: LOG_TARGET,
+					"[{:?}]: Dropping restored transaction: {:?}",
+					ext_hash,
+					err,
+				);
+			},
+			Err(validation_err) => {
+				log::debug!(
+					target: LOG_TARGET,
+					"[{:?}]: Dropping restored transaction due to error during validation: {}",
+					ext_hash,
+					validation_err,
+				);
+			},
+		}
+	}
+
+	pool.validated_pool().submit(validated);
+
+	let mut journal = journal.lock();
+	for ext_hash in &hashes {
+		journal.restored(ext_hash);
+	}
+}
+
 impl<Api: ChainApi> RevalidationWorker<Api> {
 	fn new(api: Arc<Api>, pool: Arc<Pool<Api>>) -> Self {
 		Self {
@@ -142,6 +213,8 @@ impl<Api: ChainApi> RevalidationWorker<Api> {
 			block_ordered: Default::default(),
 			members: Default::default(),
 			best_block: Zero::zero(),
+			journal: None,
+			restored: Default::default(),
 		}
 	}
 
@@ -188,6 +261,11 @@ impl<Api: ChainApi> RevalidationWorker<Api> {
 	}
 
 	fn push(&mut self, worker_payload: WorkerPayload<Api>) {
+		if let Some((journal, restored)) = worker_payload.restored {
+			self.journal = Some(journal);
+			self.restored.extend(restored);
+		}
+
 		// we don't add something that already scheduled for revalidation
 		let transactions = worker_payload.transactions;
 		let block_number = worker_payload.at;
@@ -237,6 +315,14 @@ impl<Api: ChainApi> RevalidationWorker<Api> {
 			futures::select! {
 				// Using `fuse()` in here is okay, because we reset the interval when it has fired.
 				_ = (&mut interval_fut).fuse() => {
+					if let Some(journal) = this.journal.clone() {
+						let take = this.restored.len().min(MAX_BACKGROUND_RESTORE_BATCH_SIZE);
+						let restore_batch = this.restored.drain(..take).collect::<Vec<_>>();
+						if !restore_batch.is_empty() {
+							batch_restore(this.pool.clone(), this.api.clone(), journal, this.best_block, restore_batch).await;
+						}
+					}
+
 					let next_batch = this.prepare_batch();
 					let batch_len = next_batch.len();
 
@@ -268,7 +354,7 @@ impl<Api: ChainApi> RevalidationWorker<Api> {
 								);
 							}
 
-							continue;
+							continue
 						},
 						// R.I.P. worker!
 						None => break,
@@ -340,7 +426,9 @@ where
 		}
 
 		if let Some(ref to_worker) = self.background {
-			if let Err(e) = to_worker.unbounded_send(WorkerPayload { at, transactions }) {
+			if let Err(e) =
+				to_worker.unbounded_send(WorkerPayload { at, transactions, restored: None })
+			{
 				log::warn!(target: LOG_TARGET, "Failed to update background worker: {:?}", e);
 			}
 		} else {
@@ -349,6 +437,41 @@ where
 			batch_revalidate(pool, api, at, transactions).await
 		}
 	}
+
+	/// Queue transactions restored from the journal to be validated and imported to the pool.
+	///
+	/// Every processed transaction is reported to the `journal` as restored.
+	/// If queue configured with background worker, this will return immediately.
+	/// If queue configured without background worker, this will resolve after
+	/// the transactions are actually imported.
+	pub async fn restore_later(
+		&self,
+		at: NumberFor<Api>,
+		journal: JournalFor<Api>,
+		restored: Vec<RestoredTransaction<Api>>,
+	) {
+		if restored.is_empty() {
+			return
+		}
+
+		log::debug!(
+			target: LOG_TARGET,
+			"Sent {} restored transactions to revalidation queue",
+			restored.len(),
+		);
+
+		if let Some(ref to_worker) = self.background {
+			let payload =
+				WorkerPayload { at, transactions: Vec::new(), restored: Some((journal, restored)) };
+			if let Err(e) = to_worker.unbounded_send(payload) {
+				log::warn!(target: LOG_TARGET, "Failed to update background worker: {:?}", e);
+			}
+		} else {
+			let pool = self.pool.clone();
+			let api = self.api.clone();
+			batch_restore(pool, api, journal, at, restored).await
+		}
+	}
 }
 
 #[cfg(test)]
@@ -356,6 +479,7 @@ mod tests {
 	use super::*;
 	use crate::{
 		graph::Pool,
+		journal::Journal,
 		tests::{uxt, TestApi},
 	};
 	use futures::executor::block_on;
@@ -390,4 +514,39 @@ mod tests {
 		// number of ready
 		assert_eq!(pool.validated_pool().status().ready, 1);
 	}
+
+	#[test]
+	fn restored_transactions_are_imported() {
+		let api = Arc::new(TestApi::default());
+		let pool = Arc::new(Pool::new(Default::default(), true.into(), api.clone()));
+		let queue = Arc::new(RevalidationQueue::new(api.clone(), pool.clone()));
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("txpool_journal");
+
+		let uxt = uxt(Transfer {
+			from: Alice.into(),
+			to: AccountId::from_h256(H256::from_low_u64_be(2)),
+			amount: 5,
+			nonce: 0,
+		});
+		let uxt_hash = pool.hash_of(&uxt);
+		let hash_of = |xt: &_| api.hash_and_length(xt).0;
+		Journal::open(path.clone(), hash_of).record(
+			uxt_hash,
+			TransactionSource::External,
+			uxt.clone(),
+		);
+
+		let journal = Arc::new(parking_lot::Mutex::new(Journal::open(path, hash_of)));
+		let pending = journal.lock().take_pending();
+		assert_eq!(pending, vec![(uxt_hash, TransactionSource::External, uxt)]);
+		block_on(queue.restore_later(0, journal.clone(), pending));
+
+		assert_eq!(api.validation_requests().len(), 1);
+		assert_eq!(pool.validated_pool().status().ready, 1);
+		// no longer kept in the journal once it leaves the pool
+		pool.validated_pool().remove_invalid(&[uxt_hash]);
+		journal.lock().retain(|hash| pool.validated_pool().is_imported(hash));
+		assert_eq!(journal.lock().len(), 0);
+	}
 }
@@ -28,7 +28,7 @@ use sc_block_builder::BlockBuilderProvider;
 use sc_client_api::client::BlockchainEvents;
 use sc_transaction_pool::*;
 use sc_transaction_pool_api::{
-	ChainEvent, MaintainedTransactionPool, TransactionPool, TransactionStatus,
+	ChainEvent, MaintainedTransactionPool, RemoveLocalError, TransactionPool, TransactionStatus,
 };
 use sp_blockchain::HeaderBackend;
 use sp_consensus::BlockOrigin;
@@ -188,6 +188,53 @@ fn should_ban_invalid_transactions() {
 	block_on(pool.submit_one(&BlockId::number(0), SOURCE, uxt.clone())).unwrap_err();
 }
 
+#[test]
+fn should_not_ban_local_transactions_removed_by_hash() {
+	let xt1 = uxt(Alice, 209);
+	let xt2 = uxt(Alice, 210);
+	let local = TransactionSource::Local;
+
+	let (pool, _api, _guard) = maintained_pool();
+	let watcher = block_on(pool.submit_and_watch(&BlockId::number(0), local, xt1.clone()))
+		.expect("1. Imported");
+	block_on(pool.submit_one(&BlockId::number(0), local, xt2.clone())).expect("2. Imported");
+	assert_eq!(pool.status().ready, 2);
+
+	// when
+	let hash1 = pool.hash_of(&xt1);
+	let removed = pool.remove_local(&[hash1]).expect("Submitted locally");
+
+	// then
+	assert_eq!(removed.len(), 2);
+	assert_eq!(pool.status().ready, 0);
+	assert_eq!(
+		futures::executor::block_on_stream(watcher).collect::<Vec<_>>(),
+		vec![TransactionStatus::Ready, TransactionStatus::Dropped],
+	);
+	block_on(pool.submit_one(&BlockId::number(0), local, xt1)).expect("1. Reimported");
+	assert_eq!(pool.status().ready, 1);
+}
+
+#[test]
+fn should_not_remove_external_transactions_by_hash() {
+	let xt1 = uxt(Alice, 209);
+	let xt2 = uxt(Bob, 0);
+
+	let (pool, _api, _guard) = maintained_pool();
+	block_on(pool.submit_one(&BlockId::number(0), TransactionSource::Local, xt1.clone()))
+		.expect("1. Imported");
+	block_on(pool.submit_one(&BlockId::number(0), SOURCE, xt2.clone())).expect("2. Imported");
+
+	// when
+	let hash1 = pool.hash_of(&xt1);
+	let hash2 = pool.hash_of(&xt2);
+	let result = pool.remove_local(&[hash1, hash2]);
+
+	// then
+	assert_eq!(result.map(|removed| removed.len()), Err(RemoveLocalError::NotLocal(vec![hash2])));
+	assert_eq!(pool.status().ready, 2);
+}
+
 #[test]
 fn only_prune_on_new_best() {
 	let (pool, api, _) = maintained_pool();
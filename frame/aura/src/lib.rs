@@ -44,7 +44,9 @@ use frame_support::{
 	traits::{DisabledValidators, FindAuthor, Get, OnTimestampSet, OneSessionHandler},
 	BoundedSlice, BoundedVec, ConsensusEngineId, Parameter,
 };
-use sp_consensus_aura::{AuthorityIndex, ConsensusLog, Slot, AURA_ENGINE_ID};
+use sp_consensus_aura::{
+	AuthorityIndex, ConsensusLog, Slot, SlotDuration, SlotSchedule, AURA_ENGINE_ID,
+};
 use sp_runtime::{
 	generic::DigestItem,
 	traits::{IsMember, Member, SaturatedConversion, Saturating, Zero},
@@ -112,6 +114,12 @@ pub mod pallet {
 					assert!(current_slot < new_slot, "Slot must increase");
 				}
 
+				// Anchor a slot duration change at the slot following the one of the parent.
+				let slot_schedule = Self::slot_schedule();
+				if CurrentSlotSchedule::<T>::get() != Some(slot_schedule) {
+					CurrentSlotSchedule::<T>::put(slot_schedule);
+				}
+
 				CurrentSlot::<T>::put(new_slot);
 
 				if let Some(n_authorities) = <Authorities<T>>::decode_len() {
@@ -127,7 +135,7 @@ pub mod pallet {
 				// TODO [#3398] Generate offence report for all authorities that skipped their
 				// slots.
 
-				T::DbWeight::get().reads_writes(2, 1)
+				T::DbWeight::get().reads_writes(3, 2)
 			} else {
 				T::DbWeight::get().reads(1)
 			}
@@ -152,6 +160,17 @@ pub mod pallet {
 	#[pallet::getter(fn current_slot)]
 	pub(super) type CurrentSlot<T: Config> = StorageValue<_, Slot, ValueQuery>;
 
+	/// The slot schedule of this block.
+	///
+	/// This will be set in `on_initialize`, anchoring changes of the slot duration at the slot
+	/// following the one of the parent block.
+	///
+	/// The slot duration must not change in the same runtime upgrade that introduces this
+	/// storage: until it's first set, slots are counted from the unix epoch with the new slot
+	/// duration, which doesn't match the slot of the parent block.
+	#[pallet::storage]
+	pub(super) type CurrentSlotSchedule<T: Config> = StorageValue<_, SlotSchedule, OptionQuery>;
+
 	#[pallet::genesis_config]
 	#[derive(frame_support::DefaultNoBound)]
 	pub struct GenesisConfig<T: Config> {
@@ -223,6 +242,20 @@ impl<T: Config> Pallet<T> {
 		<T as pallet_timestamp::Config>::MinimumPeriod::get().saturating_mul(2u32.into())
 	}
 
+	/// Determine the slot schedule for the children of the current slot.
+	///
+	/// This follows changes of [`Self::slot_duration`], starting with the slot after
+	/// [`CurrentSlot`].
+	pub fn slot_schedule() -> SlotSchedule {
+		let slot_duration = SlotDuration::from_millis(Self::slot_duration().saturated_into());
+
+		match CurrentSlotSchedule::<T>::get() {
+			Some(slot_schedule) =>
+				slot_schedule.change_slot_duration(CurrentSlot::<T>::get(), slot_duration),
+			None => SlotSchedule::from_slot_duration(slot_duration),
+		}
+	}
+
 	/// Ensure the correctness of the state of this pallet.
 	///
 	/// This should be valid before or after each state transition of this pallet.
@@ -370,8 +403,7 @@ impl<T: Config> OnTimestampSet<T::Moment> for Pallet<T> {
 		let slot_duration = Self::slot_duration();
 		assert!(!slot_duration.is_zero(), "Aura slot duration cannot be zero.");
 
-		let timestamp_slot = moment / slot_duration;
-		let timestamp_slot = Slot::from(timestamp_slot.saturated_into::<u64>());
+		let timestamp_slot = Self::slot_schedule().slot_at(moment.saturated_into::<u64>());
 
 		assert!(
 			CurrentSlot::<T>::get() == timestamp_slot,
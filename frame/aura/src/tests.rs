@@ -21,7 +21,7 @@
 
 use crate::mock::{build_ext_and_execute_test, Aura, MockDisabledValidators, System};
 use codec::Encode;
-use frame_support::traits::OnInitialize;
+use frame_support::traits::{OnInitialize, OnTimestampSet};
 use sp_consensus_aura::{Slot, AURA_ENGINE_ID};
 use sp_runtime::{Digest, DigestItem};
 
@@ -115,3 +115,38 @@ fn pallet_always_rejects_decreasing_slot() {
 		Aura::on_initialize(43);
 	});
 }
+
+#[test]
+fn slot_duration_change_keeps_slots_increasing() {
+	// Authors a block at the given timestamp in the slot the node derives from the parent state.
+	fn author_block(number: u64, timestamp: u64) -> Slot {
+		let slot = Aura::slot_schedule().slot_at(timestamp);
+		let pre_digest =
+			Digest { logs: vec![DigestItem::PreRuntime(AURA_ENGINE_ID, slot.encode())] };
+
+		System::reset_events();
+		System::initialize(&number, &System::parent_hash(), &pre_digest);
+		Aura::on_initialize(number);
+		Aura::on_timestamp_set(timestamp);
+		System::finalize();
+
+		assert_eq!(Aura::current_slot(), slot);
+		slot
+	}
+
+	build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+		crate::mock::MinimumPeriod::set(1_000);
+		assert_eq!(author_block(1, 10_000), 5u64);
+
+		// Counting slots of the new duration from the unix epoch would move them backwards.
+		crate::mock::MinimumPeriod::set(3_000);
+		assert_eq!(author_block(2, 12_500), 6u64);
+		assert_eq!(author_block(3, 18_000), 7u64);
+		assert_eq!(author_block(4, 30_000), 9u64);
+
+		// Going back to the previous duration is anchored as well.
+		crate::mock::MinimumPeriod::set(1_000);
+		assert_eq!(author_block(5, 37_000), 10u64);
+		assert_eq!(author_block(6, 38_000), 11u64);
+	});
+}
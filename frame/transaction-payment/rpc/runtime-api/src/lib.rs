@@ -20,7 +20,7 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use codec::Codec;
-use sp_runtime::traits::MaybeDisplay;
+use sp_runtime::{traits::MaybeDisplay, transaction_validity::TransactionPriority};
 
 pub use pallet_transaction_payment::{FeeDetails, InclusionFee, RuntimeDispatchInfo};
 
@@ -33,6 +33,19 @@ sp_api::decl_runtime_apis! {
 		fn query_fee_details(uxt: Block::Extrinsic, len: u32) -> FeeDetails<Balance>;
 		fn query_weight_to_fee(weight: sp_weights::Weight) -> Balance;
 		fn query_length_to_fee(length: u32) -> Balance;
+
+		/// Query the smallest tip the given extrinsic needs to reach the given priority in the
+		/// transaction pool.
+		#[api_version(5)]
+		fn query_tip_for_priority(
+			uxt: Block::Extrinsic,
+			len: u32,
+			priority: TransactionPriority,
+		) -> Balance;
+
+		/// Query the maximal total weight and length of `Normal` extrinsics in a block.
+		#[api_version(5)]
+		fn query_block_limits() -> (sp_weights::Weight, u32);
 	}
 
 	#[api_version(3)]
@@ -17,20 +17,24 @@
 
 //! RPC interface for the transaction payment pallet.
 
-use std::{convert::TryInto, sync::Arc};
+use std::{collections::HashMap, convert::TryInto, sync::Arc};
 
-use codec::{Codec, Decode};
+use codec::{Codec, Decode, Encode};
 use jsonrpsee::{
 	core::{Error as JsonRpseeError, RpcResult},
 	proc_macros::rpc,
 	types::error::{CallError, ErrorCode, ErrorObject},
 };
 use pallet_transaction_payment_rpc_runtime_api::{FeeDetails, InclusionFee, RuntimeDispatchInfo};
-use sp_api::ProvideRuntimeApi;
+use parking_lot::Mutex;
+use sc_rpc_api::DenyUnsafe;
+use sc_transaction_pool_api::{BlockHash, InPoolTransaction, TransactionPool, TxHash};
+use sp_api::{ApiExt, ProvideRuntimeApi};
 use sp_blockchain::HeaderBackend;
 use sp_core::Bytes;
 use sp_rpc::number::NumberOrHex;
 use sp_runtime::traits::{Block as BlockT, MaybeDisplay};
+use sp_weights::Weight;
 
 pub use pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi as TransactionPaymentRuntimeApi;
 
@@ -47,6 +51,26 @@ pub trait TransactionPaymentApi<BlockHash, ResponseType> {
 	) -> RpcResult<FeeDetails<NumberOrHex>>;
 }
 
+#[rpc(client, server)]
+pub trait TipEstimationApi<BlockHash> {
+	/// Estimate the tip the given extrinsic needs to be included within the next `blocks` blocks.
+	///
+	/// The estimation is based on the transactions currently ready in the transaction pool and
+	/// the block weight and length limits of the runtime. At most
+	/// [`MAX_QUERIED_TRANSACTIONS`] ready transactions are weighed per block, the weight of the
+	/// remaining ones is extrapolated from them.
+	///
+	/// The ready transactions are only known at the best block, so `at` has to be the best block
+	/// if given.
+	#[method(name = "payment_estimateTip", aliases = ["author_estimateTip"])]
+	fn estimate_tip(
+		&self,
+		encoded_xt: Bytes,
+		blocks: u32,
+		at: Option<BlockHash>,
+	) -> RpcResult<NumberOrHex>;
+}
+
 /// Provides RPC methods to query a dispatchable's class, weight and fee.
 pub struct TransactionPayment<C, P> {
 	/// Shared reference to the client.
@@ -61,6 +85,40 @@ impl<C, P> TransactionPayment<C, P> {
 	}
 }
 
+/// Provides RPC methods to estimate the tip needed to get a transaction included.
+pub struct TipEstimation<C, P: TransactionPool> {
+	/// Shared reference to the client.
+	client: Arc<C>,
+	/// Shared reference to the transaction pool.
+	pool: Arc<P>,
+	/// Weights of the ready transactions queried at the last estimated block.
+	weights: Mutex<Option<WeightCache<BlockHash<P>, TxHash<P>>>>,
+	/// Whether to deny unsafe calls.
+	deny_unsafe: DenyUnsafe,
+}
+
+impl<C, P: TransactionPool> TipEstimation<C, P> {
+	/// Creates a new instance of the TipEstimation Rpc helper.
+	pub fn new(client: Arc<C>, pool: Arc<P>, deny_unsafe: DenyUnsafe) -> Self {
+		Self { client, pool, weights: Mutex::new(None), deny_unsafe }
+	}
+}
+
+/// Dispatch weights of ready transactions, as queried at block `at`.
+struct WeightCache<BlockHash, TxHash> {
+	at: BlockHash,
+	weights: HashMap<TxHash, Weight>,
+}
+
+/// Minimal version of the runtime API that supports tip estimation.
+const TIP_ESTIMATION_API_VERSION: u32 = 5;
+
+/// Maximal number of ready transactions weighed by the runtime per block.
+///
+/// Every weighed transaction costs a runtime call, so the number is capped to keep the
+/// estimation cheap regardless of the size of the transaction pool.
+pub const MAX_QUERIED_TRANSACTIONS: usize = 256;
+
 /// Error type of this RPC api.
 pub enum Error {
 	/// The transaction was not decodable.
@@ -175,3 +233,152 @@ where
 		})
 	}
 }
+
+impl<C, P, Block, Balance> TipEstimationApiServer<<Block as BlockT>::Hash> for TipEstimation<C, P>
+where
+	Block: BlockT,
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: TransactionPaymentRuntimeApi<Block, Balance>,
+	P: TransactionPool<Block = Block> + 'static,
+	Balance: Codec + MaybeDisplay + Copy + TryInto<NumberOrHex> + Send + Sync + 'static,
+{
+	fn estimate_tip(
+		&self,
+		encoded_xt: Bytes,
+		blocks: u32,
+		at: Option<Block::Hash>,
+	) -> RpcResult<NumberOrHex> {
+		self.deny_unsafe.check_if_safe()?;
+
+		if blocks == 0 {
+			return Err(CallError::Custom(ErrorObject::owned(
+				ErrorCode::InvalidParams.code(),
+				"The number of blocks must be positive.",
+				None::<()>,
+			))
+			.into())
+		}
+
+		let at_hash = self.client.info().best_hash;
+		if at.map_or(false, |at| at != at_hash) {
+			return Err(CallError::Custom(ErrorObject::owned(
+				ErrorCode::InvalidParams.code(),
+				"Tips can only be estimated at the best block.",
+				None::<()>,
+			))
+			.into())
+		}
+		let api = self.client.runtime_api();
+
+		fn map_err(error: impl ToString, desc: &'static str) -> CallError {
+			CallError::Custom(ErrorObject::owned(
+				Error::RuntimeError.into(),
+				desc,
+				Some(error.to_string()),
+			))
+		}
+
+		let api_version = api
+			.api_version::<dyn TransactionPaymentRuntimeApi<Block, Balance>>(at_hash)
+			.map_err(|e| map_err(e, "Unable to estimate tip."))?;
+		if api_version.map_or(true, |version| version < TIP_ESTIMATION_API_VERSION) {
+			return Err(CallError::Custom(ErrorObject::owned(
+				Error::RuntimeError.into(),
+				"Unable to estimate tip.",
+				Some("The runtime doesn't support tip estimation."),
+			))
+			.into())
+		}
+
+		let encoded_len = encoded_xt.len() as u32;
+
+		let uxt: Block::Extrinsic = Decode::decode(&mut &*encoded_xt).map_err(|e| {
+			CallError::Custom(ErrorObject::owned(
+				Error::DecodeError.into(),
+				"Unable to estimate tip.",
+				Some(format!("{:?}", e)),
+			))
+		})?;
+		let uxt_hash = self.pool.hash_of(&uxt);
+
+		let info = api
+			.query_info(at_hash, uxt.clone(), encoded_len)
+			.map_err(|e| map_err(e, "Unable to query dispatch info."))?;
+		let (max_weight, max_length) = api
+			.query_block_limits(at_hash)
+			.map_err(|e| map_err(e, "Unable to query block limits."))?;
+		let max_weight = max_weight.saturating_mul(blocks.into());
+		let max_length = u64::from(max_length).saturating_mul(blocks.into());
+
+		// Work on a copy of the cache, so that the lock isn't held during the runtime calls.
+		let mut weights = self
+			.weights
+			.lock()
+			.as_ref()
+			.filter(|cache| cache.at == at_hash)
+			.map(|cache| cache.weights.clone())
+			.unwrap_or_default();
+
+		// Walk the ready transactions in the order they are going to be included in blocks and
+		// find the first one that doesn't fit into the next `blocks` blocks together with the
+		// given extrinsic. The extrinsic has to outbid that transaction.
+		let mut used_weight = info.weight;
+		let mut used_length = u64::from(encoded_len);
+		let mut sampled_weight = Weight::zero();
+		let mut sampled = 0u64;
+		let mut priority = 0;
+		for tx in self.pool.ready() {
+			if *tx.hash() == uxt_hash {
+				continue
+			}
+
+			let len = tx.data().encoded_size() as u32;
+			let weight = match weights.get(tx.hash()) {
+				Some(weight) => Some(*weight),
+				None if weights.len() < MAX_QUERIED_TRANSACTIONS => {
+					let weight = api
+						.query_info(at_hash, tx.data().clone(), len)
+						.map_err(|e| map_err(e, "Unable to query dispatch info."))?
+						.weight;
+					weights.insert(tx.hash().clone(), weight);
+					Some(weight)
+				},
+				None => None,
+			};
+			let weight = match weight {
+				Some(weight) => {
+					sampled_weight = sampled_weight.saturating_add(weight);
+					sampled += 1;
+					weight
+				},
+				// Out of budget for runtime calls, assume the average weight of the ones weighed
+				// so far.
+				None => sampled_weight.checked_div(sampled).unwrap_or(info.weight),
+			};
+
+			used_weight = used_weight.saturating_add(weight);
+			used_length = used_length.saturating_add(len.into());
+			if used_weight.any_gt(max_weight) || used_length > max_length {
+				priority = tx.priority().saturating_add(1);
+				break
+			}
+		}
+
+		*self.weights.lock() = Some(WeightCache { at: at_hash, weights });
+
+		let tip = api
+			.query_tip_for_priority(at_hash, uxt, encoded_len, priority)
+			.map_err(|e| map_err(e, "Unable to estimate tip."))?;
+
+		tip.try_into().map_err(|_| {
+			JsonRpseeError::Call(CallError::Custom(ErrorObject::owned(
+				ErrorCode::InvalidParams.code(),
+				format!("{} doesn't fit in NumberOrHex representation", tip),
+				None::<()>,
+			)))
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests;
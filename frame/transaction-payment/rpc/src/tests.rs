@@ -0,0 +1,239 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+
+use futures::executor::block_on;
+use jsonrpsee::RpcModule;
+use sc_transaction_pool::BasicPool;
+use sc_transaction_pool_api::TransactionSource;
+use sp_api::{ApiRef, ProvideRuntimeApi};
+use sp_blockchain::{BlockStatus, Info};
+use sp_runtime::{
+	generic::BlockId, traits::Header as _, transaction_validity::TransactionPriority,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use substrate_test_runtime_client::runtime::{Block, Extrinsic, ExtrinsicBuilder, Hash, Header};
+use substrate_test_runtime_transaction_pool::TestApi;
+
+/// Weight of every extrinsic as reported by the mocked runtime.
+const TX_WEIGHT: Weight = Weight::from_parts(1_000, 0);
+
+#[derive(Clone)]
+struct TestClient {
+	best_hash: Hash,
+	block_weight: Weight,
+	queried: Arc<AtomicUsize>,
+}
+
+#[derive(Clone)]
+struct RuntimeApi {
+	inner: TestClient,
+}
+
+impl ProvideRuntimeApi<Block> for TestClient {
+	type Api = RuntimeApi;
+
+	fn runtime_api(&self) -> ApiRef<Self::Api> {
+		RuntimeApi { inner: self.clone() }.into()
+	}
+}
+
+sp_api::mock_impl_runtime_apis! {
+	impl TransactionPaymentRuntimeApi<Block, u128> for RuntimeApi {
+		fn query_info(_uxt: Extrinsic, _len: u32) -> RuntimeDispatchInfo<u128> {
+			self.inner.queried.fetch_add(1, Ordering::SeqCst);
+			RuntimeDispatchInfo { weight: TX_WEIGHT, ..Default::default() }
+		}
+
+		fn query_tip_for_priority(
+			_uxt: Extrinsic,
+			_len: u32,
+			priority: TransactionPriority,
+		) -> u128 {
+			priority.into()
+		}
+
+		fn query_block_limits() -> (Weight, u32) {
+			(self.inner.block_weight, u32::MAX)
+		}
+	}
+}
+
+impl HeaderBackend<Block> for TestClient {
+	fn header(&self, _hash: Hash) -> sp_blockchain::Result<Option<Header>> {
+		Ok(None)
+	}
+
+	fn info(&self) -> Info<Block> {
+		Info {
+			best_hash: self.best_hash,
+			best_number: 0,
+			genesis_hash: self.best_hash,
+			finalized_hash: self.best_hash,
+			finalized_number: 0,
+			finalized_state: None,
+			number_leaves: 1,
+			block_gap: None,
+		}
+	}
+
+	fn status(&self, _hash: Hash) -> sp_blockchain::Result<BlockStatus> {
+		Ok(BlockStatus::InChain)
+	}
+
+	fn number(&self, _hash: Hash) -> sp_blockchain::Result<Option<u64>> {
+		Ok(Some(0))
+	}
+
+	fn hash(&self, _number: u64) -> sp_blockchain::Result<Option<Hash>> {
+		Ok(Some(self.best_hash))
+	}
+}
+
+struct TestSetup {
+	client: Arc<TestClient>,
+	pool: Arc<BasicPool<TestApi, Block>>,
+}
+
+impl TestSetup {
+	fn new(block_weight: Weight) -> Self {
+		let api = Arc::new(TestApi::empty());
+		let best_hash = api
+			.chain()
+			.read()
+			.block_by_number
+			.get(&0)
+			.map(|blocks| blocks[0].0.header.hash())
+			.expect("there is block 0. qed");
+		let (pool, _) = BasicPool::new_test(api, best_hash, best_hash);
+		let client = TestClient { best_hash, block_weight, queried: Arc::new(AtomicUsize::new(0)) };
+		TestSetup { client: Arc::new(client), pool: Arc::new(pool) }
+	}
+
+	fn submit(&self, range: std::ops::Range<u32>) {
+		let xts = range.map(|n| ExtrinsicBuilder::new_include_data(n.encode()).build()).collect();
+		block_on(self.pool.submit_at(&BlockId::number(0), TransactionSource::External, xts))
+			.unwrap()
+			.into_iter()
+			.for_each(|res| {
+				res.unwrap();
+			});
+	}
+
+	fn queried(&self) -> usize {
+		self.client.queried.load(Ordering::SeqCst)
+	}
+
+	fn into_rpc(&self) -> RpcModule<TipEstimation<TestClient, BasicPool<TestApi, Block>>> {
+		self.into_rpc_with(DenyUnsafe::No)
+	}
+
+	fn into_rpc_with(
+		&self,
+		deny_unsafe: DenyUnsafe,
+	) -> RpcModule<TipEstimation<TestClient, BasicPool<TestApi, Block>>> {
+		TipEstimation::new(self.client.clone(), self.pool.clone(), deny_unsafe).into_rpc()
+	}
+}
+
+fn estimated() -> Bytes {
+	ExtrinsicBuilder::new_include_data(b"estimated".to_vec())
+		.build()
+		.encode()
+		.into()
+}
+
+#[tokio::test]
+async fn estimate_tip_outbids_transactions_that_do_not_fit() {
+	let setup = TestSetup::new(TX_WEIGHT.saturating_mul(10));
+	let api = setup.into_rpc();
+
+	// The extrinsic fits into the block together with all ready transactions.
+	setup.submit(0..5);
+	let tip: NumberOrHex =
+		api.call("payment_estimateTip", (estimated(), 1, None::<Hash>)).await.unwrap();
+	assert_eq!(tip, NumberOrHex::Number(0));
+
+	// The tenth ready transaction doesn't fit anymore, so its priority has to be outbid.
+	setup.submit(5..15);
+	let tip: NumberOrHex =
+		api.call("payment_estimateTip", (estimated(), 1, None::<Hash>)).await.unwrap();
+	assert_eq!(tip, NumberOrHex::Number(2));
+
+	// Everything fits into two blocks.
+	let tip: NumberOrHex =
+		api.call("payment_estimateTip", (estimated(), 2, None::<Hash>)).await.unwrap();
+	assert_eq!(tip, NumberOrHex::Number(0));
+
+	// Every ready transaction was weighed once, the estimated extrinsic on every call.
+	assert_eq!(setup.queried(), 15 + 3);
+}
+
+#[tokio::test]
+async fn estimate_tip_weighs_limited_number_of_transactions() {
+	let setup = TestSetup::new(TX_WEIGHT.saturating_mul(MAX_QUERIED_TRANSACTIONS as u64 + 10));
+	let api = setup.into_rpc();
+
+	// The weight of the transactions beyond the limit is extrapolated.
+	setup.submit(0..MAX_QUERIED_TRANSACTIONS as u32 + 20);
+	let tip: NumberOrHex =
+		api.call("payment_estimateTip", (estimated(), 1, None::<Hash>)).await.unwrap();
+	assert_eq!(tip, NumberOrHex::Number(2));
+	assert_eq!(setup.queried(), MAX_QUERIED_TRANSACTIONS + 1);
+
+	// Subsequent estimations at the same block don't weigh any more transactions.
+	let tip: NumberOrHex =
+		api.call("payment_estimateTip", (estimated(), 2, None::<Hash>)).await.unwrap();
+	assert_eq!(tip, NumberOrHex::Number(0));
+	assert_eq!(setup.queried(), MAX_QUERIED_TRANSACTIONS + 2);
+}
+
+#[tokio::test]
+async fn estimate_tip_rejects_zero_blocks() {
+	let setup = TestSetup::new(TX_WEIGHT);
+	let api = setup.into_rpc();
+
+	assert!(api
+		.call::<_, NumberOrHex>("payment_estimateTip", (estimated(), 0, None::<Hash>))
+		.await
+		.is_err());
+	assert_eq!(setup.queried(), 0);
+}
+
+#[tokio::test]
+async fn estimate_tip_rejects_unsafe_calls_and_other_blocks() {
+	let setup = TestSetup::new(TX_WEIGHT);
+
+	let api = setup.into_rpc_with(DenyUnsafe::Yes);
+	assert!(api
+		.call::<_, NumberOrHex>("payment_estimateTip", (estimated(), 1, None::<Hash>))
+		.await
+		.is_err());
+
+	// Only the ready transactions at the best block are known.
+	let api = setup.into_rpc();
+	assert!(api
+		.call::<_, NumberOrHex>("payment_estimateTip", (estimated(), 1, Some(Hash::repeat_byte(1))))
+		.await
+		.is_err());
+	let tip: NumberOrHex = api
+		.call("payment_estimateTip", (estimated(), 1, Some(setup.client.best_hash)))
+		.await
+		.unwrap();
+	assert_eq!(tip, NumberOrHex::Number(0));
+}
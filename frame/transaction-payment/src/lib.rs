@@ -499,6 +499,38 @@ where
 		RuntimeDispatchInfo { weight, class, partial_fee }
 	}
 
+	/// Query the smallest tip the given `call` needs to reach the given transaction pool
+	/// `priority`.
+	///
+	/// Unsigned extrinsics don't pay any tip, for them this function always returns zero.
+	pub fn query_tip_for_priority<Extrinsic: sp_runtime::traits::Extrinsic + GetDispatchInfo>(
+		unchecked_extrinsic: Extrinsic,
+		len: u32,
+		priority: TransactionPriority,
+	) -> BalanceOf<T>
+	where
+		T::RuntimeCall: Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+		BalanceOf<T>: Send + Sync,
+	{
+		if !unchecked_extrinsic.is_signed().unwrap_or(false) {
+			return 0u32.into()
+		}
+
+		let dispatch_info = <Extrinsic as GetDispatchInfo>::get_dispatch_info(&unchecked_extrinsic);
+		let fee = Self::compute_fee(len, &dispatch_info, 0u32.into());
+
+		ChargeTransactionPayment::<T>::tip_for_priority(&dispatch_info, len as usize, priority, fee)
+	}
+
+	/// Query the maximal total weight and length of `Normal` extrinsics in a single block.
+	pub fn query_block_limits() -> (Weight, u32) {
+		let weights = T::BlockWeights::get();
+		let max_weight = weights.get(DispatchClass::Normal).max_total.unwrap_or(weights.max_block);
+		let max_length = *T::BlockLength::get().max.get(DispatchClass::Normal);
+
+		(max_weight, max_length)
+	}
+
 	/// Query the detailed fee of a given `call`.
 	pub fn query_fee_details<Extrinsic: sp_runtime::traits::Extrinsic + GetDispatchInfo>(
 		unchecked_extrinsic: Extrinsic,
@@ -731,28 +763,7 @@ where
 		tip: BalanceOf<T>,
 		final_fee: BalanceOf<T>,
 	) -> TransactionPriority {
-		// Calculate how many such extrinsics we could fit into an empty block and take the
-		// limiting factor.
-		let max_block_weight = T::BlockWeights::get().max_block;
-		let max_block_length = *T::BlockLength::get().max.get(info.class) as u64;
-
-		// bounded_weight is used as a divisor later so we keep it non-zero.
-		let bounded_weight = info.weight.max(Weight::from_parts(1, 1)).min(max_block_weight);
-		let bounded_length = (len as u64).clamp(1, max_block_length);
-
-		// returns the scarce resource, i.e. the one that is limiting the number of transactions.
-		let max_tx_per_block_weight = max_block_weight
-			.checked_div_per_component(&bounded_weight)
-			.defensive_proof("bounded_weight is non-zero; qed")
-			.unwrap_or(1);
-		let max_tx_per_block_length = max_block_length / bounded_length;
-		// Given our current knowledge this value is going to be in a reasonable range - i.e.
-		// less than 10^9 (2^30), so multiplying by the `tip` value is unlikely to overflow the
-		// balance type. We still use saturating ops obviously, but the point is to end up with some
-		// `priority` distribution instead of having all transactions saturate the priority.
-		let max_tx_per_block = max_tx_per_block_length
-			.min(max_tx_per_block_weight)
-			.saturated_into::<BalanceOf<T>>();
+		let max_tx_per_block = Self::max_tx_per_block(info, len);
 		let max_reward = |val: BalanceOf<T>| val.saturating_mul(max_tx_per_block);
 
 		// To distribute no-tip transactions a little bit, we increase the tip value by one.
@@ -785,6 +796,66 @@ where
 		}
 		.saturated_into::<TransactionPriority>()
 	}
+
+	/// Get the smallest tip that gives a transaction with the given `DispatchInfo` and encoded
+	/// length at least the given `priority`.
+	///
+	/// This is the inverse of [`Self::get_priority`], with `fee` being the fee of the transaction
+	/// without any tip.
+	pub fn tip_for_priority(
+		info: &DispatchInfoOf<T::RuntimeCall>,
+		len: usize,
+		priority: TransactionPriority,
+		fee: BalanceOf<T>,
+	) -> BalanceOf<T> {
+		let max_tx_per_block = Self::max_tx_per_block(info, len);
+
+		// The priority of the transaction without any tip and the priority added by each unit of
+		// the tip. Note that for `Operational` extrinsics the tip is part of the final fee as well.
+		let (base_priority, priority_per_tip) = match info.class {
+			DispatchClass::Normal | DispatchClass::Mandatory =>
+				(max_tx_per_block, max_tx_per_block),
+			DispatchClass::Operational => {
+				let fee_multiplier: BalanceOf<T> =
+					T::OperationalFeeMultiplier::get().saturated_into();
+				let virtual_tip = fee.saturating_mul(fee_multiplier);
+				(
+					max_tx_per_block.saturating_add(max_tx_per_block.saturating_mul(virtual_tip)),
+					max_tx_per_block.saturating_mul(fee_multiplier.saturating_add(One::one())),
+				)
+			},
+		};
+		let priority_per_tip = priority_per_tip.max(One::one());
+
+		// Round up, so that the resulting priority is never lower than the requested one.
+		let missing = priority.saturated_into::<BalanceOf<T>>().saturating_sub(base_priority);
+		missing.saturating_add(priority_per_tip.saturating_sub(One::one())) / priority_per_tip
+	}
+
+	/// Calculate how many extrinsics with the given `DispatchInfo` and encoded length we could
+	/// fit into an empty block, taking the limiting factor.
+	fn max_tx_per_block(info: &DispatchInfoOf<T::RuntimeCall>, len: usize) -> BalanceOf<T> {
+		let max_block_weight = T::BlockWeights::get().max_block;
+		let max_block_length = *T::BlockLength::get().max.get(info.class) as u64;
+
+		// bounded_weight is used as a divisor later so we keep it non-zero.
+		let bounded_weight = info.weight.max(Weight::from_parts(1, 1)).min(max_block_weight);
+		let bounded_length = (len as u64).clamp(1, max_block_length);
+
+		// returns the scarce resource, i.e. the one that is limiting the number of transactions.
+		let max_tx_per_block_weight = max_block_weight
+			.checked_div_per_component(&bounded_weight)
+			.defensive_proof("bounded_weight is non-zero; qed")
+			.unwrap_or(1);
+		let max_tx_per_block_length = max_block_length / bounded_length;
+		// Given our current knowledge this value is going to be in a reasonable range - i.e.
+		// less than 10^9 (2^30), so multiplying by the `tip` value is unlikely to overflow the
+		// balance type. We still use saturating ops obviously, but the point is to end up with some
+		// `priority` distribution instead of having all transactions saturate the priority.
+		max_tx_per_block_length
+			.min(max_tx_per_block_weight)
+			.saturated_into::<BalanceOf<T>>()
+	}
 }
 
 impl<T: Config> sp_std::fmt::Debug for ChargeTransactionPayment<T> {
@@ -784,6 +784,34 @@ fn higher_tip_have_higher_priority() {
 	}
 }
 
+#[test]
+fn tip_for_priority_is_inverse_of_priority() {
+	let len = 10;
+
+	ExtBuilder::default().balance_factor(100).build().execute_with(|| {
+		for class in [DispatchClass::Normal, DispatchClass::Operational] {
+			let info =
+				DispatchInfo { weight: Weight::from_parts(100, 0), class, pays_fee: Pays::Yes };
+			let fee = Pallet::<Runtime>::compute_fee(len as u32, &info, 0);
+			let priority = |tip| {
+				ChargeTransactionPayment::<Runtime>(tip)
+					.validate(&2, CALL, &info, len)
+					.unwrap()
+					.priority
+			};
+
+			for target in [0, 1, priority(0), priority(5), priority(5) + 1, priority(20) - 1] {
+				let tip =
+					ChargeTransactionPayment::<Runtime>::tip_for_priority(&info, len, target, fee);
+				assert!(priority(tip) >= target);
+				if tip > 0 {
+					assert!(priority(tip - 1) < target);
+				}
+			}
+		}
+	});
+}
+
 #[test]
 fn post_info_can_change_pays_fee() {
 	ExtBuilder::default()
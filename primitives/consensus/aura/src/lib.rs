@@ -62,7 +62,7 @@ pub mod ed25519 {
 	pub type AuthorityId = app_ed25519::Public;
 }
 
-pub use sp_consensus_slots::{Slot, SlotDuration};
+pub use sp_consensus_slots::{Slot, SlotDuration, SlotSchedule};
 
 /// The `ConsensusEngineId` of AuRa.
 pub const AURA_ENGINE_ID: ConsensusEngineId = [b'a', b'u', b'r', b'a'];
@@ -86,10 +86,17 @@ sp_api::decl_runtime_apis! {
 	pub trait AuraApi<AuthorityId: Codec> {
 		/// Returns the slot duration for Aura.
 		///
-		/// Currently, only the value provided by this type at genesis will be used.
+		/// Nodes use [`slot_schedule`](AuraApi::slot_schedule) instead if the runtime provides it.
 		fn slot_duration() -> SlotDuration;
 
 		/// Return the current set of authorities.
 		fn authorities() -> Vec<AuthorityId>;
+
+		/// Returns the slot schedule for the children of the block.
+		///
+		/// Unlike [`slot_duration`](AuraApi::slot_duration), this keeps slot numbers increasing
+		/// when the slot duration changes.
+		#[api_version(2)]
+		fn slot_schedule() -> SlotSchedule;
 	}
 }
@@ -0,0 +1,583 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![warn(missing_docs)]
+
+//! A BEEFY light client.
+//!
+//! The light client follows the finality of a chain running BEEFY and `pallet-beefy-mmr` without
+//! access to its state. It tracks the current BEEFY validator set and accepts
+//! [`VersionedFinalityProof`]s signed by a supermajority of it, remembering the MMR root they
+//! commit to. [`MmrLeaf`]s can then be proven against that root, which in particular lets the
+//! light client learn about the next validator set: every leaf commits to it through
+//! [`MmrLeaf::beefy_next_authority_set`], and once a commitment signed by that set shows up the
+//! light client hands off to it.
+//!
+//! The crate is `no_std`, so it can be used from within runtimes and smart contracts as well as
+//! from bridge relayers.
+
+use codec::{Decode, DecodeAll, Encode};
+use scale_info::TypeInfo;
+use sp_application_crypto::RuntimeAppPublic;
+use sp_consensus_beefy::{
+	check_commitment_signature, known_payloads,
+	mmr::{BeefyNextAuthoritySet, MmrLeaf},
+	BeefyAuthorityId, Commitment, MmrRootHash, SignedCommitment, ValidatorSet, ValidatorSetId,
+	VersionedFinalityProof,
+};
+use sp_mmr_primitives::{mmr_lib, utils::NodesUtils, Proof};
+use sp_runtime::{
+	traits::{Convert, Hash, Keccak256},
+	RuntimeDebug,
+};
+use sp_std::{marker::PhantomData, prelude::*};
+
+/// Light client verification error.
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+#[derive(RuntimeDebug, PartialEq, Eq)]
+pub enum Error {
+	/// The justification couldn't be decoded.
+	#[cfg_attr(feature = "std", error("Invalid justification encoding"))]
+	InvalidJustification,
+	/// The commitment isn't newer than the latest verified one.
+	#[cfg_attr(feature = "std", error("Commitment is not newer than the latest verified one"))]
+	StaleCommitment,
+	/// The commitment is signed by a validator set other than the current or the next one.
+	#[cfg_attr(feature = "std", error("Commitment signed by unexpected validator set {0}"))]
+	UnexpectedValidatorSet(ValidatorSetId),
+	/// The commitment is signed by the next validator set, which wasn't provided.
+	#[cfg_attr(feature = "std", error("Next validator set is required to verify the commitment"))]
+	MissingNextValidatorSet,
+	/// The provided validator set doesn't match the announced next authority set.
+	#[cfg_attr(feature = "std", error("Validator set doesn't match the next authority set"))]
+	InvalidNextValidatorSet,
+	/// The number of signatures doesn't match the number of validators.
+	#[cfg_attr(feature = "std", error("Signature count doesn't match the validator set size"))]
+	InvalidSignatureCount,
+	/// One of the signatures of the commitment is invalid.
+	#[cfg_attr(feature = "std", error("Invalid commitment signature"))]
+	InvalidSignature,
+	/// The commitment isn't signed by a supermajority of the validator set.
+	#[cfg_attr(feature = "std", error("Not enough signatures"))]
+	NotEnoughSignatures,
+	/// The commitment payload doesn't contain an MMR root.
+	#[cfg_attr(feature = "std", error("Commitment doesn't contain an MMR root"))]
+	MissingMmrRoot,
+	/// The MMR leaf proof is invalid.
+	#[cfg_attr(feature = "std", error("Invalid MMR leaf proof"))]
+	InvalidMmrProof,
+}
+
+/// The state of a BEEFY light client.
+#[derive(Clone, RuntimeDebug, PartialEq, Encode, Decode, TypeInfo)]
+pub struct LightClientState<Number, AuthorityId> {
+	/// The validator set expected to sign upcoming commitments.
+	pub validator_set: ValidatorSet<AuthorityId>,
+	/// The validator set following `validator_set`, as committed to in the latest verified
+	/// MMR leaf.
+	pub next_authority_set: BeefyNextAuthoritySet<MmrRootHash>,
+	/// Block number of the latest verified commitment.
+	pub latest_block: Number,
+	/// MMR root of the latest verified commitment.
+	pub latest_mmr_root: MmrRootHash,
+}
+
+/// A BEEFY light client.
+///
+/// `ToLeaf` converts validator ids into the leaves of the merkle tree whose root is found in
+/// [`BeefyNextAuthoritySet::root`], and must match the `BeefyAuthorityToMerkleLeaf` conversion
+/// used by the chain's `pallet-beefy-mmr`, e.g. `pallet_beefy_mmr::BeefyEcdsaToEthereum`.
+pub struct LightClient<Number, AuthorityId, ToLeaf> {
+	state: LightClientState<Number, AuthorityId>,
+	_phantom: PhantomData<ToLeaf>,
+}
+
+impl<Number, AuthorityId, ToLeaf> LightClient<Number, AuthorityId, ToLeaf>
+where
+	Number: Clone + Encode + PartialOrd,
+	AuthorityId: BeefyAuthorityId<Keccak256> + Clone,
+	ToLeaf: Convert<AuthorityId, Vec<u8>>,
+{
+	/// Create a light client starting from the given trusted state.
+	pub fn new(state: LightClientState<Number, AuthorityId>) -> Self {
+		LightClient { state, _phantom: PhantomData }
+	}
+
+	/// Return the current state of the light client.
+	pub fn state(&self) -> &LightClientState<Number, AuthorityId> {
+		&self.state
+	}
+
+	/// Consume the light client, returning its state.
+	pub fn into_state(self) -> LightClientState<Number, AuthorityId> {
+		self.state
+	}
+
+	/// Decode a BEEFY justification, as found in the block justifications under
+	/// [`sp_consensus_beefy::BEEFY_ENGINE_ID`], and import it with [`Self::import_finality_proof`].
+	pub fn import_justification(
+		&mut self,
+		encoded: &[u8],
+		next_validators: Option<Vec<AuthorityId>>,
+	) -> Result<MmrRootHash, Error>
+	where
+		Number: Decode,
+	{
+		let proof = VersionedFinalityProof::decode_all(&mut &encoded[..])
+			.map_err(|_| Error::InvalidJustification)?;
+
+		self.import_finality_proof(proof, next_validators)
+	}
+
+	/// Verify the given finality proof and make its commitment the latest verified one,
+	/// returning the MMR root it commits to.
+	///
+	/// Commitments signed by the next validator set are only accepted when `next_validators` is
+	/// given and matches [`LightClientState::next_authority_set`], in which case the light
+	/// client hands off to that set.
+	pub fn import_finality_proof(
+		&mut self,
+		proof: VersionedFinalityProof<Number, <AuthorityId as RuntimeAppPublic>::Signature>,
+		next_validators: Option<Vec<AuthorityId>>,
+	) -> Result<MmrRootHash, Error> {
+		let VersionedFinalityProof::V1(SignedCommitment { commitment, signatures }) = proof;
+
+		if commitment.block_number <= self.state.latest_block {
+			return Err(Error::StaleCommitment)
+		}
+
+		let next_validator_set = if commitment.validator_set_id == self.state.validator_set.id() {
+			None
+		} else if commitment.validator_set_id == self.state.next_authority_set.id {
+			let next_validators = next_validators.ok_or(Error::MissingNextValidatorSet)?;
+			Some(self.check_next_validator_set(next_validators)?)
+		} else {
+			return Err(Error::UnexpectedValidatorSet(commitment.validator_set_id))
+		};
+
+		verify_signatures(
+			next_validator_set.as_ref().unwrap_or(&self.state.validator_set),
+			&commitment,
+			&signatures,
+		)?;
+
+		let mmr_root = commitment
+			.payload
+			.get_decoded::<MmrRootHash>(&known_payloads::MMR_ROOT_ID)
+			.ok_or(Error::MissingMmrRoot)?;
+
+		if let Some(validator_set) = next_validator_set {
+			self.state.validator_set = validator_set;
+		}
+		self.state.latest_block = commitment.block_number;
+		self.state.latest_mmr_root = mmr_root;
+
+		Ok(mmr_root)
+	}
+
+	/// Verify that the given leaf is part of the MMR committed to by the latest verified
+	/// commitment.
+	///
+	/// A leaf announcing a newer next authority set than the known one updates
+	/// [`LightClientState::next_authority_set`], preparing the handoff to that set.
+	pub fn import_mmr_leaf<BlockHash, ExtraData>(
+		&mut self,
+		leaf: &MmrLeaf<Number, BlockHash, MmrRootHash, ExtraData>,
+		proof: Proof<MmrRootHash>,
+	) -> Result<(), Error>
+	where
+		BlockHash: Encode,
+		ExtraData: Encode,
+	{
+		verify_mmr_leaf_proof(self.state.latest_mmr_root, &leaf.encode(), proof)?;
+
+		if leaf.beefy_next_authority_set.id > self.state.next_authority_set.id {
+			self.state.next_authority_set = leaf.beefy_next_authority_set.clone();
+		}
+
+		Ok(())
+	}
+
+	fn check_next_validator_set(
+		&self,
+		validators: Vec<AuthorityId>,
+	) -> Result<ValidatorSet<AuthorityId>, Error> {
+		let next_authority_set = &self.state.next_authority_set;
+		let root = binary_merkle_tree::merkle_root::<Keccak256, _>(
+			validators.iter().cloned().map(ToLeaf::convert),
+		);
+
+		if validators.len() != next_authority_set.len as usize || root != next_authority_set.root {
+			return Err(Error::InvalidNextValidatorSet)
+		}
+
+		ValidatorSet::new(validators, next_authority_set.id).ok_or(Error::InvalidNextValidatorSet)
+	}
+}
+
+/// Verify the MMR proof of a single leaf, given in its encoded form, against the given root.
+pub fn verify_mmr_leaf_proof(
+	root: MmrRootHash,
+	encoded_leaf: &[u8],
+	proof: Proof<MmrRootHash>,
+) -> Result<(), Error> {
+	let leaf_index = match proof.leaf_indices[..] {
+		[leaf_index] => leaf_index,
+		_ => return Err(Error::InvalidMmrProof),
+	};
+
+	let leaf = (mmr_lib::leaf_index_to_pos(leaf_index), Keccak256::hash(encoded_leaf));
+	let proof = mmr_lib::MerkleProof::<MmrRootHash, MmrHasher>::new(
+		NodesUtils::new(proof.leaf_count).size(),
+		proof.items,
+	);
+
+	match proof.verify(root, vec![leaf]) {
+		Ok(true) => Ok(()),
+		_ => Err(Error::InvalidMmrProof),
+	}
+}
+
+/// The number of signatures required to finalize a block with `authorities` validators.
+fn threshold(authorities: usize) -> usize {
+	let faulty = authorities.saturating_sub(1) / 3;
+	authorities - faulty
+}
+
+fn verify_signatures<Number, AuthorityId>(
+	validator_set: &ValidatorSet<AuthorityId>,
+	commitment: &Commitment<Number>,
+	signatures: &[Option<<AuthorityId as RuntimeAppPublic>::Signature>],
+) -> Result<(), Error>
+where
+	Number: Clone + Encode + PartialEq,
+	AuthorityId: BeefyAuthorityId<Keccak256>,
+{
+	if signatures.len() != validator_set.len() {
+		return Err(Error::InvalidSignatureCount)
+	}
+
+	let mut signed = 0;
+	for (id, signature) in validator_set.validators().iter().zip(signatures) {
+		if let Some(signature) = signature {
+			if !check_commitment_signature::<_, _, Keccak256>(commitment, id, signature) {
+				return Err(Error::InvalidSignature)
+			}
+			signed += 1;
+		}
+	}
+
+	if signed < threshold(validator_set.len()) {
+		return Err(Error::NotEnoughSignatures)
+	}
+
+	Ok(())
+}
+
+/// Merges MMR nodes the same way `pallet-mmr` does, by hashing their concatenation.
+struct MmrHasher;
+
+impl mmr_lib::Merge for MmrHasher {
+	type Item = MmrRootHash;
+
+	fn merge(left: &Self::Item, right: &Self::Item) -> mmr_lib::Result<Self::Item> {
+		let mut concat = left.as_ref().to_vec();
+		concat.extend_from_slice(right.as_ref());
+
+		Ok(Keccak256::hash(&concat))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use pallet_beefy_mmr::BeefyEcdsaToEthereum;
+	use sp_consensus_beefy::{crypto, mmr::MmrLeafVersion, Keyring, Payload};
+	use sp_core::H256;
+
+	type TestLightClient = LightClient<u64, crypto::AuthorityId, BeefyEcdsaToEthereum>;
+	type TestMmrLeaf = MmrLeaf<u64, H256, MmrRootHash, Vec<u8>>;
+
+	fn authority_set(keys: &[Keyring], id: ValidatorSetId) -> BeefyNextAuthoritySet<MmrRootHash> {
+		let leaves = keys.iter().map(|key| BeefyEcdsaToEthereum::convert(key.public()));
+		BeefyNextAuthoritySet {
+			id,
+			len: keys.len() as u32,
+			root: binary_merkle_tree::merkle_root::<Keccak256, _>(leaves),
+		}
+	}
+
+	fn light_client(
+		next_authority_set: BeefyNextAuthoritySet<MmrRootHash>,
+		latest_mmr_root: MmrRootHash,
+	) -> TestLightClient {
+		let validators = [Keyring::Alice, Keyring::Bob, Keyring::Charlie];
+
+		LightClient::new(LightClientState {
+			validator_set: ValidatorSet::new(validators.iter().map(|key| key.public()), 0).unwrap(),
+			next_authority_set,
+			latest_block: 0,
+			latest_mmr_root,
+		})
+	}
+
+	fn finality_proof(
+		block_number: u64,
+		validator_set_id: ValidatorSetId,
+		mmr_root: MmrRootHash,
+		signers: &[Option<Keyring>],
+	) -> VersionedFinalityProof<u64, crypto::Signature> {
+		let commitment = Commitment {
+			payload: Payload::from_single_entry(known_payloads::MMR_ROOT_ID, mmr_root.encode()),
+			block_number,
+			validator_set_id,
+		};
+		let signatures = signers
+			.iter()
+			.map(|signer| signer.map(|key| key.sign(&commitment.encode())))
+			.collect();
+
+		SignedCommitment { commitment, signatures }.into()
+	}
+
+	// leaves of the first two blocks built by the `pallet-beefy-mmr` tests.
+	fn beefy_mmr_leaf(parent_number: u64, next_set_id: ValidatorSetId) -> TestMmrLeaf {
+		MmrLeaf {
+			version: MmrLeafVersion::new(1, 5),
+			parent_number_and_hash: (parent_number, H256::repeat_byte(0x45)),
+			beefy_next_authority_set: BeefyNextAuthoritySet {
+				id: next_set_id,
+				len: 2,
+				root: array_bytes::hex_n_into_unchecked(
+					"9c6b2c1b0d0b25a008e6c882cc7b415f309965c72ad2b944ac0931048ca31cd5",
+				),
+			},
+			leaf_extra: array_bytes::hex2bytes_unchecked(
+				"55b8e9e1cc9f0db7776fac0ca66318ef8acfb8ec26db11e373120583e07ee648",
+			),
+		}
+	}
+
+	fn leaf_hash(leaf: &TestMmrLeaf) -> MmrRootHash {
+		Keccak256::hash(&leaf.encode())
+	}
+
+	#[test]
+	fn imports_finality_proofs_of_current_validator_set() {
+		let mut client =
+			light_client(authority_set(&[Keyring::Dave, Keyring::Eve], 1), H256::zero());
+		let (alice, bob, charlie) =
+			(Some(Keyring::Alice), Some(Keyring::Bob), Some(Keyring::Charlie));
+		let mmr_root = H256::repeat_byte(1);
+
+		assert_eq!(client.import_justification(&[1, 2, 3], None), Err(Error::InvalidJustification));
+		assert_eq!(
+			client.import_justification(
+				&finality_proof(1, 0, mmr_root, &[alice, bob, charlie]).encode(),
+				None,
+			),
+			Ok(mmr_root),
+		);
+		assert_eq!(client.state().latest_block, 1);
+		assert_eq!(client.state().latest_mmr_root, mmr_root);
+
+		assert_eq!(
+			client.import_finality_proof(
+				finality_proof(1, 0, mmr_root, &[alice, bob, charlie]),
+				None
+			),
+			Err(Error::StaleCommitment),
+		);
+		assert_eq!(
+			client.import_finality_proof(finality_proof(2, 0, mmr_root, &[alice, bob, None]), None),
+			Err(Error::NotEnoughSignatures),
+		);
+		assert_eq!(
+			client.import_finality_proof(
+				finality_proof(2, 0, mmr_root, &[alice, bob, Some(Keyring::Dave)]),
+				None,
+			),
+			Err(Error::InvalidSignature),
+		);
+		assert_eq!(
+			client.import_finality_proof(finality_proof(2, 0, mmr_root, &[alice, bob]), None),
+			Err(Error::InvalidSignatureCount),
+		);
+		assert_eq!(
+			client.import_finality_proof(
+				finality_proof(2, 2, mmr_root, &[alice, bob, charlie]),
+				None
+			),
+			Err(Error::UnexpectedValidatorSet(2)),
+		);
+
+		// nothing changed by the rejected proofs
+		assert_eq!(client.state().latest_block, 1);
+	}
+
+	#[test]
+	fn hands_off_to_next_validator_set() {
+		let mut client =
+			light_client(authority_set(&[Keyring::Dave, Keyring::Eve], 1), H256::zero());
+		let next_validators = vec![Keyring::Dave.public(), Keyring::Eve.public()];
+		let mmr_root = H256::repeat_byte(2);
+		let proof = || finality_proof(5, 1, mmr_root, &[Some(Keyring::Dave), Some(Keyring::Eve)]);
+
+		assert_eq!(
+			client.import_finality_proof(proof(), None),
+			Err(Error::MissingNextValidatorSet)
+		);
+		assert_eq!(
+			client.import_finality_proof(
+				proof(),
+				Some(vec![Keyring::Eve.public(), Keyring::Dave.public()]),
+			),
+			Err(Error::InvalidNextValidatorSet),
+		);
+
+		assert_eq!(
+			client.import_finality_proof(proof(), Some(next_validators.clone())),
+			Ok(mmr_root)
+		);
+		assert_eq!(client.state().validator_set.id(), 1);
+		assert_eq!(client.state().validator_set.validators(), &next_validators[..]);
+
+		// the previous validator set isn't trusted anymore
+		assert_eq!(
+			client.import_finality_proof(
+				finality_proof(6, 0, mmr_root, &[Some(Keyring::Alice), Some(Keyring::Bob)]),
+				None,
+			),
+			Err(Error::UnexpectedValidatorSet(0)),
+		);
+	}
+
+	#[test]
+	fn next_authority_set_root_matches_beefy_mmr() {
+		// authorities and next authority set root of the `pallet-beefy-mmr` tests.
+		let mock_beefy_id = |id: u8| {
+			let mut buf = [id; 33];
+			buf[0] = 0x02;
+			crypto::AuthorityId::from(sp_core::ecdsa::Public::from_raw(buf))
+		};
+		let client = light_client(
+			BeefyNextAuthoritySet {
+				id: 1,
+				len: 2,
+				root: array_bytes::hex_n_into_unchecked(
+					"176e73f1bf656478b728e28dd1a7733c98621b8acf830bff585949763dca7a96",
+				),
+			},
+			H256::zero(),
+		);
+
+		let validator_set = client
+			.check_next_validator_set(vec![mock_beefy_id(1), mock_beefy_id(2)])
+			.unwrap();
+		assert_eq!(validator_set.id(), 1);
+
+		assert_eq!(
+			client.check_next_validator_set(vec![mock_beefy_id(2), mock_beefy_id(1)]),
+			Err(Error::InvalidNextValidatorSet),
+		);
+		assert_eq!(
+			client.check_next_validator_set(vec![mock_beefy_id(1)]),
+			Err(Error::InvalidNextValidatorSet),
+		);
+	}
+
+	#[test]
+	fn verifies_mmr_leaf_proofs() {
+		let (first, second) = (beefy_mmr_leaf(0, 2), beefy_mmr_leaf(1, 3));
+		let proof = |leaf_index, leaf_count, items| Proof {
+			leaf_indices: vec![leaf_index],
+			leaf_count,
+			items,
+		};
+
+		// MMR roots deposited by `pallet-beefy-mmr` after the first and second block.
+		let first_root: MmrRootHash = array_bytes::hex_n_into_unchecked(
+			"95803defe6ea9f41e7ec6afa497064f21bfded027d8812efacbdf984e630cbdc",
+		);
+		let second_root: MmrRootHash = array_bytes::hex_n_into_unchecked(
+			"a73271a0974f1e67d6e9b8dd58e506177a2e556519a330796721e98279a753e2",
+		);
+
+		assert_eq!(verify_mmr_leaf_proof(first_root, &first.encode(), proof(0, 1, vec![])), Ok(()));
+		assert_eq!(
+			verify_mmr_leaf_proof(
+				second_root,
+				&first.encode(),
+				proof(0, 2, vec![leaf_hash(&second)])
+			),
+			Ok(()),
+		);
+		assert_eq!(
+			verify_mmr_leaf_proof(
+				second_root,
+				&second.encode(),
+				proof(1, 2, vec![leaf_hash(&first)])
+			),
+			Ok(()),
+		);
+
+		assert_eq!(
+			verify_mmr_leaf_proof(
+				second_root,
+				&second.encode(),
+				proof(0, 2, vec![leaf_hash(&first)])
+			),
+			Err(Error::InvalidMmrProof),
+		);
+		assert_eq!(
+			verify_mmr_leaf_proof(first_root, &second.encode(), proof(0, 1, vec![])),
+			Err(Error::InvalidMmrProof),
+		);
+		assert_eq!(
+			verify_mmr_leaf_proof(
+				second_root,
+				&first.encode(),
+				Proof { leaf_indices: vec![0, 1], leaf_count: 2, items: vec![] },
+			),
+			Err(Error::InvalidMmrProof),
+		);
+	}
+
+	#[test]
+	fn mmr_leaves_announce_next_authority_set() {
+		let (first, second) = (beefy_mmr_leaf(0, 2), beefy_mmr_leaf(1, 3));
+		let mut client = light_client(
+			authority_set(&[Keyring::Dave, Keyring::Eve], 1),
+			array_bytes::hex_n_into_unchecked(
+				"a73271a0974f1e67d6e9b8dd58e506177a2e556519a330796721e98279a753e2",
+			),
+		);
+
+		let proof = Proof { leaf_indices: vec![1], leaf_count: 2, items: vec![leaf_hash(&first)] };
+		assert_eq!(client.import_mmr_leaf(&second, proof), Ok(()));
+		assert_eq!(client.state().next_authority_set, second.beefy_next_authority_set);
+
+		// older announcements are ignored
+		let proof = Proof { leaf_indices: vec![0], leaf_count: 2, items: vec![leaf_hash(&second)] };
+		assert_eq!(client.import_mmr_leaf(&first, proof), Ok(()));
+		assert_eq!(client.state().next_authority_set.id, 3);
+
+		let proof = Proof { leaf_indices: vec![0], leaf_count: 2, items: vec![] };
+		assert_eq!(client.import_mmr_leaf(&first, proof), Err(Error::InvalidMmrProof));
+	}
+}
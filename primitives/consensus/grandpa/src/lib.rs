@@ -593,4 +593,12 @@ sp_api::decl_runtime_apis! {
 		/// Get current GRANDPA authority set id.
 		fn current_set_id() -> SetId;
 	}
+
+	/// API for runtimes that control how far behind the best block GRANDPA voters vote, e.g.
+	/// through a finality delay that can be changed by governance.
+	pub trait FinalityLagApi {
+		/// Number of blocks the GRANDPA votes should stay behind the best block, when called
+		/// at the best block.
+		fn finality_lag() -> NumberFor<Block>;
+	}
 }
@@ -19,8 +19,8 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use codec::Decode;
-use sp_runtime::ConsensusEngineId;
+use codec::{Decode, Encode};
+use sp_runtime::{ConsensusEngineId, RuntimeDebug};
 use sp_std::vec::Vec;
 
 /// The `ConsensusEngineId` of PoW.
@@ -29,6 +29,21 @@ pub const POW_ENGINE_ID: ConsensusEngineId = [b'p', b'o', b'w', b'_'];
 /// Type of seal.
 pub type Seal = Vec<u8>;
 
+/// Identifier of a PoW algorithm, on chains accepting blocks sealed with several algorithms.
+pub type AlgorithmId = [u8; 4];
+
+/// Pre-runtime digest of chains accepting several PoW algorithms.
+///
+/// It selects the algorithm the block is sealed with, and thereby the difficulty the seal is
+/// checked against.
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub struct MultiAlgorithmPreDigest {
+	/// Algorithm the block is sealed with.
+	pub algorithm: AlgorithmId,
+	/// Pre-runtime digest passed on to the selected algorithm, e.g. the author.
+	pub inner: Option<Vec<u8>>,
+}
+
 /// Define methods that total difficulty should implement.
 pub trait TotalDifficulty {
 	fn increment(&mut self, other: Self);
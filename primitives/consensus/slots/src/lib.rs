@@ -101,7 +101,20 @@ impl From<Slot> for u64 {
 }
 
 /// A slot duration defined in milliseconds.
-#[derive(Clone, Copy, Debug, Encode, Decode, Hash, PartialOrd, Ord, PartialEq, Eq, TypeInfo)]
+#[derive(
+	Clone,
+	Copy,
+	Debug,
+	Encode,
+	Decode,
+	MaxEncodedLen,
+	Hash,
+	PartialOrd,
+	Ord,
+	PartialEq,
+	Eq,
+	TypeInfo,
+)]
 pub struct SlotDuration(u64);
 
 impl SlotDuration {
@@ -126,6 +139,62 @@ impl SlotDuration {
 	}
 }
 
+/// Maps timestamps to slots.
+///
+/// Slots are counted from `slot`, which started at `timestamp`, in steps of `slot_duration`.
+/// Anchoring a new slot duration at the slot where it takes effect keeps slot numbers
+/// increasing across duration changes, which wouldn't be the case when computing slots
+/// from the unix epoch with the new duration.
+#[derive(Clone, Copy, Debug, Encode, Decode, MaxEncodedLen, PartialEq, Eq, TypeInfo)]
+pub struct SlotSchedule {
+	/// The slot at which the schedule starts.
+	pub slot: Slot,
+	/// The unix timestamp in milliseconds at which `slot` starts.
+	pub timestamp: u64,
+	/// The duration of each slot since `slot`.
+	pub slot_duration: SlotDuration,
+}
+
+impl SlotSchedule {
+	/// Slots of the given duration, counted from the unix epoch.
+	pub const fn from_slot_duration(slot_duration: SlotDuration) -> Self {
+		Self { slot: Slot(0), timestamp: 0, slot_duration }
+	}
+
+	/// Returns the slot the given unix timestamp in milliseconds falls into.
+	///
+	/// Timestamps before the start of the schedule are mapped to its first slot.
+	pub fn slot_at<T: Into<u64>>(&self, timestamp: T) -> Slot {
+		let elapsed = timestamp.into().saturating_sub(self.timestamp);
+		self.slot
+			.saturating_add(elapsed.checked_div(self.slot_duration.as_millis()).unwrap_or_default())
+	}
+
+	/// Returns the unix timestamp in milliseconds at which the given slot starts.
+	///
+	/// Slots before the start of the schedule are mapped to its start.
+	pub fn slot_start(&self, slot: Slot) -> u64 {
+		slot.0
+			.saturating_sub(self.slot.0)
+			.saturating_mul(self.slot_duration.as_millis())
+			.saturating_add(self.timestamp)
+	}
+
+	/// Switches to `slot_duration` after `slot`.
+	///
+	/// The new duration takes effect at the slot following `slot`, so that `slot` keeps its
+	/// length and all slots after it are numbered higher. Returns `self` if the duration doesn't
+	/// change.
+	pub fn change_slot_duration(self, slot: Slot, slot_duration: SlotDuration) -> Self {
+		if slot_duration == self.slot_duration {
+			return self
+		}
+
+		let slot = slot.saturating_add(1u64);
+		Self { slot, timestamp: self.slot_start(slot), slot_duration }
+	}
+}
+
 /// Represents an equivocation proof. An equivocation happens when a validator
 /// produces more than one block on the same slot. The proof of equivocation
 /// are the given distinct headers that were signed by the validator and which
@@ -30,6 +30,7 @@ use sp_core::storage::{ChildInfo, StateVersion, TrackedStorageKey};
 #[cfg(feature = "std")]
 use sp_core::traits::RuntimeCode;
 use sp_std::vec::Vec;
+use sp_trie::MerkleValue;
 
 /// A struct containing arguments for iterating over the storage.
 #[derive(Default)]
@@ -205,6 +206,27 @@ pub trait Backend<H: Hasher>: sp_std::fmt::Debug {
 		key: &[u8],
 	) -> Result<Option<H::Out>, Self::Error>;
 
+	/// Get the merkle value or None if there is nothing associated.
+	///
+	/// Backends that are not backed by a trie return `None` by default.
+	fn closest_merkle_value(
+		&self,
+		_key: &[u8],
+	) -> Result<Option<MerkleValue<H::Out>>, Self::Error> {
+		Ok(None)
+	}
+
+	/// Get the child merkle value or None if there is nothing associated.
+	///
+	/// Backends that are not backed by a trie return `None` by default.
+	fn child_closest_merkle_value(
+		&self,
+		_child_info: &ChildInfo,
+		_key: &[u8],
+	) -> Result<Option<MerkleValue<H::Out>>, Self::Error> {
+		Ok(None)
+	}
+
 	/// true if a key exists in storage.
 	fn exists_storage(&self, key: &[u8]) -> Result<bool, Self::Error> {
 		Ok(self.storage_hash(key)?.is_some())
@@ -16,7 +16,7 @@
 // limitations under the License.
 
 pub use crate::runtime_api::StatementSource;
-use crate::{Hash, Statement, Topic};
+use crate::{AccountId, Channel, DecryptionKey, Hash, Statement, Topic, MAX_TOPICS};
 
 /// Statement store error.
 #[derive(Debug, Eq, PartialEq, thiserror::Error)]
@@ -61,6 +61,71 @@ pub enum SubmitResult {
 /// Result type for `Error`
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Topic filter for statement subscriptions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TopicFilter {
+	/// Matches statements that include all of the given topics.
+	MatchAll(Vec<Topic>),
+	/// Matches statements that include any of the given topics. An empty list matches all
+	/// statements.
+	MatchAny(Vec<Topic>),
+}
+
+/// Statement subscription filter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatementFilter {
+	/// Topics of the statements.
+	pub topics: TopicFilter,
+	/// Decryption key of the statements. `None` matches statements that have no
+	/// `DecryptionKey` field.
+	pub dest: Option<DecryptionKey>,
+}
+
+impl StatementFilter {
+	/// Returns `true` if the statement matches the filter.
+	pub fn matches(&self, statement: &Statement) -> bool {
+		if statement.decryption_key() != self.dest {
+			return false
+		}
+
+		let has_topic =
+			|topic: &Topic| (0..MAX_TOPICS).map_while(|i| statement.topic(i)).any(|t| &t == topic);
+		match &self.topics {
+			TopicFilter::MatchAll(topics) => topics.iter().all(has_topic),
+			TopicFilter::MatchAny(topics) => topics.is_empty() || topics.iter().any(has_topic),
+		}
+	}
+}
+
+/// Statement query.
+///
+/// All the provided constraints must be satisfied by the returned statements.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatementQuery {
+	/// Only statements of this account.
+	pub account: Option<AccountId>,
+	/// Only statements in this channel.
+	pub channel: Option<Channel>,
+	/// Only statements with at least this priority.
+	pub min_priority: Option<u32>,
+	/// Only statements with at most this priority.
+	pub max_priority: Option<u32>,
+}
+
+/// A page of statements returned by a query.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatementPage {
+	/// Statements ordered by hash.
+	pub statements: Vec<(Hash, Statement)>,
+	/// Cursor to fetch the next page. `None` if this is the last page.
+	pub next_cursor: Option<Hash>,
+}
+
+/// Stream of statements accepted by the store.
+///
+/// The stream ends if the subscriber falls too far behind.
+pub type StatementStream = futures::channel::mpsc::Receiver<Statement>;
+
 /// Statement store API.
 pub trait StatementStore: Send + Sync {
 	/// Return all statements.
@@ -87,4 +152,21 @@ pub trait StatementStore: Send + Sync {
 
 	/// Remove a statement from the store.
 	fn remove(&self, hash: &Hash) -> Result<()>;
+
+	/// Return statements matching the query, ordered by hash.
+	///
+	/// At most `limit` statements with a hash greater than `cursor` are returned. The cursor of
+	/// the returned page is used to fetch the next one.
+	fn query(
+		&self,
+		query: &StatementQuery,
+		cursor: Option<Hash>,
+		limit: usize,
+	) -> Result<StatementPage>;
+
+	/// Subscribe to statements matching the filter.
+	///
+	/// Returns all known statements that match the filter, followed by a stream of the matching
+	/// statements accepted by the store afterwards.
+	fn subscribe(&self, filter: StatementFilter) -> Result<(Vec<Statement>, StatementStream)>;
 }
@@ -45,7 +45,6 @@ pub use storage_proof::{CompactProof, StorageProof};
 /// for trie compact proof.
 pub use trie_codec::{decode_compact, encode_compact, Error as CompactProofError};
 pub use trie_db::proof::VerifyError;
-use trie_db::proof::{generate_proof, verify_proof};
 /// Various re-exports from the `trie-db` crate.
 pub use trie_db::{
 	nibble_ops,
@@ -53,6 +52,10 @@ pub use trie_db::{
 	CError, DBValue, Query, Recorder, Trie, TrieCache, TrieConfiguration, TrieDBIterator,
 	TrieDBKeyIterator, TrieDBRawIterator, TrieLayout, TrieMut, TrieRecorder,
 };
+use trie_db::{
+	proof::{generate_proof, verify_proof},
+	NibbleSlice, NibbleVec, TrieDBNodeIterator, TrieIterator,
+};
 /// The Substrate format implementation of `TrieStream`.
 pub use trie_stream::TrieStream;
 
@@ -295,6 +298,79 @@ pub fn read_trie_value<L: TrieLayout, DB: hash_db::HashDBRef<L::Hash, trie_db::D
 		.get(key)
 }
 
+/// The merkle value of a trie node.
+///
+/// A node is referenced by its parent either by its hash or, when the encoded node
+/// is shorter than a hash, by the encoded node itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MerkleValue<H> {
+	/// The encoded node, for nodes that are inlined into their parent.
+	Node(Vec<u8>),
+	/// The hash of the node.
+	Hash(H),
+}
+
+/// Read the [`MerkleValue`] of the closest descendant node of the given key.
+///
+/// The closest descendant is the node whose full key has `key` as prefix and is
+/// the closest to the root. Returns `None` if no such node exists.
+pub fn read_trie_first_descendant_value<L: TrieLayout, DB>(
+	db: &DB,
+	root: &TrieHash<L>,
+	key: &[u8],
+	recorder: Option<&mut dyn TrieRecorder<TrieHash<L>>>,
+	cache: Option<&mut dyn TrieCache<L::Codec>>,
+) -> Result<Option<MerkleValue<TrieHash<L>>>, Box<TrieError<L>>>
+where
+	DB: hash_db::HashDBRef<L::Hash, trie_db::DBValue>,
+{
+	let trie = TrieDBBuilder::<L>::new(db, root)
+		.with_optional_cache(cache)
+		.with_optional_recorder(recorder)
+		.build();
+
+	lookup_first_descendant::<L>(&trie, key)
+}
+
+/// Find the closest descendant node of `key` in the given trie.
+fn lookup_first_descendant<L: TrieLayout>(
+	trie: &TrieDB<L>,
+	key: &[u8],
+) -> Result<Option<MerkleValue<TrieHash<L>>>, Box<TrieError<L>>> {
+	let mut iter = TrieDBNodeIterator::new(trie)?;
+	// Seeking leaves the iterator at the first node on or after `key`.
+	iter.seek(key)?;
+
+	let Some(item) = iter.next() else { return Ok(None) };
+	let (mut node_key, hash, node) = item?;
+
+	// The iterator reports the key of the node without its own partial key.
+	match node.node_plan() {
+		NodePlan::Empty => return Ok(None),
+		NodePlan::Leaf { partial, .. } |
+		NodePlan::Extension { partial, .. } |
+		NodePlan::NibbledBranch { partial, .. } => {
+			let partial = partial.build(node.data());
+			node_key.append_partial(partial.right());
+		},
+		NodePlan::Branch { .. } => {},
+	}
+
+	let mut key_nibbles = NibbleVec::new();
+	let key_slice = NibbleSlice::new(key);
+	key_nibbles.append_partial(key_slice.right());
+
+	// The node after `key` is not necessarily a descendant of `key`.
+	if !node_key.starts_with(&key_nibbles) {
+		return Ok(None)
+	}
+
+	Ok(Some(match hash {
+		Some(hash) => MerkleValue::Hash(hash),
+		None => MerkleValue::Node(node.data().to_vec()),
+	}))
+}
+
 /// Read a value from the trie with given Query.
 pub fn read_trie_value_with<
 	L: TrieLayout,
@@ -397,6 +473,22 @@ where
 		.get_hash(key)
 }
 
+/// Read the [`MerkleValue`] of the closest descendant node of the given key in the child trie.
+pub fn read_child_trie_first_descendant_value<L: TrieConfiguration, DB>(
+	keyspace: &[u8],
+	db: &DB,
+	root: &TrieHash<L>,
+	key: &[u8],
+	recorder: Option<&mut dyn TrieRecorder<TrieHash<L>>>,
+	cache: Option<&mut dyn TrieCache<L::Codec>>,
+) -> Result<Option<MerkleValue<TrieHash<L>>>, Box<TrieError<L>>>
+where
+	DB: hash_db::HashDBRef<L::Hash, trie_db::DBValue>,
+{
+	let db = KeySpacedDB::new(db, keyspace);
+	read_trie_first_descendant_value::<L, _>(&db, root, key, recorder, cache)
+}
+
 /// Read a value from the child trie with given query.
 pub fn read_child_trie_value_with<L, Q, DB>(
 	keyspace: &[u8],
@@ -860,6 +952,54 @@ mod tests {
 		assert_eq!(pairs, iter_pairs);
 	}
 
+	#[test]
+	fn first_descendant_value_works() {
+		first_descendant_value_works_inner::<LayoutV1>();
+		first_descendant_value_works_inner::<LayoutV0>();
+	}
+	fn first_descendant_value_works_inner<Layout: TrieConfiguration>() {
+		// Values are large enough to never be inlined into their parent.
+		let pairs = vec![
+			(array_bytes::hex2bytes_unchecked("0103000000000000000464"), vec![1u8; 40]),
+			(array_bytes::hex2bytes_unchecked("0103000000000000000469"), vec![2u8; 40]),
+			(array_bytes::hex2bytes_unchecked("0204"), vec![3u8; 40]),
+		];
+
+		let mut mdb = MemoryDB::default();
+		let mut root = Default::default();
+		let _ = populate_trie::<Layout>(&mut mdb, &mut root, &pairs);
+
+		let first_descendant = |key: &[u8]| {
+			read_trie_first_descendant_value::<Layout, _>(&mdb, &root, key, None, None).unwrap()
+		};
+
+		// The root is the closest descendant of the empty key.
+		assert_eq!(first_descendant(&[]), Some(MerkleValue::Hash(root)));
+
+		// Branch node shared by the first two keys.
+		let branch = first_descendant(&array_bytes::hex2bytes_unchecked("0103"));
+		assert!(matches!(branch, Some(MerkleValue::Hash(hash)) if hash != root));
+		// Any key inside the partial key of the branch resolves to the same node.
+		assert_eq!(first_descendant(&array_bytes::hex2bytes_unchecked("01030000")), branch);
+
+		// Leaf nodes.
+		let leaf_1 = first_descendant(&pairs[0].0);
+		let leaf_2 = first_descendant(&pairs[1].0);
+		assert!(leaf_1.is_some());
+		assert!(leaf_2.is_some());
+		assert_ne!(leaf_1, leaf_2);
+		assert_ne!(leaf_1, branch);
+		assert_eq!(first_descendant(&pairs[2].0), first_descendant(&[0x02]));
+
+		// Keys without descendants.
+		assert_eq!(first_descendant(&array_bytes::hex2bytes_unchecked("0104")), None);
+		assert_eq!(first_descendant(&array_bytes::hex2bytes_unchecked("05")), None);
+		assert_eq!(
+			first_descendant(&array_bytes::hex2bytes_unchecked("010300000000000000046400")),
+			None
+		);
+	}
+
 	#[test]
 	fn proof_non_inclusion_works() {
 		let pairs = vec![
@@ -78,6 +78,10 @@ pub use extrinsic::{ExtrinsicBuilder, Transfer};
 
 const LOG_TARGET: &str = "substrate-test-runtime";
 
+/// Storage key of the GRANDPA finality lag returned by the `FinalityLagApi`, set through
+/// `storage_change` extrinsics.
+pub const FINALITY_LAG_KEY: &[u8] = b":test:finality_lag";
+
 // Include the WASM binary
 #[cfg(feature = "std")]
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
@@ -722,6 +726,14 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl sp_consensus_grandpa::FinalityLagApi<Block> for Runtime {
+		fn finality_lag() -> NumberFor<Block> {
+			sp_io::storage::get(FINALITY_LAG_KEY)
+				.and_then(|lag| Decode::decode(&mut &lag[..]).ok())
+				.unwrap_or_default()
+		}
+	}
+
 	#[cfg(not(feature = "disable-genesis-builder"))]
 	impl sp_genesis_builder::GenesisBuilder<Block> for Runtime {
 		fn create_default_config() -> Vec<u8> {